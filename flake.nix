@@ -304,6 +304,11 @@
                 };
               };
               security.pam.services.leaper-lock = {};
+              # Used by `lock.security_key`, needs `security.pam.u2f` set up with the keys
+              security.pam.services.leaper-lock-key = {
+                u2fAuth = true;
+                unixAuth = false;
+              };
             };
           };
       };
@@ -0,0 +1,99 @@
+//! Moving one app of a big list with `AppSearch::upsert` against sorting the whole list
+//! again, which is what live updates did before it
+
+#![feature(test)]
+
+extern crate test;
+
+use test::Bencher;
+
+use db::{DBEntryId, apps::AppWithIcon, history::AppLaunch};
+use leaper_core::{search::AppSearch, sort::Launches};
+use mode::config::{LauncherConfig, SortOrder};
+
+const APPS: usize = 2000;
+
+fn app(ind: usize) -> AppWithIcon {
+    let name = format!("app-{:04}", ind * 7919 % APPS);
+
+    AppWithIcon {
+        id: DBEntryId::new("app", name.as_str()),
+        desktop_entry_path: format!("/usr/share/applications/{name}.desktop"),
+        name,
+        canonical_name: String::new(),
+        generic_name: None,
+        comment: None,
+        keywords: vec![],
+        kind: Default::default(),
+        exec: vec![],
+        url: None,
+        categories: vec![],
+        icon: None,
+        only_show_in: vec![],
+        not_show_in: vec![],
+        no_display: false,
+        hidden: false,
+        terminal: false,
+        try_exec_missing: false,
+        dbus_activatable: false,
+        prefers_non_default_gpu: false,
+        actions: vec![],
+    }
+}
+
+fn setup() -> (AppSearch, Vec<AppWithIcon>, Launches) {
+    let search = AppSearch::new(LauncherConfig {
+        empty_query: SortOrder::Frecency,
+        ..Default::default()
+    });
+    let apps = (0..APPS).map(app).collect::<Vec<_>>();
+    let launches = apps
+        .iter()
+        .step_by(3)
+        .enumerate()
+        .map(|(ind, app)| {
+            let launch = AppLaunch {
+                id: DBEntryId::new("app_launch", app.name.as_str()),
+                app: app.id.clone(),
+                count: ind as u64 % 50,
+                last_launched: ind as i64 * 600,
+            };
+
+            (app.id.clone(), launch)
+        })
+        .collect::<Launches>();
+
+    (search, apps, launches)
+}
+
+#[bench]
+fn upsert_one(b: &mut Bencher) {
+    let (search, mut apps, launches) = setup();
+    search.sort(&mut apps, &launches);
+
+    let mut ind = 0;
+
+    b.iter(|| {
+        ind = (ind + 1) % APPS;
+
+        let mut renamed = apps[ind].clone();
+        renamed.name.push('~');
+
+        search.upsert(&mut apps, &launches, renamed, Some(ind))
+    });
+}
+
+#[bench]
+fn resort_all(b: &mut Bencher) {
+    let (search, mut apps, launches) = setup();
+    search.sort(&mut apps, &launches);
+
+    let mut ind = 0;
+
+    b.iter(|| {
+        ind = (ind + 1) % APPS;
+        apps[ind].name.push('~');
+
+        search.sort(&mut apps, &launches);
+    });
+}
@@ -0,0 +1,210 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+
+use leaper_error::IntoContext;
+
+use crate::{CoreError, CoreResult, opener};
+
+/// Entries marked in a list for a [`BatchAction`], in the order they were marked
+#[derive(Debug, Clone)]
+pub struct Selection<K>(Vec<K>);
+
+impl<K> Default for Selection<K> {
+    fn default() -> Self {
+        Self(vec![])
+    }
+}
+
+impl<K: PartialEq> Selection<K> {
+    /// Marks `key`, or unmarks it if it already was. Returns whether it's marked now
+    pub fn toggle(&mut self, key: K) -> bool {
+        match self.0.iter().position(|marked| *marked == key) {
+            Some(ind) => {
+                self.0.remove(ind);
+                false
+            }
+            None => {
+                self.0.push(key);
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) {
+        self.0.retain(|marked| marked != key);
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.0.contains(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &K> {
+        self.0.iter()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchAction {
+    Open,
+    CopyPaths,
+    Trash,
+}
+
+impl BatchAction {
+    /// Whether the action should be confirmed before running it
+    pub fn destructive(&self) -> bool {
+        matches!(self, Self::Trash)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Open => "Open",
+            Self::CopyPaths => "Copy paths",
+            Self::Trash => "Move to trash",
+        }
+    }
+}
+
+/// Opens every path, failures don't stop the rest from being opened
+pub async fn open_all(paths: &[PathBuf]) -> Vec<(PathBuf, CoreError)> {
+    let mut errors = vec![];
+
+    for path in paths {
+        if let Err(err) = opener::open_path(path).await {
+            errors.push((path.clone(), err));
+        }
+    }
+
+    errors
+}
+
+/// One path per line, what file managers put into the clipboard as text
+pub fn paths_text(paths: &[PathBuf]) -> String {
+    paths.iter().map(|path| path.display()).join("\n")
+}
+
+/// Moves `path` into the home trash following the freedesktop trash spec,
+/// so it can be restored from any file manager. Returns where it ended up
+pub fn trash(path: &Path) -> CoreResult<PathBuf> {
+    let path = std::path::absolute(path)?;
+    let trash = trash_dir()?;
+    let (files, info) = (trash.join("files"), trash.join("info"));
+
+    for dir in [&files, &info] {
+        std::fs::create_dir_all(dir).err_context::<CoreError>(format!("Creating {dir:?}"))?;
+    }
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| CoreError::TrashFailed(path.clone(), "no file name".into()))?
+        .to_string_lossy()
+        .into_owned();
+
+    // The info file is created first and exclusively, that's what reserves the name.
+    // Only a taken name moves on to the next one, anything else (e.g. a read-only trash)
+    // would fail for every name
+    let mut n = 1;
+    let (trashed_name, mut info_file) = loop {
+        let candidate = match n {
+            1 => name.clone(),
+            n => format!("{name}.{n}"),
+        };
+
+        match std::fs::File::create_new(info.join(format!("{candidate}.trashinfo"))) {
+            Ok(file) => break (candidate, file),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => n += 1,
+            Err(err) => return Err(CoreError::TrashFailed(path, err.to_string())),
+        }
+    };
+    let info_path = info.join(format!("{trashed_name}.trashinfo"));
+
+    write!(
+        info_file,
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(&path),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+    )?;
+
+    let trashed = files.join(&trashed_name);
+
+    if let Err(err) = std::fs::rename(&path, &trashed) {
+        let _ = std::fs::remove_file(&info_path);
+
+        return Err(CoreError::TrashFailed(
+            path,
+            match err.kind() {
+                std::io::ErrorKind::CrossesDevices => "it's on another filesystem".into(),
+                _ => err.to_string(),
+            },
+        ));
+    }
+
+    Ok(trashed)
+}
+
+fn trash_dir() -> CoreResult<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or(CoreError::NoHome)?;
+
+    Ok(data_home.join("Trash"))
+}
+
+/// Percent-encodes everything but unreserved characters and slashes, as the spec asks for
+fn encode_path(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    path.as_os_str()
+        .as_bytes()
+        .iter()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (*byte as char).to_string()
+            }
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_toggles_in_order() {
+        let mut selection = Selection::default();
+
+        assert!(selection.toggle("a"));
+        assert!(selection.toggle("b"));
+        assert!(!selection.toggle("a"));
+        assert!(selection.toggle("c"));
+
+        assert_eq!(selection.iter().copied().collect_vec(), ["b", "c"]);
+    }
+
+    #[test]
+    fn trashinfo_path_encoding() {
+        assert_eq!(
+            encode_path(Path::new("/home/me/My Notes/ü.txt")),
+            "/home/me/My%20Notes/%C3%BC.txt"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::path::{Path, PathBuf};
+
+use leaper_error::IntoContext;
+
+use crate::{CoreError, CoreResult, collate::NameCollator};
+
+/// Finder input typed as a path (`/usr/sh`, `~/Doc`) instead of a fuzzy query:
+/// the directory to list and the partial name of the child being completed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowsePath {
+    /// Directory as typed, `~` not expanded, always ends with a `/`
+    typed_dir: String,
+    dir: PathBuf,
+    partial: String,
+}
+
+impl BrowsePath {
+    /// [`None`] when the input isn't a path, so it goes to the fuzzy search instead
+    pub fn parse(input: &str) -> Option<Self> {
+        let expanded = match input {
+            "~" => return Self::parse("~/"),
+            input if input.starts_with("~/") => {
+                let home = std::env::var_os("HOME")?;
+                PathBuf::from(home).join(&input[2..])
+            }
+            input if input.starts_with('/') => PathBuf::from(input),
+            _ => return None,
+        };
+
+        let split = input.rfind('/').map(|ind| ind + 1).unwrap_or(input.len());
+        let (typed_dir, partial) = input.split_at(split);
+        let dir = match partial.is_empty() {
+            true => expanded,
+            false => expanded.parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+
+        Some(Self {
+            typed_dir: typed_dir.to_string(),
+            dir,
+            partial: partial.to_string(),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn partial(&self) -> &str {
+        &self.partial
+    }
+
+    /// Labels and inputs of every directory up to the current one, for breadcrumbs
+    pub fn breadcrumbs(&self) -> Vec<(String, String)> {
+        let mut crumbs = vec![];
+        let mut input = String::new();
+
+        for segment in self.typed_dir.split_inclusive('/') {
+            input.push_str(segment);
+
+            let label = match segment.trim_end_matches('/') {
+                "" => "/",
+                label => label,
+            };
+            crumbs.push((label.to_string(), input.clone()));
+        }
+
+        crumbs
+    }
+
+    /// Input for the parent directory, what Backspace does on an empty partial name.
+    /// [`None`] at the root
+    pub fn up(&self) -> Option<String> {
+        let crumbs = self.breadcrumbs();
+
+        (crumbs.len() > 1).then(|| crumbs[crumbs.len() - 2].1.clone())
+    }
+
+    /// Input after picking `entry`, directories get a trailing `/` to keep browsing
+    pub fn complete(&self, entry: &BrowseEntry) -> String {
+        let mut input = format!("{}{}", self.typed_dir, entry.name);
+
+        if entry.is_dir {
+            input.push('/');
+        }
+
+        input
+    }
+
+    /// Children of [`Self::dir`] starting with the partial name (case-insensitive).
+    /// Directories first, hidden entries only when the partial name starts with a dot
+    pub async fn children(&self, collator: &NameCollator) -> CoreResult<Vec<BrowseEntry>> {
+        let partial = self.partial.to_lowercase();
+        let show_hidden = partial.starts_with('.');
+
+        let mut entries = vec![];
+        let mut read_dir = tokio::fs::read_dir(&self.dir)
+            .await
+            .err_context::<CoreError>(format!("Listing {:?}", self.dir))?;
+
+        while let Some(entry) = read_dir.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            if (!show_hidden && name.starts_with('.')) || !name.to_lowercase().starts_with(&partial)
+            {
+                continue;
+            }
+
+            // Follows symlinks, so a link to a directory can be browsed into
+            let is_dir = tokio::fs::metadata(entry.path())
+                .await
+                .map(|metadata| metadata.is_dir())
+                .unwrap_or_default();
+
+            entries.push(BrowseEntry {
+                path: entry.path(),
+                name,
+                is_dir,
+            });
+        }
+
+        entries.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then_with(|| collator.compare(&a.name, &b.name))
+        });
+
+        Ok(entries)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowseEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub is_dir: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_a_path() {
+        assert_eq!(BrowsePath::parse("firefox"), None);
+        assert_eq!(BrowsePath::parse("~user"), None);
+    }
+
+    #[test]
+    fn partial_name() {
+        let browse = BrowsePath::parse("/usr/sh").unwrap();
+
+        assert_eq!(browse.dir(), Path::new("/usr"));
+        assert_eq!(browse.partial(), "sh");
+        assert_eq!(browse.up().as_deref(), Some("/"));
+    }
+
+    #[test]
+    fn breadcrumbs() {
+        let browse = BrowsePath::parse("/usr/share/").unwrap();
+
+        assert_eq!(browse.dir(), Path::new("/usr/share/"));
+        assert_eq!(
+            browse.breadcrumbs(),
+            [
+                ("/".to_string(), "/".to_string()),
+                ("usr".to_string(), "/usr/".to_string()),
+                ("share".to_string(), "/usr/share/".to_string()),
+            ]
+        );
+        assert_eq!(browse.up().as_deref(), Some("/usr/"));
+        assert_eq!(BrowsePath::parse("/").unwrap().up(), None);
+    }
+}
@@ -0,0 +1,247 @@
+use futures::{Stream, StreamExt};
+
+use db::{
+    DB, DBAction, DBEntryId, InstrumentedDBQuery,
+    apps::{
+        AppWithIcon, CategoryCount, GetAppCategoriesQuery, GetAppWithIconsQuery,
+        GetLiveAppIconUpdates, GetLiveAppWithIconsQuery, IconOrder,
+    },
+    favorites::{GetFavoritesQuery, SetFavoriteQuery},
+    history::{
+        GetAppLaunchesQuery, GetHistoryEntriesQuery, GetQueryChoicesQuery, RecordAppLaunchQuery,
+        RecordHistoryEntryQuery, RecordQueryChoiceQuery,
+    },
+};
+
+use mode::config::LauncherConfig;
+
+use crate::{
+    CoreError, CoreResult,
+    recall::QueryHistory,
+    sort::{self, Favorites, Launches, QueryChoices},
+};
+
+/// Change to the apps, see [`AppCatalog::live_apps`]
+#[derive(Debug, Clone)]
+pub enum LiveApp {
+    /// New app, or one that changed (e.g. got its icon) and is still shown
+    Changed(AppWithIcon),
+    /// App that's gone, or isn't shown any more (e.g. its TryExec binary got removed or
+    /// it's Hidden now)
+    Removed(DBEntryId),
+}
+
+/// Apps indexed by the daemon along with their launch history
+#[derive(Debug, Clone)]
+pub struct AppCatalog {
+    db: DB,
+    /// Desktops from XDG_CURRENT_DESKTOP, `None` when entries for other desktops are shown too
+    desktops: Option<Vec<String>>,
+    /// See `launcher.show_no_display`
+    no_display: bool,
+    icons: IconOrder,
+}
+
+impl AppCatalog {
+    const MAIN_CATEGORIES: &[&str] = &[
+        "AudioVideo",
+        "Audio",
+        "Video",
+        "Development",
+        "Education",
+        "Game",
+        "Graphics",
+        "Network",
+        "Office",
+        "Science",
+        "Settings",
+        "System",
+        "Utility",
+    ];
+
+    pub fn new(db: DB, config: &LauncherConfig) -> Self {
+        let desktops = (!config.show_all_desktops).then(|| Self::current_desktops(config));
+
+        Self {
+            db,
+            desktops,
+            no_display: config.show_no_display,
+            icons: Self::icon_order(config),
+        }
+    }
+
+    /// `icon_formats`/`icon_size` of the config
+    pub fn icon_order(config: &LauncherConfig) -> IconOrder {
+        let [min_size, max_size] = config.icon_size;
+
+        IconOrder {
+            formats: config
+                .icon_formats
+                .iter()
+                .map(|format| format.trim_start_matches('.').to_lowercase())
+                .collect(),
+            min_size: min_size as usize,
+            max_size: max_size as usize,
+        }
+    }
+
+    /// `current_desktop` of the config, falling back to XDG_CURRENT_DESKTOP
+    fn current_desktops(config: &LauncherConfig) -> Vec<String> {
+        if !config.current_desktop.is_empty() {
+            return config.current_desktop.clone();
+        }
+
+        std::env::var("XDG_CURRENT_DESKTOP")
+            .map(|desktops| {
+                desktops
+                    .split(':')
+                    .filter(|desktop| !desktop.is_empty())
+                    .map(Into::into)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub async fn apps(self) -> CoreResult<Vec<AppWithIcon>> {
+        Ok(
+            GetAppWithIconsQuery::new(self.desktops, self.no_display, self.icons)
+                .instrumented_execute(self.db)
+                .await?,
+        )
+    }
+
+    /// Main categories of the desktop menu spec that have any apps in them, biggest first.
+    /// Additional categories (e.g. "WebBrowser") are too fine-grained for a filter
+    pub async fn categories(self) -> CoreResult<Vec<CategoryCount>> {
+        Ok(GetAppCategoriesQuery
+            .instrumented_execute(self.db)
+            .await?
+            .into_iter()
+            .filter(|category| Self::MAIN_CATEGORIES.contains(&category.category.as_str()))
+            .collect())
+    }
+
+    pub async fn launches(self) -> CoreResult<Launches> {
+        Ok(GetAppLaunchesQuery
+            .instrumented_execute(self.db)
+            .await?
+            .into_iter()
+            .map(|launch| (launch.app.clone(), launch))
+            .collect())
+    }
+
+    pub async fn record_launch(self, app: DBEntryId) -> CoreResult<()> {
+        RecordAppLaunchQuery::builder()
+            .app(app)
+            .build()
+            .instrumented_execute(self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn favorites(self) -> CoreResult<Favorites> {
+        Ok(GetFavoritesQuery
+            .instrumented_execute(self.db)
+            .await?
+            .into_iter()
+            .map(|favorite| favorite.app)
+            .collect())
+    }
+
+    /// Pins `app` to the top of the list, or unpins it
+    pub async fn set_favorite(self, app: DBEntryId, favorite: bool) -> CoreResult<()> {
+        SetFavoriteQuery::builder()
+            .app(app)
+            .favorite(favorite)
+            .build()
+            .instrumented_execute(self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn query_choices(self) -> CoreResult<QueryChoices> {
+        Ok(QueryChoices::new(
+            GetQueryChoicesQuery.instrumented_execute(self.db).await?,
+        ))
+    }
+
+    pub async fn query_history(self) -> CoreResult<QueryHistory> {
+        Ok(QueryHistory::new(
+            GetHistoryEntriesQuery.instrumented_execute(self.db).await?,
+            sort::now(),
+        ))
+    }
+
+    /// Remembers `query` for recalling it later, blank ones are skipped
+    pub async fn record_query(self, query: String) -> CoreResult<()> {
+        let Some(key) = QueryHistory::key(&query) else {
+            return Ok(());
+        };
+
+        RecordHistoryEntryQuery::builder()
+            .key(key)
+            .query(query.trim())
+            .build()
+            .instrumented_execute(self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remembers `app` being picked from the results of `query`
+    pub async fn record_choice(self, query: String, app: DBEntryId) -> CoreResult<()> {
+        RecordQueryChoiceQuery::builder()
+            .query(query.to_lowercase())
+            .app(app)
+            .build()
+            .instrumented_execute(self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Apps as they get added, updated or removed, including newly resolved icons
+    pub async fn live_apps(self) -> CoreResult<impl Stream<Item = CoreResult<LiveApp>>> {
+        let apps = GetLiveAppWithIconsQuery::new(self.icons.clone())
+            .instrumented_execute(self.db.clone())
+            .await?;
+        // Every icon related to an app shows up here on its own, whether it's the preferred
+        // one is up to [`IconOrder::pick`] on the receiving end
+        let icon_updates = GetLiveAppIconUpdates.instrumented_execute(self.db).await?;
+        let desktops = self.desktops;
+        let no_display = self.no_display;
+
+        // Only changes to the apps themselves can remove them, a removed icon doesn't
+        let apps = apps.map(|notification| (notification, true));
+        let icon_updates = icon_updates.map(|notification| (notification, false));
+
+        Ok(
+            futures::stream::select(apps, icon_updates).filter_map(move |(notification, app)| {
+                let shown = |app: &AppWithIcon| {
+                    !app.try_exec_missing
+                        && app.listed(no_display)
+                        && desktops
+                            .as_deref()
+                            .is_none_or(|desktops| app.shown_in(desktops))
+                };
+
+                let res = match notification {
+                    Ok(notification) => match notification.action {
+                        DBAction::Create | DBAction::Update if shown(&notification.data) => {
+                            Some(Ok(LiveApp::Changed(notification.data)))
+                        }
+                        DBAction::Update | DBAction::Delete if app => {
+                            Some(Ok(LiveApp::Removed(notification.data.id)))
+                        }
+                        _ => None,
+                    },
+                    Err(err) => Some(Err(CoreError::LiveQuery(err.to_string()))),
+                };
+
+                async move { res }
+            }),
+        )
+    }
+}
@@ -0,0 +1,29 @@
+//! Copying text so it's still there after the mode exits. A Wayland client's selection
+//! dies with it, so this goes through wl-copy (or xclip on X11), which keep serving it
+
+use std::process::Stdio;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::CoreResult;
+
+pub async fn copy(text: String) -> CoreResult<()> {
+    let mut command = match std::env::var_os("WAYLAND_DISPLAY") {
+        Some(_) => tokio::process::Command::new("wl-copy"),
+        None => {
+            let mut command = tokio::process::Command::new("xclip");
+            command.args(["-selection", "clipboard"]);
+            command
+        }
+    };
+
+    let mut child = command.stdin(Stdio::piped()).spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).await?;
+    }
+
+    child.wait().await?;
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use icu_collator::{Collator, CollatorBorrowed, options::CollatorOptions};
+use icu_locale_core::Locale;
+
+/// Locale-aware ordering for names shown to the user, so accented letters and
+/// non-latin scripts end up where a speaker of the language would expect them
+#[derive(Debug, Clone)]
+pub struct NameCollator(Arc<CollatorBorrowed<'static>>);
+
+impl Default for NameCollator {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl NameCollator {
+    /// Uses the given BCP 47 locale (e.g. "de", "sv-SE"), or the one from the environment
+    /// (LC_ALL > LC_COLLATE > LANG) when not set. Falls back to the root collation
+    /// for anything ICU doesn't know
+    pub fn new(locale: Option<&str>) -> Self {
+        let locale = locale
+            .map(str::to_string)
+            .or_else(Self::env_locale)
+            .and_then(|locale| {
+                Locale::try_from_str(&locale)
+                    .inspect_err(|err| tracing::warn!("Invalid collation locale {locale:?}: {err}"))
+                    .ok()
+            })
+            .unwrap_or(Locale::UNKNOWN);
+
+        let collator = Collator::try_new((&locale).into(), CollatorOptions::default())
+            .or_else(|err| {
+                tracing::warn!("No collation data for {locale}, using the root one: {err}");
+                Collator::try_new(Default::default(), CollatorOptions::default())
+            })
+            .expect("root collation is always compiled in");
+
+        Self(Arc::new(collator))
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        // Names the collator considers equal (e.g. differing only in ignorables)
+        // still need a stable order
+        self.0.compare(a, b).then_with(|| a.cmp(b))
+    }
+
+    /// POSIX locale names (de_DE.UTF-8@euro) converted to BCP 47 (de-DE)
+    fn env_locale() -> Option<String> {
+        ["LC_ALL", "LC_COLLATE", "LANG"]
+            .into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .split(['.', '@'])
+                    .next()
+                    .unwrap_or_default()
+                    .replace('_', "-")
+            })
+            .filter(|locale| !matches!(locale.as_str(), "" | "C" | "POSIX"))
+    }
+}
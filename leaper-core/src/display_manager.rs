@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use zbus::{Connection, zvariant::OwnedObjectPath};
+
+use crate::{CoreError, CoreResult};
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Seat",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1/seat/auto"
+)]
+trait Seat {
+    #[zbus(property)]
+    fn sessions(&self) -> zbus::Result<Vec<(String, OwnedObjectPath)>>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+trait SeatSession {
+    fn activate(&self) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn class(&self) -> zbus::Result<String>;
+}
+
+#[zbus::proxy(
+    interface = "org.gnome.DisplayManager.LocalDisplayFactory",
+    default_service = "org.gnome.DisplayManager",
+    default_path = "/org/gnome/DisplayManager/LocalDisplayFactory"
+)]
+trait LocalDisplayFactory {
+    fn create_transient_display(&self) -> zbus::Result<OwnedObjectPath>;
+}
+
+/// Display manager the current session was started from,
+/// detected through the `display-manager.service` alias systemd sets up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisplayManager {
+    Gdm,
+    LightDm,
+    Other(String),
+}
+
+impl DisplayManager {
+    const SERVICE_ALIAS: &'static str = "/etc/systemd/system/display-manager.service";
+
+    pub fn detect() -> CoreResult<Self> {
+        let target =
+            std::fs::read_link(Self::SERVICE_ALIAS).map_err(|_| CoreError::NoDisplayManager)?;
+        let name = Path::new(&target)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or(CoreError::NoDisplayManager)?;
+
+        Ok(match name.as_str() {
+            "gdm" | "gdm3" => Self::Gdm,
+            "lightdm" => Self::LightDm,
+            _ => Self::Other(name),
+        })
+    }
+
+    /// Brings up the greeter while keeping the current session running
+    pub async fn switch_to_greeter(&self, connection: Option<Connection>) -> CoreResult<()> {
+        let Some(connection) = connection else {
+            return Err(CoreError::NoDBusConnection);
+        };
+
+        match self {
+            Self::Gdm => Self::gdm_greeter(&connection).await,
+            Self::LightDm => {
+                let seat_path = std::env::var("XDG_SEAT_PATH")
+                    .map_err(|_| CoreError::SwitchUserFailed("XDG_SEAT_PATH is not set".into()))
+                    .and_then(|path| {
+                        OwnedObjectPath::try_from(path)
+                            .map_err(|err| CoreError::SwitchUserFailed(err.to_string()))
+                    })?;
+
+                connection
+                    .call_method(
+                        Some("org.freedesktop.DisplayManager"),
+                        seat_path,
+                        Some("org.freedesktop.DisplayManager.Seat"),
+                        "SwitchToGreeter",
+                        &(),
+                    )
+                    .await?;
+
+                Ok(())
+            }
+            Self::Other(name) => Err(CoreError::UnsupportedDisplayManager(name.clone())),
+        }
+    }
+
+    /// Same as GNOME Shell does it: the greeter still running on the seat gets activated
+    /// through logind, GDM starts a new one otherwise
+    async fn gdm_greeter(connection: &Connection) -> CoreResult<()> {
+        for (_, path) in SeatProxy::new(connection).await?.sessions().await? {
+            let session = SeatSessionProxy::builder(connection)
+                .path(path)?
+                .build()
+                .await?;
+
+            if session.class().await? == "greeter" {
+                session.activate().await?;
+
+                return Ok(());
+            }
+        }
+
+        LocalDisplayFactoryProxy::new(connection)
+            .await?
+            .create_transient_display()
+            .await?;
+
+        Ok(())
+    }
+}
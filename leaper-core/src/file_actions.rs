@@ -0,0 +1,61 @@
+use std::path::{Path, PathBuf};
+
+use mode::terminal::Terminal;
+
+use crate::{CoreError, CoreResult, opener};
+
+/// Secondary actions on a single file or directory result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileAction {
+    OpenFolder,
+    OpenTerminal,
+    CopyPath,
+}
+
+impl FileAction {
+    pub const ALL: [Self; 3] = [Self::OpenFolder, Self::OpenTerminal, Self::CopyPath];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OpenFolder => "Open containing folder",
+            Self::OpenTerminal => "Open terminal here",
+            Self::CopyPath => "Copy path",
+        }
+    }
+}
+
+/// The entry itself for directories, its parent otherwise
+pub fn target_dir(path: &Path) -> PathBuf {
+    match path.is_dir() {
+        true => path.to_path_buf(),
+        false => path.parent().unwrap_or(path).to_path_buf(),
+    }
+}
+
+/// Absolute path as text for the clipboard
+pub fn absolute_path(path: &Path) -> CoreResult<String> {
+    Ok(std::path::absolute(path)?.to_string_lossy().into_owned())
+}
+
+pub async fn open_folder(path: &Path) -> CoreResult<()> {
+    opener::open_containing_folder(path).await
+}
+
+/// Starts `terminal` (see `LeaperModeConfig::resolve_terminal`) with [`target_dir`] as
+/// its working directory
+pub fn open_terminal(path: &Path, terminal: &Terminal) -> CoreResult<()> {
+    let dir = target_dir(path);
+    let (program, args) = terminal
+        .command
+        .split_first()
+        .ok_or_else(|| CoreError::EmptyExec("terminal".into()))?;
+
+    tracing::trace!("Opening {program} in {dir:?}");
+
+    std::process::Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .spawn()?;
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use std::cmp::Ordering;
+
+use futures::{Stream, StreamExt};
+
+use db::{
+    DB, DBAction, InstrumentedDBQuery,
+    fs::{
+        DirUsage, DuplicateGroup, FileHit, GetDirUsageQuery, GetDuplicatesQuery,
+        GetLiveFSNodesQuery, SearchFilesQuery,
+    },
+};
+
+use crate::{CoreError, CoreResult, collate::NameCollator, finder::LiveFile, query::SearchQuery};
+
+/// Files indexed by the daemon
+#[derive(Debug, Clone)]
+pub struct FileCatalog {
+    db: DB,
+}
+
+impl FileCatalog {
+    const LIMIT: usize = 500;
+    /// The finder ranks the candidates itself, so it takes a lot more of them. Getting this
+    /// many means there may be more
+    pub const FINDER_LIMIT: usize = 20_000;
+
+    pub fn new(db: DB) -> Self {
+        Self { db }
+    }
+
+    /// Candidates for the finder: files passing every term of `query`, the plain words
+    /// included so the limit doesn't cut off the ones they'd match.
+    /// [`crate::finder::FileFinder`] ranks them by the words after
+    pub async fn candidates(self, query: SearchQuery) -> CoreResult<Vec<FileHit>> {
+        Ok(SearchFilesQuery::new(query.filter(), Self::FINDER_LIMIT)
+            .instrumented_execute(self.db)
+            .await?)
+    }
+
+    /// Files as the daemon indexes or drops them
+    pub async fn live_files(self) -> CoreResult<impl Stream<Item = CoreResult<LiveFile>>> {
+        let nodes = GetLiveFSNodesQuery.instrumented_execute(self.db).await?;
+
+        Ok(nodes.filter_map(|notification| async move {
+            match notification {
+                Ok(notification) => match notification.action {
+                    DBAction::Create | DBAction::Update => {
+                        LiveFile::changed(notification.data).await.map(Ok)
+                    }
+                    DBAction::Delete => Some(Ok(LiveFile::Removed(notification.data.id))),
+                    _ => None,
+                },
+                Err(err) => Some(Err(CoreError::LiveQuery(err.to_string()))),
+            }
+        }))
+    }
+
+    /// Direct children of `dir` by the size of everything indexed under them
+    pub async fn usage(self, dir: impl AsRef<std::path::Path>) -> CoreResult<Vec<DirUsage>> {
+        Ok(GetDirUsageQuery::new(dir, Self::LIMIT)
+            .instrumented_execute(self.db)
+            .await?)
+    }
+
+    /// Files with identical contents, for the duplicates view. Only covers files
+    /// the daemon already hashed (`daemon.hash_files`)
+    pub async fn duplicates(self) -> CoreResult<Vec<DuplicateGroup>> {
+        Ok(GetDuplicatesQuery { limit: Self::LIMIT }
+            .instrumented_execute(self.db)
+            .await?)
+    }
+}
+
+/// Order of the finder's results instead of how well they match (Ctrl+S goes through them)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FileSort {
+    #[default]
+    Name,
+    Kind,
+    /// Largest first
+    Size,
+    /// Most recently modified first
+    Modified,
+}
+
+impl FileSort {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Kind => "type",
+            Self::Size => "size",
+            Self::Modified => "modified",
+        }
+    }
+
+    /// The one after this, `None` after the last one (back to the ranking)
+    pub fn next(sort: Option<Self>) -> Option<Self> {
+        match sort {
+            None => Some(Self::Name),
+            Some(Self::Name) => Some(Self::Kind),
+            Some(Self::Kind) => Some(Self::Size),
+            Some(Self::Size) => Some(Self::Modified),
+            Some(Self::Modified) => None,
+        }
+    }
+
+    pub fn compare(&self, a: &FileHit, b: &FileHit, collator: &NameCollator) -> Ordering {
+        match self {
+            Self::Name => collator.compare(&a.name, &b.name),
+            Self::Kind => a
+                .kind
+                .cmp(&b.kind)
+                .then_with(|| collator.compare(&a.name, &b.name)),
+            Self::Size => b.size.cmp(&a.size),
+            Self::Modified => b.mtime.cmp(&a.mtime),
+        }
+    }
+}
+
+/// `bytes` in the biggest binary unit that keeps it above 1
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
@@ -0,0 +1,152 @@
+//! Fuzzy file search over the daemon's fs index, see `leaper find`
+
+use std::{cmp::Reverse, path::Path};
+
+use itertools::Itertools;
+
+use db::{
+    DBEntryId,
+    fs::{FSNode, FileHit, FileKind},
+};
+
+use crate::scoring;
+
+/// Change to the indexed files, see [`crate::files::FileCatalog::live_files`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveFile {
+    /// New file, or one whose node changed
+    Changed(FileHit),
+    /// Node of the file that's gone
+    Removed(DBEntryId),
+}
+
+impl LiveFile {
+    /// The node only has a path, the rest is read from the disk. Directories are skipped,
+    /// files that are gone again by now are removed
+    pub(crate) async fn changed(node: FSNode) -> Option<Self> {
+        let metadata = match tokio::fs::metadata(&node.path).await {
+            Ok(metadata) => metadata,
+            Err(_) => return Some(Self::Removed(node.id)),
+        };
+
+        if !metadata.is_file() {
+            return None;
+        }
+
+        let kind = Path::new(&node.path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(FileKind::from_ext)
+            .unwrap_or_default();
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|mtime| mtime.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|since| since.as_secs() as i64);
+
+        Some(Self::Changed(FileHit {
+            node: node.id,
+            path: node.path,
+            name: node.name,
+            size: metadata.len(),
+            mtime,
+            kind,
+        }))
+    }
+}
+
+/// Ranks the finder candidates by how well their paths match the query text
+#[derive(Default)]
+pub struct FileFinder {
+    matcher: nucleo::Matcher,
+}
+
+impl FileFinder {
+    /// Matches in the file name count this many times more than ones spread over the path
+    const NAME_WEIGHT: u16 = 2;
+
+    /// Keys of the `files` matching `text`, best first. Without any text all of them,
+    /// most recently modified first
+    pub fn rank<'a, K>(
+        &mut self,
+        files: impl IntoIterator<Item = (K, &'a FileHit)>,
+        text: &str,
+    ) -> Vec<K> {
+        let needle = text.trim().to_lowercase();
+
+        if needle.is_empty() {
+            return files
+                .into_iter()
+                .sorted_by(|(_, a), (_, b)| b.mtime.cmp(&a.mtime).then_with(|| a.path.cmp(&b.path)))
+                .map(|(key, _)| key)
+                .collect();
+        }
+
+        files
+            .into_iter()
+            .filter_map(|(key, file)| {
+                let name = scoring::fuzzy(&mut self.matcher, &file.name, &needle)
+                    .map(|score| score.saturating_mul(Self::NAME_WEIGHT));
+                let path = scoring::fuzzy(&mut self.matcher, &file.path, &needle);
+
+                name.max(path).map(|score| (Reverse(score), key, file))
+            })
+            // Shallower paths first among equal matches
+            .sorted_by(|(score_a, _, a), (score_b, _, b)| {
+                score_a
+                    .cmp(score_b)
+                    .then_with(|| a.path.len().cmp(&b.path.len()))
+                    .then_with(|| a.path.cmp(&b.path))
+            })
+            .map(|(_, key, _)| key)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, mtime: i64) -> FileHit {
+        FileHit {
+            node: DBEntryId::new("fs_node", path),
+            path: path.into(),
+            name: Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into(),
+            size: 0,
+            mtime: Some(mtime),
+            kind: FileKind::Other,
+        }
+    }
+
+    #[test]
+    fn name_matches_first() {
+        let files = [
+            file("/home/user/report/draft/notes.txt", 0),
+            file("/home/user/docs/report.pdf", 0),
+            file("/home/user/music/song.mp3", 0),
+        ];
+
+        assert_eq!(
+            FileFinder::default().rank(files.iter().enumerate(), "report"),
+            [1, 0]
+        );
+    }
+
+    #[test]
+    fn recent_first_without_text() {
+        let files = [
+            file("/tmp/old", 10),
+            file("/tmp/new", 30),
+            file("/tmp/mid", 20),
+        ];
+
+        assert_eq!(
+            FileFinder::default().rank(files.iter().enumerate(), " "),
+            [1, 2, 0]
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! Render offload for apps asking for the non-default GPU (PrefersNonDefaultGPU)
+
+use std::path::Path;
+
+/// How apps get moved to the discrete GPU on this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuOffload {
+    /// Mesa drivers
+    Prime,
+    /// The proprietary NVIDIA driver, which ignores DRI_PRIME
+    Nvidia,
+}
+
+impl GpuOffload {
+    const NVIDIA_VENDOR: &str = "0x10de";
+
+    /// `None` unless there's more than one GPU
+    pub fn detect() -> Option<Self> {
+        let vendors = std::fs::read_dir("/sys/class/drm")
+            .ok()?
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+
+                // card0-eDP-1 and the like are connectors of the cards
+                name.starts_with("card") && !name.contains('-')
+            })
+            .filter_map(|entry| std::fs::read_to_string(entry.path().join("device/vendor")).ok())
+            .map(|vendor| vendor.trim().to_string())
+            .collect::<Vec<_>>();
+
+        if vendors.len() < 2 {
+            return None;
+        }
+
+        let offload = match vendors.iter().any(|vendor| vendor == Self::NVIDIA_VENDOR)
+            && Path::new("/proc/driver/nvidia").exists()
+        {
+            true => Self::Nvidia,
+            false => Self::Prime,
+        };
+
+        tracing::debug!("Found {} GPUs, offloading with {offload:?}", vendors.len());
+
+        Some(offload)
+    }
+
+    /// Environment of a process that should render on the discrete GPU
+    pub fn env(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Prime => &[("DRI_PRIME", "1")],
+            Self::Nvidia => &[
+                ("__NV_PRIME_RENDER_OFFLOAD", "1"),
+                ("__GLX_VENDOR_LIBRARY_NAME", "nvidia"),
+                ("__VK_LAYER_NV_optimus", "NVIDIA_only"),
+            ],
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use image::{AnimationDecoder, codecs::gif::GifDecoder, imageops::FilterType};
+use itertools::Itertools;
+
+use db::apps::AppIcon;
+use leaper_tracing::{error_limited, warn_limited};
+
+/// How an app icon should be drawn
+#[derive(Debug, Clone)]
+pub enum ResolvedIcon {
+    Svg(PathBuf),
+    Raster(PathBuf),
+    /// Decoded pixmap, for formats the renderers can't read on their own (xpm)
+    Rgba(Arc<RgbaIcon>),
+    /// Multi-frame GIF, only when animated icons are enabled
+    Animated(Arc<AnimatedIcon>),
+    /// The icon exists but couldn't be decoded
+    Broken,
+    Missing,
+}
+
+#[derive(Debug)]
+pub struct RgbaIcon {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct AnimatedIcon {
+    pub frames: Vec<(RgbaIcon, Duration)>,
+    total: Duration,
+}
+
+impl AnimatedIcon {
+    /// Browsers bump delays this short up as well, a lot of GIFs rely on it
+    const MIN_DELAY: Duration = Duration::from_millis(20);
+
+    /// Index of the frame shown `elapsed` into the (looping) animation
+    pub fn frame_at(&self, elapsed: Duration) -> usize {
+        if self.total.is_zero() {
+            return 0;
+        }
+
+        let mut at = Duration::from_nanos((elapsed.as_nanos() % self.total.as_nanos()) as u64);
+
+        self.frames
+            .iter()
+            .position(|(_, delay)| match at < *delay {
+                true => true,
+                false => {
+                    at -= *delay;
+                    false
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Maps indexed icons to something drawable, caching decoded pixmaps
+#[derive(Debug, Clone)]
+pub struct IconResolver {
+    /// Largest width/height handed to the renderer, bigger icons get downscaled
+    max_dim: u32,
+    /// Decode every frame of GIF icons instead of letting the renderer show the first one
+    animated: bool,
+    decoded: Arc<DashMap<PathBuf, Option<Arc<RgbaIcon>>>>,
+    /// Downscaled copies of rasters over `max_dim`, `None` for the ones that are fine as is
+    oversized: Arc<DashMap<PathBuf, Option<Arc<RgbaIcon>>>>,
+    /// `None` for GIFs with a single frame or that failed to decode
+    animations: Arc<DashMap<PathBuf, Option<Arc<AnimatedIcon>>>>,
+    /// Rasters decoded ahead of their first draw, see [`IconResolver::prefetch`]
+    prefetched: Arc<DashMap<PathBuf, Arc<RgbaIcon>>>,
+    accents: Arc<DashMap<PathBuf, Option<[u8; 3]>>>,
+}
+
+impl Default for IconResolver {
+    fn default() -> Self {
+        Self::new(None, false)
+    }
+}
+
+impl IconResolver {
+    /// wgpu's downlevel texture limit, which every device supports. Some wine prefixes ship
+    /// 8192x8192 .ico files that would go over it
+    pub const DEFAULT_MAX_DIM: u32 = 2048;
+    /// Icons get downscaled to this before picking the accent, plenty for an average
+    const ACCENT_SAMPLE: u32 = 32;
+
+    pub fn new(max_dim: Option<u32>, animated: bool) -> Self {
+        Self {
+            max_dim: max_dim.unwrap_or(Self::DEFAULT_MAX_DIM),
+            animated,
+            decoded: Default::default(),
+            oversized: Default::default(),
+            animations: Default::default(),
+            prefetched: Default::default(),
+            accents: Default::default(),
+        }
+    }
+
+    /// Dominant color of the icon as rgb, `None` for svgs and icons without a clear one.
+    /// Decodes the icon on the first call, so better called off the UI thread
+    pub fn accent(&self, icon: Option<&AppIcon>) -> Option<[u8; 3]> {
+        let icon = icon?;
+        let path = PathBuf::from(&icon.path);
+
+        *self.accents.entry(path.clone()).or_insert_with(|| {
+            let rgba = match self.resolve(Some(icon)) {
+                ResolvedIcon::Raster(path) => {
+                    let img = image::open(&path)
+                        .inspect_err(|err| {
+                            warn_limited!(
+                                (&path, "decode"),
+                                "Failed to decode icon {path:?}: {err}"
+                            )
+                        })
+                        .ok()?
+                        .thumbnail(Self::ACCENT_SAMPLE, Self::ACCENT_SAMPLE)
+                        .into_rgba8();
+
+                    Arc::new(RgbaIcon {
+                        width: img.width(),
+                        height: img.height(),
+                        data: img.into_raw(),
+                    })
+                }
+                ResolvedIcon::Rgba(rgba) => rgba,
+                ResolvedIcon::Animated(animation) => {
+                    return animation
+                        .frames
+                        .first()
+                        .and_then(|(rgba, _)| dominant_color(rgba));
+                }
+                ResolvedIcon::Svg(_) | ResolvedIcon::Broken | ResolvedIcon::Missing => {
+                    return None;
+                }
+            };
+
+            dominant_color(&rgba)
+        })
+    }
+
+    /// What [`Self::resolve`] came up with for the icon before, `None` when it still has to
+    /// be resolved. Never touches the disk, so it's fine to call from `view`
+    pub fn cached(&self, icon: Option<&AppIcon>) -> Option<ResolvedIcon> {
+        let Some(icon) = icon else {
+            return Some(ResolvedIcon::Missing);
+        };
+
+        let path = PathBuf::from(&icon.path);
+
+        match (icon.svg, icon.xpm) {
+            (true, _) => Some(ResolvedIcon::Svg(path)),
+            (false, false) if self.animated && path.extension().is_some_and(|ext| ext == "gif") => {
+                let animation = self.animations.get(&path)?.clone();
+
+                Some(match animation {
+                    Some(animation) => ResolvedIcon::Animated(animation),
+                    None => ResolvedIcon::Raster(path),
+                })
+            }
+            (false, false) => {
+                if let Some(rgba) = self.prefetched.get(&path) {
+                    return Some(ResolvedIcon::Rgba(rgba.clone()));
+                }
+
+                let downscaled = self.oversized.get(&path)?.clone();
+
+                Some(match downscaled {
+                    Some(rgba) => ResolvedIcon::Rgba(rgba),
+                    None => ResolvedIcon::Raster(path),
+                })
+            }
+            (false, true) => {
+                let decoded = self.decoded.get(&path)?.clone();
+
+                Some(match decoded {
+                    Some(rgba) => ResolvedIcon::Rgba(rgba),
+                    None => ResolvedIcon::Broken,
+                })
+            }
+        }
+    }
+
+    /// Reads headers and decodes whatever the renderer can't take as is, caching the
+    /// result for [`Self::cached`]. Blocks, so better called off the UI thread
+    pub fn resolve(&self, icon: Option<&AppIcon>) -> ResolvedIcon {
+        let Some(icon) = icon else {
+            return ResolvedIcon::Missing;
+        };
+
+        let path = PathBuf::from(&icon.path);
+
+        match (icon.svg, icon.xpm) {
+            (true, _) => ResolvedIcon::Svg(path),
+            (false, false) if self.animated && path.extension().is_some_and(|ext| ext == "gif") => {
+                let animation = self
+                    .animations
+                    .entry(path.clone())
+                    .or_insert_with(|| self.decode_gif(&path).map(Arc::new))
+                    .clone();
+
+                match animation {
+                    Some(animation) => ResolvedIcon::Animated(animation),
+                    None => ResolvedIcon::Raster(path),
+                }
+            }
+            (false, false) => {
+                if let Some(rgba) = self.prefetched.get(&path) {
+                    return ResolvedIcon::Rgba(rgba.clone());
+                }
+
+                let downscaled = self
+                    .oversized
+                    .entry(path.clone())
+                    .or_insert_with(|| self.downscale_raster(&path).map(Arc::new))
+                    .clone();
+
+                match downscaled {
+                    Some(rgba) => ResolvedIcon::Rgba(rgba),
+                    None => ResolvedIcon::Raster(path),
+                }
+            }
+            (false, true) => {
+                let decoded = self
+                    .decoded
+                    .entry(path.clone())
+                    .or_insert_with(|| {
+                        Self::decode_xpm(&path)
+                            .and_then(|rgba| self.fit(rgba))
+                            .map(Arc::new)
+                    })
+                    .clone();
+
+                match decoded {
+                    Some(rgba) => ResolvedIcon::Rgba(rgba),
+                    None => ResolvedIcon::Broken,
+                }
+            }
+        }
+    }
+
+    /// Decodes `icons` in order until `budget` runs out, so they're ready on the first draw
+    /// instead of the renderer loading them as they show up. Returns how many got through.
+    /// Blocks, so better called off the UI thread
+    pub fn prefetch(&self, icons: &[AppIcon], budget: Duration) -> usize {
+        let start = Instant::now();
+        self.evict_prefetched(icons);
+
+        icons
+            .iter()
+            .take_while(|_| start.elapsed() < budget)
+            .inspect(|icon| {
+                // Pixmaps, animations and oversized icons are decoded right here
+                let ResolvedIcon::Raster(path) = self.resolve(Some(icon)) else {
+                    return;
+                };
+
+                let Ok(img) = image::open(&path).inspect_err(|err| {
+                    warn_limited!((&path, "decode"), "Failed to decode icon {path:?}: {err}")
+                }) else {
+                    return;
+                };
+                let img = img.into_rgba8();
+
+                self.prefetched.insert(
+                    path,
+                    Arc::new(RgbaIcon {
+                        width: img.width(),
+                        height: img.height(),
+                        data: img.into_raw(),
+                    }),
+                );
+            })
+            .count()
+    }
+
+    /// Drops the prefetched icons that aren't among `on_screen` any more, the renderer has
+    /// its own copy of the ones it drew. Nothing to do once all of them are gone
+    pub fn evict_prefetched<'a>(&self, on_screen: impl IntoIterator<Item = &'a AppIcon>) {
+        if self.prefetched.is_empty() {
+            return;
+        }
+
+        let on_screen = on_screen
+            .into_iter()
+            .map(|icon| Path::new(&icon.path))
+            .collect::<HashSet<_>>();
+
+        self.prefetched
+            .retain(|path, _| on_screen.contains(path.as_path()));
+    }
+
+    /// Only decodes the image when its header says it's over the limit
+    fn downscale_raster(&self, path: &PathBuf) -> Option<RgbaIcon> {
+        let (width, height) = image::image_dimensions(path).ok()?;
+
+        if width.max(height) <= self.max_dim {
+            return None;
+        }
+
+        tracing::debug!(
+            "Downscaling {width}x{height} icon {path:?} to fit {}px",
+            self.max_dim
+        );
+
+        let img = image::open(path)
+            .inspect_err(|err| {
+                error_limited!(
+                    (path, "decode-oversized"),
+                    "Failed to decode oversized icon {path:?}: {err}"
+                )
+            })
+            .ok()?
+            .resize(self.max_dim, self.max_dim, FilterType::Triangle)
+            .into_rgba8();
+
+        Some(RgbaIcon {
+            width: img.width(),
+            height: img.height(),
+            data: img.into_raw(),
+        })
+    }
+
+    fn decode_gif(&self, path: &PathBuf) -> Option<AnimatedIcon> {
+        let decoder = File::open(path)
+            .map(BufReader::new)
+            .map_err(image::ImageError::from)
+            .and_then(GifDecoder::new)
+            .inspect_err(|err| {
+                error_limited!(
+                    (path, "open-gif"),
+                    "Failed to open GIF icon {path:?}: {err}"
+                )
+            })
+            .ok()?;
+        let frames = decoder
+            .into_frames()
+            .collect_frames()
+            .inspect_err(|err| {
+                error_limited!(
+                    (path, "decode-gif"),
+                    "Failed to decode GIF icon {path:?}: {err}"
+                )
+            })
+            .ok()?;
+
+        if frames.len() < 2 {
+            return None;
+        }
+
+        let frames = frames
+            .into_iter()
+            .filter_map(|frame| {
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                let delay = Duration::from_millis((numer / denom.max(1)) as u64)
+                    .max(AnimatedIcon::MIN_DELAY);
+                let img = frame.into_buffer();
+
+                let rgba = self.fit(RgbaIcon {
+                    width: img.width(),
+                    height: img.height(),
+                    data: img.into_raw(),
+                })?;
+
+                Some((rgba, delay))
+            })
+            .collect_vec();
+        let total = frames.iter().map(|(_, delay)| *delay).sum();
+
+        Some(AnimatedIcon { frames, total })
+    }
+
+    fn fit(&self, rgba: RgbaIcon) -> Option<RgbaIcon> {
+        if rgba.width.max(rgba.height) <= self.max_dim {
+            return Some(rgba);
+        }
+
+        let img = image::RgbaImage::from_raw(rgba.width, rgba.height, rgba.data)?;
+        let img = image::DynamicImage::from(img)
+            .resize(self.max_dim, self.max_dim, FilterType::Triangle)
+            .into_rgba8();
+
+        Some(RgbaIcon {
+            width: img.width(),
+            height: img.height(),
+            data: img.into_raw(),
+        })
+    }
+
+    fn decode_xpm(path: &PathBuf) -> Option<RgbaIcon> {
+        let s = std::fs::read_to_string(path).ok()?;
+
+        let start = s.find('"').unwrap_or_default();
+        let end = s.rfind('"').unwrap_or_else(|| match s.is_empty() {
+            true => 0,
+            false => s.len() - 1,
+        });
+
+        let lines = &s[start..=end]
+            .lines()
+            .map(|line| line.trim_end_matches(',').trim_matches('"'))
+            .collect_vec();
+
+        let img = ez_pixmap::RgbaImage::from(lines)
+            .inspect_err(|err| {
+                error_limited!(
+                    (path, "parse-xpm"),
+                    "Failed to parse pixmap at {path:?}: {err}\n\nLines:\n{}",
+                    lines.join("\n")
+                )
+            })
+            .ok()?;
+
+        Some(RgbaIcon {
+            width: img.width(),
+            height: img.height(),
+            data: img.data().to_vec(),
+        })
+    }
+}
+
+/// Most common color of the opaque pixels, bucketed to 4 bits per channel and weighted by
+/// saturation so that grey outlines and white backgrounds don't win
+fn dominant_color(rgba: &RgbaIcon) -> Option<[u8; 3]> {
+    let mut buckets = HashMap::<u16, (f32, [u32; 3], u32)>::new();
+
+    for px in rgba.data.chunks_exact(4) {
+        let [r, g, b, a] = [px[0], px[1], px[2], px[3]];
+
+        if a < 128 {
+            continue;
+        }
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let saturation = match max {
+            0 => 0.0,
+            max => (max - min) as f32 / max as f32,
+        };
+
+        let key = ((r as u16 >> 4) << 8) | ((g as u16 >> 4) << 4) | (b as u16 >> 4);
+        let (weight, sum, count) = buckets.entry(key).or_default();
+
+        *weight += 0.1 + saturation;
+        sum[0] += r as u32;
+        sum[1] += g as u32;
+        sum[2] += b as u32;
+        *count += 1;
+    }
+
+    let (_, sum, count) = buckets
+        .into_values()
+        .max_by(|(a, ..), (b, ..)| a.total_cmp(b))?;
+
+    Some(sum.map(|channel| (channel / count) as u8))
+}
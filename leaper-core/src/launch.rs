@@ -0,0 +1,173 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use zbus::zvariant::Value;
+
+use db::apps::{AppAction, AppKind, AppWithIcon};
+use mode::{
+    config::{GpuPreference, LeaperModeConfig},
+    terminal::Terminal,
+};
+
+use crate::{CoreError, CoreResult, gpu::GpuOffload, opener};
+
+/// Starts apps from their desktop entries
+#[derive(Debug, Default, Clone)]
+pub struct Launcher {
+    /// `None` on single GPU machines
+    offload: Option<GpuOffload>,
+    /// `launcher.gpu`
+    gpu: HashMap<String, GpuPreference>,
+    /// What `Terminal=true` apps run in
+    terminal: Terminal,
+}
+
+impl Launcher {
+    pub fn new(config: &LeaperModeConfig) -> Self {
+        Self {
+            offload: GpuOffload::detect(),
+            gpu: config.launcher.gpu.clone(),
+            terminal: config.resolve_terminal(),
+        }
+    }
+
+    /// Whether `app` gets started on the discrete GPU, from its PrefersNonDefaultGPU
+    /// unless `launcher.gpu` says otherwise
+    pub fn discrete_gpu(&self, app: &AppWithIcon) -> bool {
+        self.offload.is_some()
+            && match self.gpu.get(&app.desktop_id()) {
+                Some(preference) => *preference == GpuPreference::Discrete,
+                None => app.prefers_non_default_gpu,
+            }
+    }
+
+    pub async fn launch(&self, app: &AppWithIcon) -> CoreResult<()> {
+        match app.kind {
+            AppKind::Application => self.start(app, None).await,
+            AppKind::Link => {
+                let url = app
+                    .url
+                    .as_ref()
+                    .ok_or_else(|| CoreError::NoUrl(app.name.clone()))?;
+
+                tracing::trace!("Opening {}: {url}", app.name);
+
+                opener::open_uri(url).await
+            }
+        }
+    }
+
+    /// Starts `app` through one of its desktop actions, e.g. "New Private Window"
+    pub async fn launch_action(&self, app: &AppWithIcon, action: &AppAction) -> CoreResult<()> {
+        self.start(app, Some(action)).await
+    }
+
+    async fn start(&self, app: &AppWithIcon, action: Option<&AppAction>) -> CoreResult<()> {
+        let offload = self.offload.filter(|_| self.discrete_gpu(app));
+        let (name, exec) = match action {
+            Some(action) => (format!("{} ({})", app.name, action.name), &action.exec),
+            None => (app.name.clone(), &app.exec),
+        };
+        let exec = match app.terminal && !exec.is_empty() {
+            true => Cow::Owned(self.terminal.wrap(exec)),
+            false => Cow::Borrowed(exec.as_slice()),
+        };
+
+        // Activation can't pass the environment the offload needs, nor open a terminal
+        if !app.dbus_activatable || app.terminal || (offload.is_some() && !exec.is_empty()) {
+            return Self::exec(&name, &exec, offload);
+        }
+
+        match Self::activate(app, action).await {
+            Ok(()) => Ok(()),
+            Err(err) if !exec.is_empty() => {
+                tracing::warn!(
+                    "D-Bus activation of {name} failed, running its Exec instead: {err}"
+                );
+
+                Self::exec(&name, &exec, offload)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn exec(name: &str, exec: &[String], offload: Option<GpuOffload>) -> CoreResult<()> {
+        tracing::trace!(
+            "Running {name}: {} (offload: {offload:?})",
+            leaper_tracing::redact::cmd(exec)
+        );
+
+        let (program, args) = exec
+            .split_first()
+            .ok_or_else(|| CoreError::EmptyExec(name.into()))?;
+
+        std::process::Command::new(program)
+            .args(args)
+            .envs(
+                offload
+                    .map(GpuOffload::env)
+                    .unwrap_or_default()
+                    .iter()
+                    .copied(),
+            )
+            .spawn()
+            .map_err(std::sync::Arc::new)?;
+
+        Ok(())
+    }
+
+    /// `org.freedesktop.Application.Activate` (or `ActivateAction`) on the bus name of
+    /// the desktop file id, the bus starts the app if it isn't running yet
+    async fn activate(app: &AppWithIcon, action: Option<&AppAction>) -> CoreResult<()> {
+        let bus_name = Some(app.desktop_id())
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| CoreError::NoDesktopId(app.name.clone()))?;
+        let object_path = format!("/{}", bus_name.replace('.', "/").replace('-', "_"));
+
+        tracing::trace!(
+            "Activating {} as {bus_name} at {object_path} (action: {:?})",
+            app.name,
+            action.map(|action| &action.id)
+        );
+
+        // Lets the compositor give the new window focus, if we were started with a token
+        let platform_data = std::env::var("XDG_ACTIVATION_TOKEN")
+            .ok()
+            .map(|token| {
+                HashMap::from([
+                    ("activation-token", Value::from(token.clone())),
+                    ("desktop-startup-id", Value::from(token)),
+                ])
+            })
+            .unwrap_or_default();
+
+        let connection = zbus::Connection::session().await?;
+        let interface = Some("org.freedesktop.Application");
+
+        match action {
+            Some(action) => {
+                connection
+                    .call_method(
+                        Some(bus_name.as_str()),
+                        object_path.as_str(),
+                        interface,
+                        "ActivateAction",
+                        &(action.id.as_str(), Vec::<Value>::new(), platform_data),
+                    )
+                    .await?
+            }
+            None => {
+                connection
+                    .call_method(
+                        Some(bus_name.as_str()),
+                        object_path.as_str(),
+                        interface,
+                        "Activate",
+                        &(platform_data,),
+                    )
+                    .await?
+            }
+        };
+
+        Ok(())
+    }
+}
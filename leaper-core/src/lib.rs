@@ -0,0 +1,72 @@
+pub mod batch;
+pub mod browse;
+pub mod catalog;
+pub mod clipboard;
+pub mod collate;
+pub mod display_manager;
+pub mod file_actions;
+pub mod files;
+pub mod finder;
+pub mod gpu;
+pub mod icons;
+pub mod launch;
+pub mod opener;
+pub mod power;
+pub mod query;
+pub mod recall;
+pub mod scoring;
+pub mod search;
+pub mod snapshot;
+pub mod sort;
+pub mod translit;
+
+use std::sync::Arc;
+
+use macros::lerror;
+
+#[lerror]
+#[lerr(prefix = "[leaper_core]", result_name = CoreResult, common)]
+pub enum CoreError {
+    #[lerr(str = "[zbus] {0}")]
+    ZBus(#[lerr(from)] zbus::Error),
+
+    #[lerr(str = "{0}")]
+    DB(#[lerr(from, wrap = Arc)] db::DBError),
+    #[lerr(str = "Live query failed: {0}")]
+    LiveQuery(String),
+
+    #[lerr(str = "App {0:?} has an empty Exec line")]
+    EmptyExec(String),
+    #[lerr(str = "App {0:?} has no desktop file id to activate it by")]
+    NoDesktopId(String),
+    #[lerr(str = "Link {0:?} has no URL")]
+    NoUrl(String),
+
+    #[lerr(str = "Invalid search query: {0}")]
+    Query(#[lerr(from)] parsers::ParseError),
+    #[lerr(str = "Unknown file type {0:?}")]
+    UnknownFileKind(String),
+
+    #[lerr(str = "[serde_json] {0}")]
+    Json(#[lerr(from, wrap = Arc)] serde_json::Error),
+
+    #[lerr(str = "Couldn't move {0:?} to the trash: {1}")]
+    TrashFailed(std::path::PathBuf, String),
+    #[lerr(str = "Neither XDG_DATA_HOME nor HOME is set")]
+    NoHome,
+
+    #[lerr(str = "Empty cmd args list for action {0}")]
+    ActionCMDEmpty(String),
+    #[lerr(str = "The session didn't lock in time, not running {0}")]
+    LockTimeout(String),
+    #[lerr(str = "The lock screen exited ({1}) before locking the session, not running {0}")]
+    LockExited(String, String),
+    #[lerr(str = "No dbus connection!")]
+    NoDBusConnection,
+    #[lerr(str = "Couldn't detect the display manager (no display-manager.service)")]
+    NoDisplayManager,
+    #[lerr(str = "Switching users is not supported with display manager {0:?}")]
+    UnsupportedDisplayManager(String),
+    #[lerr(str = "Failed to switch to the greeter: {0}")]
+    SwitchUserFailed(String),
+}
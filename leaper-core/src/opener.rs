@@ -0,0 +1,115 @@
+use std::{collections::HashMap, os::fd::AsFd, path::Path};
+
+use zbus::{
+    Connection,
+    zvariant::{Fd, OwnedObjectPath, Value},
+};
+
+use crate::CoreResult;
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.OpenURI",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait OpenURI {
+    #[zbus(name = "OpenURI")]
+    fn open_uri(
+        &self,
+        parent_window: &str,
+        uri: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    fn open_file(
+        &self,
+        parent_window: &str,
+        fd: Fd<'_>,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    fn open_directory(
+        &self,
+        parent_window: &str,
+        fd: Fd<'_>,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+}
+
+/// Opens a URI (web link, mailto:, ...) with its default handler through the OpenURI portal,
+/// falls back to xdg-open when the portal isn't there
+pub async fn open_uri(uri: &str) -> CoreResult<()> {
+    let res = async {
+        let connection = Connection::session().await?;
+        let portal = OpenURIProxy::new(&connection).await?;
+
+        portal.open_uri("", uri, HashMap::new()).await
+    }
+    .await;
+
+    match res {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            tracing::debug!("OpenURI portal failed for {uri:?} ({err}), using xdg-open");
+            xdg_open(uri.as_ref()).await
+        }
+    }
+}
+
+/// Opens a local file with its default handler. The portal gets an fd instead of a path,
+/// so it works from inside sandboxes too
+pub async fn open_path(path: impl AsRef<Path>) -> CoreResult<()> {
+    let path = path.as_ref();
+
+    let res = async {
+        let file = std::fs::File::open(path).map_err(zbus::Error::from)?;
+        let connection = Connection::session().await?;
+        let portal = OpenURIProxy::new(&connection).await?;
+
+        portal
+            .open_file("", Fd::from(file.as_fd()), HashMap::new())
+            .await
+    }
+    .await;
+
+    match res {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            tracing::debug!("OpenURI portal failed for {path:?} ({err}), using xdg-open");
+            xdg_open(path.as_os_str()).await
+        }
+    }
+}
+
+/// Shows the directory containing `path` in the file manager, with the entry selected
+/// when the file manager supports it. Falls back to xdg-open on the parent directory
+pub async fn open_containing_folder(path: impl AsRef<Path>) -> CoreResult<()> {
+    let path = path.as_ref();
+
+    let res = async {
+        let file = std::fs::File::open(path).map_err(zbus::Error::from)?;
+        let connection = Connection::session().await?;
+        let portal = OpenURIProxy::new(&connection).await?;
+
+        portal
+            .open_directory("", Fd::from(file.as_fd()), HashMap::new())
+            .await
+    }
+    .await;
+
+    match res {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            tracing::debug!("OpenURI portal failed for {path:?} ({err}), using xdg-open");
+            xdg_open(path.parent().unwrap_or(path).as_os_str()).await
+        }
+    }
+}
+
+async fn xdg_open(target: &std::ffi::OsStr) -> CoreResult<()> {
+    tokio::process::Command::new("xdg-open")
+        .arg(target)
+        .spawn()?;
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use logind_zbus::{manager::ManagerProxy, session::SessionProxy};
+use zbus::{Connection, connection};
+
+use std::time::Duration;
+
+use mode::config::{ActionMethod, Actions, LockFirst, PowerIcons};
+
+use crate::{CoreError, CoreResult, display_manager::DisplayManager};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    Lock,
+    SwitchUser,
+    LogOut,
+    Hibernate,
+    Reboot,
+    Shutdown,
+}
+
+impl PowerAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Lock => "Lock",
+            Self::SwitchUser => "Switch User",
+            Self::LogOut => "Log Out",
+            Self::Hibernate => "Hibernate",
+            Self::Reboot => "Reboot",
+            Self::Shutdown => "Shutdown",
+        }
+    }
+
+    pub fn method<'a>(&self, actions: &'a Actions) -> &'a ActionMethod {
+        match self {
+            Self::Lock => &actions.lock,
+            Self::SwitchUser => &actions.switch_user,
+            Self::LogOut => &actions.log_out,
+            Self::Hibernate => &actions.hibernate,
+            Self::Reboot => &actions.reboot,
+            Self::Shutdown => &actions.shutdown,
+        }
+    }
+
+    /// Whether the session gets locked before the action, see [`LockFirst`]
+    pub fn lock_first(&self, lock_first: &LockFirst) -> bool {
+        match self {
+            Self::SwitchUser => lock_first.switch_user,
+            Self::Hibernate => lock_first.hibernate,
+            Self::Lock | Self::LogOut | Self::Reboot | Self::Shutdown => false,
+        }
+    }
+
+    /// Glyph configured to replace the default icon
+    pub fn icon<'a>(&self, icons: &'a PowerIcons) -> Option<&'a str> {
+        match self {
+            Self::Lock => icons.lock.as_deref(),
+            Self::SwitchUser => icons.switch_user.as_deref(),
+            Self::LogOut => icons.log_out.as_deref(),
+            Self::Hibernate => icons.hibernate.as_deref(),
+            Self::Reboot => icons.reboot.as_deref(),
+            Self::Shutdown => icons.shutdown.as_deref(),
+        }
+    }
+}
+
+/// Session and system power actions, either through logind or user-configured commands
+#[derive(Debug, Clone)]
+pub struct PowerActions {
+    connection: Connection,
+}
+
+impl PowerActions {
+    /// How long [`Self::lock`] waits for the lock screen to come up
+    const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+    const LOCK_POLL: Duration = Duration::from_millis(100);
+
+    pub async fn connect() -> CoreResult<Self> {
+        let connection = connection::Builder::system()?
+            .internal_executor(false)
+            .build()
+            .await?;
+
+        Ok(Self { connection })
+    }
+
+    /// With `lock_first` the action only runs once the session is locked, an action that
+    /// would leave the session open on resume isn't run at all
+    pub async fn perform(
+        self,
+        action: PowerAction,
+        method: ActionMethod,
+        lock_first: bool,
+    ) -> CoreResult<()> {
+        if lock_first {
+            self.lock(action).await?;
+        }
+
+        match method {
+            ActionMethod::Dbus => self.dbus(action).await,
+            ActionMethod::Cmd(args) => Self::cmd(action, args).await,
+        }
+    }
+
+    /// Starts `leaper lock` and waits until it reports the session as locked
+    async fn lock(&self, action: PowerAction) -> CoreResult<()> {
+        let session = SessionProxy::new(&self.connection).await?;
+
+        if session.locked_hint().await? {
+            return Ok(());
+        }
+
+        // The same binary as this one, whatever is (or isn't) called `leaper` on PATH
+        let exe = std::env::current_exe().map_err(std::sync::Arc::new)?;
+        let mut child = tokio::process::Command::new(exe)
+            .arg("lock")
+            .spawn()
+            .map_err(std::sync::Arc::new)?;
+
+        let locked = async {
+            loop {
+                tokio::select! {
+                    status = child.wait() => {
+                        let status = status.map_err(std::sync::Arc::new)?;
+                        return Err(CoreError::LockExited(action.name().into(), status.to_string()));
+                    }
+                    _ = tokio::time::sleep(Self::LOCK_POLL) => {}
+                }
+
+                if session.locked_hint().await? {
+                    return CoreResult::Ok(());
+                }
+            }
+        };
+
+        let res = match tokio::time::timeout(Self::LOCK_TIMEOUT, locked).await {
+            Ok(res) => res,
+            Err(_) => Err(CoreError::LockTimeout(action.name().into())),
+        };
+
+        // Reaped once unlocked, the lock outlives the action
+        tokio::spawn(async move {
+            if let Err(err) = child.wait().await {
+                tracing::warn!("Failed to wait for the lock screen: {err}");
+            }
+        });
+
+        res
+    }
+
+    async fn dbus(self, action: PowerAction) -> CoreResult<()> {
+        let connection = &self.connection;
+
+        match action {
+            PowerAction::Lock => SessionProxy::new(connection).await?.lock().await?,
+            PowerAction::LogOut => SessionProxy::new(connection).await?.terminate().await?,
+            PowerAction::SwitchUser => {
+                let display_manager = DisplayManager::detect()?;
+                tracing::debug!("Switching user through {display_manager:?}");
+
+                display_manager
+                    .switch_to_greeter(Some(self.connection.clone()))
+                    .await?
+            }
+            PowerAction::Hibernate => {
+                ManagerProxy::new(connection)
+                    .await?
+                    .hibernate(false)
+                    .await?
+            }
+            PowerAction::Reboot => ManagerProxy::new(connection).await?.reboot(false).await?,
+            PowerAction::Shutdown => {
+                ManagerProxy::new(connection)
+                    .await?
+                    .power_off(false)
+                    .await?
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn cmd(action: PowerAction, args: Vec<String>) -> CoreResult<()> {
+        let (program, args) = args
+            .split_first()
+            .ok_or_else(|| CoreError::ActionCMDEmpty(action.name().into()))?;
+
+        let mut process = tokio::process::Command::new(program)
+            .args(args)
+            .spawn()
+            .map_err(std::sync::Arc::new)?;
+        process.wait().await.map_err(std::sync::Arc::new)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+
+use db::{
+    DBEnum,
+    fs::{FileFilter, FileHit, FileKind},
+};
+use parsers::query::Term as RawTerm;
+
+pub use parsers::query::SizeCmp;
+
+use crate::{CoreError, CoreResult};
+
+/// Parsed search string, e.g. `report ext:pdf in:~/docs before:2024-01-01`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SearchQuery {
+    pub terms: Vec<Term>,
+}
+
+/// [`parsers::query::Term`] with the file type resolved
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Text(String),
+    Ext(String),
+    In(PathBuf),
+    Kind(FileKind),
+    Size(SizeCmp, u64),
+    Before(i64),
+    After(i64),
+}
+
+impl TryFrom<RawTerm> for Term {
+    type Error = CoreError;
+
+    fn try_from(term: RawTerm) -> CoreResult<Self> {
+        Ok(match term {
+            RawTerm::Text(word) => Self::Text(word),
+            RawTerm::Ext(ext) => Self::Ext(ext),
+            RawTerm::In(dir) => Self::In(dir),
+            RawTerm::Kind(kind) => Self::Kind(
+                FileKind::ALL
+                    .iter()
+                    .copied()
+                    .find(|k| k.name() == kind)
+                    .ok_or(CoreError::UnknownFileKind(kind))?,
+            ),
+            RawTerm::Size(cmp, size) => Self::Size(cmp, size),
+            RawTerm::Before(time) => Self::Before(time),
+            RawTerm::After(time) => Self::After(time),
+        })
+    }
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> CoreResult<Self> {
+        let terms = parsers::query::parse(input)?
+            .into_iter()
+            .map(Term::try_from)
+            .collect::<CoreResult<_>>()?;
+
+        Ok(Self { terms })
+    }
+
+    /// Plain words of the query joined back together, for fuzzy matching on this side
+    pub fn text(&self) -> String {
+        self.terms
+            .iter()
+            .filter_map(|term| match term {
+                Term::Text(word) => Some(word.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Everything but the plain words, the part the DB narrows the files down by
+    pub fn without_text(&self) -> Self {
+        Self {
+            terms: self
+                .terms
+                .iter()
+                .filter(|term| !matches!(term, Term::Text(_)))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Whether everything matching `self` matches `broader` too, so files loaded for
+    /// `broader` only need to be filtered down instead of asked for again. Same non-text
+    /// terms, and each of its words is part of one of ours
+    pub fn narrows(&self, broader: &Self) -> bool {
+        let words = |query: &Self| {
+            query
+                .terms
+                .iter()
+                .filter_map(|term| match term {
+                    Term::Text(word) => Some(word.to_lowercase()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let ours = words(self);
+
+        self.without_text() == broader.without_text()
+            && words(broader)
+                .iter()
+                .all(|word| ours.iter().any(|our| our.contains(word.as_str())))
+    }
+
+    /// Directory of the last `in:` term, what the query is limited to
+    pub fn dir(&self) -> Option<&Path> {
+        self.terms.iter().rev().find_map(|term| match term {
+            Term::In(dir) => Some(dir.as_path()),
+            _ => None,
+        })
+    }
+
+    /// Translates the query into the filter the DB query is built from. Several `ext:`
+    /// and `type:` terms are alternatives, of several `in:` the last one counts
+    pub fn filter(&self) -> FileFilter {
+        let mut filter = FileFilter::default();
+
+        for term in &self.terms {
+            match term {
+                Term::Text(word) => filter.words.push(word.to_lowercase()),
+                Term::Ext(ext) => filter.exts.push(ext.to_lowercase()),
+                Term::In(dir) => filter.under = Some(dir.to_string_lossy().into()),
+                Term::Kind(kind) => filter.kinds.push(*kind),
+                Term::Size(cmp, size) => {
+                    if matches!(cmp, SizeCmp::Greater | SizeCmp::Equal) {
+                        filter.min_size = Some(*size);
+                    }
+
+                    if matches!(cmp, SizeCmp::Less | SizeCmp::Equal) {
+                        filter.max_size = Some(*size);
+                    }
+                }
+                Term::Before(time) => filter.modified_before = Some(*time),
+                Term::After(time) => filter.modified_after = Some(*time),
+            }
+        }
+
+        filter
+    }
+
+    /// Client-side counterpart of [`SearchQuery::filter`], for refining results already
+    /// loaded. Goes through the same filter, so the two can't disagree
+    pub fn matches(&self, hit: &FileHit) -> bool {
+        self.filter().matches(hit)
+    }
+}
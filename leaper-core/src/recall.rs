@@ -0,0 +1,115 @@
+//! Past launcher queries, walked through with Up/Down like a shell history
+
+use db::history::HistoryEntry;
+
+/// Queries ranked by how often and how recently they were used, best first
+#[derive(Debug, Default, Clone)]
+pub struct QueryHistory(Vec<String>);
+
+impl QueryHistory {
+    /// Queries lose half their weight every week they're not used
+    const HALF_LIFE: f64 = 60.0 * 60.0 * 24.0 * 7.0;
+    /// Below this a query is forgotten, a single use fades out after about a month
+    const MIN_WEIGHT: f64 = 0.05;
+    const MAX_ENTRIES: usize = 100;
+
+    pub fn new(queries: Vec<HistoryEntry>, now: i64) -> Self {
+        Self::rank(
+            queries
+                .into_iter()
+                .map(|query| (query.query, query.count, query.last_used)),
+            now,
+        )
+    }
+
+    /// `(query, count, last_used)` entries
+    fn rank(queries: impl Iterator<Item = (String, u64, i64)>, now: i64) -> Self {
+        let mut weighted = queries
+            .map(|(query, count, last_used)| {
+                let age = now.saturating_sub(last_used).max(0) as f64;
+                let weight = count as f64 * 0.5f64.powf(age / Self::HALF_LIFE);
+
+                (query, weight, last_used)
+            })
+            .filter(|(_, weight, _)| *weight >= Self::MIN_WEIGHT)
+            .collect::<Vec<_>>();
+
+        weighted.sort_by(|(_, a, a_used), (_, b, b_used)| {
+            b.total_cmp(a).then_with(|| b_used.cmp(a_used))
+        });
+
+        Self(
+            weighted
+                .into_iter()
+                .map(|(query, ..)| query)
+                .take(Self::MAX_ENTRIES)
+                .collect(),
+        )
+    }
+
+    /// `None` past the oldest one
+    pub fn get(&self, ind: usize) -> Option<&str> {
+        self.0.get(ind).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Key the DB deduplicates queries by, `None` for ones not worth remembering
+    pub fn key(query: &str) -> Option<String> {
+        let query = query.trim();
+        (!query.is_empty()).then(|| query.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 60 * 60 * 24;
+
+    fn history(queries: &[(&str, u64, i64)], now: i64) -> QueryHistory {
+        QueryHistory::rank(
+            queries
+                .iter()
+                .map(|(query, count, last_used)| (query.to_string(), *count, *last_used)),
+            now,
+        )
+    }
+
+    #[test]
+    fn frequent_recent_queries_first() {
+        let now = 100 * DAY;
+        let history = history(
+            &[
+                ("fire", 1, now - DAY),
+                ("term", 10, now - DAY),
+                ("code", 10, now - 30 * DAY),
+            ],
+            now,
+        );
+
+        assert_eq!(history.get(0), Some("term"));
+        assert_eq!(history.get(1), Some("fire"));
+        assert_eq!(history.get(2), Some("code"));
+    }
+
+    #[test]
+    fn stale_queries_are_forgotten() {
+        let now = 100 * DAY;
+        let history = history(&[("gimp", 1, now - 60 * DAY)], now);
+
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn keys_ignore_case_and_padding() {
+        assert_eq!(QueryHistory::key("  Fire "), Some("fire".into()));
+        assert_eq!(QueryHistory::key("   "), None);
+    }
+}
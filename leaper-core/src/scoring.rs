@@ -0,0 +1,206 @@
+//! Match scoring on top of nucleo. Its bonuses are tuned for paths and code,
+//! so e.g. "draw" could rank "LibreOffice Draw" above "Draw". The boosts here
+//! favor the entries people mean when they type (the start of) a name
+
+/// Query is the whole name
+const EXACT_NAME: u16 = 300;
+/// Query is the desktop file id (`firefox` for firefox.desktop,
+/// also the last component of reverse-DNS ids like org.gnome.Nautilus)
+const EXACT_DESKTOP_ID: u16 = 250;
+/// Name starts with the query
+const PREFIX: u16 = 150;
+/// One of the words in the name starts with the query
+const WORD_BOUNDARY: u16 = 75;
+/// Divides the scores of GenericName and Keywords matches, so the same match in the name wins
+const SECONDARY_DIVISOR: u16 = 2;
+/// Comments are whole sentences, their (substring only) matches count the least
+const COMMENT_DIVISOR: u16 = 4;
+
+/// Fuzzy score of `needle` in `haystack`, `needle` has to be lowercase
+pub fn fuzzy(matcher: &mut nucleo::Matcher, haystack: &str, needle: &str) -> Option<u16> {
+    matcher.fuzzy_match(
+        nucleo::Utf32Str::new(haystack, &mut vec![]),
+        nucleo::Utf32Str::new(needle, &mut vec![]),
+    )
+}
+
+/// Fuzzy score with the boosts added, only entries that match at all get boosted
+pub fn boosted(
+    matcher: &mut nucleo::Matcher,
+    name: &str,
+    desktop_id: &str,
+    needle: &str,
+) -> Option<u16> {
+    fuzzy(matcher, name, needle).map(|score| score.saturating_add(boost(name, desktop_id, needle)))
+}
+
+/// Score of the fields describing an app besides its name, e.g. "browser" finds Firefox
+/// through its "Web Browser" GenericName
+pub fn secondary(
+    matcher: &mut nucleo::Matcher,
+    generic_name: Option<&str>,
+    keywords: &[String],
+    comment: Option<&str>,
+    needle: &str,
+) -> Option<u16> {
+    if needle.is_empty() {
+        return None;
+    }
+
+    let generic_name = generic_name.and_then(|generic_name| fuzzy(matcher, generic_name, needle));
+    let keywords = keywords
+        .iter()
+        .filter_map(|keyword| fuzzy(matcher, keyword, needle))
+        .max();
+    let comment = comment
+        .and_then(|comment| {
+            matcher.substring_match(
+                nucleo::Utf32Str::new(comment, &mut vec![]),
+                nucleo::Utf32Str::new(needle, &mut vec![]),
+            )
+        })
+        .map(|score| score / COMMENT_DIVISOR);
+
+    generic_name
+        .max(keywords)
+        .map(|score| score / SECONDARY_DIVISOR)
+        .max(comment)
+}
+
+pub fn boost(name: &str, desktop_id: &str, needle: &str) -> u16 {
+    if needle.is_empty() {
+        return 0;
+    }
+
+    let name = name.to_lowercase();
+    let desktop_id = desktop_id.to_lowercase();
+
+    let name_boost = match () {
+        _ if name == needle => EXACT_NAME,
+        _ if name.starts_with(needle) => PREFIX,
+        _ if words(&name).any(|word| word.starts_with(needle)) => WORD_BOUNDARY,
+        _ => 0,
+    };
+    let id_boost = match desktop_id == needle
+        || desktop_id
+            .rsplit('.')
+            .next()
+            .is_some_and(|last| last == needle)
+    {
+        true => EXACT_DESKTOP_ID,
+        false => 0,
+    };
+
+    name_boost.saturating_add(id_boost)
+}
+
+/// Words split on anything that's not a letter or a digit ("GNU Image Manipulation Program",
+/// "qt5-settings")
+fn words(name: &str) -> impl Iterator<Item = &str> {
+    name.split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(name: &str, desktop_entry_path: &str, needle: &str) -> Option<u16> {
+        let mut matcher = nucleo::Matcher::default();
+        boosted(
+            &mut matcher,
+            name,
+            &db::apps::desktop_id(desktop_entry_path),
+            needle,
+        )
+    }
+
+    #[test]
+    fn exact_name_beats_suffix() {
+        let draw = score("Draw", "/usr/share/applications/draw.desktop", "draw");
+        let libre = score(
+            "LibreOffice Draw",
+            "/usr/share/applications/libreoffice-draw.desktop",
+            "draw",
+        );
+
+        assert!(draw > libre, "{draw:?} <= {libre:?}");
+    }
+
+    #[test]
+    fn prefix_beats_infix() {
+        let firefox = score("Firefox", "/usr/share/applications/firefox.desktop", "fire");
+        let campfire = score(
+            "Campfire Tools",
+            "/usr/share/applications/campfire.desktop",
+            "fire",
+        );
+
+        assert!(firefox > campfire, "{firefox:?} <= {campfire:?}");
+    }
+
+    #[test]
+    fn word_boundary_and_desktop_id() {
+        let vscode = score(
+            "Visual Studio Code",
+            "/usr/share/applications/code.desktop",
+            "code",
+        );
+        let barcode = score(
+            "Barcode Scanner",
+            "/usr/share/applications/barcode.desktop",
+            "code",
+        );
+
+        assert!(vscode > barcode, "{vscode:?} <= {barcode:?}");
+    }
+
+    #[test]
+    fn reverse_dns_desktop_id() {
+        assert_eq!(
+            boost("Files", "org.gnome.Nautilus", "nautilus"),
+            EXACT_DESKTOP_ID
+        );
+    }
+
+    #[test]
+    fn generic_name_and_keywords() {
+        let mut matcher = nucleo::Matcher::default();
+
+        let firefox = secondary(&mut matcher, Some("Web Browser"), &[], None, "browser");
+        let keyword = secondary(
+            &mut matcher,
+            None,
+            &["Internet".into(), "WWW".into()],
+            None,
+            "www",
+        );
+        let browser = score(
+            "Browser",
+            "/usr/share/applications/browser.desktop",
+            "browser",
+        );
+
+        assert!(firefox.is_some());
+        assert!(keyword.is_some());
+        assert!(browser > firefox, "{browser:?} <= {firefox:?}");
+    }
+
+    #[test]
+    fn comments_need_a_substring_match() {
+        let mut matcher = nucleo::Matcher::default();
+        let comment = Some("Browse the World Wide Web");
+
+        assert!(secondary(&mut matcher, None, &[], comment, "world").is_some());
+        assert_eq!(secondary(&mut matcher, None, &[], comment, "bwww"), None);
+    }
+
+    #[test]
+    fn no_match_no_boost() {
+        assert_eq!(
+            score("Draw", "/usr/share/applications/draw.desktop", "xyz"),
+            None
+        );
+        assert_eq!(boost("Draw", "draw", ""), 0);
+    }
+}
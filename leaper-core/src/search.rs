@@ -0,0 +1,338 @@
+use std::borrow::Borrow;
+
+use itertools::Itertools;
+
+use db::apps::AppWithIcon;
+use mode::config::LauncherConfig;
+
+use crate::{
+    collate::NameCollator,
+    scoring,
+    sort::{AppComparator, Favorites, Launches, QueryChoices},
+    translit::Transliterator,
+};
+
+/// Fuzzy app search with the launcher's ordering rules applied. Searching doesn't change
+/// it, so one can be shared with a search running off the UI thread
+#[derive(Clone)]
+pub struct AppSearch {
+    translit: Option<Transliterator>,
+    collator: NameCollator,
+    choices: QueryChoices,
+    favorites: Favorites,
+    config: LauncherConfig,
+}
+
+impl AppSearch {
+    pub fn new(config: LauncherConfig) -> Self {
+        Self {
+            translit: Transliterator::new(&config.transliteration),
+            collator: NameCollator::new(config.collation.as_deref()),
+            choices: Default::default(),
+            favorites: Default::default(),
+            config,
+        }
+    }
+
+    pub fn set_choices(&mut self, choices: QueryChoices) {
+        self.choices = choices;
+    }
+
+    /// Pinned to the top of the list for an empty query, takes a [`Self::sort`] to apply
+    pub fn set_favorites(&mut self, favorites: Favorites) {
+        self.favorites = favorites;
+    }
+
+    pub fn favorites(&self) -> &Favorites {
+        &self.favorites
+    }
+
+    /// Orders the full list for an empty query
+    pub fn sort<A: Borrow<AppWithIcon>>(&self, apps: &mut [A], launches: &Launches) {
+        let comparator = AppComparator::new(self.config.empty_query, launches, &self.collator)
+            .pinning(&self.favorites);
+        apps.sort_by(|a, b| comparator.compare(a.borrow(), b.borrow()));
+    }
+
+    /// Inserts `app` into an already sorted list (or moves it, if it's already there)
+    /// without resorting the whole list. `existing` is where the caller last saw the app,
+    /// `None` if it's new. A stale position is looked up again
+    /// Returns whether anything changed, identical snapshots of an app are ignored
+    pub fn upsert<A: Borrow<AppWithIcon>>(
+        &self,
+        apps: &mut Vec<A>,
+        launches: &Launches,
+        app: A,
+        existing: Option<usize>,
+    ) -> bool {
+        let comparator = AppComparator::new(self.config.empty_query, launches, &self.collator)
+            .pinning(&self.favorites);
+        let key = comparator.key(app.borrow());
+        let id = &app.borrow().id;
+
+        let existing = existing.and_then(|ind| {
+            match apps.get(ind).is_some_and(|probe| probe.borrow().id == *id) {
+                true => Some(ind),
+                false => apps.iter().position(|probe| probe.borrow().id == *id),
+            }
+        });
+
+        if let Some(existing) = existing {
+            if apps[existing].borrow() == app.borrow() {
+                return false;
+            }
+
+            // Updates usually keep the sort key, the entry stays where it is
+            if comparator.key(apps[existing].borrow()) == key {
+                apps[existing] = app;
+                return true;
+            }
+
+            // Renamed/relaunched apps have to be moved
+            apps.remove(existing);
+        }
+
+        let ind = apps.partition_point(|probe| comparator.key(probe.borrow()) < key);
+        apps.insert(ind, app);
+
+        true
+    }
+
+    /// Apps matching `query`, best matches first
+    pub fn search<A: Borrow<AppWithIcon> + Clone>(
+        &self,
+        apps: &[A],
+        launches: &Launches,
+        query: &str,
+    ) -> Vec<A> {
+        self.search_indices(apps, launches, query)
+            .into_iter()
+            .map(|ind| apps[ind].clone())
+            .collect()
+    }
+
+    /// Positions in `apps` of the ones matching `query`, best matches first
+    pub fn search_indices<A: Borrow<AppWithIcon>>(
+        &self,
+        apps: &[A],
+        launches: &Launches,
+        query: &str,
+    ) -> Vec<usize> {
+        self.search_scored(apps, launches, query)
+            .into_iter()
+            .map(|(ind, _)| ind)
+            .collect()
+    }
+
+    /// [`Self::search_indices`] along with the scores, to rank them against other results
+    pub fn search_scored<A: Borrow<AppWithIcon>>(
+        &self,
+        apps: &[A],
+        launches: &Launches,
+        query: &str,
+    ) -> Vec<(usize, u16)> {
+        let comparator = AppComparator::new(self.config.tie_break, launches, &self.collator);
+        let needle = self.needle(query);
+        let mut matcher = nucleo::Matcher::default();
+
+        apps.iter()
+            .map(Borrow::borrow)
+            .enumerate()
+            .filter_map(|(ind, app)| {
+                Self::score(&mut matcher, self.translit.as_ref(), app, &needle).map(|score| {
+                    let score = score.saturating_add(self.choices.boost(&needle.plain, &app.id));
+                    (score, ind, app)
+                })
+            })
+            .sorted_by(|(score_a, _, a), (score_b, _, b)| {
+                score_b.cmp(score_a).then_with(|| comparator.compare(a, b))
+            })
+            .map(|(score, ind, _)| (ind, score))
+            .collect()
+    }
+
+    /// Whether `app` matches `query`, to tell if a change to it can alter the results
+    /// by re-scoring just that entry instead of rerunning the whole search
+    pub fn matches(&self, app: &AppWithIcon, query: &str) -> bool {
+        let needle = self.needle(query);
+        Self::score(
+            &mut nucleo::Matcher::default(),
+            self.translit.as_ref(),
+            app,
+            &needle,
+        )
+        .is_some()
+    }
+
+    fn needle(&self, query: &str) -> Needle {
+        let plain = query.to_lowercase();
+        let translit = self
+            .translit
+            .as_ref()
+            .map(|translit| translit.normalize(&plain));
+
+        Needle { plain, translit }
+    }
+
+    fn score(
+        matcher: &mut nucleo::Matcher,
+        translit: Option<&Transliterator>,
+        app: &AppWithIcon,
+        needle: &Needle,
+    ) -> Option<u16> {
+        let desktop_id = app.desktop_id();
+
+        let score = scoring::boosted(matcher, &app.name, &desktop_id, &needle.plain);
+        // Untranslated names of translated apps, e.g. "Files" for "Dateien"
+        let canonical_score = Some(&app.canonical_name)
+            .filter(|canonical| !canonical.is_empty() && **canonical != app.name)
+            .and_then(|canonical| scoring::boosted(matcher, canonical, &desktop_id, &needle.plain));
+        let translit_score =
+            translit
+                .zip(needle.translit.as_ref())
+                .and_then(|(translit, needle)| {
+                    scoring::boosted(matcher, &translit.normalize(&app.name), &desktop_id, needle)
+                });
+
+        let secondary = scoring::secondary(
+            matcher,
+            app.generic_name.as_deref(),
+            &app.keywords,
+            app.comment.as_deref(),
+            &needle.plain,
+        );
+
+        score
+            .max(canonical_score)
+            .max(translit_score)
+            .max(secondary)
+    }
+}
+
+struct Needle {
+    plain: String,
+    translit: Option<String>,
+}
+
+impl Default for AppSearch {
+    fn default() -> Self {
+        Self::new(LauncherConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use db::{DBEntryId, history::AppLaunch};
+    use mode::config::SortOrder;
+
+    use super::*;
+    use crate::sort;
+
+    fn app(name: &str) -> AppWithIcon {
+        AppWithIcon {
+            id: DBEntryId::new("app", name),
+            desktop_entry_path: format!("/usr/share/applications/{name}.desktop"),
+            name: name.into(),
+            canonical_name: String::new(),
+            generic_name: None,
+            comment: None,
+            keywords: vec![],
+            kind: Default::default(),
+            exec: vec![],
+            url: None,
+            categories: vec![],
+            icon: None,
+            only_show_in: vec![],
+            not_show_in: vec![],
+            no_display: false,
+            hidden: false,
+            terminal: false,
+            try_exec_missing: false,
+            dbus_activatable: false,
+            prefers_non_default_gpu: false,
+            actions: vec![],
+        }
+    }
+
+    fn launched(app: &AppWithIcon, count: u64, ago: i64) -> (DBEntryId, AppLaunch) {
+        let launch = AppLaunch {
+            id: DBEntryId::new("app_launch", app.name.as_str()),
+            app: app.id.clone(),
+            count,
+            last_launched: sort::now() - ago,
+        };
+
+        (app.id.clone(), launch)
+    }
+
+    fn names(apps: &[AppWithIcon]) -> Vec<&str> {
+        apps.iter().map(|app| app.name.as_str()).collect()
+    }
+
+    #[test]
+    fn upsert_matches_sort() {
+        let apps = [
+            "firefox",
+            "Files",
+            "alacritty",
+            "Zed",
+            "blender",
+            "gimp",
+            "Krita",
+            "mpv",
+            "Obs",
+            "steam",
+        ]
+        .map(app);
+
+        let launches = [
+            launched(&apps[0], 12, 60),
+            launched(&apps[4], 3, 60 * 60 * 30),
+            launched(&apps[7], 40, 60 * 60 * 24 * 40),
+            launched(&apps[9], 1, 10),
+        ]
+        .into_iter()
+        .collect::<Launches>();
+
+        for order in [
+            SortOrder::Alphabetical,
+            SortOrder::Frecency,
+            SortOrder::LastLaunched,
+        ] {
+            let mut search = AppSearch::new(LauncherConfig {
+                empty_query: order,
+                ..Default::default()
+            });
+            search.set_favorites([apps[5].id.clone()].into());
+
+            let mut sorted = apps.to_vec();
+            search.sort(&mut sorted, &launches);
+
+            // Every 3rd one, wrapping around, goes through all 10
+            let mut upserted = vec![];
+            for ind in (0..apps.len()).map(|ind| ind * 3 % apps.len()) {
+                assert!(search.upsert(&mut upserted, &launches, apps[ind].clone(), None));
+            }
+
+            assert_eq!(names(&upserted), names(&sorted), "{order:?}");
+        }
+    }
+
+    #[test]
+    fn upsert_moves_renamed() {
+        let search = AppSearch::default();
+        let launches = Launches::default();
+
+        let mut apps = ["alacritty", "blender", "firefox"].map(app).to_vec();
+        search.sort(&mut apps, &launches);
+
+        let mut renamed = apps[0].clone();
+        renamed.name = "zathura".into();
+
+        assert!(search.upsert(&mut apps, &launches, renamed, Some(0)));
+
+        let unchanged = apps[1].clone();
+        assert!(!search.upsert(&mut apps, &launches, unchanged, Some(1)));
+        assert_eq!(names(&apps), ["blender", "firefox", "zathura"]);
+    }
+}
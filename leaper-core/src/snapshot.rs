@@ -0,0 +1,57 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use db::apps::AppWithIcon;
+use leaper_error::IntoContext;
+
+use crate::{CoreError, CoreResult};
+
+/// App list the launcher showed last time, so the next start has something to render
+/// before the DB connection is up
+pub struct AppSnapshot;
+
+impl AppSnapshot {
+    const FILE_NAME: &str = "apps.json";
+
+    pub fn path(cache_dir: &Path) -> PathBuf {
+        cache_dir.join(Self::FILE_NAME)
+    }
+
+    /// [`None`] when there's no snapshot yet or it can't be read anymore
+    /// (e.g. written by an older version)
+    pub async fn load(path: PathBuf) -> Option<Vec<AppWithIcon>> {
+        let contents = tokio::fs::read(&path).await.ok()?;
+
+        serde_json::from_slice(&contents)
+            .inspect_err(|err| tracing::debug!("Ignoring app snapshot {path:?}: {err}"))
+            .ok()
+    }
+
+    /// Takes the launcher's shared entries as they are, the list is only read
+    pub async fn save(path: PathBuf, apps: Vec<Arc<AppWithIcon>>) -> CoreResult<()> {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .err_context::<CoreError>(format!("Creating {dir:?}"))?;
+        }
+
+        let apps = apps
+            .iter()
+            .map(AsRef::as_ref)
+            .collect::<Vec<&AppWithIcon>>();
+        let contents = serde_json::to_vec(&apps).map_err(Arc::new)?;
+
+        // Written next to it and renamed, so a concurrent start never reads half a file
+        let tmp = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, contents)
+            .await
+            .err_context::<CoreError>(format!("Writing {tmp:?}"))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .err_context::<CoreError>(format!("Renaming {tmp:?} to {path:?}"))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{HashMap, HashSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use db::{
+    DBEntryId,
+    apps::AppWithIcon,
+    history::{AppLaunch, QueryChoice},
+};
+use mode::config::SortOrder;
+
+use crate::collate::NameCollator;
+
+pub type Launches = HashMap<DBEntryId, AppLaunch>;
+
+/// Apps pinned to the top of the list for an empty query
+pub type Favorites = HashSet<DBEntryId>;
+
+/// Apps picked for past search queries ("adaptive results"), grouped by app since every
+/// app gets looked up on each query
+#[derive(Debug, Default, Clone)]
+pub struct QueryChoices(HashMap<DBEntryId, Vec<Choice>>);
+
+#[derive(Debug, Clone)]
+struct Choice {
+    query: String,
+    count: u64,
+    last_chosen: i64,
+}
+
+impl QueryChoices {
+    /// Choices lose half their weight every week they're not repeated
+    const HALF_LIFE: f64 = 60.0 * 60.0 * 24.0 * 7.0;
+    const BOOST_PER_CHOICE: f64 = 40.0;
+    const MAX_BOOST: f64 = 200.0;
+
+    pub fn new(choices: Vec<QueryChoice>) -> Self {
+        let mut by_app = HashMap::<DBEntryId, Vec<Choice>>::new();
+
+        for choice in choices {
+            by_app.entry(choice.app).or_default().push(Choice {
+                query: choice.query,
+                count: choice.count,
+                last_chosen: choice.last_chosen,
+            });
+        }
+
+        Self(by_app)
+    }
+
+    /// Boost for `app` when searching `query` (lowercase). Choices count for queries
+    /// that are a prefix of theirs ("fi" after picking Firefox for "fire") and the other way around
+    pub fn boost(&self, query: &str, app: &DBEntryId) -> u16 {
+        let Some(choices) = self.0.get(app).filter(|_| !query.is_empty()) else {
+            return 0;
+        };
+
+        let now = now();
+        let weight = choices
+            .iter()
+            .filter(|choice| choice.query.starts_with(query) || query.starts_with(&choice.query))
+            .map(|choice| {
+                let age = now.saturating_sub(choice.last_chosen).max(0) as f64;
+                choice.count as f64 * 0.5f64.powf(age / Self::HALF_LIFE)
+            })
+            .sum::<f64>();
+
+        (weight * Self::BOOST_PER_CHOICE).min(Self::MAX_BOOST) as u16
+    }
+}
+
+pub(crate) fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Favorites first, then higher rank, then by name
+#[derive(Debug)]
+pub struct SortKey<'a> {
+    favorite: Reverse<bool>,
+    rank: Reverse<i64>,
+    name: &'a str,
+    collator: &'a NameCollator,
+}
+
+impl Ord for SortKey<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.favorite
+            .cmp(&other.favorite)
+            .then_with(|| self.rank.cmp(&other.rank))
+            .then_with(|| self.collator.compare(self.name, other.name))
+    }
+}
+
+impl PartialOrd for SortKey<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SortKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SortKey<'_> {}
+
+/// Secondary ordering for entries nucleo scored equally,
+/// or for the whole list when the search query is empty
+pub struct AppComparator<'a> {
+    order: SortOrder,
+    launches: &'a Launches,
+    /// Only pinned when listing everything, see [`Self::pinning`]
+    favorites: Option<&'a Favorites>,
+    collator: &'a NameCollator,
+    now: i64,
+}
+
+impl<'a> AppComparator<'a> {
+    const HOUR: i64 = 60 * 60;
+    const DAY: i64 = Self::HOUR * 24;
+    const WEEK: i64 = Self::DAY * 7;
+
+    pub fn new(order: SortOrder, launches: &'a Launches, collator: &'a NameCollator) -> Self {
+        Self {
+            order,
+            launches,
+            favorites: None,
+            collator,
+            now: now(),
+        }
+    }
+
+    /// Puts `favorites` before everything else
+    pub fn pinning(self, favorites: &'a Favorites) -> Self {
+        Self {
+            favorites: Some(favorites),
+            ..self
+        }
+    }
+
+    pub fn compare(&self, a: &AppWithIcon, b: &AppWithIcon) -> Ordering {
+        self.key(a).cmp(&self.key(b))
+    }
+
+    /// Position of `app` in the ordering, computed once so it can be
+    /// reused across comparisons (e.g. while binary searching for an insertion point)
+    pub fn key<'b>(&'b self, app: &'b AppWithIcon) -> SortKey<'b> {
+        let rank = match self.order {
+            SortOrder::Alphabetical => 0,
+            // Weights are multiples of 0.5, so this is lossless
+            SortOrder::Frecency => (self.frecency(app) * 2.0) as i64,
+            SortOrder::LastLaunched => self.last_launched(app),
+        };
+
+        SortKey {
+            favorite: Reverse(
+                self.favorites
+                    .is_some_and(|favorites| favorites.contains(&app.id)),
+            ),
+            rank: Reverse(rank),
+            name: &app.name,
+            collator: self.collator,
+        }
+    }
+
+    fn frecency(&self, app: &AppWithIcon) -> f64 {
+        let Some(launch) = self.launches.get(&app.id) else {
+            return 0.0;
+        };
+
+        let weight = match self.now - launch.last_launched {
+            age if age < Self::HOUR => 4.0,
+            age if age < Self::DAY => 2.0,
+            age if age < Self::WEEK => 1.0,
+            _ => 0.5,
+        };
+
+        launch.count as f64 * weight
+    }
+
+    fn last_launched(&self, app: &AppWithIcon) -> i64 {
+        self.launches
+            .get(&app.id)
+            .map(|launch| launch.last_launched)
+            .unwrap_or(i64::MIN)
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+/// Folds text from the configured scripts into latin,
+/// so that e.g. "firefoks" matches "Фаерфокс" and "фаер" matches "Firefox"
+#[derive(Clone)]
+pub struct Transliterator(HashMap<char, &'static str>);
+
+impl Transliterator {
+    pub fn new(locales: &[String]) -> Option<Self> {
+        let table: HashMap<_, _> = locales
+            .iter()
+            .filter_map(|locale| {
+                let table = Self::table(locale);
+
+                if table.is_none() {
+                    tracing::warn!("No transliteration table for locale {locale:?}");
+                }
+
+                table
+            })
+            // Earlier locales take precedence on conflicting characters
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+
+        (!table.is_empty()).then_some(Self(table))
+    }
+
+    pub fn normalize(&self, str: &str) -> String {
+        str.chars().flat_map(char::to_lowercase).fold(
+            String::with_capacity(str.len()),
+            |mut res, ch| {
+                match self.0.get(&ch) {
+                    Some(latin) => res.push_str(latin),
+                    None => res.push(ch),
+                }
+
+                res
+            },
+        )
+    }
+
+    fn table(locale: &str) -> Option<&'static [(char, &'static str)]> {
+        let lang = locale
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or(locale)
+            .to_lowercase();
+
+        match lang.as_str() {
+            "ru" => Some(RU),
+            "uk" => Some(UK),
+            "be" => Some(BE),
+            "el" => Some(EL),
+            _ => None,
+        }
+    }
+}
+
+const RU: &[(char, &str)] = &[
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('г', "g"),
+    ('д', "d"),
+    ('е', "e"),
+    ('ё', "e"),
+    ('ж', "zh"),
+    ('з', "z"),
+    ('и', "i"),
+    ('й', "y"),
+    ('к', "k"),
+    ('л', "l"),
+    ('м', "m"),
+    ('н', "n"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('у', "u"),
+    ('ф', "f"),
+    ('х', "kh"),
+    ('ц', "ts"),
+    ('ч', "ch"),
+    ('ш', "sh"),
+    ('щ', "shch"),
+    ('ъ', ""),
+    ('ы', "y"),
+    ('ь', ""),
+    ('э', "e"),
+    ('ю', "yu"),
+    ('я', "ya"),
+];
+
+const UK: &[(char, &str)] = &[
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('г', "h"),
+    ('ґ', "g"),
+    ('д', "d"),
+    ('е', "e"),
+    ('є', "ye"),
+    ('ж', "zh"),
+    ('з', "z"),
+    ('и', "y"),
+    ('і', "i"),
+    ('ї', "yi"),
+    ('й', "y"),
+    ('к', "k"),
+    ('л', "l"),
+    ('м', "m"),
+    ('н', "n"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('у', "u"),
+    ('ф', "f"),
+    ('х', "kh"),
+    ('ц', "ts"),
+    ('ч', "ch"),
+    ('ш', "sh"),
+    ('щ', "shch"),
+    ('ь', ""),
+    ('ю', "yu"),
+    ('я', "ya"),
+    ('\'', ""),
+];
+
+const BE: &[(char, &str)] = &[
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('г', "h"),
+    ('д', "d"),
+    ('е', "ie"),
+    ('ё', "io"),
+    ('ж', "zh"),
+    ('з', "z"),
+    ('і', "i"),
+    ('й', "j"),
+    ('к', "k"),
+    ('л', "l"),
+    ('м', "m"),
+    ('н', "n"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('у', "u"),
+    ('ў', "u"),
+    ('ф', "f"),
+    ('х', "kh"),
+    ('ц', "ts"),
+    ('ч', "ch"),
+    ('ш', "sh"),
+    ('ы', "y"),
+    ('ь', ""),
+    ('э', "e"),
+    ('ю', "iu"),
+    ('я', "ia"),
+];
+
+const EL: &[(char, &str)] = &[
+    ('α', "a"),
+    ('ά', "a"),
+    ('β', "v"),
+    ('γ', "g"),
+    ('δ', "d"),
+    ('ε', "e"),
+    ('έ', "e"),
+    ('ζ', "z"),
+    ('η', "i"),
+    ('ή', "i"),
+    ('θ', "th"),
+    ('ι', "i"),
+    ('ί', "i"),
+    ('ϊ', "i"),
+    ('ΐ', "i"),
+    ('κ', "k"),
+    ('λ', "l"),
+    ('μ', "m"),
+    ('ν', "n"),
+    ('ξ', "x"),
+    ('ο', "o"),
+    ('ό', "o"),
+    ('π', "p"),
+    ('ρ', "r"),
+    ('σ', "s"),
+    ('ς', "s"),
+    ('τ', "t"),
+    ('υ', "y"),
+    ('ύ', "y"),
+    ('ϋ', "y"),
+    ('ΰ', "y"),
+    ('φ', "f"),
+    ('χ', "ch"),
+    ('ψ', "ps"),
+    ('ω', "o"),
+    ('ώ', "o"),
+];
@@ -0,0 +1,277 @@
+use std::{collections::HashMap, fs::Permissions, os::unix::fs::PermissionsExt, time::Duration};
+
+use color_eyre::{
+    Result,
+    eyre::{bail, ensure},
+};
+use serde::Serialize;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+};
+
+use db::{
+    DBEnum, InstrumentedDBQuery,
+    apps::GetAppWithIconsQuery,
+    fs::{FileFilter, FileKind, SearchFilesQuery},
+};
+
+use crate::{DB_REF, api_socket_path, prepare_socket};
+
+/// One page of results, `total` is only known for the app catalog
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub offset: usize,
+    pub limit: usize,
+    pub total: Option<usize>,
+    pub items: Vec<T>,
+}
+
+impl<T> Page<T> {
+    const DEFAULT_LIMIT: usize = 50;
+    const MAX_LIMIT: usize = 500;
+}
+
+/// Longest request line or header accepted
+const MAX_LINE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+/// Clients get this long to send the whole request
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Read-only HTTP API over the app catalog and file index, for scripts and other launchers.
+/// Served on [`api_socket_path`] next to the RPC socket, so only the user can reach it,
+/// unlike a localhost port that sandboxed apps and web pages can get to as well:
+///
+/// - `GET /apps?offset=&limit=`
+/// - `GET /files?text=&ext=&kind=&under=&offset=&limit=` (`ext` and `kind` can be repeated)
+pub async fn serve() -> Result<()> {
+    let socket = api_socket_path()?;
+    prepare_socket(&socket)?;
+
+    let listener = UnixListener::bind(&socket)?;
+    std::fs::set_permissions(&socket, Permissions::from_mode(0o600))?;
+
+    tracing::info!("Serving the read-only API on {socket:?}");
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+
+        tokio::spawn(async move {
+            if let Err(err) = handle(stream).await {
+                tracing::warn!("API request failed: {err}");
+            }
+        });
+    }
+}
+
+async fn handle(stream: UnixStream) -> Result<()> {
+    let mut stream = BufReader::new(stream);
+
+    let request = tokio::time::timeout(READ_TIMEOUT, Request::read(&mut stream)).await??;
+
+    let mut parts = request.line.split_whitespace();
+    let (method, target) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or("/"),
+    );
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params = Params::parse(query);
+
+    let (status, body) = match (method, path) {
+        ("GET", "/apps") => respond(apps(&params).await),
+        ("GET", "/files") => respond(files(&params).await),
+        ("GET", _) => ("404 Not Found", error_body("Unknown endpoint")),
+        _ => (
+            "405 Method Not Allowed",
+            error_body("Only GET is supported"),
+        ),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+
+    stream.get_mut().write_all(response.as_bytes()).await?;
+    stream.get_mut().shutdown().await?;
+
+    Ok(())
+}
+
+/// The parts of a request the API looks at
+struct Request {
+    line: String,
+}
+
+impl Request {
+    async fn read(stream: &mut BufReader<UnixStream>) -> Result<Self> {
+        let line = read_line(stream).await?;
+
+        // GETs don't have a body, the headers end the request
+        for _ in 0..=MAX_HEADERS {
+            let header = read_line(stream).await?;
+
+            if header.trim().is_empty() {
+                return Ok(Self { line });
+            }
+        }
+
+        bail!("Request has more than {MAX_HEADERS} headers")
+    }
+}
+
+/// A line of at most [`MAX_LINE`] bytes, empty at the end of the stream
+async fn read_line(stream: &mut BufReader<UnixStream>) -> Result<String> {
+    let mut line = String::new();
+    (&mut *stream)
+        .take(MAX_LINE as u64)
+        .read_line(&mut line)
+        .await?;
+
+    ensure!(
+        line.is_empty() || line.ends_with('\n'),
+        "Request line or header is longer than {MAX_LINE} bytes"
+    );
+
+    Ok(line)
+}
+
+fn respond<T: Serialize>(res: Result<Page<T>>) -> (&'static str, String) {
+    match res.and_then(|page| Ok(serde_json::to_string(&page)?)) {
+        Ok(body) => ("200 OK", body),
+        Err(err) => ("500 Internal Server Error", error_body(&err.to_string())),
+    }
+}
+
+fn error_body(msg: &str) -> String {
+    serde_json::json!({ "error": msg }).to_string()
+}
+
+async fn apps(params: &Params) -> Result<Page<db::apps::AppWithIcon>> {
+    let db = DB_REF.get().unwrap();
+    let (offset, limit) = params.paging();
+
+    let apps = GetAppWithIconsQuery::new(None, true, Default::default())
+        .instrumented_execute(db.clone())
+        .await?;
+    let total = apps.len();
+    let items = apps.into_iter().skip(offset).take(limit).collect();
+
+    Ok(Page {
+        offset,
+        limit,
+        total: Some(total),
+        items,
+    })
+}
+
+async fn files(params: &Params) -> Result<Page<db::fs::FileHit>> {
+    let db = DB_REF.get().unwrap();
+    let (offset, limit) = params.paging();
+
+    let filter = FileFilter {
+        words: params
+            .first("text")
+            .map(|text| {
+                text.to_lowercase()
+                    .split_whitespace()
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        exts: params.all("ext").map(str::to_lowercase).collect(),
+        under: params.first("under").map(str::to_string),
+        kinds: params
+            .all("kind")
+            .filter_map(|name| {
+                FileKind::ALL
+                    .iter()
+                    .copied()
+                    .find(|kind| kind.name() == name)
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let items = SearchFilesQuery::new(filter, offset.saturating_add(limit))
+        .instrumented_execute(db.clone())
+        .await?
+        .into_iter()
+        .skip(offset)
+        .collect();
+
+    Ok(Page {
+        offset,
+        limit,
+        total: None,
+        items,
+    })
+}
+
+/// Percent-decoded query string parameters
+struct Params(HashMap<String, Vec<String>>);
+
+impl Params {
+    fn parse(query: &str) -> Self {
+        let mut params = HashMap::<String, Vec<String>>::new();
+
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            params.entry(decode(key)).or_default().push(decode(value));
+        }
+
+        Self(params)
+    }
+
+    fn first(&self, key: &str) -> Option<&str> {
+        self.all(key).next()
+    }
+
+    fn all(&self, key: &str) -> impl Iterator<Item = &str> {
+        self.0
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    fn paging(&self) -> (usize, usize) {
+        let num = |key| {
+            self.first(key)
+                .and_then(|value| value.parse::<usize>().ok())
+        };
+
+        (
+            num("offset").unwrap_or_default(),
+            num("limit")
+                .unwrap_or(Page::<()>::DEFAULT_LIMIT)
+                .min(Page::<()>::MAX_LIMIT),
+        )
+    }
+}
+
+fn decode(s: &str) -> String {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [iter.next(), iter.next()];
+                let decoded = match hex {
+                    [Some(hi), Some(lo)] => std::str::from_utf8(&[hi, lo])
+                        .ok()
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+                    _ => None,
+                };
+
+                bytes.push(decoded.unwrap_or(b'%'));
+            }
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -0,0 +1,347 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        LazyLock,
+        atomic::{AtomicBool, Ordering::SeqCst},
+    },
+};
+
+use color_eyre::Result;
+use futures::StreamExt;
+use itertools::Itertools;
+use tokio::task::{self, JoinSet};
+
+use db::{
+    DBAction, DBNotification, InstrumentedDBQuery,
+    apps::{
+        DeleteAppQuery, GetAppRecordKeysQuery, GetTryExecAppsQuery, LiveSearchAppsQuery,
+        SetTryExecMissingQuery, set_search_path, try_exec_found,
+    },
+};
+
+use crate::{
+    DB_REF,
+    fs::{IndexStats, search_paths},
+    jobs::JobRecorder,
+    parser_pool::ParserPool,
+};
+
+static SEARCHING_FOR_APPS_ICONS: AtomicBool = AtomicBool::new(false);
+static WATCHING_APPS: AtomicBool = AtomicBool::new(false);
+/// `icons` of the config
+static ICONS: AtomicBool = AtomicBool::new(true);
+
+/// Clears the flag once the task holding it is over, however it ends (an error returned
+/// early, a panic or the runtime shutting down)
+struct ClearOnDrop(&'static AtomicBool);
+
+impl Drop for ClearOnDrop {
+    fn drop(&mut self) {
+        self.0.store(false, SeqCst);
+    }
+}
+
+pub fn searching() -> bool {
+    SEARCHING_FOR_APPS_ICONS.load(SeqCst)
+}
+
+pub fn set_icons(enabled: bool) {
+    ICONS.store(enabled, SeqCst);
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.systemd1.Manager",
+    default_service = "org.freedesktop.systemd1",
+    default_path = "/org/freedesktop/systemd1"
+)]
+trait SystemdManager {
+    #[zbus(property)]
+    fn environment(&self) -> zbus::Result<Vec<String>>;
+}
+
+/// Takes the PATH the session imported into the systemd user manager (what the
+/// compositor or `dbus-update-activation-environment --systemd` exported) for the
+/// TryExec checks. Keeps the daemon's own if it can't be had
+pub async fn load_session_path() {
+    let environment = async {
+        let connection = zbus::Connection::session().await?;
+        SystemdManagerProxy::new(&connection)
+            .await?
+            .environment()
+            .await
+    };
+
+    match environment.await {
+        Ok(environment) => match environment.iter().find_map(|var| var.strip_prefix("PATH=")) {
+            Some(path) => {
+                tracing::debug!("Looking TryExec binaries up in the session PATH {path:?}");
+                set_search_path(path.into());
+            }
+            None => tracing::debug!("The session didn't import its PATH into systemd"),
+        },
+        Err(err) => tracing::debug!("Failed to get the session environment: {err}"),
+    }
+}
+
+/// Indexes .desktop entries and icons in the background, only one search runs at a time
+#[tracing::instrument(level = "debug", name = "leaper_daemon::search_apps")]
+pub fn search_apps() {
+    if SEARCHING_FOR_APPS_ICONS.load(SeqCst) {
+        tracing::warn!("Search job for apps and icons is already running");
+        return;
+    }
+
+    SEARCHING_FOR_APPS_ICONS.store(true, SeqCst);
+
+    let mut tasks = JoinSet::new();
+
+    static DEFAULT_PATHS: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+        ["/usr/share/", "/usr/local/share/"]
+            .into_iter()
+            .map(PathBuf::from)
+            .filter(|p| p.exists())
+            .collect_vec()
+    });
+
+    let xdg_paths = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .map(|dirs_str| {
+            dirs_str
+                .split(":")
+                .map(PathBuf::from)
+                .filter(|p| p.exists())
+                .collect_vec()
+        })
+        .into_iter()
+        .flatten()
+        .collect_vec();
+
+    let home_path = std::env::var("HOME").ok().map(PathBuf::from);
+
+    let home_icons_path = home_path.as_ref().and_then(|hp| {
+        let p = hp.join(".icons/");
+        p.exists().then_some(p)
+    });
+
+    let home_share_path = home_path.as_ref().and_then(|hp| {
+        let p = hp.join(".local/share/applications/");
+        p.exists().then_some(p)
+    });
+
+    let packaged_paths = flatpak_exports(home_path.as_deref())
+        .into_iter()
+        .chain(snap_desktop_dir())
+        .collect_vec();
+    // Only for the icons, the entries in them are exported into the snap desktop dir
+    let snap_gui_dirs = snap_gui_dirs();
+
+    let icon_paths = DEFAULT_PATHS
+        .iter()
+        .chain(xdg_paths.iter())
+        .chain(home_icons_path.iter())
+        .chain(packaged_paths.iter())
+        .chain(snap_gui_dirs.iter())
+        .unique()
+        .cloned()
+        .collect_vec();
+
+    let app_paths = DEFAULT_PATHS
+        .iter()
+        .chain(xdg_paths.iter())
+        .chain(home_share_path.iter())
+        .chain(packaged_paths.iter())
+        .unique()
+        .cloned()
+        .collect_vec();
+
+    let db = DB_REF.get().unwrap();
+
+    // Apps Search, the live query keeps running after the search job is done,
+    // so it's only started once
+    if !WATCHING_APPS.swap(true, SeqCst) {
+        let db_clone = db.clone();
+
+        task::spawn(async move {
+            let _watching = ClearOnDrop(&WATCHING_APPS);
+            let mut desktop_entries_stream = LiveSearchAppsQuery
+                .instrumented_execute(db_clone.clone())
+                .await?;
+            let parsers = ParserPool::spawn(db_clone.clone());
+
+            while let Some(entry) = desktop_entries_stream.next().await {
+                match entry {
+                    Ok(DBNotification { action, data, .. }) => match action {
+                        DBAction::Create => parsers.submit(data).await,
+                        DBAction::Update => {
+                            tracing::error!("UPDATE???");
+                            // TODO
+                        }
+                        DBAction::Delete => {
+                            tracing::error!("DELETE???");
+                            // TODO
+                        }
+                        _ => todo!(),
+                    },
+                    Err(err) => {
+                        tracing::error!("{err}");
+                        continue;
+                    }
+                }
+            }
+
+            Result::<()>::Ok(())
+        });
+    }
+
+    // .desktop Search
+    search_paths(&mut tasks, app_paths, vec!["desktop"], ".desktop".into());
+
+    // Icons Search
+    match ICONS.load(SeqCst) {
+        true => search_paths(
+            &mut tasks,
+            icon_paths,
+            vec![
+                "png", "jpg", "jpeg", "gif", "webp", "pbm", "pam", "ppm", "pgm", "tiff", "tif",
+                "tga", "dds", "bmp", "ico", "hdr", "exr", "ff", "avif", "qoi", "pcx", "svg", "xpm",
+            ],
+            "icon".into(),
+        ),
+        false => tracing::info!("Icons are disabled, skipping the icon directories"),
+    }
+
+    task::spawn(async move {
+        let _searching = ClearOnDrop(&SEARCHING_FOR_APPS_ICONS);
+        let job = JobRecorder::start("search_apps").await;
+
+        let stats = tasks
+            .join_all()
+            .await
+            .into_iter()
+            .fold(IndexStats::default(), |stats, res| match res {
+                Ok(other) => stats.merge(other),
+                Err(err) => {
+                    let mut stats = stats;
+                    stats.push_error(err.to_string());
+                    stats
+                }
+            });
+
+        tracing::info!(
+            "Done searching for apps and icons! [{} items, {} errors]",
+            stats.items,
+            stats.errors.len()
+        );
+
+        job.finish(stats).await;
+    });
+
+    tracing::info!("Waiting on rest of apps and icons in a detached task...");
+}
+
+/// Flatpak exports the .desktop entries and icons of installed apps (as symlinks into
+/// the app dirs) under `exports/share`, which isn't always part of XDG_DATA_DIRS
+fn flatpak_exports(home_path: Option<&Path>) -> Vec<PathBuf> {
+    let system = PathBuf::from("/var/lib/flatpak/exports/share/");
+    let user = home_path.map(|hp| hp.join(".local/share/flatpak/exports/share/"));
+
+    [Some(system), user]
+        .into_iter()
+        .flatten()
+        .filter(|p| p.exists())
+        .collect_vec()
+}
+
+/// Snaps keep their icons (and the original .desktop entries) in
+/// `/snap/<name>/current/meta/gui`, walking the whole of `/snap/` would go through every
+/// file of every mounted snap. `current` is resolved to the revision, that's what the
+/// `Icon` paths snapd writes into the exported entries (see [`snap_desktop_dir`]) point to
+fn snap_gui_dirs() -> Vec<PathBuf> {
+    std::fs::read_dir("/snap/")
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.path().join("current/meta/gui").canonicalize().ok())
+        .filter(|p| p.is_dir())
+        .collect_vec()
+}
+
+/// Where snapd exports the .desktop entries of installed snaps, with their `Exec` lines
+/// rewritten to go through `snap run`. The originals in [`snap_gui_dirs`] can't be run
+/// as they are
+fn snap_desktop_dir() -> Option<PathBuf> {
+    let exported = PathBuf::from("/var/lib/snapd/desktop/");
+    exported.exists().then_some(exported)
+}
+
+/// Drops the apps whose desktop file is gone and re-checks the TryExec binaries of the
+/// rest, so entries show up again once their binary gets installed and hide once it's
+/// removed
+#[tracing::instrument(level = "debug", name = "leaper_daemon::apps::validate")]
+pub async fn validate() -> IndexStats {
+    let db = DB_REF.get().unwrap();
+    let mut stats = IndexStats::default();
+
+    match GetAppRecordKeysQuery.instrumented_execute(db.clone()).await {
+        Ok(apps) => {
+            for app in apps
+                .into_iter()
+                .filter(|app| !Path::new(&app.desktop_entry_path).exists())
+            {
+                tracing::debug!(
+                    "Desktop file {:?} of {:?} is gone",
+                    app.desktop_entry_path,
+                    app.id
+                );
+
+                let res = DeleteAppQuery::builder()
+                    .app(app.id)
+                    .build()
+                    .instrumented_execute(db.clone())
+                    .await;
+
+                if let Err(err) = res {
+                    stats.push_error(err.to_string());
+                }
+            }
+        }
+        Err(err) => stats.push_error(err.to_string()),
+    }
+
+    let apps = match GetTryExecAppsQuery.instrumented_execute(db.clone()).await {
+        Ok(apps) => apps,
+        Err(err) => {
+            stats.push_error(err.to_string());
+            return stats;
+        }
+    };
+
+    for app in apps {
+        let missing = !try_exec_found(&app.try_exec);
+        stats.items += 1;
+
+        if missing == app.try_exec_missing {
+            continue;
+        }
+
+        tracing::debug!(
+            "TryExec {:?} of {:?} is {}",
+            app.try_exec,
+            app.id,
+            if missing { "gone" } else { "back" }
+        );
+
+        let res = SetTryExecMissingQuery::builder()
+            .app(app.id)
+            .missing(missing)
+            .build()
+            .instrumented_execute(db.clone())
+            .await;
+
+        if let Err(err) = res {
+            stats.push_error(err.to_string());
+        }
+    }
+
+    stats
+}
@@ -0,0 +1,143 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use db::{
+    InstrumentedDBQuery,
+    jobs::{ClearCheckpointQuery, GetCheckpointQuery, SaveCheckpointQuery},
+};
+
+use crate::{DB_REF, jobs::now_millis};
+
+/// Persisted progress of an indexing walk, so a walk cut short by the daemon dying
+/// picks up where it left off instead of starting over.
+///
+/// Entries are fed in walk order, which stays the same between runs as long as
+/// the tree doesn't change. A directory counts as completed once the walk moves on
+/// to entries of another one, everything up to that point is already indexed.
+pub struct Checkpoint {
+    root: String,
+    full: bool,
+    parents: bool,
+    /// Directory whose entries are being walked right now
+    current: Option<PathBuf>,
+    resume: Option<Resume>,
+    last_saved: Instant,
+}
+
+/// Skips the entries that were indexed before the daemon stopped
+struct Resume {
+    dir: PathBuf,
+    reached: bool,
+}
+
+impl Checkpoint {
+    /// Saving on every directory would double the writes of a walk
+    const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+    /// `full` and `parents` are saved along, see [`db::jobs::IndexCheckpoint::full`]
+    pub async fn load(root: &Path, full: bool, parents: bool) -> Self {
+        let db = DB_REF.get().unwrap();
+        let root = root.to_string_lossy().into_owned();
+
+        let resume = GetCheckpointQuery { root: root.clone() }
+            .instrumented_execute(db.clone())
+            .await
+            .ok()
+            .flatten()
+            .map(|checkpoint| Resume {
+                dir: checkpoint.dir.into(),
+                reached: false,
+            });
+
+        Self {
+            root,
+            full,
+            parents,
+            current: None,
+            resume,
+            last_saved: Instant::now(),
+        }
+    }
+
+    /// Directory the walk resumes after, if a previous one was interrupted
+    pub fn resume_after(&self) -> Option<&Path> {
+        self.resume.as_ref().map(|resume| resume.dir.as_path())
+    }
+
+    /// `false` if the walk went through everything without finding the directory
+    /// it should resume after, e.g. because it got removed in the meantime
+    pub fn resumed(&self) -> bool {
+        self.resume.is_none()
+    }
+
+    /// Drops the resume point, for walking the whole tree again
+    pub fn restart(&mut self) {
+        self.resume = None;
+        self.current = None;
+    }
+
+    /// Moves the checkpoint to `path`, the next entry of the walk.
+    /// Returns `true` if it was already indexed before the interruption
+    pub async fn advance(&mut self, path: &Path) -> bool {
+        let dir = path.parent();
+
+        if let Some(resume) = &mut self.resume {
+            if dir == Some(resume.dir.as_path()) {
+                resume.reached = true;
+                return true;
+            }
+
+            if !resume.reached {
+                return true;
+            }
+
+            self.resume = None;
+        }
+
+        if self.current.as_deref() != dir
+            && let Some(completed) = std::mem::replace(&mut self.current, dir.map(Into::into))
+            && self.last_saved.elapsed() >= Self::SAVE_INTERVAL
+        {
+            self.save(&completed).await;
+        }
+
+        false
+    }
+
+    async fn save(&mut self, completed: &Path) {
+        let db = DB_REF.get().unwrap();
+
+        let res = SaveCheckpointQuery::builder()
+            .root(self.root.clone())
+            .dir(completed.to_string_lossy())
+            .updated(now_millis())
+            .full(self.full)
+            .parents(self.parents)
+            .build()
+            .instrumented_execute(db.clone())
+            .await;
+
+        if let Err(err) = res {
+            tracing::warn!(
+                "Failed to save the indexing checkpoint of {}: {err}",
+                self.root
+            );
+        }
+
+        self.last_saved = Instant::now();
+    }
+
+    /// The walk finished, the next one starts from the beginning
+    pub async fn clear(self) {
+        let db = DB_REF.get().unwrap();
+
+        if let Err(err) = (ClearCheckpointQuery { root: self.root })
+            .instrumented_execute(db.clone())
+            .await
+        {
+            tracing::warn!("Failed to clear an indexing checkpoint: {err}");
+        }
+    }
+}
@@ -1,12 +1,12 @@
 use color_eyre::eyre::Result;
 use tarpc::{client::Config, tokio_serde::formats::Bincode};
 
-pub use tarpc::context;
+pub use tarpc::{client::RpcError, context};
 
-use crate::{ADDRESS, LeaperDaemonClient};
+use crate::{LeaperDaemonClient, socket_path};
 
 pub async fn connect() -> Result<LeaperDaemonClient> {
-    let mut transport = tarpc::serde_transport::tcp::connect(ADDRESS, Bincode::default);
+    let mut transport = tarpc::serde_transport::unix::connect(socket_path()?, Bincode::default);
     transport.config_mut().max_frame_length(usize::MAX);
 
     let transport = transport.await?;
@@ -0,0 +1,149 @@
+use std::{
+    process::Stdio,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use color_eyre::{
+    Result,
+    eyre::{OptionExt, bail},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    sync::mpsc,
+};
+
+use db::{
+    DBEntryId, InstrumentedDBQuery,
+    commands::{AppendCommandOutputQuery, CommandRun, FinishCommandRunQuery, StartCommandRunQuery},
+};
+
+use crate::{DB_REF, jobs::now_millis};
+
+/// Output gets written to the DB at most this often, so chatty commands don't flood it
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+/// Lines waiting to be recorded, past this the command waits on its pipes
+const OUTPUT_BUFFER: usize = 1024;
+
+/// `kiosk.enabled`, the runner is off there and so are commands through the daemon
+static KIOSK: AtomicBool = AtomicBool::new(false);
+
+pub fn set_kiosk(enabled: bool) {
+    KIOSK.store(enabled, Ordering::Relaxed);
+}
+
+/// Starts `cmd` detached from the caller, its output and exit code end up in the
+/// `command_run` table. Returns the id of the run as soon as the process is up
+pub async fn run_detached(cmd: Vec<String>) -> Result<DBEntryId> {
+    if KIOSK.load(Ordering::Relaxed) {
+        bail!("Running commands is disabled in kiosk mode");
+    }
+
+    let (program, args) = cmd.split_first().ok_or_eyre("Command is empty")?;
+    let db = DB_REF.get().unwrap();
+
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(false)
+        .spawn()?;
+
+    let run = StartCommandRunQuery::builder()
+        .cmd(cmd.clone())
+        .started(now_millis())
+        .build()
+        .instrumented_execute(db.clone())
+        .await?
+        .ok_or_eyre("Failed to record the command run")?;
+
+    tracing::info!(
+        "Running {} detached as {run:?}",
+        leaper_tracing::redact::cmd(&cmd)
+    );
+
+    let (output_tx, output_rx) = mpsc::channel(OUTPUT_BUFFER);
+
+    if let Some(stdout) = child.stdout.take() {
+        tokio::spawn(forward_lines(stdout, output_tx.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(forward_lines(stderr, output_tx));
+    }
+
+    let supervised = run.clone();
+
+    tokio::spawn(async move {
+        let record = tokio::spawn(record_output(supervised.clone(), output_rx));
+
+        let exit_code = match child.wait().await {
+            Ok(status) => status.code(),
+            Err(err) => {
+                tracing::error!("Lost track of detached command {supervised:?}: {err}");
+                None
+            }
+        };
+
+        // Pipes close with the process, the recorder flushes what's left and returns
+        let _ = record.await;
+
+        let _ = FinishCommandRunQuery::builder()
+            .run(supervised)
+            .finished(now_millis())
+            .maybe_exit_code(exit_code)
+            .build()
+            .instrumented_execute(db.clone())
+            .await;
+    });
+
+    Ok(run)
+}
+
+async fn forward_lines(pipe: impl AsyncRead + Unpin, tx: mpsc::Sender<String>) {
+    let mut lines = BufReader::new(pipe).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if tx.send(line).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn record_output(run: DBEntryId, mut rx: mpsc::Receiver<String>) {
+    let db = DB_REF.get().unwrap();
+    let mut chunk = String::new();
+    let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+
+    loop {
+        let (flush, done) = tokio::select! {
+            line = rx.recv() => match line {
+                Some(line) => {
+                    chunk.push_str(&line);
+                    chunk.push('\n');
+                    // Only the last bit of it is kept anyway
+                    (chunk.len() >= CommandRun::MAX_OUTPUT, false)
+                }
+                None => (true, true),
+            },
+            _ = interval.tick() => (true, false),
+        };
+
+        if flush && !chunk.is_empty() {
+            let res = AppendCommandOutputQuery::builder()
+                .run(run.clone())
+                .chunk(std::mem::take(&mut chunk))
+                .build()
+                .instrumented_execute(db.clone())
+                .await;
+
+            if let Err(err) = res {
+                tracing::warn!("Failed to record output of {run:?}: {err}");
+            }
+        }
+
+        if done {
+            return;
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::{path::PathBuf, time::Duration};
+
+use color_eyre::{Result, eyre::OptionExt};
+use logind_zbus::manager::ManagerProxy;
+use tokio::{io::AsyncReadExt, task::JoinSet};
+use zbus::{Connection, zvariant::OwnedFd};
+
+use mode::config::{EventAction, PowerEvents};
+
+const HANDLED_WHY: &str = "Handled by leaper-daemon";
+
+/// Holds a logind "block" inhibitor lock for as long as it's alive,
+/// logind takes back the handling of the event as soon as the fd gets closed
+pub(crate) struct Inhibitor {
+    what: &'static str,
+    _fd: OwnedFd,
+}
+
+impl Inhibitor {
+    pub(crate) async fn take(
+        connection: &Connection,
+        what: &'static str,
+        why: &str,
+    ) -> Result<Self> {
+        let reply = connection
+            .call_method(
+                Some("org.freedesktop.login1"),
+                "/org/freedesktop/login1",
+                Some("org.freedesktop.login1.Manager"),
+                "Inhibit",
+                &(what, "leaper", why, "block"),
+            )
+            .await?;
+        let fd = reply.body().deserialize::<OwnedFd>()?;
+
+        tracing::info!("Took {what} inhibitor lock");
+
+        Ok(Self { what, _fd: fd })
+    }
+}
+
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        tracing::info!("Released {} inhibitor lock", self.what);
+    }
+}
+
+/// Takes over lid switch and power key handling from logind for the events
+/// that have a leaper action configured, events left on [`EventAction::System`] are not touched
+pub async fn watch(events: PowerEvents) -> Result<()> {
+    let mut tasks = JoinSet::new();
+
+    if events.lid_close != EventAction::System || events.power_key != EventAction::System {
+        let connection = Connection::system().await?;
+
+        if events.lid_close != EventAction::System {
+            let inhibitor = Inhibitor::take(&connection, "handle-lid-switch", HANDLED_WHY).await?;
+            tasks.spawn(watch_lid(
+                connection.clone(),
+                inhibitor,
+                events.lid_close.clone(),
+            ));
+        }
+
+        if events.power_key != EventAction::System {
+            let inhibitor = Inhibitor::take(&connection, "handle-power-key", HANDLED_WHY).await?;
+            tasks.spawn(watch_power_key(inhibitor, events.power_key.clone()));
+        }
+    }
+
+    // A watcher only returns on failure, its inhibitor is dropped along with it
+    // so logind goes back to handling that event itself
+    while let Some(res) = tasks.join_next().await {
+        match res {
+            Ok(Err(err)) => tracing::error!("Stopped watching power events: {err}"),
+            Err(err) => tracing::error!("Power event watcher panicked: {err}"),
+            Ok(Ok(())) => {}
+        }
+    }
+
+    Ok(())
+}
+
+async fn watch_lid(
+    connection: Connection,
+    _inhibitor: Inhibitor,
+    action: EventAction,
+) -> Result<()> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    let manager = ManagerProxy::new(&connection).await?;
+    let mut was_closed = manager.lid_closed().await?;
+
+    // logind doesn't emit PropertiesChanged for LidClosed, so it has to be polled
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let closed = manager.lid_closed().await?;
+
+        if closed && !was_closed {
+            tracing::debug!("Lid closed");
+            run_action(&action).await;
+        }
+
+        was_closed = closed;
+    }
+}
+
+async fn watch_power_key(_inhibitor: Inhibitor, action: EventAction) -> Result<()> {
+    // struct input_event { struct timeval time; __u16 type; __u16 code; __s32 value; }
+    const EVENT_SIZE: usize = 24;
+    const EV_KEY: u16 = 1;
+    const KEY_POWER: u16 = 116;
+    const PRESSED: i32 = 1;
+
+    let device = power_button_device()?;
+    let mut file = tokio::fs::File::open(&device).await.inspect_err(|err| {
+        tracing::error!("Failed to open {device:?} (is the user in the input group?): {err}")
+    })?;
+
+    tracing::info!("Listening for power key presses on {device:?}");
+
+    let mut buf = [0u8; EVENT_SIZE];
+
+    loop {
+        file.read_exact(&mut buf).await?;
+
+        let ty = u16::from_ne_bytes([buf[16], buf[17]]);
+        let code = u16::from_ne_bytes([buf[18], buf[19]]);
+        let value = i32::from_ne_bytes([buf[20], buf[21], buf[22], buf[23]]);
+
+        if ty == EV_KEY && code == KEY_POWER && value == PRESSED {
+            tracing::debug!("Power key pressed");
+            run_action(&action).await;
+        }
+    }
+}
+
+fn power_button_device() -> Result<PathBuf> {
+    std::fs::read_dir("/sys/class/input")?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("event"))
+        .find(|entry| {
+            std::fs::read_to_string(entry.path().join("device/name"))
+                .is_ok_and(|name| name.trim() == "Power Button")
+        })
+        .map(|entry| PathBuf::from("/dev/input").join(entry.file_name()))
+        .ok_or_eyre("No power button input device found")
+}
+
+async fn run_action(action: &EventAction) {
+    let args = match action {
+        EventAction::System | EventAction::Ignore => return,
+        EventAction::Lock => vec!["leaper".into(), "lock".into()],
+        EventAction::PowerMenu => vec!["leaper".into(), "power".into()],
+        EventAction::Cmd(args) => args.clone(),
+    };
+
+    let Some((program, args)) = args.split_first() else {
+        tracing::warn!("Empty command configured for power event");
+        return;
+    };
+
+    if let Err(err) = tokio::process::Command::new(program).args(args).spawn() {
+        tracing::error!("Failed to run {program:?} for power event: {err}");
+    }
+}
@@ -1,76 +1,281 @@
-use std::{collections::HashSet, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, LazyLock, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 use color_eyre::Result;
 use futures::StreamExt;
-use itertools::Itertools;
-use tokio::task::JoinSet;
+use serde::{Deserialize, Serialize};
+use tokio::{sync::watch, task::JoinSet};
 use vfs::async_vfs::{AsyncPhysicalFS, AsyncVfsPath};
 
-use db::fs::FSNode;
+use db::{
+    InstrumentedDBQuery, fs::FSNode, jobs::GetFullCheckpointsQuery, volumes::TagVolumeNodesQuery,
+};
 
-use crate::DB_REF;
+use crate::{DB_REF, checkpoint::Checkpoint, jobs::JobRecorder, maintenance};
 
+/// What an indexing run went through, recorded in the job history
+#[derive(Debug, Default)]
+pub struct IndexStats {
+    pub items: u64,
+    pub errors: Vec<String>,
+}
+
+impl IndexStats {
+    /// Keeps the job history entries small when a whole tree fails
+    const MAX_ERRORS: usize = 100;
+
+    fn record(&mut self, res: std::result::Result<(), String>) {
+        match res {
+            Ok(()) => self.items += 1,
+            Err(err) => self.push_error(err),
+        }
+    }
+
+    pub fn push_error(&mut self, err: String) {
+        if self.errors.len() < Self::MAX_ERRORS {
+            self.errors.push(err);
+        }
+    }
+
+    pub fn merge(mut self, other: Self) -> Self {
+        self.items += other.items;
+        other
+            .errors
+            .into_iter()
+            .for_each(|err| self.push_error(err));
+
+        self
+    }
+}
+
+/// Roots that are being indexed right now
+static IN_FLIGHT: LazyLock<Mutex<HashMap<PathBuf, InFlight>>> = LazyLock::new(Default::default);
+
+#[derive(Clone)]
+struct InFlight {
+    /// Entries handled so far
+    items: Arc<AtomicU64>,
+    /// Closes once the run is over
+    done: watch::Receiver<()>,
+}
+
+/// Snapshot of an indexing run that hasn't finished yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexProgress {
+    pub root: PathBuf,
+    pub items: u64,
+}
+
+pub fn in_flight() -> Vec<IndexProgress> {
+    IN_FLIGHT
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(root, run)| IndexProgress {
+            root: root.clone(),
+            items: run.items.load(Ordering::Relaxed),
+        })
+        .collect()
+}
+
+/// Removes the root from [`IN_FLIGHT`] once the run is over, however it ends
+struct InFlightGuard {
+    root: PathBuf,
+    _done: watch::Sender<()>,
+}
+
+impl InFlightGuard {
+    /// `Err` with the run that is indexing `root` already
+    fn register(root: PathBuf) -> std::result::Result<(Self, Arc<AtomicU64>), InFlight> {
+        let mut in_flight = IN_FLIGHT.lock().unwrap();
+
+        if let Some(run) = in_flight.get(&root) {
+            return Err(run.clone());
+        }
+
+        let (done_tx, done) = watch::channel(());
+        let items = Arc::<AtomicU64>::default();
+
+        in_flight.insert(
+            root.clone(),
+            InFlight {
+                items: items.clone(),
+                done,
+            },
+        );
+
+        Ok((
+            Self {
+                root,
+                _done: done_tx,
+            },
+            items,
+        ))
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        IN_FLIGHT.lock().unwrap().remove(&self.root);
+    }
+}
+
+/// Indexes everything under `root`. If the daemon stops midway, the next one resumes it
+pub async fn index(root: PathBuf, parents: bool) -> IndexStats {
+    index_with(root, parents, |_| None, true).await
+}
+
+/// Resumes the [`index`] walks the previous daemon run didn't finish, each after its
+/// checkpoint
+pub async fn resume_interrupted() {
+    let db = DB_REF.get().unwrap();
+
+    let checkpoints = match GetFullCheckpointsQuery
+        .instrumented_execute(db.clone())
+        .await
+    {
+        Ok(checkpoints) => checkpoints,
+        Err(err) => {
+            tracing::warn!("Failed to look for interrupted indexing: {err}");
+            return;
+        }
+    };
+
+    for checkpoint in checkpoints {
+        tokio::spawn(async move {
+            let job = JobRecorder::start("index").await;
+            let stats = index(checkpoint.root.into(), checkpoint.parents).await;
+
+            job.finish(stats).await;
+        });
+    }
+}
+
+/// Walks of a root that is being indexed already wait for that run instead, and get
+/// what it handled. Only `full` walks are resumed by the daemon itself, filtered ones
+/// are by whatever runs them again
 #[tracing::instrument(skip(pre_filter), level = "debug", name = "daemon::index")]
-pub async fn index(
+async fn index_with(
     root: PathBuf,
     parents: bool,
     pre_filter: impl Fn(&PathBuf) -> Option<bool> + Clone + Send + Sync + 'static,
-) {
+    full: bool,
+) -> IndexStats {
+    let db = DB_REF.get().unwrap();
+    let (_guard, progress) = match InFlightGuard::register(root.clone()) {
+        Ok(registered) => registered,
+        Err(mut run) => {
+            tracing::info!("{root:?} is being indexed already, waiting for that run");
+
+            // Nothing is sent, it only errors once the other run drops its guard
+            let _ = run.done.changed().await;
+
+            return IndexStats {
+                items: run.items.load(Ordering::Relaxed),
+                errors: vec![],
+            };
+        }
+    };
+    let mut checkpoint = Checkpoint::load(&root, full, parents).await;
+
+    if let Some(dir) = checkpoint.resume_after() {
+        tracing::info!("Resuming the interrupted indexing of {root:?} after {dir:?}");
+    }
+
+    let mut stats = walk(&root, parents, &pre_filter, &mut checkpoint, &progress).await;
+
+    if !checkpoint.resumed() {
+        tracing::warn!("Checkpoint of {root:?} is gone, indexing it from the start");
+
+        checkpoint.restart();
+        stats = walk(&root, parents, &pre_filter, &mut checkpoint, &progress).await;
+    }
+
+    checkpoint.clear().await;
+
+    if let Err(err) = TagVolumeNodesQuery.instrumented_execute(db.clone()).await {
+        tracing::error!("Failed to link indexed nodes to their volumes: {err}");
+    }
+
+    stats
+}
+
+/// Adds every entry under `root` that passes `pre_filter`, one at a time so the
+/// [`Checkpoint`] always trails what is actually in the index
+async fn walk(
+    root: &Path,
+    parents: bool,
+    pre_filter: &impl Fn(&PathBuf) -> Option<bool>,
+    checkpoint: &mut Checkpoint,
+    progress: &AtomicU64,
+) -> IndexStats {
     let db = DB_REF.get().unwrap();
 
-    let mut walkdir = AsyncVfsPath::new(AsyncPhysicalFS::new(&root))
+    let mut walkdir = AsyncVfsPath::new(AsyncPhysicalFS::new(root))
         .walk_dir()
         .await
         .expect("Initialize walkdir")
-        .filter_map(|path| {
-            let pre_filter = pre_filter.clone();
-            let db = db.clone();
-            let root = root.clone();
-
-            async move {
-                let path = match path {
-                    Ok(path) => path,
-                    Err(err) => {
-                        match err.kind() {
-                            vfs::error::VfsErrorKind::FileNotFound => {}
-                            _ => {
-                                tracing::error!("[{:?}] {err}", err.kind());
-                            }
-                        }
+        .boxed();
 
-                        return None;
+    let mut stats = IndexStats::default();
+
+    while let Some(path) = walkdir.next().await {
+        let path = match path {
+            Ok(path) => path,
+            Err(err) => {
+                match err.kind() {
+                    vfs::error::VfsErrorKind::FileNotFound => {}
+                    _ => {
+                        tracing::error!("[{:?}] {err}", err.kind());
                     }
-                };
+                }
 
-                let path_real = root.join(path.as_str().trim_start_matches('/'));
+                continue;
+            }
+        };
 
-                if let Some(res) = pre_filter.clone()(&path_real)
-                    && !res
-                {
-                    return None;
-                }
+        let path_real = root.join(path.as_str().trim_start_matches('/'));
 
-                if let Err(err) = FSNode::add_db()
-                    .path(&path_real)
-                    .db(db)
-                    .parents(parents)
-                    .call()
-                    .await
-                {
-                    tracing::error!("Failed to add fs_node: {err}");
-                }
+        if checkpoint.advance(&path_real).await {
+            continue;
+        }
 
-                Some(())
-            }
-        })
-        .boxed();
+        if let Some(res) = pre_filter(&path_real)
+            && !res
+        {
+            continue;
+        }
+
+        let _maintenance = maintenance::pause_point().await;
 
-    while walkdir.next().await.is_some() {}
+        let res = FSNode::add_db()
+            .path(&path_real)
+            .db(db.clone())
+            .parents(parents)
+            .call()
+            .await
+            .map(|_| ())
+            .map_err(|err| {
+                tracing::error!("Failed to add fs_node: {err}");
+                format!("{path_real:?}: {err}")
+            });
+
+        stats.record(res);
+        progress.fetch_add(1, Ordering::Relaxed);
+    }
+
+    stats
 }
 
 #[tracing::instrument(skip(tasks), level = "debug", name = "daemon::search_paths")]
 pub fn search_paths(
-    tasks: &mut JoinSet<Result<()>>,
+    tasks: &mut JoinSet<Result<IndexStats>>,
     paths: Vec<PathBuf>,
     exts: Vec<&'static str>,
     kind: String,
@@ -86,27 +291,34 @@ pub fn search_paths(
                 return;
             }
 
-            index_tasks.spawn(index(path.clone(), false, move |path| {
-                if path.is_dir() {
-                    return Some(false);
-                }
+            index_tasks.spawn(index_with(
+                path.clone(),
+                false,
+                move |path| {
+                    if path.is_dir() {
+                        return Some(false);
+                    }
 
-                let Some(ext) = path.extension().and_then(|x| x.to_str()) else {
-                    return Some(false);
-                };
+                    let Some(ext) = path.extension().and_then(|x| x.to_str()) else {
+                        return Some(false);
+                    };
 
-                if exts.contains(&ext) {
-                    return None;
-                }
+                    if exts.contains(&ext) {
+                        return None;
+                    }
 
-                Some(false)
-            }));
+                    Some(false)
+                },
+                false,
+            ));
 
             indexed.insert(path);
         });
 
-        index_tasks.join_all().await.into_iter().collect_vec();
-
-        Ok(())
+        Ok(index_tasks
+            .join_all()
+            .await
+            .into_iter()
+            .fold(IndexStats::default(), IndexStats::merge))
     });
 }
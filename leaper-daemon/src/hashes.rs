@@ -0,0 +1,120 @@
+use std::sync::{
+    Mutex,
+    atomic::{AtomicU64, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+
+use db::{
+    InstrumentedDBQuery,
+    fs::{GetUnhashedFilesQuery, SetFileHashQuery},
+};
+
+use crate::{DB_REF, fs::IndexStats, maintenance};
+
+/// `daemon.hash_max_size` in bytes
+static MAX_SIZE: AtomicU64 = AtomicU64::new(64 * 1024 * 1024);
+static PROGRESS: Mutex<Option<HashProgress>> = Mutex::new(None);
+
+/// Snapshot of a hashing run that hasn't finished yet
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HashProgress {
+    pub done: u64,
+    pub total: u64,
+}
+
+pub fn set_max_size(mib: u64) {
+    MAX_SIZE.store(mib * 1024 * 1024, Ordering::Relaxed);
+}
+
+pub fn progress() -> Option<HashProgress> {
+    *PROGRESS.lock().unwrap()
+}
+
+/// Clears [`PROGRESS`] once the run is over, however it ends
+struct ProgressGuard;
+
+impl Drop for ProgressGuard {
+    fn drop(&mut self) {
+        *PROGRESS.lock().unwrap() = None;
+    }
+}
+
+/// Hashes every indexed file that doesn't have a hash yet, one at a time so it stays
+/// in the background. Only one run goes at a time, others return right away
+#[tracing::instrument(level = "debug", name = "daemon::hash_files")]
+pub async fn hash_files() -> IndexStats {
+    let mut stats = IndexStats::default();
+
+    {
+        let mut progress = PROGRESS.lock().unwrap();
+
+        if progress.is_some() {
+            tracing::warn!("Hashing job is already running");
+            return stats;
+        }
+
+        *progress = Some(HashProgress { done: 0, total: 0 });
+    }
+
+    let _guard = ProgressGuard;
+    let db = DB_REF.get().unwrap();
+
+    let files = match (GetUnhashedFilesQuery {
+        max_size: MAX_SIZE.load(Ordering::Relaxed),
+    })
+    .instrumented_execute(db.clone())
+    .await
+    {
+        Ok(files) => files,
+        Err(err) => {
+            stats.push_error(format!("Failed to get unhashed files: {err}"));
+            return stats;
+        }
+    };
+
+    tracing::info!("Hashing {} files", files.len());
+
+    let total = files.len() as u64;
+
+    for (done, file) in files.into_iter().enumerate() {
+        *PROGRESS.lock().unwrap() = Some(HashProgress {
+            done: done as u64,
+            total,
+        });
+
+        let path = file.path.clone();
+        let hash = tokio::task::spawn_blocking(move || {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_reader(std::fs::File::open(&path)?)?;
+
+            std::io::Result::Ok(hasher.finalize().to_hex().to_string())
+        })
+        .await;
+
+        let res = match hash {
+            Ok(Ok(hash)) => {
+                let _maintenance = maintenance::pause_point().await;
+
+                SetFileHashQuery::builder()
+                    .file(file.file)
+                    .hash(hash)
+                    .build()
+                    .instrumented_execute(db.clone())
+                    .await
+                    .map_err(|err| format!("{:?}: {err}", file.path))
+            }
+            Ok(Err(err)) => Err(format!("{:?}: {err}", file.path)),
+            Err(err) => Err(format!("{:?}: {err}", file.path)),
+        };
+
+        if let Err(err) = res {
+            tracing::debug!("Failed to hash {err}");
+            stats.push_error(err);
+        } else {
+            stats.items += 1;
+        }
+    }
+
+    stats
+}
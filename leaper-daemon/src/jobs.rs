@@ -0,0 +1,54 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use db::{
+    DBEntryId, InstrumentedDBQuery,
+    jobs::{FinishJobQuery, StartJobQuery},
+};
+
+use crate::{DB_REF, fs::IndexStats};
+
+/// Keeps track of a running job in the `job` table
+pub struct JobRecorder {
+    job: Option<DBEntryId>,
+}
+
+impl JobRecorder {
+    pub async fn start(kind: impl Into<String>) -> Self {
+        let db = DB_REF.get().unwrap();
+
+        let job = StartJobQuery::builder()
+            .kind(kind)
+            .started(now_millis())
+            .build()
+            .instrumented_execute(db.clone())
+            .await
+            .ok()
+            .flatten();
+
+        Self { job }
+    }
+
+    pub async fn finish(self, stats: IndexStats) {
+        let Some(job) = self.job else {
+            return;
+        };
+
+        let db = DB_REF.get().unwrap();
+
+        let _ = FinishJobQuery::builder()
+            .job(job)
+            .finished(now_millis())
+            .items(stats.items)
+            .errors(stats.errors)
+            .build()
+            .instrumented_execute(db.clone())
+            .await;
+    }
+}
+
+pub(crate) fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis() as i64)
+        .unwrap_or_default()
+}
@@ -1,21 +1,137 @@
+pub mod api;
+pub mod apps;
+pub mod checkpoint;
 pub mod client;
+pub mod commands;
 
+pub mod events;
 pub mod fs;
+pub mod hashes;
+pub mod jobs;
+pub mod maintenance;
+pub mod parser_pool;
+pub mod screensaver;
+pub mod shortcuts;
+pub mod volumes;
 
 use std::{
-    net::{Ipv4Addr, SocketAddrV4},
-    path::PathBuf,
+    fs::{DirBuilder, Permissions},
+    os::unix::fs::{DirBuilderExt, PermissionsExt},
+    path::{Path, PathBuf},
     sync::OnceLock,
+    time::Duration,
 };
 
-use db::DB;
+use color_eyre::{
+    Result,
+    eyre::{OptionExt, bail},
+};
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+
+use db::{DB, DBEntryId, commands::CommandRun, fs::DuplicateGroup, jobs::Job};
+
+use crate::{
+    fs::IndexProgress, hashes::HashProgress, parser_pool::ParseStats, screensaver::Inhibitor,
+};
+
+/// Unix socket the daemon serves its RPC on. The runtime dir is only accessible to its
+/// user, unlike a localhost port that any local process (sandboxed apps and web pages
+/// included) can reach
+pub fn socket_path() -> Result<PathBuf> {
+    runtime_path("daemon.sock")
+}
+
+/// Unix socket of the read-only HTTP API, see [`api::serve`]
+pub fn api_socket_path() -> Result<PathBuf> {
+    runtime_path("api.sock")
+}
+
+fn runtime_path(name: &str) -> Result<PathBuf> {
+    let runtime_dir = BaseDirs::new()
+        .and_then(|dirs| dirs.runtime_dir().map(PathBuf::from))
+        .ok_or_eyre("XDG_RUNTIME_DIR is not set")?;
+
+    Ok(runtime_dir.join("leaper").join(name))
+}
+
+/// Makes sure only the user can get to the socket and no other daemon is serving it. A
+/// socket left behind by a crashed daemon is removed
+pub fn prepare_socket(socket: &Path) -> Result<()> {
+    let dir = socket.parent().ok_or_eyre("Socket path has no parent")?;
 
-pub const ADDRESS: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 9876);
+    DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    std::fs::set_permissions(dir, Permissions::from_mode(0o700))?;
+
+    if socket.exists() {
+        if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+            bail!("Another daemon is already listening on {socket:?}");
+        }
+
+        std::fs::remove_file(socket)?;
+    }
+
+    Ok(())
+}
 
 pub static DB_REF: OnceLock<DB> = OnceLock::new();
 
+/// Runs the app/icon search inside the current process instead of the daemon,
+/// for ephemeral sessions where the DB only lives in memory
+pub fn search_apps_in_process(db: DB, icons: bool) {
+    if DB_REF.set(db).is_err() {
+        tracing::warn!("DB for in-process indexing was already set");
+    }
+
+    apps::set_icons(icons);
+
+    apps::search_apps();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub version: String,
+    pub uptime: Duration,
+    pub searching_apps: bool,
+    /// Time left until the next automatic re-index, `None` if they're disabled
+    pub next_reindex: Option<Duration>,
+    pub desktop_entries: ParseStats,
+    /// Clients holding off the idle lock through org.freedesktop.ScreenSaver
+    pub inhibitors: Vec<Inhibitor>,
+}
+
+/// Outcome of an on-demand [`LeaperDaemon::index`] run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexSummary {
+    pub items: u64,
+    pub errors: usize,
+}
+
 #[tarpc::service]
 pub trait LeaperDaemon {
     async fn search_apps();
-    async fn index(root: PathBuf, parents: bool);
+    /// Indexes everything under `root`, returns once the whole tree went through
+    async fn index(root: PathBuf, parents: bool) -> IndexSummary;
+    /// Indexing runs that are still going, to show progress while waiting on [`LeaperDaemon::index`]
+    async fn index_progress() -> Vec<IndexProgress>;
+    async fn status() -> DaemonStatus;
+    /// Most recent indexing/search jobs, newest first
+    async fn jobs(limit: usize) -> Result<Vec<Job>, String>;
+    /// Exports the DB to `path` (on the daemon's side), with indexing paused meanwhile
+    async fn backup(path: PathBuf) -> Result<(), String>;
+    /// Cleans up dangling records and compacts storage, with indexing paused meanwhile
+    async fn compact() -> Result<(), String>;
+    /// Runs `cmd` under the daemon, capturing its output, returns the id of the run
+    async fn run_detached(cmd: Vec<String>) -> Result<DBEntryId, String>;
+    /// Most recent detached command runs, newest first
+    async fn command_runs(limit: usize) -> Result<Vec<CommandRun>, String>;
+    /// Hashes indexed files that don't have a hash yet, returns once all went through
+    async fn hash_files() -> IndexSummary;
+    /// Hashing run that's still going, to show progress while waiting on [`LeaperDaemon::hash_files`]
+    async fn hash_progress() -> Option<HashProgress>;
+    /// Groups of indexed files with identical contents, biggest first
+    async fn duplicates(limit: usize) -> Result<Vec<DuplicateGroup>, String>;
+    /// Changes what the daemon logs without restarting it, `filter` is a level or
+    /// `RUST_LOG`-style directives. Returns the directives now in effect
+    async fn set_log_filter(filter: String) -> Result<String, String>;
 }
@@ -1,49 +1,163 @@
+mod scheduler;
+
 use std::{
-    path::PathBuf,
-    sync::{
-        LazyLock,
-        atomic::{
-            AtomicBool,
-            Ordering::{self, SeqCst},
-        },
-    },
+    fs::Permissions, os::unix::fs::PermissionsExt, path::PathBuf, sync::LazyLock, time::Instant,
 };
 
 use color_eyre::{Result, eyre::OptionExt};
 use directories::ProjectDirs;
 use futures::prelude::*;
-use itertools::Itertools;
 use tarpc::{
     server::{BaseChannel, Channel},
     tokio_serde::formats::Bincode,
 };
-use tokio::task::{self, JoinSet};
+use tokio::signal::unix::{SignalKind, signal};
 
 use db::{
-    DBAction, DBNotification, InstrumentedDBQuery,
-    apps::{CreateAppEntryQuery, LiveSearchAppsQuery},
+    DBEntryId, InstrumentedDBQuery,
+    apps::migrate_app_keys,
+    commands::{CommandRun, GetCommandRunsQuery, MarkInterruptedCommandRunsQuery},
+    fs::{DuplicateGroup, GetDuplicatesQuery, MigrateDirUsageQuery},
     init_db,
+    jobs::{GetJobsQuery, Job, MarkInterruptedJobsQuery},
 };
 
 use leaper_daemon::{
-    ADDRESS, DB_REF, LeaperDaemon,
-    fs::{self, search_paths},
+    DB_REF, DaemonStatus, IndexSummary, LeaperDaemon, api, apps, commands, events,
+    fs::{self, IndexProgress},
+    hashes::{self, HashProgress},
+    jobs::JobRecorder,
+    maintenance, parser_pool, prepare_socket, screensaver, shortcuts, volumes,
 };
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<()> {
     color_eyre::install()?;
-    leaper_tracing::init_tracing(false, false, false)?;
+
+    LazyLock::force(&STARTED_AT);
 
     let project_dirs = ProjectDirs::from("com", "tukanoid", "leaper")
         .ok_or_eyre("Failed to get project directories")?;
-    let config = mode::config::LeaperModeConfig::open(&project_dirs)?;
+    let mut config = mode::config::LeaperModeConfig::open(&project_dirs)?;
+
+    leaper_tracing::init_tracing(
+        false,
+        false,
+        false,
+        leaper_tracing::LogOutputs::new(config.log.journald.enabled(), config.log.keep_stderr),
+    )?;
     let db = init_db(config.db_port).await?;
 
+    if let Err(err) = MarkInterruptedJobsQuery
+        .instrumented_execute(db.clone())
+        .await
+    {
+        tracing::warn!("Failed to mark the jobs of the previous run as interrupted: {err}");
+    }
+
+    if let Err(err) = MarkInterruptedCommandRunsQuery
+        .instrumented_execute(db.clone())
+        .await
+    {
+        tracing::warn!("Failed to mark the commands of the previous run as interrupted: {err}");
+    }
+
+    match migrate_app_keys(db.clone()).await {
+        Ok(0) => {}
+        Ok(migrated) => tracing::info!("Moved {migrated} apps to stable record ids"),
+        Err(err) => tracing::error!("Failed to move apps to stable record ids: {err}"),
+    }
+
+    match MigrateDirUsageQuery.instrumented_execute(db.clone()).await {
+        Ok(0) => {}
+        Ok(added) => tracing::info!("Added {added} files indexed before to the disk usage"),
+        Err(err) => {
+            tracing::error!("Failed to add the files indexed before to the disk usage: {err}")
+        }
+    }
+
     DB_REF.set(db).unwrap();
 
-    let mut listener = tarpc::serde_transport::tcp::listen(ADDRESS, Bincode::default).await?;
+    // Needs the DB set, the walks it resumes go through it
+    fs::resume_interrupted().await;
+
+    let power_events = config.power.events;
+
+    tokio::spawn(async move {
+        if let Err(err) = events::watch(power_events).await {
+            tracing::error!("Failed to take over power events: {err}");
+        }
+    });
+
+    let shortcuts = std::mem::take(&mut config.daemon.shortcuts);
+
+    tokio::spawn(async move {
+        if let Err(err) = shortcuts::register(shortcuts).await {
+            tracing::error!("Failed to register global shortcuts: {err}");
+        }
+    });
+
+    if config.daemon.api {
+        tokio::spawn(async {
+            if let Err(err) = api::serve().await {
+                tracing::error!("Read-only API stopped: {err}");
+            }
+        });
+    }
+
+    if config.daemon.screensaver {
+        tokio::spawn(async {
+            if let Err(err) = screensaver::serve().await {
+                tracing::warn!("Not serving org.freedesktop.ScreenSaver: {err}");
+            }
+        });
+    }
+
+    let index_removable = config.daemon.index_removable;
+
+    tokio::spawn(async move {
+        if let Err(err) = volumes::watch(index_removable).await {
+            tracing::error!("Stopped watching removable volumes: {err}");
+        }
+    });
+
+    apps::set_icons(config.icons);
+    apps::load_session_path().await;
+    hashes::set_max_size(config.daemon.hash_max_size);
+    commands::set_kiosk(config.kiosk.enabled);
+
+    tokio::spawn(async {
+        let job = JobRecorder::start("validate").await;
+        let stats = apps::validate().await;
+
+        job.finish(stats).await;
+    });
+
+    if config.daemon.hash_files {
+        tokio::spawn(async {
+            let job = JobRecorder::start("hash").await;
+            let stats = hashes::hash_files().await;
+
+            job.finish(stats).await;
+        });
+    }
+
+    tokio::spawn(scheduler::run(config.daemon));
+
+    tokio::spawn(async {
+        if let Err(err) = cycle_log_level().await {
+            tracing::warn!("Not changing the log level on SIGUSR2: {err}");
+        }
+    });
+
+    let socket = leaper_daemon::socket_path()?;
+    prepare_socket(&socket)?;
+
+    let mut listener = tarpc::serde_transport::unix::listen(&socket, Bincode::default).await?;
     listener.config_mut().max_frame_length(usize::MAX);
+    std::fs::set_permissions(&socket, Permissions::from_mode(0o600))?;
+
+    tracing::info!("Listening on {socket:?}");
 
     listener
         .filter_map(|r| futures::future::ready(r.inspect_err(|err| tracing::error!("{err}")).ok()))
@@ -63,148 +177,167 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-static SEARCHING_FOR_APPS_ICONS: AtomicBool = AtomicBool::new(false);
+static STARTED_AT: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// Every SIGUSR2 makes the daemon more verbose, wrapping back around to info after trace
+async fn cycle_log_level() -> Result<()> {
+    let mut signals = signal(SignalKind::user_defined2())?;
+
+    while signals.recv().await.is_some() {
+        match leaper_tracing::cycle_level() {
+            Ok(level) => tracing::info!("Logging at {level} after SIGUSR2"),
+            Err(err) => tracing::error!("Failed to change the log level: {err}"),
+        }
+    }
+
+    Ok(())
+}
 
 #[derive(Clone)]
 struct LeaperDaemonServer;
 
 impl LeaperDaemon for LeaperDaemonServer {
-    #[tracing::instrument(
-        skip(self, _context),
-        level = "debug",
-        name = "leaper_daemon::search_apps"
-    )]
     async fn search_apps(self, _context: ::tarpc::context::Context) {
-        if SEARCHING_FOR_APPS_ICONS.load(SeqCst) {
-            tracing::warn!("Search job for apps and icons is already running");
-            return;
+        apps::search_apps();
+    }
+
+    async fn index(
+        self,
+        _context: ::tarpc::context::Context,
+        root: PathBuf,
+        parents: bool,
+    ) -> IndexSummary {
+        tracing::info!("Indexing {root:?}");
+
+        let job = JobRecorder::start("index").await;
+        let stats = fs::index(root, parents).await;
+        let summary = IndexSummary {
+            items: stats.items,
+            errors: stats.errors.len(),
+        };
+
+        job.finish(stats).await;
+
+        summary
+    }
+
+    async fn index_progress(self, _context: ::tarpc::context::Context) -> Vec<IndexProgress> {
+        fs::in_flight()
+    }
+
+    async fn status(self, _context: ::tarpc::context::Context) -> DaemonStatus {
+        DaemonStatus {
+            version: env!("CARGO_PKG_VERSION").into(),
+            uptime: STARTED_AT.elapsed(),
+            searching_apps: apps::searching(),
+            next_reindex: scheduler::NEXT_REINDEX
+                .lock()
+                .unwrap()
+                .map(|next| next.saturating_duration_since(Instant::now())),
+            desktop_entries: parser_pool::stats(),
+            inhibitors: screensaver::inhibitors(),
         }
+    }
 
-        SEARCHING_FOR_APPS_ICONS.store(true, Ordering::SeqCst);
+    async fn jobs(
+        self,
+        _context: ::tarpc::context::Context,
+        limit: usize,
+    ) -> Result<Vec<Job>, String> {
+        let db = DB_REF.get().unwrap();
 
-        let mut tasks = JoinSet::new();
+        GetJobsQuery { limit }
+            .instrumented_execute(db.clone())
+            .await
+            .map_err(|err| err.to_string())
+    }
 
-        static DEFAULT_PATHS: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
-            ["/usr/share/", "/usr/local/share/", "/snap/"]
-                .into_iter()
-                .map(PathBuf::from)
-                .filter(|p| p.exists())
-                .collect_vec()
-        });
+    async fn backup(
+        self,
+        _context: ::tarpc::context::Context,
+        path: PathBuf,
+    ) -> Result<(), String> {
+        let _maintenance = maintenance::begin().await;
+        let db = DB_REF.get().unwrap();
 
-        let xdg_paths = std::env::var("XDG_DATA_DIRS")
-            .ok()
-            .map(|dirs_str| {
-                dirs_str
-                    .split(":")
-                    .map(PathBuf::from)
-                    .filter(|p| p.exists())
-                    .collect_vec()
-            })
-            .into_iter()
-            .flatten()
-            .collect_vec();
+        tracing::info!("Backing up the DB to {path:?}");
 
-        let home_path = std::env::var("HOME").ok().map(PathBuf::from);
+        db::maintenance::backup(db.clone(), path)
+            .await
+            .map_err(|err| err.to_string())
+    }
 
-        let home_icons_path = home_path.as_ref().and_then(|hp| {
-            let p = hp.join(".icons/");
-            p.exists().then_some(p)
-        });
+    async fn compact(self, _context: ::tarpc::context::Context) -> Result<(), String> {
+        let _maintenance = maintenance::begin().await;
+        let db = DB_REF.get().unwrap();
 
-        let home_share_path = home_path.as_ref().and_then(|hp| {
-            let p = hp.join(".local/share/applications/");
-            p.exists().then_some(p)
-        });
+        tracing::info!("Compacting the DB");
 
-        let icon_paths = DEFAULT_PATHS
-            .iter()
-            .chain(xdg_paths.iter())
-            .chain(home_icons_path.iter())
-            .unique()
-            .cloned()
-            .collect_vec();
-
-        let app_paths = DEFAULT_PATHS
-            .iter()
-            .chain(xdg_paths.iter())
-            .chain(home_share_path.iter())
-            .unique()
-            .cloned()
-            .collect_vec();
+        db::maintenance::compact(db.clone())
+            .await
+            .map_err(|err| err.to_string())
+    }
 
+    async fn run_detached(
+        self,
+        _context: ::tarpc::context::Context,
+        cmd: Vec<String>,
+    ) -> Result<DBEntryId, String> {
+        commands::run_detached(cmd)
+            .await
+            .map_err(|err| err.to_string())
+    }
+
+    async fn command_runs(
+        self,
+        _context: ::tarpc::context::Context,
+        limit: usize,
+    ) -> Result<Vec<CommandRun>, String> {
         let db = DB_REF.get().unwrap();
 
-        // Apps Search
-        {
-            let db_clone = db.clone();
-
-            tasks.spawn(async move {
-                let mut desktop_entries_stream = LiveSearchAppsQuery
-                    .instrumented_execute(db_clone.clone())
-                    .await?;
-
-                while let Some(entry) = desktop_entries_stream.next().await {
-                    match entry {
-                        Ok(DBNotification { action, data, .. }) => match action {
-                            DBAction::Create => {
-                                let _ = CreateAppEntryQuery::new(data)
-                                    .inspect_err(|err| tracing::error!("{err}"))?
-                                    .instrumented_execute(db_clone.clone())
-                                    .await;
-                            }
-                            DBAction::Update => {
-                                tracing::error!("UPDATE???");
-                                // TODO
-                            }
-                            DBAction::Delete => {
-                                tracing::error!("DELETE???");
-                                // TODO
-                            }
-                            _ => todo!(),
-                        },
-                        Err(err) => {
-                            tracing::error!("{err}");
-                            continue;
-                        }
-                    }
-                }
-
-                Ok(())
-            });
-        }
+        GetCommandRunsQuery { limit }
+            .instrumented_execute(db.clone())
+            .await
+            .map_err(|err| err.to_string())
+    }
 
-        // .desktop Search
-        search_paths(&mut tasks, app_paths, vec!["desktop"], ".desktop".into());
-
-        // Icons Search
-        search_paths(
-            &mut tasks,
-            icon_paths,
-            vec![
-                "png", "jpg", "jpeg", "gif", "webp", "pbm", "pam", "ppm", "pgm", "tiff", "tif",
-                "tga", "dds", "bmp", "ico", "hdr", "exr", "ff", "avif", "qoi", "pcx", "svg", "xpm",
-            ],
-            "icon".into(),
-        );
-
-        task::spawn(async move {
-            let _ = tasks
-                .join_all()
-                .await
-                .into_iter()
-                .collect::<Result<Vec<_>>>();
-
-            tracing::info!("Done searching for apps and icons!");
-            SEARCHING_FOR_APPS_ICONS.store(false, SeqCst);
-        });
+    async fn hash_files(self, _context: ::tarpc::context::Context) -> IndexSummary {
+        tracing::info!("Hashing indexed files");
+
+        let job = JobRecorder::start("hash").await;
+        let stats = hashes::hash_files().await;
+        let summary = IndexSummary {
+            items: stats.items,
+            errors: stats.errors.len(),
+        };
 
-        tracing::info!("Waiting on rest of apps and icons in a detached task...");
+        job.finish(stats).await;
+
+        summary
     }
 
-    async fn index(self, _context: ::tarpc::context::Context, root: PathBuf, parents: bool) {
-        tracing::info!("Indexing {root:?}");
+    async fn hash_progress(self, _context: ::tarpc::context::Context) -> Option<HashProgress> {
+        hashes::progress()
+    }
+
+    async fn duplicates(
+        self,
+        _context: ::tarpc::context::Context,
+        limit: usize,
+    ) -> Result<Vec<DuplicateGroup>, String> {
+        let db = DB_REF.get().unwrap();
+
+        GetDuplicatesQuery { limit }
+            .instrumented_execute(db.clone())
+            .await
+            .map_err(|err| err.to_string())
+    }
 
-        fs::index(root, parents, |_| None).await
+    async fn set_log_filter(
+        self,
+        _context: ::tarpc::context::Context,
+        filter: String,
+    ) -> Result<String, String> {
+        leaper_tracing::set_filter(&filter).map_err(|err| err.to_string())
     }
 }
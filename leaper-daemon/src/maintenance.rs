@@ -0,0 +1,16 @@
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Held for writing while the DB is being backed up or compacted, everything that writes
+/// into the DB holds it for reading per record
+static MAINTENANCE: RwLock<()> = RwLock::const_new(());
+
+/// Waits out a running backup/compaction
+pub async fn pause_point() -> RwLockReadGuard<'static, ()> {
+    MAINTENANCE.read().await
+}
+
+/// Waits for in-flight writes to finish and keeps new ones from starting
+pub async fn begin() -> RwLockWriteGuard<'static, ()> {
+    tracing::info!("Pausing indexing for DB maintenance");
+    MAINTENANCE.write().await
+}
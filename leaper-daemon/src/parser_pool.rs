@@ -0,0 +1,208 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Semaphore, mpsc};
+
+use db::{DB, DBErrorKind, InstrumentedDBQuery, apps::CreateAppEntryQuery};
+use leaper_tracing::error_limited;
+
+use crate::maintenance;
+
+static METRICS: Metrics = Metrics::new();
+
+/// Counters of the desktop entry parsing since the daemon started
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct ParseStats {
+    /// Waiting for a worker right now
+    pub queued: u64,
+    pub parsed: u64,
+    /// Entries that aren't apps (e.g. `Type=Directory`)
+    pub skipped: u64,
+    pub failed: u64,
+    pub created: u64,
+    /// Desktop files that already had an app, which got updated instead
+    pub updated: u64,
+    /// Apps left out because another desktop file already has their name
+    pub conflicts: u64,
+    /// Desktop files left out for one with the same id in a data dir of higher precedence
+    pub masked: u64,
+    /// Writes that failed for other reasons
+    pub write_errors: u64,
+    pub batches: u64,
+}
+
+struct Metrics {
+    queued: AtomicU64,
+    parsed: AtomicU64,
+    skipped: AtomicU64,
+    failed: AtomicU64,
+    created: AtomicU64,
+    updated: AtomicU64,
+    conflicts: AtomicU64,
+    masked: AtomicU64,
+    write_errors: AtomicU64,
+    batches: AtomicU64,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Self {
+            queued: AtomicU64::new(0),
+            parsed: AtomicU64::new(0),
+            skipped: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            created: AtomicU64::new(0),
+            updated: AtomicU64::new(0),
+            conflicts: AtomicU64::new(0),
+            masked: AtomicU64::new(0),
+            write_errors: AtomicU64::new(0),
+            batches: AtomicU64::new(0),
+        }
+    }
+}
+
+pub fn stats() -> ParseStats {
+    let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+    ParseStats {
+        queued: load(&METRICS.queued),
+        parsed: load(&METRICS.parsed),
+        skipped: load(&METRICS.skipped),
+        failed: load(&METRICS.failed),
+        created: load(&METRICS.created),
+        updated: load(&METRICS.updated),
+        conflicts: load(&METRICS.conflicts),
+        masked: load(&METRICS.masked),
+        write_errors: load(&METRICS.write_errors),
+        batches: load(&METRICS.batches),
+    }
+}
+
+/// Parses the .desktop entries found by the indexer on a few blocking workers at once,
+/// the app records are created in batches of whatever got parsed in the meantime.
+///
+/// The queue is bounded, [`Self::submit`] waits while it's full so a big walk can't
+/// pile up paths faster than they're parsed. Dropping the pool lets the queued
+/// entries finish and stops the workers
+pub struct ParserPool {
+    paths: mpsc::Sender<String>,
+}
+
+impl ParserPool {
+    const QUEUE: usize = 256;
+    const MAX_WORKERS: usize = 8;
+    const BATCH_SIZE: usize = 64;
+
+    pub fn spawn(db: DB) -> Self {
+        let (paths, paths_rx) = mpsc::channel(Self::QUEUE);
+        let (queries, queries_rx) = mpsc::channel(Self::BATCH_SIZE);
+
+        tokio::spawn(Self::dispatch(paths_rx, queries));
+        tokio::spawn(Self::create(db, queries_rx));
+
+        Self { paths }
+    }
+
+    pub async fn submit(&self, path: String) {
+        METRICS.queued.fetch_add(1, Ordering::Relaxed);
+
+        if self.paths.send(path).await.is_err() {
+            METRICS.queued.fetch_sub(1, Ordering::Relaxed);
+            tracing::error!("Desktop entry parser pool stopped, dropping an entry");
+        }
+    }
+
+    fn workers() -> usize {
+        std::thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(1)
+            .clamp(1, Self::MAX_WORKERS)
+    }
+
+    /// Hands the queued paths to the workers, at most [`Self::workers`] parse at a time
+    async fn dispatch(
+        mut paths: mpsc::Receiver<String>,
+        queries: mpsc::Sender<CreateAppEntryQuery>,
+    ) {
+        let workers = Arc::new(Semaphore::new(Self::workers()));
+
+        while let Some(path) = paths.recv().await {
+            let Ok(permit) = workers.clone().acquire_owned().await else {
+                break;
+            };
+            let queries = queries.clone();
+
+            METRICS.queued.fetch_sub(1, Ordering::Relaxed);
+
+            tokio::spawn(async move {
+                let parse_path = path.clone();
+                let res =
+                    tokio::task::spawn_blocking(move || CreateAppEntryQuery::new(&parse_path))
+                        .await;
+                drop(permit);
+
+                let query = match res {
+                    Ok(Ok(Some(query))) => query,
+                    Ok(Ok(None)) => {
+                        METRICS.skipped.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    Ok(Err(err)) => {
+                        METRICS.failed.fetch_add(1, Ordering::Relaxed);
+                        // A broken entry shows up again on every walk
+                        error_limited!((&path, "desktop-parse"), "{err}");
+                        return;
+                    }
+                    Err(err) => {
+                        METRICS.failed.fetch_add(1, Ordering::Relaxed);
+                        tracing::error!("Desktop entry parser of {path} panicked: {err}");
+                        return;
+                    }
+                };
+
+                METRICS.parsed.fetch_add(1, Ordering::Relaxed);
+                let _ = queries.send(query).await;
+            });
+        }
+    }
+
+    /// Creates the parsed entries, everything that's ready at once
+    async fn create(db: DB, mut queries: mpsc::Receiver<CreateAppEntryQuery>) {
+        let mut batch = Vec::with_capacity(Self::BATCH_SIZE);
+
+        while queries.recv_many(&mut batch, Self::BATCH_SIZE).await > 0 {
+            let _maintenance = maintenance::pause_point().await;
+
+            let results = futures::future::join_all(
+                batch
+                    .drain(..)
+                    .map(|query| query.instrumented_execute(db.clone())),
+            )
+            .await;
+
+            for res in results {
+                let counter = match res {
+                    Ok(Some(write)) if write.masked => &METRICS.masked,
+                    Ok(Some(write)) if write.created => &METRICS.created,
+                    Ok(Some(_)) => &METRICS.updated,
+                    Ok(None) => &METRICS.write_errors,
+                    Err(err) if err.kind() == DBErrorKind::Conflict => {
+                        tracing::debug!("Skipping an app entry: {err}");
+                        &METRICS.conflicts
+                    }
+                    Err(err) => {
+                        tracing::warn!("Failed to write an app entry: {err}");
+                        &METRICS.write_errors
+                    }
+                };
+
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+
+            METRICS.batches.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    hash::{BuildHasher, Hasher, RandomState},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use mode::config::DaemonConfig;
+
+use leaper_daemon::{apps, fs, hashes, jobs::JobRecorder};
+
+/// When the next automatic re-index is due, reported through the status RPC
+pub static NEXT_REINDEX: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Periodically re-runs the app/icon search and re-indexes the configured directories
+pub async fn run(config: DaemonConfig) {
+    if config.reindex_interval == 0 {
+        tracing::info!("Automatic re-indexing is disabled");
+        return;
+    }
+
+    let interval = Duration::from_secs(config.reindex_interval * 60);
+
+    loop {
+        let delay = interval + jitter(config.reindex_jitter);
+        *NEXT_REINDEX.lock().unwrap() = Some(Instant::now() + delay);
+
+        tokio::time::sleep(delay).await;
+
+        if apps::searching() {
+            tracing::info!("Skipping scheduled re-index, a search job is already running");
+            continue;
+        }
+
+        tracing::info!("Running scheduled re-index");
+
+        apps::search_apps();
+
+        let job = JobRecorder::start("validate").await;
+        let stats = apps::validate().await;
+
+        job.finish(stats).await;
+
+        for root in &config.reindex_paths {
+            let job = JobRecorder::start("reindex").await;
+            let stats = fs::index(root.clone(), false).await;
+
+            job.finish(stats).await;
+        }
+
+        if config.hash_files {
+            let job = JobRecorder::start("hash").await;
+            let stats = hashes::hash_files().await;
+
+            job.finish(stats).await;
+        }
+    }
+}
+
+fn jitter(max_secs: u64) -> Duration {
+    if max_secs == 0 {
+        return Duration::ZERO;
+    }
+
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_secs(random % (max_secs + 1))
+}
@@ -0,0 +1,185 @@
+use std::sync::{
+    Mutex,
+    atomic::{AtomicU32, Ordering},
+};
+
+use color_eyre::{Result, eyre::bail};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use zbus::{
+    Connection,
+    fdo::{DBusProxy, RequestNameFlags, RequestNameReply},
+    interface,
+    message::Header,
+    names::UniqueName,
+};
+
+use crate::{events::Inhibitor as IdleLock, jobs};
+
+static INHIBITORS: Mutex<Vec<Inhibitor>> = Mutex::new(vec![]);
+/// Woken whenever [`INHIBITORS`] changes, see [`hold_idle_lock`]
+static CHANGED: Notify = Notify::const_new();
+/// Cookies start at 1, some clients treat 0 as a failed call
+static NEXT_COOKIE: AtomicU32 = AtomicU32::new(1);
+
+/// Client keeping the screen on (e.g. a video player), see [`serve`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Inhibitor {
+    pub cookie: u32,
+    pub app: String,
+    pub reason: String,
+    /// Unique bus name of the client, its inhibitors go away when it disconnects
+    pub client: String,
+    /// Unix timestamp (milliseconds)
+    pub since: i64,
+}
+
+/// Inhibitors active right now, oldest first
+pub fn inhibitors() -> Vec<Inhibitor> {
+    INHIBITORS.lock().unwrap().clone()
+}
+
+/// Whether the idle lock should hold off, see [`hold_idle_lock`]
+pub fn inhibited() -> bool {
+    !INHIBITORS.lock().unwrap().is_empty()
+}
+
+struct ScreenSaver;
+
+#[interface(name = "org.freedesktop.ScreenSaver")]
+impl ScreenSaver {
+    fn inhibit(
+        &self,
+        application_name: String,
+        reason_for_inhibit: String,
+        #[zbus(header)] header: Header<'_>,
+    ) -> u32 {
+        let cookie = NEXT_COOKIE.fetch_add(1, Ordering::Relaxed);
+        let client = header.sender().map(ToString::to_string).unwrap_or_default();
+
+        tracing::debug!(
+            "{application_name} ({client}) inhibits the idle lock: {reason_for_inhibit} [{cookie}]"
+        );
+
+        INHIBITORS.lock().unwrap().push(Inhibitor {
+            cookie,
+            app: application_name,
+            reason: reason_for_inhibit,
+            client,
+            since: jobs::now_millis(),
+        });
+        CHANGED.notify_one();
+
+        cookie
+    }
+
+    /// Only the client that got the cookie can release it
+    fn un_inhibit(&self, cookie: u32, #[zbus(header)] header: Header<'_>) {
+        let client = header.sender().map(ToString::to_string).unwrap_or_default();
+
+        INHIBITORS
+            .lock()
+            .unwrap()
+            .retain(|inhibitor| !(inhibitor.cookie == cookie && inhibitor.client == client));
+        CHANGED.notify_one();
+
+        tracing::debug!("{client} released the idle lock inhibitor [{cookie}]");
+    }
+
+    /// Some clients (e.g. xdg-screensaver) poke this instead of inhibiting, the idle
+    /// timer is the compositor's so there's nothing to reset
+    fn simulate_user_activity(&self) {}
+
+    fn get_active(&self) -> bool {
+        false
+    }
+}
+
+/// Serves `org.freedesktop.ScreenSaver` on the session bus, so apps can keep the idle
+/// lock from kicking in. Gives up if something else (e.g. hypridle or a desktop
+/// environment) already owns the name, it's never taken away from them
+pub async fn serve() -> Result<()> {
+    let connection = Connection::session().await?;
+
+    // Both paths are in use, /ScreenSaver by the older clients
+    for path in ["/org/freedesktop/ScreenSaver", "/ScreenSaver"] {
+        connection.object_server().at(path, ScreenSaver).await?;
+    }
+
+    match connection
+        .request_name_with_flags(
+            "org.freedesktop.ScreenSaver",
+            RequestNameFlags::DoNotQueue.into(),
+        )
+        .await?
+    {
+        RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner => {}
+        _ => bail!("org.freedesktop.ScreenSaver is already served by something else"),
+    }
+
+    tracing::info!("Serving org.freedesktop.ScreenSaver");
+
+    tokio::spawn(async {
+        if let Err(err) = hold_idle_lock().await {
+            tracing::error!("Screen saver inhibitors won't hold off the idle lock: {err}");
+        }
+    });
+
+    let dbus = DBusProxy::new(&connection).await?;
+    let mut owner_changes = dbus.receive_name_owner_changed().await?;
+
+    while let Some(change) = owner_changes.next().await {
+        let Ok(args) = change.args() else {
+            continue;
+        };
+
+        // Unique names lose their owner when the client disconnects
+        if args.new_owner().is_none()
+            && let Ok(client) = UniqueName::try_from(args.name().as_str())
+        {
+            drop_client(client.as_str());
+        }
+    }
+
+    Ok(())
+}
+
+/// The idle lock itself is up to the compositor's idle daemon (hypridle, swayidle with
+/// systemd support, ...), those hold off while there's a logind "idle" inhibitor. One is
+/// held for as long as any client inhibits
+async fn hold_idle_lock() -> Result<()> {
+    let connection = Connection::system().await?;
+    let mut lock = None;
+
+    loop {
+        match (inhibited(), lock.is_some()) {
+            (true, false) => {
+                lock = Some(
+                    IdleLock::take(
+                        &connection,
+                        "idle",
+                        "Inhibited through org.freedesktop.ScreenSaver",
+                    )
+                    .await?,
+                )
+            }
+            (false, true) => lock = None,
+            _ => {}
+        }
+
+        CHANGED.notified().await;
+    }
+}
+
+fn drop_client(client: &str) {
+    let mut inhibitors = INHIBITORS.lock().unwrap();
+    let before = inhibitors.len();
+
+    inhibitors.retain(|inhibitor| inhibitor.client != client);
+
+    if inhibitors.len() != before {
+        tracing::debug!("Dropped the idle lock inhibitors of {client}, it disconnected");
+        CHANGED.notify_one();
+    }
+}
@@ -0,0 +1,230 @@
+use std::{collections::HashMap, process::Stdio};
+
+use color_eyre::{Result, eyre::eyre};
+use futures::StreamExt;
+use tokio::{sync::oneshot, task::JoinHandle};
+use zbus::{
+    Connection,
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value},
+};
+
+use mode::config::{Shortcut, ShortcutMode};
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.GlobalShortcuts",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait GlobalShortcuts {
+    fn create_session(&self, options: HashMap<&str, Value<'_>>) -> zbus::Result<OwnedObjectPath>;
+
+    fn bind_shortcuts(
+        &self,
+        session_handle: &ObjectPath<'_>,
+        shortcuts: &[(&str, HashMap<&str, Value<'_>>)],
+        parent_window: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(signal)]
+    fn activated(
+        &self,
+        session_handle: ObjectPath<'_>,
+        shortcut_id: &str,
+        timestamp: u64,
+        options: HashMap<&str, OwnedValue>,
+    ) -> zbus::Result<()>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.Request",
+    default_service = "org.freedesktop.portal.Desktop"
+)]
+trait Request {
+    #[zbus(signal)]
+    fn response(&self, response: u32, results: HashMap<&str, OwnedValue>) -> zbus::Result<()>;
+}
+
+/// Binds the configured shortcuts through the GlobalShortcuts portal and toggles their modes
+/// whenever they get activated. Returns right away when nothing is configured
+pub async fn register(shortcuts: Vec<Shortcut>) -> Result<()> {
+    if shortcuts.is_empty() {
+        return Ok(());
+    }
+
+    let connection = Connection::session().await?;
+    let portal = GlobalShortcutsProxy::new(&connection).await?;
+
+    let session_token = "leaper";
+    let results = request(&connection, "leaper_session", |mut options| {
+        let portal = portal.clone();
+
+        async move {
+            options.insert("session_handle_token", session_token.into());
+
+            portal.create_session(options).await
+        }
+    })
+    .await?;
+    let session = results
+        .get("session_handle")
+        .and_then(|handle| String::try_from(handle.clone()).ok())
+        .and_then(|handle| OwnedObjectPath::try_from(handle).ok())
+        .ok_or_else(|| eyre!("Portal didn't return a session handle"))?;
+
+    let bindings = shortcuts
+        .iter()
+        .map(|shortcut| {
+            let description = format!("Toggle leaper {}", shortcut.mode.name());
+            let options = HashMap::from([
+                ("description", Value::from(description)),
+                ("preferred_trigger", Value::from(shortcut.trigger.as_str())),
+            ]);
+
+            (shortcut.mode.name(), options)
+        })
+        .collect::<Vec<_>>();
+
+    request(&connection, "leaper_bind", |options| {
+        let portal = portal.clone();
+        let session = session.clone();
+
+        async move {
+            portal
+                .bind_shortcuts(&session, &bindings, "", options)
+                .await
+        }
+    })
+    .await?;
+
+    tracing::info!("Registered {} global shortcut(s)", shortcuts.len());
+
+    let mut activated = portal.receive_activated().await?;
+    let mut running = HashMap::<ShortcutMode, Running>::new();
+
+    while let Some(signal) = activated.next().await {
+        let args = match signal.args() {
+            Ok(args) => args,
+            Err(err) => {
+                tracing::warn!("Failed to read an activated shortcut: {err}");
+                continue;
+            }
+        };
+
+        let Some(shortcut) = shortcuts
+            .iter()
+            .find(|shortcut| shortcut.mode.name() == args.shortcut_id)
+        else {
+            tracing::warn!("Unknown shortcut {:?} activated", args.shortcut_id);
+            continue;
+        };
+
+        toggle(&mut running, shortcut.mode).await;
+    }
+
+    Ok(())
+}
+
+/// Runs a portal method and waits for its `Response` on the request object.
+/// The request path is predictable from the handle token, so the signal gets
+/// subscribed to before the call and can't be missed
+async fn request<'a, F, Fut>(
+    connection: &Connection,
+    token: &'a str,
+    call: F,
+) -> Result<HashMap<String, OwnedValue>>
+where
+    F: FnOnce(HashMap<&'a str, Value<'a>>) -> Fut,
+    Fut: Future<Output = zbus::Result<OwnedObjectPath>>,
+{
+    let sender = connection
+        .unique_name()
+        .ok_or_else(|| eyre!("Session bus connection has no unique name"))?
+        .trim_start_matches(':')
+        .replace('.', "_");
+    let path = format!("/org/freedesktop/portal/desktop/request/{sender}/{token}");
+
+    let request = RequestProxy::builder(connection)
+        .path(path)?
+        .build()
+        .await?;
+    let mut responses = request.receive_response().await?;
+
+    call(HashMap::from([("handle_token", Value::from(token))])).await?;
+
+    let response = responses
+        .next()
+        .await
+        .ok_or_else(|| eyre!("Portal request was dropped"))?;
+    let args = response.args()?;
+
+    match args.response {
+        0 => Ok(args
+            .results
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect()),
+        1 => Err(eyre!("Portal request was cancelled by the user")),
+        _ => Err(eyre!("Portal request failed")),
+    }
+}
+
+/// A mode the daemon started. Its task waits on the process, so it gets reaped as soon
+/// as it exits, whether on its own or after being closed
+struct Running {
+    close: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+/// Closes the mode if the daemon started it and it's still open, starts it otherwise.
+/// The lock screen is never closed, a shortcut shouldn't be a way around it
+async fn toggle(running: &mut HashMap<ShortcutMode, Running>, mode: ShortcutMode) {
+    if let Some(open) = running.remove(&mode)
+        && !open.task.is_finished()
+    {
+        match mode {
+            ShortcutMode::Lock => {
+                running.insert(mode, open);
+            }
+            _ => {
+                tracing::debug!("Closing {}", mode.name());
+
+                let _ = open.close.send(());
+                let _ = open.task.await;
+            }
+        }
+
+        return;
+    }
+
+    tracing::debug!("Opening {}", mode.name());
+
+    let mut child = match tokio::process::Command::new("leaper")
+        .arg(mode.name())
+        .stdin(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            tracing::error!("Failed to run leaper {}: {err}", mode.name());
+            return;
+        }
+    };
+
+    let (close, closed) = oneshot::channel();
+    let task = tokio::spawn(async move {
+        tokio::select! {
+            status = child.wait() => match status {
+                Ok(status) => tracing::debug!("{} exited with {status}", mode.name()),
+                Err(err) => tracing::warn!("Failed to wait on {}: {err}", mode.name()),
+            },
+            _ = closed => {
+                if let Err(err) = child.kill().await {
+                    tracing::error!("Failed to close {}: {err}", mode.name());
+                }
+            }
+        }
+    });
+
+    running.insert(mode, Running { close, task });
+}
@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet};
+
+use color_eyre::Result;
+use futures::StreamExt;
+use zbus::{
+    Connection, MatchRule, MessageStream,
+    message::Type,
+    zvariant::{OwnedObjectPath, OwnedValue},
+};
+
+use db::{
+    InstrumentedDBQuery,
+    volumes::{MountVolumeQuery, UnmountVolumesQuery},
+};
+
+use crate::{DB_REF, fs, jobs::JobRecorder};
+
+const UDISKS: &str = "org.freedesktop.UDisks2";
+const FILESYSTEM: &str = "org.freedesktop.UDisks2.Filesystem";
+
+type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
+
+/// Mounted removable/external filesystem, as reported by udisks2
+#[derive(Debug, Clone)]
+struct Mount {
+    uuid: String,
+    label: Option<String>,
+    mount_point: String,
+}
+
+/// Keeps the `volume` table in sync with what udisks2 reports, so results from
+/// unmounted drives get hidden. Newly mounted drives are indexed when `index_removable` is set
+pub async fn watch(index_removable: bool) -> Result<()> {
+    let connection = Connection::system().await?;
+    let signal = |interface: &'static str, member: &'static str| -> zbus::Result<_> {
+        Ok(MatchRule::builder()
+            .msg_type(Type::Signal)
+            .sender(UDISKS)?
+            .interface(interface)?
+            .member(member)?)
+    };
+
+    // Drives coming and going, and filesystems getting (un)mounted. Not every signal
+    // udisks2 sends, e.g. SMART updates and job progress don't change what's mounted
+    let rules = [
+        signal("org.freedesktop.DBus.ObjectManager", "InterfacesAdded")?.build(),
+        signal("org.freedesktop.DBus.ObjectManager", "InterfacesRemoved")?.build(),
+        signal("org.freedesktop.DBus.Properties", "PropertiesChanged")?
+            .arg(0, FILESYSTEM)?
+            .build(),
+    ];
+    let mut streams = vec![];
+
+    for rule in rules {
+        streams.push(MessageStream::for_match_rule(rule, &connection, None).await?);
+    }
+
+    let mut signals = futures::stream::select_all(streams);
+    let mut mounted = HashSet::new();
+
+    loop {
+        match mounts(&connection).await {
+            Ok(mounts) => sync(mounts, &mut mounted, index_removable).await,
+            Err(err) => tracing::warn!("Failed to list udisks2 mounts: {err}"),
+        }
+
+        loop {
+            match signals.next().await {
+                Some(Ok(signal)) if changes_mounts(&signal) => break,
+                Some(Ok(_)) => {}
+                Some(Err(err)) => tracing::warn!("Failed to read a udisks2 signal: {err}"),
+                None => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Only the MountPoints of a filesystem matter out of its property changes
+fn changes_mounts(signal: &zbus::Message) -> bool {
+    let header = signal.header();
+
+    if header
+        .member()
+        .is_none_or(|member| member.as_str() != "PropertiesChanged")
+    {
+        return true;
+    }
+
+    signal
+        .body()
+        .deserialize::<(String, HashMap<String, OwnedValue>, Vec<String>)>()
+        .is_ok_and(|(_, changed, invalidated)| {
+            changed.contains_key("MountPoints")
+                || invalidated.iter().any(|name| name == "MountPoints")
+        })
+}
+
+async fn sync(mounts: Vec<Mount>, mounted: &mut HashSet<String>, index_removable: bool) {
+    let db = DB_REF.get().unwrap();
+
+    for mount in &mounts {
+        if let Err(err) = MountVolumeQuery::builder()
+            .uuid(&mount.uuid)
+            .maybe_label(mount.label.clone())
+            .mount_point(&mount.mount_point)
+            .build()
+            .instrumented_execute(db.clone())
+            .await
+        {
+            tracing::error!("Failed to record volume {:?}: {err}", mount.mount_point);
+            continue;
+        }
+
+        if mounted.insert(mount.uuid.clone()) && index_removable {
+            tracing::info!("Indexing removable volume {:?}", mount.mount_point);
+
+            let root = mount.mount_point.clone().into();
+
+            tokio::spawn(async move {
+                let job = JobRecorder::start("volume").await;
+                let stats = fs::index(root, false).await;
+
+                job.finish(stats).await;
+            });
+        }
+    }
+
+    let uuids = mounts
+        .into_iter()
+        .map(|mount| mount.uuid)
+        .collect::<Vec<_>>();
+    mounted.retain(|uuid| uuids.contains(uuid));
+
+    if let Err(err) = (UnmountVolumesQuery { mounted: uuids })
+        .instrumented_execute(db.clone())
+        .await
+    {
+        tracing::error!("Failed to mark unmounted volumes: {err}");
+    }
+}
+
+/// Mounted filesystems that udisks2 doesn't consider part of the system
+async fn mounts(connection: &Connection) -> Result<Vec<Mount>> {
+    let reply = connection
+        .call_method(
+            Some(UDISKS),
+            "/org/freedesktop/UDisks2",
+            Some("org.freedesktop.DBus.ObjectManager"),
+            "GetManagedObjects",
+            &(),
+        )
+        .await?;
+    let objects: ManagedObjects = reply.body().deserialize()?;
+
+    Ok(objects
+        .values()
+        .filter_map(|interfaces| {
+            let block = interfaces.get("org.freedesktop.UDisks2.Block")?;
+            let filesystem = interfaces.get(FILESYSTEM)?;
+
+            if property::<bool>(block, "HintSystem").unwrap_or(true) {
+                return None;
+            }
+
+            let uuid = property::<String>(block, "IdUUID").filter(|uuid| !uuid.is_empty())?;
+            let label = property::<String>(block, "IdLabel").filter(|label| !label.is_empty());
+
+            // NUL-terminated byte strings, only the first mount point is used
+            let mount_point = property::<Vec<Vec<u8>>>(filesystem, "MountPoints")?
+                .into_iter()
+                .next()
+                .map(|mut bytes| {
+                    bytes.pop_if(|byte| *byte == 0);
+                    String::from_utf8_lossy(&bytes).into_owned()
+                })?;
+
+            Some(Mount {
+                uuid,
+                label,
+                mount_point,
+            })
+        })
+        .collect())
+}
+
+fn property<T>(properties: &HashMap<String, OwnedValue>, name: &str) -> Option<T>
+where
+    T: TryFrom<OwnedValue>,
+{
+    let value = properties.get(name)?.try_clone().ok()?;
+
+    T::try_from(value).ok()
+}
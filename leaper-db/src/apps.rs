@@ -1,11 +1,18 @@
-use std::path::Path;
+use std::{
+    ffi::OsString,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    sync::{LazyLock, RwLock},
+};
 
 use freedesktop_desktop_entry::DesktopEntry;
 use serde::{Deserialize, Serialize};
 use surrealdb::types::{RecordId, SurrealValue};
 use surrealdb_extras::{SurrealQuery, SurrealTable};
 
-use crate::{DBError, DBResult};
+use parsers::exec::ExecFields;
+
+use crate::{DB, DBEnum, DBError, DBResult, InstrumentedDBQuery};
 
 #[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
 #[table(
@@ -16,16 +23,19 @@ use crate::{DBError, DBResult};
         "DEFINE INDEX app_name_ind ON TABLE app COLUMNS name UNIQUE",
         "
         DEFINE EVENT app_entry_added ON TABLE app
-            WHEN $event = 'CREATE' && $after.icon_name != NULL
+            WHEN $after.icon_name != NULL && (
+                $event = 'CREATE'
+                || ($event = 'UPDATE' && $before.icon_name != $after.icon_name)
+            )
             THEN {
-                LET $icon = (SELECT * FROM icon
-                    WHERE name == $value.icon_name
-                    ORDER BY dims.width,dims.height,svg
-                    LIMIT 1);
+                DELETE has_icon WHERE in == $value.id;
 
-                IF $icon != NONE THEN
+                // Every candidate gets related, the preferred one is picked when querying.
+                // Snap entries point to their icon with an absolute path instead of a name
+                FOR $icon IN (SELECT VALUE id FROM icon
+                    WHERE name == $value.icon_name OR path == $value.icon_name) {
                     RELATE $value->has_icon->$icon;
-                END;
+                };
             }
         "
     )
@@ -33,98 +43,770 @@ use crate::{DBError, DBResult};
 pub struct AppEntry {
     pub id: RecordId,
     pub desktop_entry_path: String,
+    /// See [`data_dir_rank`]
+    #[serde(default)]
+    pub data_dir_rank: u32,
+    /// Translated for the user's locale when the entry has a translation
     pub name: String,
+    /// Untranslated Name, searched along with the translated one
+    #[serde(default)]
+    pub canonical_name: String,
+    /// e.g. "Web Browser" (GenericName)
+    #[serde(default)]
+    pub generic_name: Option<String>,
+    /// Tooltip line (Comment)
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Additional search terms (Keywords)
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    pub kind: AppKind,
     pub exec: Vec<String>,
+    /// Target of Type=Link entries
+    pub url: Option<String>,
     pub icon_name: Option<String>,
+    pub categories: Vec<String>,
+    /// Desktops (XDG_CURRENT_DESKTOP names) the entry is limited to, empty for all of them
+    #[serde(default)]
+    pub only_show_in: Vec<String>,
+    #[serde(default)]
+    pub not_show_in: Vec<String>,
+    /// Installed but not meant to be in menus (NoDisplay), e.g. helpers opening files
+    #[serde(default)]
+    pub no_display: bool,
+    /// Deleted by the user or the distro (Hidden), never listed
+    #[serde(default)]
+    pub hidden: bool,
+    /// Runs in a terminal (Terminal)
+    #[serde(default)]
+    pub terminal: bool,
+    /// Binary whose presence tells if the app is installed (TryExec)
+    #[serde(default)]
+    pub try_exec: Option<String>,
+    /// [`Self::try_exec`] wasn't found last time it was checked, the entry is hidden
+    #[serde(default)]
+    pub try_exec_missing: bool,
+    /// Started over D-Bus by its desktop file id (DBusActivatable), Exec is only a fallback
+    #[serde(default)]
+    pub dbus_activatable: bool,
+    /// Asks to run on the discrete GPU (PrefersNonDefaultGPU)
+    #[serde(default)]
+    pub prefers_non_default_gpu: bool,
+    /// `[Desktop Action ...]` groups, in the order of the Actions key
+    #[serde(default)]
+    pub actions: Vec<AppAction>,
+}
+
+/// Additional way to start an app, e.g. "New Private Window"
+#[derive(Debug, Clone, PartialEq, SurrealValue, Serialize, Deserialize)]
+pub struct AppAction {
+    /// Name of the action group, what D-Bus activation refers to it by
+    pub id: String,
+    pub name: String,
+    /// Empty for D-Bus activated apps that leave it out
+    pub exec: Vec<String>,
+    pub icon_name: Option<String>,
+}
+
+/// Desktop entry `Type`, Type=Directory entries are not stored
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, SurrealValue, DBEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum AppKind {
+    #[default]
+    Application,
+    Link,
+}
+
+/// What [`CreateAppEntryQuery`] did with an entry
+#[derive(Debug, Clone, SurrealValue, Serialize, Deserialize)]
+pub struct AppEntryWrite {
+    pub id: RecordId,
+    /// `false` if an app with the same desktop file existed and got updated instead
+    pub created: bool,
+    /// A desktop file with the same id in a data dir of higher precedence has the app,
+    /// nothing was written
+    #[serde(default)]
+    pub masked: bool,
+}
+
+/// Key of the app record of a desktop file, a hash of its desktop file id so the record
+/// (and the launch history pointing at it) stays the same when the file moves between
+/// data dirs, e.g. a package going from /usr/share to a flatpak export
+pub fn app_key(desktop_entry_path: &str) -> String {
+    let desktop_id = desktop_id(desktop_entry_path);
+
+    // FNV-1a, std's hashers aren't guaranteed to give the same result between releases
+    let hash = desktop_id
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+
+    format!("{hash:016x}")
 }
 
+/// Desktop file id of an entry (desktop entry spec): its path relative to the
+/// `applications` dir of its data dir without `.desktop`, subdirectories joined with `-`.
+/// E.g. `org.gnome.Nautilus` for `/usr/share/applications/org.gnome.Nautilus.desktop`
+/// and `kde4-kate` for `/usr/share/applications/kde4/kate.desktop`
+pub fn desktop_id(desktop_entry_path: &str) -> String {
+    let path = Path::new(desktop_entry_path);
+
+    match applications_dir(path).and_then(|dir| path.strip_prefix(dir).ok()) {
+        Some(relative) => relative
+            .with_extension("")
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("-"),
+        None => path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+/// Data dirs by the precedence the spec gives them: `XDG_DATA_HOME` (`~/.local/share`
+/// unless set), then `XDG_DATA_DIRS` (`/usr/local/share:/usr/share` unless set)
+static DATA_DIRS: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    let home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+    let dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+
+    home.into_iter()
+        .chain(
+            dirs.split(':')
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+        )
+        .collect()
+});
+
+/// Position of the data dir of an entry in the spec's order of precedence, lower first.
+/// Of the entries sharing a [`desktop_id`] (e.g. an override in `~/.local/share` of one
+/// in `/usr/share`) only the first one counts. Entries outside of the data dirs (e.g. a
+/// flatpak export missing from `XDG_DATA_DIRS`) come after all of them
+pub fn data_dir_rank(desktop_entry_path: &str) -> u32 {
+    rank_in(Path::new(desktop_entry_path), &DATA_DIRS)
+}
+
+fn rank_in(path: &Path, data_dirs: &[PathBuf]) -> u32 {
+    let data_dir = applications_dir(path).and_then(Path::parent);
+
+    data_dirs
+        .iter()
+        .position(|dir| Some(dir.as_path()) == data_dir)
+        .unwrap_or(data_dirs.len()) as u32
+}
+
+/// Closest `applications` dir the entry is in
+fn applications_dir(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.file_name().is_some_and(|name| name == "applications"))
+}
+
+/// Creates the app of a desktop file, or updates it if it's already there (e.g. the
+/// live query reporting the same file twice, or the file having moved). Records are
+/// keyed by [`app_key`], so `app_dep_ind` never gets violated. A file with the same id
+/// in a data dir of higher precedence (see [`data_dir_rank`]) keeps the app for as long
+/// as it's indexed, this one is left out.
+/// Two desktop files of apps with the same name still conflict on `app_name_ind`,
+/// see [`crate::DBErrorKind::Conflict`]
 #[derive(Debug, SurrealQuery)]
 #[query(
-    output = "Option<RecordId>",
+    output = "Option<AppEntryWrite>",
     error = DBError,
     sql = "
         BEGIN TRANSACTION;
 
-        LET $app = (CREATE app SET
-            desktop_entry_path = {path},
-            name = {name},
-            exec = {exec},
-            icon_name = {icon_name}).id;
-        LET $file = (SELECT VALUE ->is_file->file.id FROM ONLY fs_node WHERE path == {path} LIMIT 1);
+        LET $id = type::record('app', {key});
+        LET $existing = (SELECT desktop_entry_path, data_dir_rank FROM ONLY $id);
+        // Records from before the rank was stored don't mask anything
+        LET $masked = $existing != NONE
+            AND $existing.desktop_entry_path != {path}
+            AND ($existing.data_dir_rank ?? {data_dir_rank}) < {data_dir_rank}
+            AND (SELECT VALUE id FROM fs_node
+                WHERE path == $existing.desktop_entry_path LIMIT 1) != [];
+        LET $fields = {
+            desktop_entry_path: {path},
+            data_dir_rank: {data_dir_rank},
+            name: {name},
+            canonical_name: {canonical_name},
+            generic_name: {generic_name},
+            comment: {comment},
+            keywords: {keywords},
+            kind: {kind},
+            exec: {exec},
+            url: {url},
+            icon_name: {icon_name},
+            categories: {categories},
+            only_show_in: {only_show_in},
+            not_show_in: {not_show_in},
+            no_display: {no_display},
+            hidden: {hidden},
+            terminal: {terminal},
+            try_exec: {try_exec},
+            try_exec_missing: {try_exec_missing},
+            dbus_activatable: {dbus_activatable},
+            prefers_non_default_gpu: {prefers_non_default_gpu},
+            actions: {actions}
+        };
 
-        RELATE $file->is_app->$app;
+        IF !$masked {
+            IF $existing == NONE {
+                CREATE ONLY $id CONTENT $fields;
+            } ELSE {
+                UPDATE ONLY $id MERGE $fields;
+            };
+
+            // The file may have moved, the app belongs to the one it was found at now
+            DELETE is_app WHERE out == $id;
+            LET $file = (SELECT VALUE ->is_file->file.id FROM ONLY fs_node WHERE path == {path} LIMIT 1);
+            RELATE $file->is_app->$id;
+        };
 
         COMMIT TRANSACTION;
 
-        RETURN $app;
+        RETURN { id: $id, created: $existing == NONE, masked: $masked };
     "
 )]
 pub struct CreateAppEntryQuery {
+    /// [`app_key`]
+    key: String,
     path: String,
+    /// [`data_dir_rank`]
+    data_dir_rank: u32,
     name: String,
+    canonical_name: String,
+    generic_name: Option<String>,
+    comment: Option<String>,
+    keywords: Vec<String>,
+    kind: AppKind,
     exec: Vec<String>,
+    url: Option<String>,
     icon_name: Option<String>,
+    categories: Vec<String>,
+    only_show_in: Vec<String>,
+    not_show_in: Vec<String>,
+    no_display: bool,
+    hidden: bool,
+    terminal: bool,
+    try_exec: Option<String>,
+    try_exec_missing: bool,
+    dbus_activatable: bool,
+    prefers_non_default_gpu: bool,
+    actions: Vec<AppAction>,
 }
 
+/// Languages of the user (LC_ALL, LC_MESSAGES, LANG and LANGUAGE), best first
+static LOCALES: LazyLock<Vec<String>> =
+    LazyLock::new(freedesktop_desktop_entry::get_languages_from_env);
+
 impl CreateAppEntryQuery {
-    pub fn new(path: impl AsRef<Path>) -> DBResult<Self> {
+    /// `None` for entries that can't be launched (Type=Directory)
+    pub fn new(path: impl AsRef<Path>) -> DBResult<Option<Self>> {
         let path = path.as_ref();
-        let entry = DesktopEntry::from_path::<&str>(path, None)?;
-        let name = entry
-            .full_name::<&str>(&[])
-            .ok_or_else(|| DBError::DesktopEntryNoName(path.to_path_buf()))
-            .map(|s| s.trim().to_string())
-            .unwrap_or_else(|_| "Unknown".into());
-
-        let exec = entry
-            .exec()
-            .map(
-                |exec_str| match exec_str.split(" ").skip(1).any(|x| x.contains("%")) {
-                    true => entry.parse_exec().map_err(DBError::from).or_else(|_| {
-                        entry
-                            .parse_exec_with_uris::<&str>(&[], &[])
-                            .map_err(DBError::from)
-                            .or_else(|_| {
-                                entry
-                                    .exec()
-                                    .ok_or_else(|| DBError::DesktopEntryNoExec(path.into()))
-                                    .and_then(|exec_str| {
-                                        shlex::split(exec_str).ok_or_else(|| {
-                                            DBError::DesktopEntryParseExec(
-                                                path.to_path_buf(),
-                                                exec_str.into(),
-                                            )
-                                        })
-                                    })
-                            })
-                    }),
-                    false => shlex::split(exec_str).ok_or_else(|| {
-                        DBError::DesktopEntryParseExec(path.to_path_buf(), exec_str.into())
-                    }),
-                },
-            )
-            .transpose()?
-            .ok_or_else(|| DBError::DesktopEntryNoExec(path.into()))?;
+        let locales = LOCALES.as_slice();
+        let entry = DesktopEntry::from_path(path, Some(locales))?;
+        let full_name = |locales: &[String]| {
+            entry
+                .full_name(locales)
+                .ok_or_else(|| DBError::DesktopEntryNoName(path.to_path_buf()))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_else(|_| "Unknown".into())
+        };
+        let name = full_name(locales);
+        let canonical_name = full_name(&[]);
 
-        let icon_name = entry.icon().map(|icon_name| icon_name.to_string());
+        let flag = |key: &str| {
+            entry
+                .desktop_entry(key)
+                .is_some_and(|value| value.trim() == "true")
+        };
+        let dbus_activatable = flag("DBusActivatable");
+        let prefers_non_default_gpu = flag("PrefersNonDefaultGPU");
+        let no_display = flag("NoDisplay");
+        let hidden = flag("Hidden");
+        let terminal = flag("Terminal");
+
+        let desktop_file = path.to_string_lossy();
+        let fields = ExecFields {
+            name: &name,
+            icon: entry.icon(),
+            desktop_file: &desktop_file,
+        };
+
+        let (kind, exec, url) = match entry.type_() {
+            // Overrides deleting an app are often just `Hidden=true`, they're never launched
+            // and only have to mask the entry they override (see [`data_dir_rank`])
+            _ if hidden => (AppKind::Application, vec![], None),
+            Some("Directory") => {
+                tracing::debug!("Skipping directory entry {path:?}");
+                return Ok(None);
+            }
+            Some("Link") => {
+                let url = entry
+                    .desktop_entry("URL")
+                    .ok_or_else(|| DBError::DesktopEntryNoUrl(path.into()))?;
 
-        Ok(Self {
-            path: path.to_string_lossy().into(),
+                (AppKind::Link, vec![], Some(url.to_string()))
+            }
+            // Exec is optional for D-Bus activated apps
+            _ if dbus_activatable && entry.exec().is_none() => (AppKind::Application, vec![], None),
+            _ => {
+                let exec = entry
+                    .exec()
+                    .ok_or_else(|| DBError::DesktopEntryNoExec(path.into()))?;
+
+                (
+                    AppKind::Application,
+                    Self::parse_exec(path, exec, &fields)?,
+                    None,
+                )
+            }
+        };
+
+        let icon_name = entry.icon().map(|icon_name| {
+            // Absolute icon paths (snaps) may go through symlinks like `/snap/<name>/current`,
+            // indexed icons are stored under the resolved path
+            let icon_path = Path::new(icon_name);
+
+            match icon_path.is_absolute() {
+                true => icon_path
+                    .canonicalize()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .unwrap_or_else(|_| icon_name.to_string()),
+                false => icon_name.to_string(),
+            }
+        });
+        let categories = entry
+            .categories()
+            .map(|categories| categories.into_iter().map(Into::into).collect())
+            .unwrap_or_default();
+        let try_exec = entry.desktop_entry("TryExec").map(ToString::to_string);
+        let try_exec_missing = try_exec
+            .as_deref()
+            .is_some_and(|try_exec| !try_exec_found(try_exec));
+
+        if try_exec_missing {
+            tracing::debug!("Hiding {path:?}, its TryExec binary isn't installed");
+        }
+
+        let text = |value: Option<std::borrow::Cow<'_, str>>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let generic_name = text(entry.generic_name(locales));
+        let comment = text(entry.comment(locales));
+        let keywords = entry
+            .keywords(locales)
+            .map(|keywords| {
+                keywords
+                    .into_iter()
+                    .map(|keyword| keyword.trim().to_string())
+                    .filter(|keyword| !keyword.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let desktops = |list: Option<Vec<&str>>| {
+            list.map(|list| list.into_iter().map(Into::into).collect())
+                .unwrap_or_default()
+        };
+
+        let actions = match kind {
+            AppKind::Application if !hidden => Self::parse_actions(path, &entry, &fields),
+            _ => vec![],
+        };
+
+        let path = path.to_string_lossy().to_string();
+
+        Ok(Some(Self {
+            key: app_key(&path),
+            data_dir_rank: data_dir_rank(&path),
+            path,
             name,
+            canonical_name,
+            generic_name,
+            comment,
+            keywords,
+            kind,
             exec,
+            url,
             icon_name,
-        })
+            categories,
+            only_show_in: desktops(entry.only_show_in()),
+            not_show_in: desktops(entry.not_show_in()),
+            no_display,
+            hidden,
+            terminal,
+            try_exec,
+            try_exec_missing,
+            dbus_activatable,
+            prefers_non_default_gpu,
+            actions,
+        }))
+    }
+
+    /// Actions without a name, or with an Exec that doesn't parse, are left out
+    fn parse_actions(path: &Path, entry: &DesktopEntry, fields: &ExecFields) -> Vec<AppAction> {
+        entry
+            .actions()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|id| !id.is_empty())
+            .filter_map(|id| {
+                let name = entry
+                    .action_name(id, LOCALES.as_slice())?
+                    .trim()
+                    .to_string();
+                let exec = match entry.action_exec(id) {
+                    Some(exec) => match Self::parse_exec(path, exec, fields) {
+                        Ok(exec) => exec,
+                        Err(err) => {
+                            tracing::debug!("Skipping action {id} of {path:?}: {err}");
+                            return None;
+                        }
+                    },
+                    None => vec![],
+                };
+
+                Some(AppAction {
+                    id: id.to_string(),
+                    name,
+                    exec,
+                    icon_name: entry.action_entry(id, "Icon").map(ToString::to_string),
+                })
+            })
+            .collect()
+    }
+
+    /// Splits the Exec line and fills in its field codes
+    fn parse_exec(path: &Path, exec: &str, fields: &ExecFields) -> DBResult<Vec<String>> {
+        shlex::split(exec)
+            .map(|args| fields.expand(args))
+            .ok_or_else(|| DBError::DesktopEntryParseExec(path.to_path_buf(), exec.into()))
+    }
+}
+
+/// PATH of the user's session, see [`set_search_path`]
+static SEARCH_PATH: RwLock<Option<OsString>> = RwLock::new(None);
+
+/// Looks TryExec binaries up in `path` instead of the PATH of this process. A service
+/// (the daemon) gets a shorter one than the session it serves, which would hide apps
+/// installed into e.g. `~/.local/bin` or `~/.cargo/bin`
+pub fn set_search_path(path: OsString) {
+    *SEARCH_PATH.write().unwrap() = Some(path);
+}
+
+/// Whether the TryExec binary exists and is executable, looked up in the session's PATH
+/// (see [`set_search_path`]) unless it's an absolute path (desktop entry spec)
+pub fn try_exec_found(try_exec: &str) -> bool {
+    let executable = |path: &Path| {
+        path.metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    };
+
+    let path = Path::new(try_exec);
+
+    if path.is_absolute() {
+        return executable(path);
+    }
+
+    let paths = SEARCH_PATH
+        .read()
+        .unwrap()
+        .clone()
+        .or_else(|| std::env::var_os("PATH"));
+
+    paths.is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| executable(&dir.join(try_exec)))
+    })
+}
+
+/// App record along with the key it's stored under
+#[derive(Debug, Clone, SurrealValue, Serialize, Deserialize)]
+pub struct AppRecordKey {
+    pub id: RecordId,
+    pub key: String,
+    pub desktop_entry_path: String,
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<AppRecordKey>",
+    error = DBError,
+    sql = "SELECT id, <string> record::id(id) AS key, desktop_entry_path FROM app"
+)]
+pub struct GetAppRecordKeysQuery;
+
+/// Moves an app from a record created before they were keyed by [`app_key`] to its
+/// stable one, along with the user data pointing at it. If the stable record exists
+/// already (two files with the same desktop file id), the histories get merged
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        BEGIN TRANSACTION;
+
+        LET $new = type::record('app', {key});
+
+        IF (SELECT VALUE id FROM ONLY $new) == NONE {
+            LET $content = (SELECT * OMIT id FROM ONLY {old});
+            DELETE is_app WHERE out == {old};
+            DELETE has_icon WHERE in == {old};
+            // Frees the unique indexes for the new record
+            DELETE {old};
+            CREATE $new CONTENT $content;
+
+            LET $file = (SELECT VALUE ->is_file->file.id FROM ONLY fs_node
+                WHERE path == $content.desktop_entry_path LIMIT 1);
+            RELATE $file->is_app->$new;
+        } ELSE {
+            DELETE {old};
+        };
+
+        LET $launch = (SELECT * FROM ONLY app_launch WHERE app == {old} LIMIT 1);
+
+        IF $launch != NONE {
+            DELETE $launch.id;
+            UPSERT app_launch SET
+                app = $new,
+                count += $launch.count,
+                last_launched = math::max([last_launched ?? 0, $launch.last_launched])
+            WHERE app == $new;
+        };
+
+        FOR $choice IN (SELECT * FROM query_choice WHERE app == {old}) {
+            DELETE $choice.id;
+            UPSERT query_choice SET
+                query = $choice.query,
+                app = $new,
+                count += $choice.count,
+                last_chosen = math::max([last_chosen ?? 0, $choice.last_chosen])
+            WHERE query == $choice.query AND app == $new;
+        };
+
+        LET $favorite = (SELECT * FROM ONLY favorite WHERE app == {old} LIMIT 1);
+
+        IF $favorite != NONE {
+            DELETE $favorite.id;
+            UPSERT favorite SET
+                app = $new,
+                added = math::min([added ?? $favorite.added, $favorite.added])
+            WHERE app == $new;
+        };
+
+        COMMIT TRANSACTION;
+    "
+)]
+pub struct MigrateAppKeyQuery {
+    old: RecordId,
+    #[builder(into)]
+    key: String,
+}
+
+/// Re-keys the apps created before [`app_key`] was used, returns how many got moved
+#[tracing::instrument(skip(db), level = "debug", name = "db::apps::migrate_app_keys")]
+pub async fn migrate_app_keys(db: DB) -> DBResult<usize> {
+    let stale = GetAppRecordKeysQuery
+        .instrumented_execute(db.clone())
+        .await?
+        .into_iter()
+        .filter(|app| app.key != app_key(&app.desktop_entry_path))
+        .collect::<Vec<_>>();
+
+    for app in &stale {
+        MigrateAppKeyQuery::builder()
+            .old(app.id.clone())
+            .key(app_key(&app.desktop_entry_path))
+            .build()
+            .instrumented_execute(db.clone())
+            .await?;
     }
+
+    Ok(stale.len())
 }
 
+/// Apps with a TryExec key, for re-checking them as binaries come and go
 #[derive(Debug, Clone, SurrealValue, Serialize, Deserialize)]
+pub struct TryExecApp {
+    pub id: RecordId,
+    pub try_exec: String,
+    pub try_exec_missing: bool,
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<TryExecApp>",
+    error = DBError,
+    sql = "SELECT id, try_exec, try_exec_missing ?? false AS try_exec_missing FROM app WHERE try_exec != NONE"
+)]
+pub struct GetTryExecAppsQuery;
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "UPDATE {app} SET try_exec_missing = {missing}"
+)]
+pub struct SetTryExecMissingQuery {
+    app: RecordId,
+    missing: bool,
+}
+
+/// Drops an app whose desktop file is gone. Its launch history stays, so it's back with
+/// the app if the same desktop file id gets installed again
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        BEGIN TRANSACTION;
+
+        DELETE is_app WHERE out == {app};
+        DELETE has_icon WHERE in == {app};
+        DELETE {app};
+
+        COMMIT TRANSACTION;
+    "
+)]
+pub struct DeleteAppQuery {
+    app: RecordId,
+}
+
+#[derive(Debug, Clone, PartialEq, SurrealValue, Serialize, Deserialize)]
 pub struct AppWithIcon {
     pub id: RecordId,
     pub desktop_entry_path: String,
     pub name: String,
+    #[serde(default)]
+    pub canonical_name: String,
+    #[serde(default)]
+    pub generic_name: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub kind: AppKind,
     pub exec: Vec<String>,
     #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
     pub icon: Option<AppIcon>,
+    #[serde(default)]
+    pub only_show_in: Vec<String>,
+    #[serde(default)]
+    pub not_show_in: Vec<String>,
+    #[serde(default)]
+    pub no_display: bool,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub terminal: bool,
+    #[serde(default)]
+    pub try_exec_missing: bool,
+    #[serde(default)]
+    pub dbus_activatable: bool,
+    #[serde(default)]
+    pub prefers_non_default_gpu: bool,
+    #[serde(default)]
+    pub actions: Vec<AppAction>,
+}
+
+impl AppWithIcon {
+    /// See [`desktop_id`]
+    pub fn desktop_id(&self) -> String {
+        desktop_id(&self.desktop_entry_path)
+    }
+
+    /// Hidden/NoDisplay check, same as [`GetAppWithIconsQuery`] does
+    pub fn listed(&self, no_display: bool) -> bool {
+        !self.hidden && (no_display || !self.no_display)
+    }
+
+    /// OnlyShowIn/NotShowIn check against the running desktops, same as [`GetAppWithIconsQuery`] does
+    pub fn shown_in(&self, desktops: &[String]) -> bool {
+        (self.only_show_in.is_empty() || self.only_show_in.iter().any(|d| desktops.contains(d)))
+            && !self.not_show_in.iter().any(|d| desktops.contains(d))
+    }
+}
+
+/// Which of the icons named after an app gets picked, mirrored by `fn::app_icon` (see
+/// [`AppIcon`]) so the DB and [`IconOrder::pick`] agree
+#[derive(Debug, Clone, PartialEq, Eq, SurrealValue, Serialize, Deserialize)]
+pub struct IconOrder {
+    /// Lowercase file extensions, best first. Icons in other formats are never picked,
+    /// empty allows every format
+    pub formats: Vec<String>,
+    /// Preferred width range in px, rasters further away from it rank lower.
+    /// SVGs fit any size
+    pub min_size: usize,
+    pub max_size: usize,
+}
+
+impl Default for IconOrder {
+    fn default() -> Self {
+        Self {
+            formats: vec![],
+            min_size: 48,
+            max_size: 128,
+        }
+    }
+}
+
+impl IconOrder {
+    /// Rasters without a size in their theme directory rank after the sized ones
+    pub const UNKNOWN_SIZE_PENALTY: usize = 100_000;
+
+    /// Sort key of `icon`, lower is better, `None` if its format isn't allowed
+    pub fn rank(&self, icon: &AppIcon) -> Option<(usize, usize)> {
+        let format = icon.format();
+        let format_rank = match self.formats.is_empty() {
+            true => 0,
+            false => self.formats.iter().position(|allowed| *allowed == format)?,
+        };
+
+        let size_penalty = match (icon.svg, &icon.dims) {
+            (true, _) => 0,
+            (false, Some(dims)) => self
+                .min_size
+                .saturating_sub(dims.width)
+                .max(dims.width.saturating_sub(self.max_size)),
+            (false, None) => Self::UNKNOWN_SIZE_PENALTY,
+        };
+
+        Some((format_rank, size_penalty))
+    }
+
+    /// The better one of two candidates for the same app, live updates deliver them one by one
+    pub fn pick(&self, current: Option<AppIcon>, candidate: Option<AppIcon>) -> Option<AppIcon> {
+        let ranked =
+            |icon: Option<AppIcon>| icon.and_then(|icon| self.rank(&icon).map(|rank| (rank, icon)));
+
+        match (ranked(current), ranked(candidate)) {
+            (Some((current_rank, current)), Some((candidate_rank, candidate))) => {
+                match candidate_rank < current_rank {
+                    true => Some(candidate),
+                    false => Some(current),
+                }
+            }
+            (current, candidate) => current.or(candidate).map(|(_, icon)| icon),
+        }
+    }
 }
 
 #[derive(Debug, SurrealQuery)]
@@ -132,25 +814,82 @@ pub struct AppWithIcon {
     output = "Vec<AppWithIcon>",
     error = DBError,
     sql = "
-        SELECT *, array::at(->has_icon->icon, 0) as icon FROM app
-            ORDER BY name ASC FETCH icon
+        SELECT *, fn::app_icon(id, {icons}, {unknown_size_penalty}) AS icon FROM app
+            WHERE !(try_exec_missing ?? false)
+                AND !(hidden ?? false)
+                AND ({no_display} OR !(no_display ?? false))
+                AND ({desktops} == NONE OR (
+                    (array::len(only_show_in ?? []) == 0
+                        OR array::len(array::intersect(only_show_in, {desktops})) > 0)
+                    AND array::len(array::intersect(not_show_in ?? [], {desktops})) == 0
+                ))
+            ORDER BY name COLLATE ASC FETCH icon
     "
 )]
-pub struct GetAppWithIconsQuery;
+pub struct GetAppWithIconsQuery {
+    /// Running desktops to filter OnlyShowIn/NotShowIn by, `None` lists every app
+    desktops: Option<Vec<String>>,
+    /// List NoDisplay entries too
+    no_display: bool,
+    icons: IconOrder,
+    /// [`IconOrder::UNKNOWN_SIZE_PENALTY`]
+    unknown_size_penalty: usize,
+}
+
+impl GetAppWithIconsQuery {
+    pub fn new(desktops: Option<Vec<String>>, no_display: bool, icons: IconOrder) -> Self {
+        Self {
+            desktops,
+            no_display,
+            icons,
+            unknown_size_penalty: IconOrder::UNKNOWN_SIZE_PENALTY,
+        }
+    }
+}
+
+/// Number of apps in a desktop entry category
+#[derive(Debug, Clone, PartialEq, Eq, SurrealValue, Serialize, Deserialize)]
+pub struct CategoryCount {
+    pub category: String,
+    pub count: u64,
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<CategoryCount>",
+    error = DBError,
+    sql = "
+        SELECT category, count() AS count
+            FROM (SELECT categories AS category FROM app)
+            SPLIT category
+            GROUP BY category
+            ORDER BY count DESC
+    "
+)]
+pub struct GetAppCategoriesQuery;
 
 #[derive(Debug, SurrealQuery)]
 #[query(
     stream = "AppWithIcon",
     error = DBError,
     sql = "
-        LIVE SELECT
-            *,
-            array::at((SELECT * FROM ->has_icon->icon
-                ORDER BY dims.width,dims.height,svg), 0) as icon
-        FROM app
+        LIVE SELECT *, fn::app_icon(id, {icons}, {unknown_size_penalty}) AS icon FROM app
     "
 )]
-pub struct GetLiveAppWithIconsQuery;
+pub struct GetLiveAppWithIconsQuery {
+    icons: IconOrder,
+    /// [`IconOrder::UNKNOWN_SIZE_PENALTY`]
+    unknown_size_penalty: usize,
+}
+
+impl GetLiveAppWithIconsQuery {
+    pub fn new(icons: IconOrder) -> Self {
+        Self {
+            icons,
+            unknown_size_penalty: IconOrder::UNKNOWN_SIZE_PENALTY,
+        }
+    }
+}
 
 #[derive(Debug, SurrealQuery)]
 #[query(
@@ -165,17 +904,35 @@ pub struct GetLiveAppWithIconsQuery;
 )]
 pub struct GetLiveAppIconUpdates;
 
-#[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, SurrealValue, SurrealTable, Serialize, Deserialize)]
 #[table(
     db = icon,
     sql(
         "DEFINE INDEX icon_path_ind ON TABLE icon COLUMNS path UNIQUE",
+        // The best icon of an app by an IconOrder, the app queries share it
+        "
+        DEFINE FUNCTION fn::app_icon($app: record<app>, $icons: object, $unknown_size_penalty: int) {
+            RETURN array::at((
+                SELECT *,
+                    array::find_index($icons.formats,
+                        string::lowercase(array::last(string::split(path, '.')))) ?? 0 AS format_rank,
+                    IF svg THEN 0
+                    ELSE IF dims == NONE THEN $unknown_size_penalty
+                    ELSE math::max([$icons.min_size - dims.width, dims.width - $icons.max_size, 0])
+                    END AS size_penalty
+                FROM $app->has_icon->icon
+                WHERE array::len($icons.formats) == 0
+                    OR string::lowercase(array::last(string::split(path, '.'))) IN $icons.formats
+                ORDER BY format_rank, size_penalty
+            ), 0);
+        }
+        ",
         "
         DEFINE EVENT icon_added ON TABLE icon
             WHEN $event = 'CREATE'
             THEN {
                 LET $app = (SELECT * FROM ONLY app
-                    WHERE icon_name == $value.name LIMIT 1).id;
+                    WHERE icon_name == $value.name OR icon_name == $value.path LIMIT 1).id;
 
                 IF $app != NONE THEN
                     RELATE $app->has_icon->$value;
@@ -192,7 +949,17 @@ pub struct AppIcon {
     pub dims: Option<AppIconDims>,
 }
 
-#[derive(Debug, Clone, SurrealValue, Serialize, Deserialize)]
+impl AppIcon {
+    /// Lowercase file extension, e.g. "png"
+    pub fn format(&self) -> String {
+        Path::new(&self.path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, SurrealValue, Serialize, Deserialize)]
 pub struct AppIconDims {
     pub width: usize,
     pub height: usize,
@@ -209,3 +976,63 @@ pub struct AppIconDims {
     "
 )]
 pub struct LiveSearchAppsQuery;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_dirs() -> Vec<PathBuf> {
+        ["/home/user/.local/share", "/usr/local/share", "/usr/share"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    #[test]
+    fn desktop_ids() {
+        assert_eq!(
+            desktop_id("/usr/share/applications/org.gnome.Nautilus.desktop"),
+            "org.gnome.Nautilus"
+        );
+        assert_eq!(
+            desktop_id("/usr/share/applications/kde4/kate.desktop"),
+            "kde4-kate"
+        );
+        assert_eq!(desktop_id("/opt/foo/foo.desktop"), "foo");
+    }
+
+    #[test]
+    fn data_dir_precedence() {
+        let data_dirs = data_dirs();
+        let rank = |path: &str| rank_in(Path::new(path), &data_dirs);
+
+        assert_eq!(rank("/home/user/.local/share/applications/foo.desktop"), 0);
+        assert_eq!(rank("/usr/share/applications/kde4/kate.desktop"), 2);
+        assert_eq!(
+            rank("/var/lib/flatpak/exports/share/applications/foo.desktop"),
+            3
+        );
+    }
+
+    #[test]
+    fn hidden_override_masks_the_system_entry() {
+        let data_dir = std::env::temp_dir().join(format!("leaper-db-{}", std::process::id()));
+        let path = data_dir.join("applications/foo.desktop");
+        let system = "/usr/share/applications/foo.desktop";
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "[Desktop Entry]\nType=Application\nHidden=true\n").unwrap();
+
+        let query = CreateAppEntryQuery::new(&path);
+        std::fs::remove_dir_all(&data_dir).unwrap();
+        let query = query.unwrap().expect("Hidden entries are indexed");
+
+        // Same record as the entry it overrides, from a data dir ranked ahead of it
+        let data_dirs = [data_dir, PathBuf::from("/usr/share")];
+
+        assert!(query.hidden);
+        assert_eq!(query.key, app_key(system));
+        assert_eq!(rank_in(Path::new(&query.path), &data_dirs), 0);
+        assert_eq!(rank_in(Path::new(system), &data_dirs), 1);
+    }
+}
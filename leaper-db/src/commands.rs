@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use surrealdb_extras::{SurrealQuery, SurrealTable};
+
+use crate::DBError;
+
+/// A command the daemon ran on behalf of the runner, with everything it printed
+#[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
+#[table(
+    db = command_run,
+    sql("DEFINE INDEX command_run_started_ind ON TABLE command_run COLUMNS started")
+)]
+pub struct CommandRun {
+    pub id: RecordId,
+    pub cmd: Vec<String>,
+    /// Unix timestamp (milliseconds)
+    pub started: i64,
+    /// Unix timestamp (milliseconds), `None` while the command is still running
+    pub finished: Option<i64>,
+    /// `None` while running or when the process was killed by a signal
+    pub exit_code: Option<i32>,
+    /// Interleaved stdout and stderr, the last [`CommandRun::MAX_OUTPUT`] characters of it
+    pub output: String,
+    /// The daemon stopped before the command finished
+    #[serde(default)]
+    pub interrupted: bool,
+}
+
+impl CommandRun {
+    /// Characters of output kept per run, older ones are dropped as new ones come in
+    pub const MAX_OUTPUT: usize = 64 * 1024;
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.finished
+            .map(|finished| Duration::from_millis(finished.saturating_sub(self.started) as u64))
+    }
+}
+
+#[derive(bon::Builder, SurrealQuery)]
+#[query(
+    output = "Option<RecordId>",
+    error = DBError,
+    sql = "
+        RETURN (CREATE ONLY command_run SET
+            cmd = {cmd},
+            started = {started},
+            finished = NONE,
+            exit_code = NONE,
+            output = '',
+            interrupted = false).id;
+    "
+)]
+pub struct StartCommandRunQuery {
+    cmd: Vec<String>,
+    started: i64,
+}
+
+/// Queries get logged when executed, the command line can have secrets in it
+impl std::fmt::Debug for StartCommandRunQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StartCommandRunQuery")
+            .field("cmd", &leaper_tracing::redact::cmd(&self.cmd))
+            .field("started", &self.started)
+            .finish()
+    }
+}
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        UPDATE {run} SET output = string::slice(
+            output + {chunk},
+            math::max([0, string::len(output + {chunk}) - {max_output}]),
+            {max_output}
+        )
+    "
+)]
+pub struct AppendCommandOutputQuery {
+    run: RecordId,
+    chunk: String,
+    #[builder(default = CommandRun::MAX_OUTPUT)]
+    max_output: usize,
+}
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "UPDATE {run} SET finished = {finished}, exit_code = {exit_code}"
+)]
+pub struct FinishCommandRunQuery {
+    run: RecordId,
+    finished: i64,
+    exit_code: Option<i32>,
+}
+
+/// Flags commands left running by a previous daemon run, only call it on startup. Their
+/// processes may live on, but nothing records them any more
+#[derive(Debug, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "UPDATE command_run SET interrupted = true WHERE finished == NONE AND !interrupted"
+)]
+pub struct MarkInterruptedCommandRunsQuery;
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<CommandRun>",
+    error = DBError,
+    sql = "SELECT * FROM command_run ORDER BY started DESC LIMIT {limit}"
+)]
+pub struct GetCommandRunsQuery {
+    pub limit: usize,
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use surrealdb_extras::{SurrealQuery, SurrealTable};
+
+use crate::{DBError, user_data::UserTable};
+
+/// App pinned to the top of the launcher list
+#[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
+#[table(
+    db = favorite,
+    sql("DEFINE INDEX favorite_app_ind ON TABLE favorite COLUMNS app UNIQUE")
+)]
+pub struct Favorite {
+    pub id: RecordId,
+    pub app: RecordId,
+    /// Unix timestamp (seconds)
+    pub added: i64,
+}
+
+impl UserTable for Favorite {
+    const TABLE: &'static str = "favorite";
+}
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        IF {favorite} {
+            UPSERT favorite SET
+                app = {app},
+                added = added ?? time::unix(time::now())
+            WHERE app == {app}
+        } ELSE {
+            DELETE favorite WHERE app == {app}
+        }
+    "
+)]
+pub struct SetFavoriteQuery {
+    app: RecordId,
+    favorite: bool,
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<Favorite>",
+    error = DBError,
+    sql = "SELECT * FROM favorite ORDER BY added"
+)]
+pub struct GetFavoritesQuery;
@@ -5,7 +5,11 @@ use surrealdb::types::{RecordId, SurrealValue};
 use surrealdb_extras::{SurrealQuery, SurrealTable};
 use surrealdb_types::ToSql;
 
-use crate::{DB, DBError, DBResult, InstrumentedDBQuery, queries::RelateQuery};
+use parsers::icon::IconDims;
+
+use crate::{
+    DB, DBEnum, DBError, DBResult, InstrumentedDBQuery, apps::AppIconDims, queries::RelateQuery,
+};
 
 #[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
 #[table(
@@ -21,6 +25,8 @@ pub struct FSNode {
     pub id: RecordId,
     pub path: String,
     pub name: String,
+    /// Removable volume the node is on, see [`crate::volumes::Volume`]
+    pub volume: Option<RecordId>,
 }
 
 #[bon::bon]
@@ -119,6 +125,11 @@ struct CreateFsNodeQuery {
     name: String,
 }
 
+/// Nodes as the daemon indexes or drops them, what the finder follows while it's open
+#[derive(Debug, SurrealQuery)]
+#[query(stream = "FSNode", error = DBError, sql = "LIVE SELECT * FROM fs_node")]
+pub struct GetLiveFSNodesQuery;
+
 #[bon::bon]
 impl CreateFsNodeQuery {
     #[builder]
@@ -202,17 +213,8 @@ struct CreateDirectoryQuery {
             THEN {
                 LET $fs_node = $value.in;
                 LET $file = $value.out;
-                LET $dims = array::filter(
-                    string::split($fs_node.path, '/'), 
-                    |$str| string::contains($str, 'x')
-                )
-                    .map(|$str| string::split($str, 'x'))
-                    .filter(|$split| array::len($split) == 2 && array::all($split, |$dim| string::is_numeric($dim)))
-                    .map(|$split| {{
-                        width: <int>($split[0]),
-                        height: <int>($split[0])
-                    }})[0];
-                LET $icon = (CREATE icon SET
+                // Upserted, a file indexed again (e.g. after a cleanup) would violate icon_path_ind
+                LET $icon = (UPSERT icon SET
                     name = ($file
                         .stem
                         .replace('-default', '')
@@ -221,27 +223,151 @@ struct CreateDirectoryQuery {
                     path = $fs_node.path,
                     svg = ($file.ext == 'svg'),
                     xpm = ($file.ext == 'xpm'),
-                    dims = $dims).id;
+                    dims = $file.icon_dims
+                    WHERE path == $fs_node.path).id;
                 RELATE $file->is_icon->$icon;
             }
         ",
+
+        // Per-directory sizes, kept up to date as files get indexed and dropped. Every
+        // ancestor of a file has a row for the child the file is under, so the usage of a
+        // directory is the rows with its path, see GetDirUsageQuery
+        "DEFINE INDEX dir_usage_dir_ind ON TABLE dir_usage COLUMNS dir",
+        "
+        DEFINE FUNCTION fn::dir_usage($path: string, $volume: option<record>, $size: int, $files: int) {
+            LET $parts = string::split($path, '/');
+            LET $last = array::len($parts) - 1;
+
+            FOR $i IN array::range(1, $last) {
+                LET $dir = array::join(array::slice($parts, 0, $i), '/') + '/';
+                LET $usage = (UPSERT ONLY type::record('dir_usage', [$dir, $parts[$i], $volume ?? NULL]) SET
+                    dir = $dir,
+                    name = $parts[$i],
+                    is_dir = $i < $last,
+                    volume = $volume,
+                    size = (size ?? 0) + $size,
+                    files = (files ?? 0) + $files);
+
+                IF $usage.files <= 0 {
+                    DELETE $usage.id;
+                };
+            };
+        }
+        ",
+        // The edge keeps the path, volume and size, they're gone with the nodes on delete
+        "
+        DEFINE EVENT dir_usage_added ON TABLE is_file
+            WHEN $event = 'CREATE' && $value.size != NONE
+            THEN fn::dir_usage($value.path, $value.volume, $value.size, 1)
+        ",
+        "
+        DEFINE EVENT dir_usage_removed ON TABLE is_file
+            WHEN $event = 'DELETE' && $before.size != NONE
+            THEN fn::dir_usage($before.path, $before.volume, -$before.size, -1)
+        ",
     )
 )]
 pub struct File {
     id: RecordId,
     stem: String,
     ext: Option<String>,
+    /// Bytes
+    size: u64,
+    /// Unix timestamp (seconds)
+    mtime: Option<i64>,
+    kind: FileKind,
+    /// Size encoded in the icon theme directory, e.g. `48x48/apps/foo.png`
+    icon_dims: Option<AppIconDims>,
+    /// Hex blake3 hash of the contents, filled in lazily by the daemon
+    hash: Option<String>,
+}
+
+/// Rough file type guessed from the extension, used for filtering
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    SurrealValue,
+    DBEnum,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    Image,
+    Video,
+    Audio,
+    Document,
+    Archive,
+    Code,
+    Text,
+    #[default]
+    Other,
+}
+
+impl FileKind {
+    pub fn from_ext(ext: &str) -> Self {
+        match ext.to_lowercase().as_str() {
+            "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "ico" | "svg" | "xpm" | "tiff"
+            | "tif" | "avif" | "heic" | "qoi" | "tga" | "exr" | "hdr" => Self::Image,
+            "mp4" | "mkv" | "webm" | "avi" | "mov" | "wmv" | "flv" | "m4v" => Self::Video,
+            "mp3" | "flac" | "ogg" | "opus" | "wav" | "m4a" | "aac" | "wma" => Self::Audio,
+            "pdf" | "doc" | "docx" | "odt" | "xls" | "xlsx" | "ods" | "ppt" | "pptx" | "odp"
+            | "epub" | "djvu" => Self::Document,
+            "zip" | "tar" | "gz" | "xz" | "zst" | "bz2" | "7z" | "rar" | "deb" | "rpm" => {
+                Self::Archive
+            }
+            "rs" | "c" | "h" | "cpp" | "hpp" | "py" | "js" | "ts" | "go" | "java" | "kt"
+            | "nix" | "sh" | "lua" | "zig" | "hs" => Self::Code,
+            "txt" | "md" | "org" | "rst" | "log" | "csv" | "json" | "toml" | "yaml" | "yml"
+            | "ini" | "conf" | "xml" | "html" => Self::Text,
+            _ => Self::Other,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Image => "img",
+            Self::Video => "video",
+            Self::Audio => "audio",
+            Self::Document => "doc",
+            Self::Archive => "archive",
+            Self::Code => "code",
+            Self::Text => "text",
+            Self::Other => "other",
+        }
+    }
 }
 
 impl File {
     #[tracing::instrument(skip(db), level = "debug", name = "fs::File::add_db")]
     async fn add_db(path: PathBuf, fs_node_id: RecordId, db: DB) -> DBResult<()> {
+        let ext = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_owned());
+        let metadata = tokio::fs::metadata(&path).await?;
+
         CreateFileQuery::builder()
             .fs_node(fs_node_id.clone())
-            .maybe_ext(
-                path.extension()
-                    .and_then(|s| s.to_str())
-                    .map(|s| s.to_owned()),
+            .kind(ext.as_deref().map(FileKind::from_ext).unwrap_or_default())
+            .maybe_ext(ext)
+            .size(metadata.len())
+            .maybe_icon_dims(
+                IconDims::from_path(&path.to_string_lossy())
+                    .map(|IconDims { width, height }| AppIconDims { width, height }),
+            )
+            .maybe_mtime(
+                metadata
+                    .modified()
+                    .ok()
+                    .and_then(|mtime| mtime.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|since| since.as_secs() as i64),
             )
             .stem(
                 path.file_stem()
@@ -267,8 +393,18 @@ impl File {
     sql = "
         BEGIN TRANSACTION;
 
-        LET $file = (CREATE file SET ext = {ext}, stem = {stem}).id;
-        RELATE {fs_node}->is_file->$file;
+        LET $file = (CREATE file SET
+            ext = {ext},
+            stem = {stem},
+            size = {size},
+            mtime = {mtime},
+            kind = {kind},
+            icon_dims = {icon_dims},
+            hash = NONE).id;
+        RELATE {fs_node}->is_file->$file SET
+            path = {fs_node}.path,
+            volume = {fs_node}.volume,
+            size = {size};
 
         COMMIT TRANSACTION;
 
@@ -281,6 +417,293 @@ struct CreateFileQuery {
     stem: String,
     #[builder(into)]
     ext: Option<String>,
+    size: u64,
+    mtime: Option<i64>,
+    kind: FileKind,
+    icon_dims: Option<AppIconDims>,
+}
+
+/// Indexed file matching a [`FileFilter`]
+#[derive(Debug, Clone, PartialEq, SurrealValue, Serialize, Deserialize)]
+pub struct FileHit {
+    /// [`FSNode`] of the file
+    pub node: RecordId,
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub mtime: Option<i64>,
+    pub kind: FileKind,
+}
+
+/// Constraints on indexed files, unset fields don't filter anything
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileFilter {
+    /// Lowercase words that all have to be in the file name
+    pub words: Vec<String>,
+    /// Lowercase extensions without the dot, any of them
+    pub exts: Vec<String>,
+    /// Only files under this directory
+    pub under: Option<String>,
+    /// Any of them
+    pub kinds: Vec<FileKind>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub modified_after: Option<i64>,
+    pub modified_before: Option<i64>,
+}
+
+impl FileFilter {
+    /// Adds or removes `kind`, for filter chips
+    pub fn toggle_kind(&mut self, kind: FileKind) {
+        match self.kinds.iter().position(|k| *k == kind) {
+            Some(ind) => {
+                self.kinds.remove(ind);
+            }
+            None => self.kinds.push(kind),
+        }
+    }
+
+    /// Same as [`SearchFilesQuery`] checks in the DB, for files that are already loaded
+    pub fn matches(&self, hit: &FileHit) -> bool {
+        let name = hit.name.to_lowercase();
+        let path = std::path::Path::new(&hit.path);
+        let ext = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        self.words.iter().all(|word| name.contains(word.as_str()))
+            && (self.exts.is_empty() || self.exts.contains(&ext))
+            && self
+                .under
+                .as_ref()
+                .is_none_or(|under| path.starts_with(under))
+            && (self.kinds.is_empty() || self.kinds.contains(&hit.kind))
+            && self.min_size.is_none_or(|min| hit.size >= min)
+            && self.max_size.is_none_or(|max| hit.size <= max)
+            && self
+                .modified_after
+                .is_none_or(|after| hit.mtime.is_some_and(|mtime| mtime >= after))
+            && self
+                .modified_before
+                .is_none_or(|before| hit.mtime.is_some_and(|mtime| mtime < before))
+    }
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<FileHit>",
+    error = DBError,
+    sql = "
+        SELECT
+            in AS node,
+            in.path AS path,
+            in.name AS name,
+            out.size AS size,
+            out.mtime AS mtime,
+            out.kind AS kind
+        FROM is_file
+        WHERE array::all({words}, |$word| string::contains(string::lowercase(in.name), $word))
+            AND (array::is_empty({exts}) OR string::lowercase(out.ext ?? '') IN {exts})
+            AND ({under} == NONE OR string::starts_with(in.path, {under}))
+            AND (array::is_empty({kinds}) OR out.kind IN {kinds})
+            AND ({min_size} == NONE OR out.size >= {min_size})
+            AND ({max_size} == NONE OR out.size <= {max_size})
+            AND ({modified_after} == NONE OR out.mtime >= {modified_after})
+            AND ({modified_before} == NONE OR out.mtime < {modified_before})
+            AND (in.volume == NONE OR in.volume.mounted)
+        LIMIT {limit}
+    "
+)]
+pub struct SearchFilesQuery {
+    words: Vec<String>,
+    exts: Vec<String>,
+    under: Option<String>,
+    kinds: Vec<FileKind>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    modified_after: Option<i64>,
+    modified_before: Option<i64>,
+    limit: usize,
+}
+
+impl SearchFilesQuery {
+    pub fn new(filter: FileFilter, limit: usize) -> Self {
+        let FileFilter {
+            words,
+            exts,
+            under,
+            kinds,
+            min_size,
+            max_size,
+            modified_after,
+            modified_before,
+        } = filter;
+
+        Self {
+            words,
+            exts,
+            under: under.as_deref().map(Self::dir_prefix),
+            kinds,
+            min_size,
+            max_size,
+            modified_after,
+            modified_before,
+            limit,
+        }
+    }
+
+    /// `dir` ending with a `/`, so a plain prefix check doesn't take `/a/bc` to be under `/a/b`
+    fn dir_prefix(dir: &str) -> String {
+        match dir.ends_with('/') {
+            true => dir.to_string(),
+            false => format!("{dir}/"),
+        }
+    }
+}
+
+/// Disk usage of a direct child of a directory, summed up from the indexed files under it
+#[derive(Debug, Clone, PartialEq, SurrealValue, Serialize, Deserialize)]
+pub struct DirUsage {
+    pub name: String,
+    pub is_dir: bool,
+    /// Bytes
+    pub size: u64,
+    pub files: u64,
+}
+
+/// Children of `dir` (ending with a `/`) by indexed size, biggest first. Summed up from
+/// the per-directory sizes the `is_file` events keep, not the files themselves
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<DirUsage>",
+    error = DBError,
+    sql = "
+        SELECT
+            name,
+            is_dir,
+            math::sum(size) AS size,
+            math::sum(files) AS files
+        FROM dir_usage
+        WHERE dir == {dir}
+            AND (volume == NONE OR volume.mounted)
+        GROUP BY name, is_dir
+        ORDER BY size DESC
+        LIMIT {limit}
+    "
+)]
+pub struct GetDirUsageQuery {
+    dir: String,
+    limit: usize,
+}
+
+impl GetDirUsageQuery {
+    pub fn new(dir: impl AsRef<std::path::Path>, limit: usize) -> Self {
+        let mut dir = dir.as_ref().to_string_lossy().to_string();
+
+        if !dir.ends_with('/') {
+            dir.push('/');
+        }
+
+        Self { dir, limit }
+    }
+}
+
+/// Adds the files indexed before the per-directory sizes were kept to them, returns how
+/// many got added
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "usize",
+    error = DBError,
+    sql = "
+        BEGIN TRANSACTION;
+
+        LET $stale = (SELECT id, in.path AS path, in.volume AS volume, out.size AS size
+            FROM is_file
+            WHERE size == NONE AND in.path != NONE AND out.size != NONE);
+
+        FOR $edge IN $stale {
+            UPDATE $edge.id SET path = $edge.path, volume = $edge.volume, size = $edge.size;
+            fn::dir_usage($edge.path, $edge.volume, $edge.size, 1);
+        };
+
+        COMMIT TRANSACTION;
+
+        RETURN array::len($stale);
+    "
+)]
+pub struct MigrateDirUsageQuery;
+
+/// Indexed file whose contents haven't been hashed yet
+#[derive(Debug, Clone, SurrealValue)]
+pub struct UnhashedFile {
+    pub file: RecordId,
+    pub path: String,
+    pub size: u64,
+}
+
+/// Non-empty files up to `max_size` bytes without a hash
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<UnhashedFile>",
+    error = DBError,
+    sql = "
+        SELECT out AS file, in.path AS path, out.size AS size
+        FROM is_file
+        WHERE out.hash == NONE AND out.size > 0 AND out.size <= {max_size}
+            AND (in.volume == NONE OR in.volume.mounted)
+    "
+)]
+pub struct GetUnhashedFilesQuery {
+    pub max_size: u64,
+}
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(check, error = DBError, sql = "UPDATE {file} SET hash = {hash}")]
+pub struct SetFileHashQuery {
+    file: RecordId,
+    #[builder(into)]
+    hash: String,
+}
+
+/// Indexed files with identical contents
+#[derive(Debug, Clone, PartialEq, SurrealValue, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    /// Bytes, of each copy
+    pub size: u64,
+    pub paths: Vec<String>,
+}
+
+impl DuplicateGroup {
+    /// Bytes that would be freed by keeping a single copy
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.paths.len() as u64).saturating_sub(1)
+    }
+}
+
+/// Groups of hashed files sharing a hash, biggest files first
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<DuplicateGroup>",
+    error = DBError,
+    sql = "
+        SELECT hash, size, paths FROM (
+            SELECT
+                out.hash AS hash,
+                math::max(out.size) AS size,
+                array::group(in.path) AS paths
+            FROM is_file
+            WHERE out.hash != NONE AND (in.volume == NONE OR in.volume.mounted)
+            GROUP BY hash
+        )
+        WHERE array::len(paths) > 1
+        ORDER BY size DESC
+        LIMIT {limit}
+    "
+)]
+pub struct GetDuplicatesQuery {
+    pub limit: usize,
 }
 
 #[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
@@ -303,7 +726,14 @@ impl Symlink {
         parents: bool,
     ) -> DBResult<()> {
         let links_to = match path.read_link() {
-            Ok(path) => path,
+            // Relative targets (e.g. flatpak's exports) are relative to the link's directory,
+            // resolved so the node doesn't end up with `..` in its path
+            Ok(links_to) if links_to.is_relative() => path
+                .parent()
+                .map(|parent| parent.join(&links_to))
+                .and_then(|joined| joined.canonicalize().ok())
+                .unwrap_or(links_to),
+            Ok(links_to) => links_to,
             Err(err) => {
                 tracing::trace!("WARN: Failed to read the symlink {path:?}: {err}");
                 return Ok(());
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use surrealdb_extras::{SurrealQuery, SurrealTable};
+
+use crate::{DBError, user_data::UserTable};
+
+#[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
+#[table(
+    db = app_launch,
+    sql("DEFINE INDEX app_launch_app_ind ON TABLE app_launch COLUMNS app UNIQUE")
+)]
+pub struct AppLaunch {
+    pub id: RecordId,
+    pub app: RecordId,
+    pub count: u64,
+    /// Unix timestamp (seconds)
+    pub last_launched: i64,
+}
+
+impl UserTable for AppLaunch {
+    const TABLE: &'static str = "app_launch";
+}
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        UPSERT app_launch SET
+            app = {app},
+            count += 1,
+            last_launched = time::unix(time::now())
+        WHERE app == {app}
+    "
+)]
+pub struct RecordAppLaunchQuery {
+    app: RecordId,
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<AppLaunch>",
+    error = DBError,
+    sql = "SELECT * FROM app_launch"
+)]
+pub struct GetAppLaunchesQuery;
+
+/// App picked from the results of a search query, so it can rank higher next time
+#[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
+#[table(
+    db = query_choice,
+    sql("DEFINE INDEX query_choice_ind ON TABLE query_choice COLUMNS query, app UNIQUE")
+)]
+pub struct QueryChoice {
+    pub id: RecordId,
+    /// Lowercase search query
+    pub query: String,
+    pub app: RecordId,
+    pub count: u64,
+    /// Unix timestamp (seconds)
+    pub last_chosen: i64,
+}
+
+impl UserTable for QueryChoice {
+    const TABLE: &'static str = "query_choice";
+}
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        UPSERT query_choice SET
+            query = {query},
+            app = {app},
+            count += 1,
+            last_chosen = time::unix(time::now())
+        WHERE query == {query} AND app == {app}
+    "
+)]
+pub struct RecordQueryChoiceQuery {
+    #[builder(into)]
+    query: String,
+    app: RecordId,
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<QueryChoice>",
+    error = DBError,
+    sql = "SELECT * FROM query_choice"
+)]
+pub struct GetQueryChoicesQuery;
+
+/// Search query typed into the launcher before launching something, recalled with Up/Down
+#[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
+#[table(
+    db = search_query,
+    sql("DEFINE INDEX search_query_ind ON TABLE search_query COLUMNS key UNIQUE")
+)]
+pub struct HistoryEntry {
+    pub id: RecordId,
+    /// Trimmed, lowercase query the entries are deduplicated by
+    pub key: String,
+    /// Query as it was last typed
+    pub query: String,
+    pub count: u64,
+    /// Unix timestamp (seconds)
+    pub last_used: i64,
+}
+
+impl UserTable for HistoryEntry {
+    const TABLE: &'static str = "search_query";
+}
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        UPSERT search_query SET
+            key = {key},
+            query = {query},
+            count += 1,
+            last_used = time::unix(time::now())
+        WHERE key == {key}
+    "
+)]
+pub struct RecordHistoryEntryQuery {
+    #[builder(into)]
+    key: String,
+    #[builder(into)]
+    query: String,
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<HistoryEntry>",
+    error = DBError,
+    sql = "SELECT * FROM search_query"
+)]
+pub struct GetHistoryEntriesQuery;
@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use surrealdb_extras::{SurrealQuery, SurrealTable};
+
+use crate::DBError;
+
+/// A single indexing/search run of the daemon
+#[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
+#[table(
+    db = job,
+    sql("DEFINE INDEX job_started_ind ON TABLE job COLUMNS started")
+)]
+pub struct Job {
+    pub id: RecordId,
+    pub kind: String,
+    /// Unix timestamp (milliseconds)
+    pub started: i64,
+    /// Unix timestamp (milliseconds), `None` while the job is still running
+    pub finished: Option<i64>,
+    pub items: u64,
+    pub errors: Vec<String>,
+    /// The daemon stopped before the job finished
+    #[serde(default)]
+    pub interrupted: bool,
+}
+
+impl Job {
+    pub fn duration(&self) -> Option<Duration> {
+        self.finished
+            .map(|finished| Duration::from_millis(finished.saturating_sub(self.started) as u64))
+    }
+}
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    output = "Option<RecordId>",
+    error = DBError,
+    sql = "
+        RETURN (CREATE ONLY job SET
+            kind = {kind},
+            started = {started},
+            finished = NONE,
+            items = 0,
+            errors = [],
+            interrupted = false).id;
+    "
+)]
+pub struct StartJobQuery {
+    #[builder(into)]
+    kind: String,
+    started: i64,
+}
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "UPDATE {job} SET finished = {finished}, items = {items}, errors = {errors}"
+)]
+pub struct FinishJobQuery {
+    job: RecordId,
+    finished: i64,
+    items: u64,
+    errors: Vec<String>,
+}
+
+/// Flags jobs left unfinished by a previous daemon run, only call it on startup
+#[derive(Debug, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "UPDATE job SET interrupted = true WHERE finished == NONE AND !interrupted"
+)]
+pub struct MarkInterruptedJobsQuery;
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<Job>",
+    error = DBError,
+    sql = "SELECT * FROM job ORDER BY started DESC LIMIT {limit}"
+)]
+pub struct GetJobsQuery {
+    pub limit: usize,
+}
+
+/// Progress of an indexing walk over `root`, everything up to and including `dir`
+/// (in walk order) is already in the index
+#[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
+#[table(
+    db = index_checkpoint,
+    sql("DEFINE INDEX index_checkpoint_root_ind ON TABLE index_checkpoint COLUMNS root UNIQUE")
+)]
+pub struct IndexCheckpoint {
+    pub id: RecordId,
+    pub root: String,
+    /// Last completed directory
+    pub dir: String,
+    /// Unix timestamp (milliseconds)
+    pub updated: i64,
+    /// Walk of everything under `root`, not just what some search looks for. The daemon
+    /// resumes these itself when it starts
+    #[serde(default)]
+    pub full: bool,
+    #[serde(default)]
+    pub parents: bool,
+}
+
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        UPSERT index_checkpoint SET
+            root = {root},
+            dir = {dir},
+            updated = {updated},
+            full = {full},
+            parents = {parents}
+        WHERE root == {root}
+    "
+)]
+pub struct SaveCheckpointQuery {
+    #[builder(into)]
+    root: String,
+    #[builder(into)]
+    dir: String,
+    updated: i64,
+    full: bool,
+    parents: bool,
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Option<IndexCheckpoint>",
+    error = DBError,
+    sql = "SELECT * FROM ONLY index_checkpoint WHERE root == {root} LIMIT 1"
+)]
+pub struct GetCheckpointQuery {
+    pub root: String,
+}
+
+/// Checkpoints of [`IndexCheckpoint::full`] walks, the ones left over on startup were
+/// cut short by the previous daemon run
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "Vec<IndexCheckpoint>",
+    error = DBError,
+    sql = "SELECT * FROM index_checkpoint WHERE full == true"
+)]
+pub struct GetFullCheckpointsQuery;
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "DELETE index_checkpoint WHERE root == {root}"
+)]
+pub struct ClearCheckpointQuery {
+    pub root: String,
+}
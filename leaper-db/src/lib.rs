@@ -1,27 +1,55 @@
+extern crate self as db;
+
 pub mod apps;
+pub mod commands;
+pub mod favorites;
 pub mod fs;
+pub mod history;
+pub mod jobs;
+pub mod maintenance;
 pub mod queries;
+pub mod stats;
+pub mod user_data;
+pub mod volumes;
 
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
+use leaper_error::CommonError;
 use macros::lerror;
 use surrealdb::{
     Surreal,
     opt::{Config, capabilities::Capabilities},
+    types::SurrealValue,
 };
 use surrealdb_extras::{SurrealExt, SurrealQuery, SurrealTableInfo};
+use surrealdb_types::ToSql;
+
+pub use macros::DBEnum;
 
 use crate::{
-    apps::{AppEntry, AppIcon},
-    fs::{Directory, FSNode, File, Symlink},
+    apps::{AppEntry, AppIcon, AppKind},
+    commands::CommandRun,
+    favorites::Favorite,
+    fs::{Directory, FSNode, File, FileKind, Symlink},
+    history::{AppLaunch, HistoryEntry, QueryChoice},
+    jobs::{IndexCheckpoint, Job},
+    volumes::Volume,
 };
 
-pub type Db = surrealdb::engine::remote::ws::Client;
-pub type Scheme = surrealdb::engine::remote::ws::Ws;
+/// Remote (ws) for the shared daemon DB, in-memory for ephemeral sessions
+pub type Db = surrealdb::engine::any::Any;
 
 pub type DB = Surreal<Db>;
 pub type DBNotification<T> = surrealdb::Notification<T>;
 pub type DBAction = surrealdb::types::Action;
+pub type DBEntryId = surrealdb::types::RecordId;
 
 pub async fn init_db(port: u16) -> DBResult<DB> {
     let endpoint: String = format!("localhost:{port}");
@@ -32,6 +60,8 @@ pub async fn init_db(port: u16) -> DBResult<DB> {
     loop {
         match connect(endpoint.clone()).await {
             Ok(db) => return Ok(db),
+            // Only a server that isn't up yet is worth waiting for
+            Err(err) if err.kind() != DBErrorKind::Connection => return Err(err),
             Err(err) => match tries < MAX_TRIES {
                 true => {
                     tracing::warn!(
@@ -47,13 +77,67 @@ pub async fn init_db(port: u16) -> DBResult<DB> {
     }
 }
 
-async fn connect(endpoint: String) -> DBResult<DB> {
-    let db = DB::new::<Scheme>((
-        endpoint,
+/// In-process DB that's gone with the process, nothing gets written to disk
+pub async fn init_mem_db() -> DBResult<DB> {
+    let db = surrealdb::engine::any::connect((
+        "mem://",
         Config::default()
             .capabilities(Capabilities::all().with_all_experimental_features_allowed()),
     ))
     .await?;
+
+    setup(db).await
+}
+
+/// Single connection attempt without [`init_db`]'s retries, for diagnostics
+pub async fn try_init_db(port: u16) -> DBResult<DB> {
+    connect(format!("localhost:{port}")).await
+}
+
+static EXPERIMENTAL: AtomicBool = AtomicBool::new(true);
+
+/// Whether the server accepted experimental capabilities, `false` when running in
+/// compatibility mode
+pub fn experimental_features() -> bool {
+    EXPERIMENTAL.load(Ordering::Relaxed)
+}
+
+async fn connect(endpoint: String) -> DBResult<DB> {
+    match connect_with(endpoint.clone(), true).await {
+        Err(err) if err.kind() == DBErrorKind::Capability => {
+            tracing::warn!(
+                "Server rejected experimental capabilities ({err}), retrying in compatibility mode"
+            );
+
+            let db = connect_with(endpoint, false).await?;
+            EXPERIMENTAL.store(false, Ordering::Relaxed);
+
+            Ok(db)
+        }
+        res => {
+            EXPERIMENTAL.store(true, Ordering::Relaxed);
+            res
+        }
+    }
+}
+
+async fn connect_with(endpoint: String, experimental: bool) -> DBResult<DB> {
+    let capabilities = match experimental {
+        true => Capabilities::all().with_all_experimental_features_allowed(),
+        false => Capabilities::all(),
+    };
+
+    let db = surrealdb::engine::any::connect((
+        format!("ws://{endpoint}"),
+        Config::default().capabilities(capabilities),
+    ))
+    .await?;
+
+    setup(db).await
+}
+
+/// Registers the tables and field constraints
+async fn setup(db: DB) -> DBResult<DB> {
     db.use_ns_db_checked(
         "leaper",
         "data",
@@ -63,9 +147,19 @@ async fn connect(endpoint: String) -> DBResult<DB> {
             Directory::register(),
             File::register(),
             Symlink::register(),
+            Volume::register(),
             // Apps & Icons
             AppEntry::register(),
             AppIcon::register(),
+            // Daemon
+            Job::register(),
+            IndexCheckpoint::register(),
+            CommandRun::register(),
+            // User Data
+            AppLaunch::register(),
+            QueryChoice::register(),
+            HistoryEntry::register(),
+            Favorite::register(),
         ]
         .into_iter()
         .map(|res| res.map_err(DBError::SurrealExtra))
@@ -73,9 +167,41 @@ async fn connect(endpoint: String) -> DBResult<DB> {
     )
     .await?;
 
+    for sql in [
+        AppKind::define_field("app", "kind"),
+        FileKind::define_field("file", "kind"),
+    ] {
+        // Only a safety net, older servers without OVERWRITE can live without it
+        if let Err(err) = db
+            .query(sql.as_str())
+            .await
+            .and_then(|res| res.check())
+            .map_err(DBError::from)
+        {
+            tracing::warn!("Skipping field constraint ({sql}): {err}");
+        }
+    }
+
     Ok(db)
 }
 
+/// Unit enum stored in a table field, see [`DBEnum::define_field`]
+pub trait DBEnum: SurrealValue + Copy + 'static {
+    const ALL: &'static [Self];
+
+    /// Field definition that only lets the variants of the enum in, encoded the same way
+    /// they are when bound as query parameters
+    fn define_field(table: &str, field: &str) -> String {
+        let values = Self::ALL
+            .iter()
+            .map(|variant| variant.into_value().to_sql())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("DEFINE FIELD OVERWRITE {field} ON TABLE {table} ASSERT $value IN [{values}]")
+    }
+}
+
 pub trait InstrumentedDBQuery: SurrealQuery {
     fn instrumented_execute(
         self,
@@ -97,14 +223,8 @@ where
 }
 
 #[lerror]
-#[lerr(prefix = "[leaper-db]", result_name = DBResult)]
+#[lerr(prefix = "[leaper-db]", result_name = DBResult, common)]
 pub enum DBError {
-    #[lerr(str = "[std::io] {0}")]
-    IO(#[lerr(from, wrap = Arc)] std::io::Error),
-
-    #[lerr(str = "[tokio::task::join] {0}")]
-    Join(#[lerr(from, wrap = Arc)] tokio::task::JoinError),
-
     #[lerr(str = "[surrealdb] {0}")]
     Surreal(#[lerr(from, wrap = Arc)] surrealdb::Error),
     #[lerr(str = "[surrealdb_core::rpc] {0}")]
@@ -116,6 +236,8 @@ pub enum DBError {
     DesktopEntryNoName(PathBuf),
     #[lerr(str = "{0:?} provides no exec!")]
     DesktopEntryNoExec(PathBuf),
+    #[lerr(str = "{0:?} is a link but provides no URL!")]
+    DesktopEntryNoUrl(PathBuf),
     #[lerr(str = "Failed to parse exec '{1}' from {0:?}!")]
     DesktopEntryParseExec(PathBuf, String),
 
@@ -124,8 +246,46 @@ pub enum DBError {
     #[lerr(str = "[.desktop::exec] {0}")]
     DesktopEntryExec(#[lerr(from, wrap = Arc)] freedesktop_desktop_entry::ExecError),
 
-    #[lerr(str = "Interrupted by parent")]
-    InterruptedByParent,
-    #[lerr(str = "Lost connection to the parent")]
-    LostConnectionToParent,
+    #[lerr(str = "[serde_json] {0}")]
+    Json(#[lerr(from, wrap = Arc)] serde_json::Error),
+    #[lerr(str = "Unknown user data table '{0}'")]
+    UnknownUserTable(String),
+}
+
+/// Rough cause of a [`DBError`], to decide whether retrying makes sense
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DBErrorKind {
+    /// Server not reachable (yet)
+    Connection,
+    /// Server refused something we asked for, e.g. experimental features
+    Capability,
+    /// Write violated a unique index
+    Conflict,
+    Other,
+}
+
+impl DBError {
+    pub fn kind(&self) -> DBErrorKind {
+        use surrealdb::error::{Api, Db};
+        use surrealdb_core::rpc::DbResultError;
+
+        match self {
+            Self::Common(CommonError::IO(_)) => DBErrorKind::Connection,
+            Self::Context { source, .. } => source.kind(),
+            Self::Surreal(err) => match err.as_ref() {
+                surrealdb::Error::Api(Api::Ws(_) | Api::Http(_) | Api::ConnectionUninitialised) => {
+                    DBErrorKind::Connection
+                }
+                surrealdb::Error::Db(Db::IndexExists { .. } | Db::RecordExists { .. }) => {
+                    DBErrorKind::Conflict
+                }
+                surrealdb::Error::Db(Db::FunctionNotAllowed(_) | Db::NetTargetNotAllowed(_)) => {
+                    DBErrorKind::Capability
+                }
+                _ => DBErrorKind::Other,
+            },
+            Self::SurrealRPCResponse(DbResultError::MethodNotAllowed) => DBErrorKind::Capability,
+            _ => DBErrorKind::Other,
+        }
+    }
 }
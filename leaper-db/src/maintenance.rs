@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use surrealdb_extras::SurrealQuery;
+
+use crate::{DB, DBError, DBResult, InstrumentedDBQuery};
+
+/// Writes a SurrealQL export of the leaper namespace to `path`
+#[tracing::instrument(skip(db), level = "debug", name = "db::maintenance::backup")]
+pub async fn backup(db: DB, path: PathBuf) -> DBResult<()> {
+    db.export(path).await?;
+
+    Ok(())
+}
+
+/// Drops leftovers of removed records and asks the storage engine to compact
+#[tracing::instrument(skip(db), level = "debug", name = "db::maintenance::compact")]
+pub async fn compact(db: DB) -> DBResult<()> {
+    CleanupQuery.instrumented_execute(db.clone()).await?;
+
+    // Not every storage engine/server version supports it, the cleanup above is what matters
+    if let Err(err) = db
+        .query("ALTER SYSTEM COMPACT")
+        .await
+        .and_then(|res| res.check())
+    {
+        tracing::warn!("Storage compaction is not available: {err}");
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        BEGIN TRANSACTION;
+
+        DELETE is_parent_of, is_dir, is_file, is_symlink, is_symlink_of, is_icon, is_app, has_icon
+            WHERE in.id == NONE OR out.id == NONE;
+        DELETE job WHERE started < (SELECT VALUE started FROM job ORDER BY started DESC LIMIT 1 START 100)[0];
+
+        COMMIT TRANSACTION;
+    "
+)]
+struct CleanupQuery;
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::types::SurrealValue;
+use surrealdb_extras::SurrealQuery;
+
+use crate::DBError;
+
+#[derive(Debug, Clone, SurrealValue, Serialize, Deserialize)]
+pub struct DBStats {
+    pub apps: usize,
+    pub apps_with_icon: usize,
+    pub icons: usize,
+    pub fs_nodes: usize,
+    pub files: usize,
+}
+
+#[derive(Debug, SurrealQuery)]
+#[query(
+    output = "DBStats",
+    error = DBError,
+    sql = "
+        RETURN {
+            apps: array::len((SELECT VALUE id FROM app)),
+            apps_with_icon: array::len((SELECT VALUE id FROM app
+                WHERE array::len(->has_icon) > 0)),
+            icons: array::len((SELECT VALUE id FROM icon)),
+            fs_nodes: array::len((SELECT VALUE id FROM fs_node)),
+            files: array::len((SELECT VALUE id FROM file))
+        }
+    "
+)]
+pub struct GetDBStatsQuery;
@@ -0,0 +1,84 @@
+use futures::future::BoxFuture;
+use serde::{Serialize, de::DeserializeOwned};
+use surrealdb::types::SurrealValue;
+
+use crate::{
+    DB, DBError, DBResult,
+    favorites::Favorite,
+    history::{AppLaunch, HistoryEntry, QueryChoice},
+};
+
+/// Tables holding user-generated data (as opposed to the apps/icons/fs caches that
+/// can be rebuilt by the daemon at any time), which are carried over by `leaper export`
+pub static USER_TABLES: &[UserTableHandler] = &[
+    UserTableHandler::of::<AppLaunch>(),
+    UserTableHandler::of::<QueryChoice>(),
+    UserTableHandler::of::<HistoryEntry>(),
+    UserTableHandler::of::<Favorite>(),
+];
+
+pub trait UserTable: SurrealValue + Serialize + DeserializeOwned + Send + 'static {
+    const TABLE: &'static str;
+}
+
+pub struct UserTableHandler {
+    pub table: &'static str,
+    export: fn(DB) -> BoxFuture<'static, DBResult<Vec<u8>>>,
+    import: fn(DB, Vec<u8>) -> BoxFuture<'static, DBResult<usize>>,
+}
+
+impl UserTableHandler {
+    pub const fn of<T: UserTable>() -> Self {
+        Self {
+            table: T::TABLE,
+            export: export_table_boxed::<T>,
+            import: import_table_boxed::<T>,
+        }
+    }
+
+    pub async fn export(&self, db: DB) -> DBResult<Vec<u8>> {
+        (self.export)(db).await
+    }
+
+    pub async fn import(&self, db: DB, data: Vec<u8>) -> DBResult<usize> {
+        (self.import)(db, data).await
+    }
+
+    pub fn find(table: &str) -> DBResult<&'static Self> {
+        USER_TABLES
+            .iter()
+            .find(|handler| handler.table == table)
+            .ok_or_else(|| DBError::UnknownUserTable(table.into()))
+    }
+}
+
+fn export_table_boxed<T: UserTable>(db: DB) -> BoxFuture<'static, DBResult<Vec<u8>>> {
+    Box::pin(export_table::<T>(db))
+}
+
+fn import_table_boxed<T: UserTable>(db: DB, data: Vec<u8>) -> BoxFuture<'static, DBResult<usize>> {
+    Box::pin(import_table::<T>(db, data))
+}
+
+#[tracing::instrument(skip(db), level = "debug", name = "db::user_data::export_table")]
+async fn export_table<T: UserTable>(db: DB) -> DBResult<Vec<u8>> {
+    let rows: Vec<T> = db
+        .query(format!("SELECT * FROM {}", T::TABLE))
+        .await?
+        .take(0)?;
+
+    Ok(serde_json::to_vec_pretty(&rows)?)
+}
+
+#[tracing::instrument(skip(db, data), level = "debug", name = "db::user_data::import_table")]
+async fn import_table<T: UserTable>(db: DB, data: Vec<u8>) -> DBResult<usize> {
+    let rows: Vec<T> = serde_json::from_slice(&data)?;
+    let count = rows.len();
+
+    db.query(format!("INSERT IGNORE INTO {} $rows", T::TABLE))
+        .bind(("rows", rows))
+        .await?
+        .check()?;
+
+    Ok(count)
+}
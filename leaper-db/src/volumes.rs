@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use surrealdb_extras::{SurrealQuery, SurrealTable};
+
+use crate::DBError;
+
+/// Removable/external filesystem seen by the daemon. Files indexed under its mount point
+/// are linked to it, so they can be hidden while it's not mounted
+#[derive(Debug, Clone, SurrealValue, SurrealTable, Serialize, Deserialize)]
+#[table(
+    db = volume,
+    sql("DEFINE INDEX volume_uuid_ind ON TABLE volume COLUMNS uuid UNIQUE")
+)]
+pub struct Volume {
+    pub id: RecordId,
+    /// Filesystem UUID, stable across mounts
+    pub uuid: String,
+    pub label: Option<String>,
+    /// Where it was last mounted
+    pub mount_point: String,
+    pub mounted: bool,
+}
+
+/// Marks the volume as mounted at `mount_point` and links the already indexed
+/// nodes under it
+#[derive(Debug, bon::Builder, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        BEGIN TRANSACTION;
+
+        UPSERT volume SET
+            uuid = {uuid},
+            label = {label},
+            mount_point = {mount_point},
+            mounted = true
+        WHERE uuid == {uuid};
+        LET $volume = (SELECT VALUE id FROM ONLY volume WHERE uuid == {uuid} LIMIT 1);
+        UPDATE fs_node SET volume = $volume
+            WHERE path == {mount_point} OR string::starts_with(path, {mount_point} + '/');
+
+        COMMIT TRANSACTION;
+    "
+)]
+pub struct MountVolumeQuery {
+    #[builder(into)]
+    uuid: String,
+    label: Option<String>,
+    #[builder(into)]
+    mount_point: String,
+}
+
+/// Marks every volume that isn't in `mounted` (UUIDs) as unmounted
+#[derive(Debug, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "UPDATE volume SET mounted = false WHERE mounted AND uuid NOT IN {mounted}"
+)]
+pub struct UnmountVolumesQuery {
+    pub mounted: Vec<String>,
+}
+
+/// Links nodes indexed since their volume got mounted
+#[derive(Debug, SurrealQuery)]
+#[query(
+    check,
+    error = DBError,
+    sql = "
+        FOR $volume IN (SELECT id, mount_point FROM volume WHERE mounted) {
+            UPDATE fs_node SET volume = $volume.id
+                WHERE volume == NONE
+                    AND string::starts_with(path, $volume.mount_point + '/');
+        };
+    "
+)]
+pub struct TagVolumeNodesQuery;
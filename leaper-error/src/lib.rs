@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use macros::lerror;
+
+pub use tokio::task::JoinError;
+
+/// Variants every crate ended up defining, added to an lerror enum with `#[lerr(common)]`
+#[lerror]
+pub enum CommonError {
+    #[lerr(str = "[std::io] {0}")]
+    IO(#[lerr(from, wrap = Arc)] std::io::Error),
+    #[lerr(str = "[tokio::task::join] {0}")]
+    Join(#[lerr(from, wrap = Arc)] JoinError),
+}
+
+/// Implemented by lerror enums with `#[lerr(common)]`
+pub trait LeaperError: From<CommonError> + Sized {
+    /// Wraps the error with a description of what was being done
+    fn context(self, context: String) -> Self;
+    /// The common error underneath any context
+    fn common(&self) -> Option<&CommonError>;
+}
+
+/// Typed counterpart of anyhow's `.context()`, for results already carrying a crate error
+pub trait ResultExt<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T, E>;
+    fn with_context<S: Into<String>>(self, f: impl FnOnce() -> S) -> Result<T, E>;
+}
+
+impl<T, E: LeaperError> ResultExt<T, E> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T, E> {
+        self.map_err(|err| err.context(context.into()))
+    }
+
+    fn with_context<S: Into<String>>(self, f: impl FnOnce() -> S) -> Result<T, E> {
+        self.map_err(|err| err.context(f().into()))
+    }
+}
+
+/// Converts a foreign error into the crate error and attaches context in one go,
+/// e.g. `std::fs::read(path).err_context::<ConfigError>("reading the config")`
+pub trait IntoContext<T, S> {
+    fn err_context<E>(self, context: impl Into<String>) -> Result<T, E>
+    where
+        E: LeaperError + From<S>;
+}
+
+impl<T, S> IntoContext<T, S> for Result<T, S> {
+    fn err_context<E>(self, context: impl Into<String>) -> Result<T, E>
+    where
+        E: LeaperError + From<S>,
+    {
+        self.map_err(|err| E::from(err).context(context.into()))
+    }
+}
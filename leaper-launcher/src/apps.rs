@@ -0,0 +1,80 @@
+//! The apps as one of the [`ResultProvider`]s
+
+use std::sync::{Arc, Mutex};
+
+use futures::future::BoxFuture;
+
+use leaper_core::{search::AppSearch, sort::Launches};
+
+use crate::{
+    AppsIcons,
+    provider::{ResultItem, ResultKind, ResultProvider},
+};
+
+/// Searches the launcher's apps off the UI thread. The launcher owns the list and keeps
+/// changing it, so this searches the last snapshot handed over by [`Self::update`]
+/// instead. Queries only hold the lock to take the snapshot, they search it afterwards
+#[derive(Clone, Default)]
+pub struct AppsProvider(Arc<Mutex<Arc<AppsSnapshot>>>);
+
+#[derive(Default)]
+struct AppsSnapshot {
+    apps: AppsIcons,
+    launches: Launches,
+    search: Arc<AppSearch>,
+}
+
+impl AppsProvider {
+    /// Takes over the current list, whenever the apps, their launches or the search
+    /// (choices, favorites) change
+    pub fn update(&self, apps: &AppsIcons, launches: &Launches, search: &Arc<AppSearch>) {
+        let snapshot = Arc::new(AppsSnapshot {
+            apps: apps.clone(),
+            launches: launches.clone(),
+            search: search.clone(),
+        });
+
+        *self.0.lock().expect("Should be fine") = snapshot;
+    }
+
+    fn snapshot(&self) -> Arc<AppsSnapshot> {
+        self.0.lock().expect("Should be fine").clone()
+    }
+}
+
+impl ResultProvider for AppsProvider {
+    fn name(&self) -> &'static str {
+        "apps"
+    }
+
+    /// All of them in list order for an empty query, it's only asked then to filter by
+    /// category
+    fn query(&self, query: &str) -> BoxFuture<'static, Vec<ResultItem>> {
+        let snapshot = self.snapshot();
+        let query = query.to_string();
+
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || match query.is_empty() {
+                true => snapshot
+                    .apps
+                    .iter()
+                    .map(|app| ResultItem {
+                        score: 0,
+                        kind: ResultKind::App(app.clone()),
+                    })
+                    .collect(),
+                false => snapshot
+                    .search
+                    .search_scored(&snapshot.apps, &snapshot.launches, &query)
+                    .into_iter()
+                    .map(|(ind, score)| ResultItem {
+                        score: score as u32,
+                        kind: ResultKind::App(snapshot.apps[ind].clone()),
+                    })
+                    .collect(),
+            })
+            .await
+            .unwrap_or_default()
+        })
+    }
+}
@@ -0,0 +1,297 @@
+//! Arithmetic typed into the search input (e.g. `12*37+5`), shown as the first entry
+
+use futures::future::BoxFuture;
+
+use crate::provider::{ActionItem, ResultAction, ResultItem, ResultKind, ResultProvider};
+
+/// Lists the result of the query as arithmetic, Enter copies it. See `launcher.calculator`
+pub struct CalcProvider;
+
+impl ResultProvider for CalcProvider {
+    fn name(&self) -> &'static str {
+        "calculator"
+    }
+
+    fn query(&self, query: &str) -> BoxFuture<'static, Vec<ResultItem>> {
+        let items = Calculation::evaluate(query)
+            .map(|calc| ResultItem {
+                score: ResultItem::EXACT,
+                kind: ResultKind::Action(ActionItem {
+                    glyph: "=".into(),
+                    nerd_glyph: false,
+                    title: calc.value.clone(),
+                    detail: calc.expr,
+                    action: ResultAction::Copy(calc.value),
+                }),
+            })
+            .into_iter()
+            .collect();
+
+        Box::pin(futures::future::ready(items))
+    }
+}
+
+/// Result of a query that's an arithmetic expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calculation {
+    pub expr: String,
+    /// Formatted for showing and copying, integers without a fraction
+    pub value: String,
+}
+
+impl Calculation {
+    /// Fraction digits kept at most, trailing zeroes are trimmed
+    const PRECISION: usize = 10;
+
+    /// [`None`] unless the whole `query` is an expression with at least one operator, so
+    /// plain numbers and app names never turn into results. Division by zero and
+    /// overflows don't give one either
+    pub fn evaluate(query: &str) -> Option<Self> {
+        let expr = query.trim();
+
+        if !expr.contains(['+', '-', '*', '/', '%', '^', 'x', '×', '÷']) {
+            return None;
+        }
+
+        let mut parser = Parser {
+            tokens: tokenize(expr)?,
+            pos: 0,
+            operators: 0,
+        };
+        let value = parser.expr()?;
+
+        // Leftover tokens mean it wasn't arithmetic after all, e.g. `2 3`
+        (parser.pos == parser.tokens.len() && parser.operators > 0 && value.is_finite()).then(
+            || Self {
+                expr: expr.to_string(),
+                value: Self::format(value),
+            },
+        )
+    }
+
+    fn format(value: f64) -> String {
+        if value.fract() == 0.0 && value.abs() < 1e15 {
+            return format!("{}", value as i64);
+        }
+
+        let value = format!("{value:.prec$}", prec = Self::PRECISION);
+
+        value
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Num(f64),
+    Op(char),
+    Open,
+    Close,
+}
+
+fn tokenize(expr: &str) -> Option<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = expr.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        let token = match ch {
+            ch if ch.is_whitespace() => continue,
+            '0'..='9' | '.' => {
+                let mut num = String::from(ch);
+
+                while let Some(next) = chars.next_if(|next| next.is_ascii_digit() || *next == '.') {
+                    num.push(next);
+                }
+
+                Token::Num(num.parse().ok()?)
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' => Token::Op(ch),
+            // Typed on phones and copied from documents
+            'x' | '×' => Token::Op('*'),
+            '÷' => Token::Op('/'),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            _ => return None,
+        };
+
+        tokens.push(token);
+    }
+
+    Some(tokens)
+}
+
+/// Recursive descent over the usual precedence: `+ -`, then `* / %`, then unary minus,
+/// then `^` (right associative)
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Binary operators applied, `-5` alone isn't a calculation
+    operators: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn eat_op(&mut self, ops: &[char]) -> Option<char> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(&op) => {
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+
+        while let Some(op) = self.eat_op(&['+', '-']) {
+            let rhs = self.term()?;
+            self.operators += 1;
+
+            value = match op {
+                '+' => value + rhs,
+                _ => value - rhs,
+            };
+        }
+
+        Some(value)
+    }
+
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.unary()?;
+
+        while let Some(op) = self.eat_op(&['*', '/', '%']) {
+            let rhs = self.unary()?;
+            self.operators += 1;
+
+            value = match op {
+                '*' => value * rhs,
+                _ if rhs == 0.0 => return None,
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+
+        Some(value)
+    }
+
+    fn unary(&mut self) -> Option<f64> {
+        match self.eat_op(&['-', '+']) {
+            Some('-') => self.unary().map(|value| -value),
+            Some(_) => self.unary(),
+            None => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Option<f64> {
+        let base = self.atom()?;
+
+        match self.eat_op(&['^']) {
+            Some(_) => {
+                let exp = self.unary()?;
+                self.operators += 1;
+
+                Some(base.powf(exp))
+            }
+            None => Some(base),
+        }
+    }
+
+    fn atom(&mut self) -> Option<f64> {
+        match self.peek()? {
+            Token::Num(num) => {
+                self.pos += 1;
+                Some(num)
+            }
+            Token::Open => {
+                self.pos += 1;
+                let value = self.expr()?;
+
+                match self.peek()? {
+                    Token::Close => {
+                        self.pos += 1;
+                        Some(value)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(query: &str) -> Option<String> {
+        Calculation::evaluate(query).map(|calc| calc.value)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(value("2+3*4").as_deref(), Some("14"));
+        assert_eq!(value("10-4/2").as_deref(), Some("8"));
+        assert_eq!(value("7 % 4 * 2").as_deref(), Some("6"));
+        assert_eq!(value("3x4 + 1").as_deref(), Some("13"));
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(value("-3+5").as_deref(), Some("2"));
+        assert_eq!(value("2*-3").as_deref(), Some("-6"));
+        assert_eq!(value("--2+1").as_deref(), Some("3"));
+        // Binds looser than `^`
+        assert_eq!(value("-2^2").as_deref(), Some("-4"));
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        assert_eq!(value("2^3^2").as_deref(), Some("512"));
+        assert_eq!(value("2^-1").as_deref(), Some("0.5"));
+    }
+
+    #[test]
+    fn parentheses() {
+        assert_eq!(value("(2+3)*4").as_deref(), Some("20"));
+        assert_eq!(value("((1+1))^(1+2)").as_deref(), Some("8"));
+        assert_eq!(value("(2+3"), None);
+        assert_eq!(value("2+3)"), None);
+    }
+
+    #[test]
+    fn fractions() {
+        assert_eq!(value("10/4").as_deref(), Some("2.5"));
+        assert_eq!(value("1/3").as_deref(), Some("0.3333333333"));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(value("1/0"), None);
+        assert_eq!(value("5%0"), None);
+        assert_eq!(value("1/(2-2)"), None);
+    }
+
+    #[test]
+    fn not_arithmetic() {
+        for query in ["2 3", "2 + 3 3", "-5", "+5", "2048", "7zip", "firefox", ""] {
+            assert_eq!(value(query), None, "{query:?}");
+        }
+    }
+
+    #[test]
+    fn keeps_the_expression() {
+        assert_eq!(
+            Calculation::evaluate(" 1 + 1 "),
+            Some(Calculation {
+                expr: "1 + 1".into(),
+                value: "2".into(),
+            })
+        );
+    }
+}
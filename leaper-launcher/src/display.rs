@@ -0,0 +1,61 @@
+use mode::config::LeaperModeConfig;
+
+/// Sizes the app list is laid out with. The entries are all equally high, which the
+/// scroll offsets and on screen ranges rely on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayProfile {
+    pub icons: bool,
+    pub entry_height: f32,
+    /// Horizontal and vertical padding inside an entry
+    pub entry_padding: [f32; 2],
+    /// Between the icon and the name
+    pub entry_spacing: f32,
+    pub text_size: f32,
+    /// Between the entries
+    pub list_spacing: f32,
+}
+
+impl DisplayProfile {
+    /// Space left around the name without icons, see the `icons` config
+    const TEXT_ENTRY_MARGIN: f32 = 15.0;
+
+    /// The configured `launcher.entry_size`, with icons unless they're turned off
+    pub fn regular(config: &LeaperModeConfig) -> Self {
+        let entry_padding = [10.0, 5.0];
+        let size = config.launcher.entry_size.height();
+        let text_size = (size - entry_padding[1] * 2.0) * 0.5;
+
+        Self {
+            icons: config.icons,
+            entry_height: match config.icons {
+                true => size,
+                false => text_size + Self::TEXT_ENTRY_MARGIN,
+            },
+            entry_padding,
+            entry_spacing: 10.0,
+            text_size,
+            list_spacing: 5.0,
+        }
+    }
+
+    /// Names only with tight paddings, about three times the regular entries per screen
+    pub fn compact() -> Self {
+        Self {
+            icons: false,
+            entry_height: 22.0,
+            entry_padding: [8.0, 2.0],
+            entry_spacing: 0.0,
+            text_size: 14.0,
+            list_spacing: 0.0,
+        }
+    }
+
+    pub fn image_size(&self) -> f32 {
+        self.entry_height - self.entry_padding[1] * 2.0
+    }
+
+    /// Distance between the tops of two neighbouring entries
+    pub fn entry_stride(&self) -> f32 {
+        self.entry_height + self.list_spacing
+    }
+}
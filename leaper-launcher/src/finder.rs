@@ -0,0 +1,1506 @@
+//! `leaper find`: fuzzy search through the files indexed by the daemon, laid out like the
+//! launcher's app list
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use directories::ProjectDirs;
+use futures::SinkExt;
+use iced::{
+    Event, Length,
+    alignment::{Horizontal, Vertical},
+    keyboard::{self, Key, Modifiers, key},
+    mouse, stream,
+    widget::{
+        button, center, column, container, horizontal_rule, row, scrollable, text, text_input,
+    },
+    window,
+};
+use iced_layershell::{
+    build_pattern::MainSettings,
+    reexport::{Anchor, KeyboardInteractivity, Layer},
+    settings::{LayerShellSettings, Settings, StartMode},
+    to_layer_message,
+};
+use tokio_stream::StreamExt;
+
+use daemon::{IndexSummary, LeaperDaemonClient, client::context};
+use db::{
+    DB, DBEntryId, DBEnum, DBResult,
+    fs::{DuplicateGroup, FileHit, FileKind},
+    init_db,
+};
+use executor::LeaperExecutor;
+use leaper_core::{
+    CoreResult,
+    batch::{self, BatchAction, Selection},
+    browse::{BrowseEntry, BrowsePath},
+    clipboard,
+    collate::NameCollator,
+    file_actions::{self, FileAction},
+    files::{FileCatalog, FileSort, human_size},
+    finder::{FileFinder, LiveFile},
+    opener,
+    query::SearchQuery,
+};
+use mode::{
+    LeaperMode, LeaperModeTheme,
+    catcher::KeyCatcher,
+    config::LeaperModeConfig,
+    keys::{Binding, KeyBindings, KeyLayout},
+    selection::SelectionMove,
+};
+use style::hints::hint_bar;
+
+use crate::{
+    LeaperLauncherError,
+    display::DisplayProfile,
+    list::{self, ListScroll},
+};
+
+#[derive(Default)]
+pub struct LeaperFinder {
+    config: LeaperModeConfig,
+    catalog: Option<FileCatalog>,
+    finder: FileFinder,
+
+    /// Files passing [`Self::query`] by their node, at most
+    /// [`FileCatalog::FINDER_LIMIT`] of them
+    files: HashMap<DBEntryId, FileHit>,
+    /// Query [`Self::files`] were loaded for, narrower ones just filter them down
+    loaded: Option<SearchQuery>,
+    /// [`Self::files`] were cut off at [`FileCatalog::FINDER_LIMIT`], narrower queries
+    /// could still find others
+    truncated: bool,
+    /// Keys in [`Self::files`] of the results, best first
+    results: Vec<DBEntryId>,
+    loading: bool,
+
+    search: String,
+    /// Parsed [`Self::search`], the last one that parsed while it's being typed
+    query: SearchQuery,
+    query_error: Option<String>,
+    /// Set while [`Self::search`] is a path (`/usr/sh`, `~/Doc`), the list shows the
+    /// children of its directory instead of the search results
+    browse: Option<BrowsePath>,
+    /// Children of the [`Self::browse`] directory matching the partial name
+    entries: Vec<BrowseEntry>,
+    collator: NameCollator,
+    selected: usize,
+    /// Paths of the files marked for a [`BatchAction`], they stay marked while the query
+    /// changes
+    marked: Selection<String>,
+    /// Destructive action on [`Self::marked`] waiting to be confirmed
+    confirm: Option<BatchAction>,
+    /// Groups of identical files, listed instead of the search results while set
+    duplicates: Option<Vec<DuplicateGroup>>,
+    /// Order of the results instead of how well they match
+    sort: Option<FileSort>,
+    /// Type and size chips under the search, see [`LeaperFinder::filter_terms`]
+    show_filters: bool,
+
+    list: ListScroll,
+
+    daemon: Option<LeaperDaemonClient>,
+    /// On-demand indexing of a directory the search came up empty in, see
+    /// [`LeaperFinder::index_root`]
+    indexing: Option<IndexRun>,
+}
+
+/// Directory the daemon is indexing for the finder, with the entries it got through so far
+#[derive(Debug, Clone)]
+struct IndexRun {
+    root: PathBuf,
+    items: u64,
+}
+
+impl LeaperMode for LeaperFinder {
+    type RunError = LeaperLauncherError;
+    type Task = iced::Task<Self::Msg>;
+
+    type Subscription = iced::Subscription<Self::Msg>;
+
+    type Renderer = iced::Renderer;
+
+    type Element<'a>
+        = iced::Element<'a, Self::Msg, LeaperModeTheme, Self::Renderer>
+    where
+        Self: 'a;
+
+    type Msg = LeaperFinderMsg;
+
+    fn run() -> Result<(), Self::RunError> {
+        let project_dirs = Self::project_dirs();
+        let config = LeaperModeConfig::open(&project_dirs)?;
+        config.renderer.apply();
+        KeyLayout::init();
+
+        let (width, height) = (Self::WINDOW_SIZE.0 as u32, Self::WINDOW_SIZE.1 as u32);
+
+        let Settings {
+            fonts,
+            default_font,
+            default_text_size,
+            antialiasing,
+            virtual_keyboard_support,
+            ..
+        } = Settings::<()>::default();
+
+        let settings = MainSettings {
+            id: Some("com.tukanoid.leaper-find".into()),
+            layer_settings: LayerShellSettings {
+                anchor: Anchor::empty(),
+                layer: Layer::Overlay,
+                exclusive_zone: 0,
+                size: Some((width, height)),
+                margin: (0, 0, 0, 0),
+                keyboard_interactivity: KeyboardInteractivity::Exclusive,
+                start_mode: StartMode::Active,
+                events_transparent: false,
+            },
+            fonts,
+            default_font,
+            default_text_size,
+            antialiasing,
+            virtual_keyboard_support,
+        };
+
+        mode::run_mode!(Self {
+            settings: settings,
+            window_size: Self::WINDOW_SIZE,
+            executor: LeaperExecutor,
+            init: move || Self::init(project_dirs, config, ()),
+        });
+
+        Ok(())
+    }
+
+    fn init(
+        _project_dirs: ProjectDirs,
+        config: LeaperModeConfig,
+        _args: Self::InitArgs,
+    ) -> (Self, Self::Task)
+    where
+        Self: Sized,
+    {
+        let db_port = config.db_port;
+        // Ephemeral sessions don't touch the daemon, there's just no indexing on demand
+        let daemon = (!config.ephemeral).then(|| {
+            Self::Task::perform(daemon::client::connect(), |res| {
+                Self::Msg::InitDaemon(
+                    res.inspect_err(|err| {
+                        tracing::warn!("Failed to initialize daemon client: {err}")
+                    })
+                    .ok(),
+                )
+            })
+        });
+        let finder = Self {
+            collator: NameCollator::new(config.launcher.collation.as_deref()),
+            config,
+            loading: true,
+            list: ListScroll::new(Self::WINDOW_SIZE.1),
+            ..Default::default()
+        };
+
+        (
+            finder,
+            Self::Task::batch(
+                [
+                    text_input::focus(Self::SEARCH_ID),
+                    Self::Task::perform(init_db(db_port), Self::Msg::InitDB),
+                ]
+                .into_iter()
+                .chain(daemon),
+            ),
+        )
+    }
+
+    fn view(&self) -> Self::Element<'_> {
+        KeyCatcher::new(
+            column![self.search()]
+                .push_maybe(self.breadcrumbs())
+                .push_maybe(self.filter_chips())
+                .push_maybe(
+                    self.query_error
+                        .as_ref()
+                        .map(|err| text(err).size(14).style(text::danger)),
+                )
+                .push_maybe(self.indexing.as_ref().map(|run| {
+                    text(format!(
+                        "Indexing {}: {} entries...",
+                        run.root.display(),
+                        run.items
+                    ))
+                    .size(14)
+                }))
+                .push(horizontal_rule(2))
+                .push(self.list())
+                .push_maybe(self.batch_bar())
+                .push_maybe(
+                    self.config
+                        .show_hints
+                        .then(|| hint_bar(Self::BINDINGS.hints(|action| self.available(action)))),
+                )
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .padding(20)
+                .spacing(10),
+            |event| {
+                Self::BINDINGS
+                    .catches(event, |action| self.available(action))
+                    .then(|| Self::Msg::IcedEvent(Event::Keyboard(event.clone())))
+            },
+        )
+        .into()
+    }
+
+    fn update(&mut self, msg: Self::Msg) -> Self::Task {
+        match msg {
+            Self::Msg::Exit => return iced::exit(),
+
+            Self::Msg::InitDB(db) => match db {
+                Ok(db) => {
+                    self.catalog = Some(FileCatalog::new(db));
+
+                    return match self.browse.is_some() {
+                        true => Self::Task::none(),
+                        false => self.load_candidates(),
+                    };
+                }
+                Err(err) => {
+                    tracing::error!("Failed to initialize the database: {err}");
+                    return Self::Task::done(Self::Msg::Exit);
+                }
+            },
+            Self::Msg::InitDaemon(daemon) => self.daemon = daemon,
+            // Answers to queries typed over since are dropped, unless the query only got
+            // narrower
+            Self::Msg::Candidates(query, _) if !self.query.narrows(&query) => {}
+            Self::Msg::Candidates(query, files) => {
+                self.loading = false;
+
+                match files {
+                    Ok(files) => {
+                        self.truncated = files.len() >= FileCatalog::FINDER_LIMIT;
+                        self.files = files
+                            .into_iter()
+                            .map(|file| (file.node.clone(), file))
+                            .collect();
+                        self.loaded = Some(query);
+                        self.refilter();
+                    }
+                    Err(err) => tracing::error!("Failed to search the indexed files: {err}"),
+                }
+
+                return Self::Task::done(Self::Msg::ScrollToSelected);
+            }
+            Self::Msg::LiveFiles(changes) => {
+                for change in changes {
+                    match change {
+                        LiveFile::Changed(file) if self.query.matches(&file) => {
+                            // Past the limit it's a query that needs narrowing down anyway,
+                            // the next one asks the DB again
+                            if self.files.len() >= FileCatalog::FINDER_LIMIT
+                                && !self.files.contains_key(&file.node)
+                            {
+                                self.truncated = true;
+                                continue;
+                            }
+
+                            self.files.insert(file.node.clone(), file);
+                        }
+                        // Doesn't pass the query any more
+                        LiveFile::Changed(file) => {
+                            self.files.remove(&file.node);
+                        }
+                        LiveFile::Removed(node) => {
+                            self.files.remove(&node);
+                        }
+                    }
+                }
+
+                // Once for the whole batch
+                self.rerank();
+            }
+
+            Self::Msg::SearchInput(search) => {
+                self.search = search;
+                // Typing goes back to the search
+                self.duplicates = None;
+
+                if let Some(browse) = BrowsePath::parse(&self.search) {
+                    let (collator, request) = (self.collator.clone(), browse.clone());
+                    self.browse = Some(browse);
+                    self.query_error = None;
+
+                    return Self::Task::perform(
+                        async move {
+                            let entries = request.children(&collator).await;
+                            (request, entries)
+                        },
+                        |(browse, entries)| Self::Msg::BrowseEntries(browse, entries),
+                    );
+                }
+
+                // Left the path, back to the search results
+                if self.browse.take().is_some() {
+                    self.entries.clear();
+                    self.selected = 0;
+                }
+
+                return self.parse_search();
+            }
+            // Answers for paths typed over since are dropped
+            Self::Msg::BrowseEntries(browse, _) if self.browse.as_ref() != Some(&browse) => {}
+            Self::Msg::BrowseEntries(_, entries) => {
+                self.entries = entries.unwrap_or_else(|err| {
+                    tracing::debug!("Failed to list the directory: {err}");
+                    vec![]
+                });
+                self.entries.truncate(Self::MAX_SHOWN);
+                self.selected = 0;
+
+                return Self::Task::done(Self::Msg::ScrollToSelected);
+            }
+            Self::Msg::BrowseUp => {
+                if let Some(up) = self.browse.as_ref().and_then(BrowsePath::up) {
+                    return Self::Task::batch([
+                        Self::Task::done(Self::Msg::SearchInput(up)),
+                        Self::focus_search(),
+                    ]);
+                }
+            }
+            Self::Msg::BrowseInto(ind) => {
+                let completed = self.browse.as_ref().and_then(|browse| {
+                    let entry = self.entries.get(ind)?;
+                    Some(browse.complete(entry))
+                });
+
+                if let Some(completed) = completed {
+                    return Self::Task::batch([
+                        Self::Task::done(Self::Msg::SearchInput(completed)),
+                        Self::focus_search(),
+                    ]);
+                }
+            }
+            Self::Msg::Select(movement) => {
+                self.selected = movement.apply(
+                    self.selected,
+                    self.shown_len(),
+                    self.list.page_len(self.display()),
+                );
+
+                return Self::Task::done(Self::Msg::ScrollToSelected);
+            }
+
+            Self::Msg::OpenSelected if !self.marked.is_empty() && self.browse.is_none() => {
+                return Self::Task::done(Self::Msg::Batch(BatchAction::Open));
+            }
+            Self::Msg::OpenSelected => return Self::Task::done(Self::Msg::Open(self.selected)),
+            Self::Msg::Open(ind)
+                if self
+                    .browse
+                    .as_ref()
+                    .and_then(|_| self.entries.get(ind))
+                    .is_some_and(|entry| entry.is_dir) =>
+            {
+                return Self::Task::done(Self::Msg::BrowseInto(ind));
+            }
+            Self::Msg::Open(ind) => match self.path(ind) {
+                Some(path) => {
+                    return Self::Task::perform(
+                        async move { opener::open_path(path).await },
+                        |res| {
+                            if let Err(err) = res {
+                                tracing::error!("Failed to open the file: {err}");
+                            }
+
+                            Self::Msg::Exit
+                        },
+                    );
+                }
+                None => tracing::warn!("Logic error!"),
+            },
+            Self::Msg::FileAction(action) => match self.path(self.selected) {
+                Some(path) => return self.file_action(action, path),
+                None => tracing::warn!("Logic error!"),
+            },
+
+            Self::Msg::ToggleDuplicates => {
+                self.selected = 0;
+
+                match self.duplicates.take() {
+                    Some(_) => return Self::Task::done(Self::Msg::ScrollToSelected),
+                    None => {
+                        if let Some(catalog) = self.catalog.clone() {
+                            self.duplicates = Some(vec![]);
+                            self.loading = true;
+
+                            return Self::Task::perform(
+                                catalog.duplicates(),
+                                Self::Msg::Duplicates,
+                            );
+                        }
+                    }
+                }
+            }
+            // Left the duplicates view before they came in
+            Self::Msg::Duplicates(_) if self.duplicates.is_none() => {}
+            Self::Msg::Duplicates(groups) => {
+                self.loading = false;
+                self.duplicates = Some(groups.unwrap_or_else(|err| {
+                    tracing::error!("Failed to find duplicate files: {err}");
+                    vec![]
+                }));
+
+                return Self::Task::done(Self::Msg::ScrollToSelected);
+            }
+
+            Self::Msg::ToggleMark(ind) => {
+                if let Some(path) = self.path(ind) {
+                    self.marked.toggle(path.to_string_lossy().into_owned());
+                }
+
+                if self.marked.is_empty() {
+                    self.confirm = None;
+                }
+            }
+            Self::Msg::Batch(action) if action.destructive() && self.confirm != Some(action) => {
+                self.confirm = Some(action);
+            }
+            Self::Msg::Batch(action) => {
+                self.confirm = None;
+
+                let paths = self.marked.iter().map(PathBuf::from).collect::<Vec<_>>();
+
+                return match action {
+                    BatchAction::Open => Self::Task::perform(
+                        async move { batch::open_all(&paths).await },
+                        |errors| {
+                            for (path, err) in errors {
+                                tracing::error!("Failed to open {path:?}: {err}");
+                            }
+
+                            Self::Msg::Exit
+                        },
+                    ),
+                    BatchAction::CopyPaths => {
+                        Self::Task::perform(clipboard::copy(batch::paths_text(&paths)), |res| {
+                            if let Err(err) = res {
+                                tracing::error!("Failed to copy the paths: {err}");
+                            }
+
+                            Self::Msg::Exit
+                        })
+                    }
+                    BatchAction::Trash => Self::Task::perform(
+                        tokio::task::spawn_blocking(move || {
+                            paths
+                                .into_iter()
+                                .filter_map(|path| match batch::trash(&path) {
+                                    Ok(_) => Some(path.to_string_lossy().into_owned()),
+                                    Err(err) => {
+                                        tracing::error!("{err}");
+                                        None
+                                    }
+                                })
+                                .collect::<Vec<_>>()
+                        }),
+                        |trashed| Self::Msg::Trashed(trashed.unwrap_or_default()),
+                    ),
+                };
+            }
+            Self::Msg::CancelBatch => self.confirm = None,
+            // The daemon drops them from the index too, they just shouldn't linger until then
+            Self::Msg::Trashed(paths) => {
+                for path in &paths {
+                    self.marked.remove(path);
+                }
+
+                self.files.retain(|_, file| !paths.contains(&file.path));
+
+                if let Some(groups) = &mut self.duplicates {
+                    for group in groups.iter_mut() {
+                        group.paths.retain(|path| !paths.contains(path));
+                    }
+
+                    groups.retain(|group| group.paths.len() > 1);
+                }
+
+                self.rerank();
+            }
+
+            Self::Msg::ToggleFilters => self.show_filters = !self.show_filters,
+            Self::Msg::ToggleFilter(term) => {
+                let on = self.search.split_whitespace().any(|word| word == term);
+                // One size at a time, the bigger one would just hide the smaller one
+                let size = term.starts_with("size:");
+                let mut words = self
+                    .search
+                    .split_whitespace()
+                    .filter(|word| {
+                        *word != term
+                            && !(size && Self::filter_terms().any(|(_, other)| other == *word))
+                    })
+                    .collect::<Vec<_>>();
+
+                if !on {
+                    words.push(&term);
+                }
+
+                return Self::Task::batch([
+                    Self::Task::done(Self::Msg::SearchInput(words.join(" "))),
+                    Self::focus_search(),
+                ]);
+            }
+            Self::Msg::CycleSort => {
+                self.sort = FileSort::next(self.sort);
+                self.selected = 0;
+                self.rerank();
+
+                return Self::Task::done(Self::Msg::ScrollToSelected);
+            }
+
+            Self::Msg::IndexNow => {
+                let (Some(daemon), Some(root)) = (
+                    self.daemon.clone(),
+                    self.index_root().map(Path::to_path_buf),
+                ) else {
+                    return Self::Task::none();
+                };
+
+                self.indexing = Some(IndexRun {
+                    root: root.clone(),
+                    items: 0,
+                });
+
+                let mut ctx = context::current();
+                ctx.deadline = Instant::now() + Self::INDEX_TIMEOUT;
+
+                return Self::Task::perform(
+                    async move {
+                        daemon
+                            .index(ctx, root, false)
+                            .await
+                            .map_err(|err| err.to_string())
+                    },
+                    Self::Msg::Indexed,
+                );
+            }
+            Self::Msg::PollIndex => {
+                let (Some(daemon), Some(run)) = (self.daemon.clone(), &self.indexing) else {
+                    return Self::Task::none();
+                };
+
+                let root = run.root.clone();
+
+                return Self::Task::perform(
+                    async move { daemon.index_progress(context::current()).await },
+                    move |progress| {
+                        let items = progress
+                            .ok()
+                            .and_then(|runs| runs.into_iter().find(|run| run.root == root))
+                            .map(|run| run.items);
+
+                        Self::Msg::IndexProgress(items)
+                    },
+                );
+            }
+            Self::Msg::IndexProgress(items) => {
+                if let (Some(run), Some(items)) = (&mut self.indexing, items) {
+                    run.items = items;
+                }
+            }
+            Self::Msg::Indexed(summary) => {
+                let Some(run) = self.indexing.take() else {
+                    return Self::Task::none();
+                };
+
+                match summary {
+                    Ok(summary) => tracing::debug!(
+                        "Indexed {:?}: {} entries, {} errors",
+                        run.root,
+                        summary.items,
+                        summary.errors
+                    ),
+                    Err(err) => tracing::error!("Failed to index {:?}: {err}", run.root),
+                }
+
+                // Whatever got indexed before it failed counts too
+                return self.load_candidates();
+            }
+
+            Self::Msg::ListScrolled(viewport) => {
+                self.list.scrolled(viewport);
+
+                return Self::focus_search();
+            }
+            Self::Msg::ScrollToSelected => {
+                if self.shown_len() > 0 {
+                    return self
+                        .list
+                        .scroll_to(Self::LIST_ID, self.selected, self.display());
+                }
+            }
+
+            Self::Msg::IcedEvent(event) => {
+                if let Event::Window(window::Event::Resized(size)) = event {
+                    self.list.resized(size);
+                }
+
+                if let Event::Mouse(mouse::Event::ButtonReleased(_)) = event {
+                    return Self::focus_search();
+                }
+
+                if let Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event
+                    && let Some(action) = Self::BINDINGS
+                        .find(key.as_ref(), modifiers, |action| self.available(action))
+                {
+                    return self.key_action(action);
+                }
+            }
+
+            Self::Msg::AnchorChange(_)
+            | Self::Msg::SetInputRegion(_)
+            | Self::Msg::AnchorSizeChange(_, _)
+            | Self::Msg::LayerChange(_)
+            | Self::Msg::MarginChange(_)
+            | Self::Msg::SizeChange(_)
+            | Self::Msg::VirtualKeyboardPressed { .. } => {}
+        }
+
+        Self::Task::none()
+    }
+
+    fn subscription(&self) -> Self::Subscription {
+        let iced_events = iced::event::listen().map(Self::Msg::IcedEvent);
+        let iced_events = match self.indexing.is_some() {
+            true => Self::Subscription::batch([
+                iced_events,
+                iced::time::every(Self::INDEX_POLL).map(|_| Self::Msg::PollIndex),
+            ]),
+            false => iced_events,
+        };
+
+        let Some(catalog) = self.catalog.clone() else {
+            return iced_events;
+        };
+
+        Self::Subscription::batch([
+            iced_events,
+            Self::Subscription::run_with_id(
+                "live_files",
+                stream::channel(1, |mut msg_sender| async move {
+                    let stream = match catalog.live_files().await {
+                        Ok(stream) => Box::pin(stream),
+                        Err(err) => {
+                            // The results just don't update, they're still usable
+                            tracing::error!("{err}");
+                            return;
+                        }
+                    };
+
+                    // Files indexed together (e.g. a whole directory) come in one update
+                    let mut batches =
+                        futures::StreamExt::ready_chunks(stream, Self::LIVE_BATCH_SIZE);
+
+                    while let Some(batch) = batches.next().await {
+                        let changes = match batch.into_iter().collect::<CoreResult<Vec<_>>>() {
+                            Ok(changes) => changes,
+                            Err(err) => {
+                                tracing::error!(
+                                    "Failed to get notification from fs live table: {err}"
+                                );
+                                return;
+                            }
+                        };
+
+                        if let Err(err) = msg_sender.send(Self::Msg::LiveFiles(changes)).await {
+                            tracing::error!(
+                                "Failed to send file changes from fs live table subscription: {err}"
+                            );
+                            return;
+                        }
+                    }
+                }),
+            ),
+        ])
+    }
+
+    fn title(&self) -> String {
+        "leaper-find".into()
+    }
+
+    fn theme(&self) -> LeaperModeTheme {
+        self.config.theme.clone()
+    }
+}
+
+impl LeaperFinder {
+    const SEARCH_ID: &'static str = "file_search_input";
+    const LIST_ID: &'static str = "file_list";
+    const LIVE_BATCH_SIZE: usize = 256;
+    const WINDOW_SIZE: (f32, f32) = (700.0, 800.0);
+    /// Results beyond this aren't laid out, narrowing the query brings them up
+    const MAX_SHOWN: usize = 200;
+    const INDEX_POLL: Duration = Duration::from_millis(500);
+    /// Big trees take a while, the progress row shows it's still going
+    const INDEX_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+    /// `size:>` chips, smallest first
+    const SIZE_FILTERS: [&'static str; 3] = ["1M", "100M", "1G"];
+
+    const BINDINGS: KeyBindings<KeyAction> = KeyBindings(&[
+        Binding::new(Key::Named(key::Named::Enter), KeyAction::Confirm, "confirm"),
+        Binding::new(Key::Named(key::Named::Escape), KeyAction::Cancel, "cancel"),
+        Binding::new(Key::Named(key::Named::Enter), KeyAction::Open, "open"),
+        Binding::new(Key::Named(key::Named::Tab), KeyAction::BrowseInto, "into"),
+        Binding::new(
+            Key::Named(key::Named::ArrowRight),
+            KeyAction::BrowseInto,
+            "into",
+        ),
+        Binding::new(Key::Named(key::Named::Backspace), KeyAction::BrowseUp, "up"),
+        Binding::new(Key::Named(key::Named::Space), KeyAction::Mark, "mark").with(Modifiers::CTRL),
+        Binding::new(
+            Key::Character("c"),
+            KeyAction::Batch(BatchAction::CopyPaths),
+            "copy paths",
+        )
+        .with(Modifiers::CTRL.union(Modifiers::SHIFT)),
+        Binding::new(
+            Key::Named(key::Named::Delete),
+            KeyAction::Batch(BatchAction::Trash),
+            "trash",
+        )
+        .with(Modifiers::CTRL),
+        Binding::new(
+            Key::Character("c"),
+            KeyAction::FileAction(FileAction::CopyPath),
+            "copy path",
+        )
+        .with(Modifiers::CTRL.union(Modifiers::SHIFT)),
+        Binding::new(
+            Key::Character("o"),
+            KeyAction::FileAction(FileAction::OpenFolder),
+            "folder",
+        )
+        .with(Modifiers::CTRL),
+        Binding::new(
+            Key::Character("t"),
+            KeyAction::FileAction(FileAction::OpenTerminal),
+            "terminal",
+        )
+        .with(Modifiers::CTRL),
+        Binding::new(Key::Character("d"), KeyAction::Duplicates, "duplicates")
+            .with(Modifiers::CTRL),
+        Binding::new(Key::Character("i"), KeyAction::Index, "index").with(Modifiers::CTRL),
+        Binding::new(Key::Character("s"), KeyAction::Sort, "sort").with(Modifiers::CTRL),
+        Binding::new(Key::Character("f"), KeyAction::Filters, "filters").with(Modifiers::CTRL),
+        Binding::new(
+            Key::Named(key::Named::ArrowUp),
+            KeyAction::Select(SelectionMove::Up),
+            "select",
+        ),
+        Binding::new(
+            Key::Named(key::Named::ArrowDown),
+            KeyAction::Select(SelectionMove::Down),
+            "select",
+        ),
+        Binding::new(
+            Key::Named(key::Named::PageUp),
+            KeyAction::Select(SelectionMove::PageUp),
+            "page",
+        ),
+        Binding::new(
+            Key::Named(key::Named::PageDown),
+            KeyAction::Select(SelectionMove::PageDown),
+            "page",
+        ),
+        Binding::new(
+            Key::Named(key::Named::Home),
+            KeyAction::Select(SelectionMove::First),
+            "first/last",
+        ),
+        Binding::new(
+            Key::Named(key::Named::End),
+            KeyAction::Select(SelectionMove::Last),
+            "first/last",
+        ),
+        Binding::new(Key::Named(key::Named::Escape), KeyAction::Back, "back"),
+        Binding::new(Key::Named(key::Named::Escape), KeyAction::Clear, "clear"),
+        Binding::new(Key::Named(key::Named::Escape), KeyAction::Exit, "exit"),
+    ]);
+
+    fn available(&self, action: KeyAction) -> bool {
+        // Escape leaves the duplicates first
+        let back = self.duplicates.is_some();
+        let clears = !back && self.config.escape_clears && !self.search.is_empty();
+
+        // Nothing else until the action is confirmed or cancelled
+        if self.confirm.is_some() {
+            return matches!(action, KeyAction::Confirm | KeyAction::Cancel);
+        }
+
+        match action {
+            KeyAction::Confirm | KeyAction::Cancel => false,
+            KeyAction::Open => self.shown_len() > 0 || !self.marked.is_empty(),
+            // Ctrl+Shift+C copies the marked paths instead while there are any
+            KeyAction::FileAction(FileAction::CopyPath) => {
+                self.shown_len() > 0 && self.marked.is_empty()
+            }
+            KeyAction::FileAction(_) => self.shown_len() > 0,
+            KeyAction::BrowseInto => self
+                .entries
+                .get(self.selected)
+                .is_some_and(|entry| entry.is_dir && self.browse.is_some()),
+            // Only in place of deleting the `/` of an empty partial name
+            KeyAction::BrowseUp => self
+                .browse
+                .as_ref()
+                .is_some_and(|browse| browse.partial().is_empty() && browse.up().is_some()),
+            KeyAction::Mark => self.browse.is_none() && self.shown_len() > 0,
+            KeyAction::Duplicates => self.browse.is_none() && self.catalog.is_some(),
+            KeyAction::Index => self.index_root().is_some(),
+            KeyAction::Sort | KeyAction::Filters => {
+                self.browse.is_none() && self.duplicates.is_none()
+            }
+            KeyAction::Back => back,
+            KeyAction::Batch(_) => self.browse.is_none() && !self.marked.is_empty(),
+            KeyAction::Select(_) => self.shown_len() > 1,
+            KeyAction::Clear => clears,
+            KeyAction::Exit => !back && !clears,
+        }
+    }
+
+    fn key_action(&self, action: KeyAction) -> <Self as LeaperMode>::Task {
+        let msg = match action {
+            KeyAction::Confirm => LeaperFinderMsg::Batch(self.confirm.expect("confirming")),
+            KeyAction::Cancel => LeaperFinderMsg::CancelBatch,
+            KeyAction::Open => LeaperFinderMsg::OpenSelected,
+            KeyAction::Mark => LeaperFinderMsg::ToggleMark(self.selected),
+            KeyAction::Batch(action) => LeaperFinderMsg::Batch(action),
+            KeyAction::FileAction(action) => LeaperFinderMsg::FileAction(action),
+            KeyAction::BrowseInto => LeaperFinderMsg::BrowseInto(self.selected),
+            KeyAction::BrowseUp => LeaperFinderMsg::BrowseUp,
+            KeyAction::Duplicates | KeyAction::Back => LeaperFinderMsg::ToggleDuplicates,
+            KeyAction::Index => LeaperFinderMsg::IndexNow,
+            KeyAction::Sort => LeaperFinderMsg::CycleSort,
+            KeyAction::Filters => LeaperFinderMsg::ToggleFilters,
+            KeyAction::Select(movement) => LeaperFinderMsg::Select(movement),
+            KeyAction::Clear => {
+                return <Self as LeaperMode>::Task::batch([
+                    <Self as LeaperMode>::Task::done(LeaperFinderMsg::SearchInput(String::new())),
+                    Self::focus_search(),
+                ]);
+            }
+            KeyAction::Exit => LeaperFinderMsg::Exit,
+        };
+
+        <Self as LeaperMode>::Task::done(msg)
+    }
+
+    fn focus_search() -> <Self as LeaperMode>::Task {
+        <Self as LeaperMode>::Task::batch([
+            text_input::focus(Self::SEARCH_ID),
+            text_input::move_cursor_to_end(Self::SEARCH_ID),
+        ])
+    }
+
+    /// Takes over [`Self::search`] as the query, unless it doesn't parse. Files loaded for a
+    /// broader query are filtered down, anything else asks the DB again
+    fn parse_search(&mut self) -> <Self as LeaperMode>::Task {
+        let query = match SearchQuery::parse(&self.search) {
+            Ok(query) => query,
+            Err(err) => {
+                self.query_error = Some(err.to_string());
+                return <Self as LeaperMode>::Task::none();
+            }
+        };
+
+        self.query_error = None;
+
+        let refetch = self.truncated
+            || !self
+                .loaded
+                .as_ref()
+                .is_some_and(|loaded| query.narrows(loaded));
+        self.query = query;
+
+        match refetch {
+            true => self.load_candidates(),
+            false => {
+                self.refilter();
+                <Self as LeaperMode>::Task::none()
+            }
+        }
+    }
+
+    /// Asks the DB for the files passing the current query
+    fn load_candidates(&mut self) -> <Self as LeaperMode>::Task {
+        let Some(catalog) = self.catalog.clone() else {
+            return <Self as LeaperMode>::Task::none();
+        };
+
+        let query = self.query.clone();
+        self.loading = true;
+
+        <Self as LeaperMode>::Task::perform(catalog.candidates(query.clone()), move |files| {
+            LeaperFinderMsg::Candidates(query.clone(), files)
+        })
+    }
+
+    /// Directory to index when the search came up empty: the `in:` one of the query, or
+    /// the browsed one. `None` while one is indexed already or without the daemon
+    fn index_root(&self) -> Option<&Path> {
+        if self.daemon.is_none() || self.indexing.is_some() || self.duplicates.is_some() {
+            return None;
+        }
+
+        match &self.browse {
+            Some(browse) => Some(browse.dir()),
+            None if !self.loading && self.results.is_empty() => self.query.dir(),
+            None => None,
+        }
+    }
+
+    /// Drops the files the query no longer matches, then ranks the rest
+    fn refilter(&mut self) {
+        let query = &self.query;
+        self.files.retain(|_, file| query.matches(file));
+
+        self.rerank();
+    }
+
+    fn rerank(&mut self) {
+        self.results = self.finder.rank(
+            self.files.iter().map(|(node, file)| (node.clone(), file)),
+            &self.query.text(),
+        );
+
+        if let Some(sort) = self.sort {
+            let (files, collator) = (&self.files, &self.collator);
+            self.results
+                .sort_by(|a, b| sort.compare(&files[a], &files[b], collator));
+        }
+
+        self.selected = match self.shown_len() {
+            0 => 0,
+            len => self.selected.min(len - 1),
+        };
+    }
+
+    fn result(&self, ind: usize) -> Option<&FileHit> {
+        self.results
+            .get(ind)
+            .filter(|_| ind < Self::MAX_SHOWN)
+            .and_then(|node| self.files.get(node))
+    }
+
+    fn file_action(&self, action: FileAction, path: PathBuf) -> <Self as LeaperMode>::Task {
+        let terminal = self.config.resolve_terminal();
+
+        <Self as LeaperMode>::Task::perform(
+            async move {
+                match action {
+                    FileAction::OpenFolder => file_actions::open_folder(&path).await,
+                    FileAction::OpenTerminal => file_actions::open_terminal(&path, &terminal),
+                    FileAction::CopyPath => {
+                        clipboard::copy(file_actions::absolute_path(&path)?).await
+                    }
+                }
+            },
+            move |res| {
+                if let Err(err) = res {
+                    tracing::error!("{}: {err}", action.name());
+                }
+
+                <Self as LeaperMode>::Msg::Exit
+            },
+        )
+    }
+
+    /// Path of the result (or the directory entry while browsing) at `ind`
+    fn path(&self, ind: usize) -> Option<PathBuf> {
+        match (&self.browse, &self.duplicates) {
+            (Some(_), _) => self.entries.get(ind).map(|entry| entry.path.clone()),
+            (None, Some(_)) => self
+                .duplicate(ind)
+                .map(|(group, copy)| PathBuf::from(&group.paths[copy])),
+            (None, None) => self.result(ind).map(|file| PathBuf::from(&file.path)),
+        }
+    }
+
+    /// Group of the duplicate at `ind`, counting through the paths of all groups, and which
+    /// of its paths it is
+    fn duplicate(&self, mut ind: usize) -> Option<(&DuplicateGroup, usize)> {
+        for group in self.duplicates.as_ref()? {
+            match ind < group.paths.len() {
+                true => return Some((group, ind)),
+                false => ind -= group.paths.len(),
+            }
+        }
+
+        None
+    }
+
+    fn shown_len(&self) -> usize {
+        match (&self.browse, &self.duplicates) {
+            (Some(_), _) => self.entries.len(),
+            (None, Some(groups)) => groups
+                .iter()
+                .map(|group| group.paths.len())
+                .sum::<usize>()
+                .min(Self::MAX_SHOWN),
+            (None, None) => self.results.len().min(Self::MAX_SHOWN),
+        }
+    }
+
+    fn display(&self) -> DisplayProfile {
+        DisplayProfile::regular(&self.config)
+    }
+
+    fn search(&self) -> <Self as LeaperMode>::Element<'_> {
+        center(
+            text_input(
+                "Search files or browse from / or ~/ (ext:pdf in:~/docs type:img)",
+                &self.search,
+            )
+            .id(text_input::Id::new(Self::SEARCH_ID))
+            .on_input(<Self as LeaperMode>::Msg::SearchInput)
+            .on_submit(<Self as LeaperMode>::Msg::OpenSelected)
+            .size(25)
+            .padding(10)
+            .style(style::text_input),
+        )
+        .width(Length::Fill)
+        .height(Length::Shrink)
+        .padding(10)
+        .into()
+    }
+
+    fn list(&self) -> <Self as LeaperMode>::Element<'_> {
+        if self.browse.is_some() {
+            if self.entries.is_empty() {
+                return center(text("Nothing here!").size(25)).into();
+            }
+
+            return list::view(
+                Self::LIST_ID,
+                self.display(),
+                self.entries
+                    .iter()
+                    .enumerate()
+                    .map(|(ind, entry)| self.browse_entry(entry, ind)),
+                <Self as LeaperMode>::Msg::ListScrolled,
+            );
+        }
+
+        if self.duplicates.is_some() {
+            if self.shown_len() == 0 {
+                let message = match self.loading {
+                    true => "Looking for duplicates...",
+                    false => "No duplicates among the hashed files (daemon.hash_files)",
+                };
+
+                return center(text(message).size(25)).into();
+            }
+
+            return list::view(
+                Self::LIST_ID,
+                self.display(),
+                (0..self.shown_len()).filter_map(|ind| {
+                    self.duplicate(ind)
+                        .map(|(group, copy)| self.duplicate_entry(group, copy, ind))
+                }),
+                <Self as LeaperMode>::Msg::ListScrolled,
+            );
+        }
+
+        if self.results.is_empty() {
+            let message = match self.loading {
+                true => "Searching...",
+                false => "No matches found!",
+            };
+            let index = self.index_root().map(|root| {
+                button(text(format!("Index {} now", root.display())).size(14))
+                    .on_press(<Self as LeaperMode>::Msg::IndexNow)
+                    .style(button::secondary)
+            });
+
+            return center(
+                column![text(message).size(25)]
+                    .push_maybe(index)
+                    .spacing(10)
+                    .align_x(Horizontal::Center),
+            )
+            .into();
+        }
+
+        list::view(
+            Self::LIST_ID,
+            self.display(),
+            (0..self.shown_len())
+                .filter_map(|ind| self.result(ind).map(|file| self.file_entry(file, ind))),
+            <Self as LeaperMode>::Msg::ListScrolled,
+        )
+    }
+
+    /// Search terms the filter chips toggle, with their labels
+    fn filter_terms() -> impl Iterator<Item = (String, String)> {
+        FileKind::ALL
+            .iter()
+            .filter(|kind| **kind != FileKind::Other)
+            .map(|kind| (kind.name().to_string(), format!("type:{}", kind.name())))
+            .chain(
+                Self::SIZE_FILTERS
+                    .iter()
+                    .map(|size| (format!(">{size}"), format!("size:>{size}"))),
+            )
+    }
+
+    /// The sort, and the type and size filters while [`Self::show_filters`]. Lit up ones are
+    /// in the search already, clicking one adds or removes it
+    fn filter_chips(&self) -> Option<<Self as LeaperMode>::Element<'_>> {
+        if self.browse.is_some()
+            || self.duplicates.is_some()
+            || (!self.show_filters && self.sort.is_none())
+        {
+            return None;
+        }
+
+        let sorted = self.sort.is_some();
+        let sort = button(
+            text(format!(
+                "sort: {}",
+                self.sort.map_or("relevance", |sort| sort.name())
+            ))
+            .size(14),
+        )
+        .on_press(<Self as LeaperMode>::Msg::CycleSort)
+        .padding([4, 12])
+        .style(move |theme, status| style::chip(theme, status, sorted));
+
+        let words = self.search.split_whitespace().collect::<Vec<_>>();
+        let filters = Self::filter_terms()
+            .filter(|_| self.show_filters)
+            .map(|(label, term)| {
+                let on = words.contains(&term.as_str());
+
+                button(text(label).size(14))
+                    .on_press(<Self as LeaperMode>::Msg::ToggleFilter(term))
+                    .padding([4, 12])
+                    .style(move |theme, status| style::chip(theme, status, on))
+                    .into()
+            });
+
+        Some(
+            scrollable(row(std::iter::once(sort.into()).chain(filters)).spacing(5))
+                .direction(scrollable::Direction::Horizontal(
+                    scrollable::Scrollbar::new().width(2).scroller_width(2),
+                ))
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    /// Every directory up to the browsed one, clicking one goes back to it
+    fn breadcrumbs(&self) -> Option<<Self as LeaperMode>::Element<'_>> {
+        let crumbs = self.browse.as_ref()?.breadcrumbs();
+        let last = crumbs.len().saturating_sub(1);
+
+        let crumbs = crumbs.into_iter().enumerate().map(|(ind, (label, input))| {
+            button(text(label).size(14))
+                .on_press(<Self as LeaperMode>::Msg::SearchInput(input))
+                .padding([4, 12])
+                .style(move |theme, status| style::chip(theme, status, ind == last))
+                .into()
+        });
+
+        Some(
+            scrollable(row(crumbs).spacing(5))
+                .direction(scrollable::Direction::Horizontal(
+                    scrollable::Scrollbar::new().width(2).scroller_width(2),
+                ))
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    fn browse_entry<'a>(
+        &'a self,
+        entry: &'a BrowseEntry,
+        ind: usize,
+    ) -> <Self as LeaperMode>::Element<'a> {
+        let selected = self.selected;
+        let display = self.display();
+        let kind = match entry.is_dir {
+            true => "dir",
+            false => FileKind::from_ext(
+                &Path::new(&entry.name)
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            )
+            .name(),
+        };
+
+        let r = row![
+            text(kind)
+                .size(display.text_size * 0.6)
+                .width(Length::Fixed(display.image_size())),
+            text(&entry.name)
+                .size(display.text_size * 0.8)
+                .width(Length::Fill),
+        ]
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .spacing(display.entry_spacing)
+        .padding(display.entry_padding)
+        .align_y(Vertical::Center);
+
+        button(r)
+            .on_press(<Self as LeaperMode>::Msg::Open(ind))
+            .style(move |theme, status| style::list_button(theme, status, selected == ind, None))
+            .height(Length::Fixed(display.entry_height))
+            .width(Length::Fill)
+            .into()
+    }
+
+    /// How many files are marked, or the confirmation of [`Self::confirm`]
+    fn batch_bar(&self) -> Option<<Self as LeaperMode>::Element<'_>> {
+        if self.marked.is_empty() {
+            return None;
+        }
+
+        let files = match self.marked.len() {
+            1 => "1 file".to_string(),
+            len => format!("{len} files"),
+        };
+
+        let bar = match self.confirm {
+            Some(action) => row![
+                text(format!("{} {files}?", action.name()))
+                    .size(14)
+                    .width(Length::Fill),
+                button(text(action.name()).size(14))
+                    .on_press(<Self as LeaperMode>::Msg::Batch(action))
+                    .style(button::danger),
+                button(text("Cancel").size(14))
+                    .on_press(<Self as LeaperMode>::Msg::CancelBatch)
+                    .style(button::secondary),
+            ],
+            None => row![text(format!("{files} marked")).size(14).width(Length::Fill)].extend(
+                [
+                    BatchAction::Open,
+                    BatchAction::CopyPaths,
+                    BatchAction::Trash,
+                ]
+                .map(|action| {
+                    button(text(action.name()).size(14))
+                        .on_press(<Self as LeaperMode>::Msg::Batch(action))
+                        .style(button::secondary)
+                        .into()
+                }),
+            ),
+        };
+
+        Some(
+            container(bar.spacing(10).align_y(Vertical::Center))
+                .width(Length::Fill)
+                .padding(10)
+                .style(style::tooltip)
+                .into(),
+        )
+    }
+
+    fn file_entry<'a>(
+        &'a self,
+        file: &'a FileHit,
+        ind: usize,
+    ) -> <Self as LeaperMode>::Element<'a> {
+        let selected = self.selected;
+        let display = self.display();
+        let dir = Path::new(&file.path)
+            .parent()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let r = row![
+            self.mark_button(&file.path, ind),
+            text(file.kind.name())
+                .size(display.text_size * 0.6)
+                .width(Length::Fixed(display.image_size())),
+            column![
+                text(&file.name).size(display.text_size * 0.8),
+                text(dir).size(display.text_size * 0.5),
+            ]
+            .width(Length::Fill),
+        ]
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .spacing(display.entry_spacing)
+        .padding(display.entry_padding)
+        .align_y(Vertical::Center);
+
+        button(r)
+            .on_press(<Self as LeaperMode>::Msg::Open(ind))
+            .style(move |theme, status| style::list_button(theme, status, selected == ind, None))
+            .height(Length::Fixed(display.entry_height))
+            .width(Length::Fill)
+            .into()
+    }
+
+    /// One copy out of a group of identical files
+    fn duplicate_entry<'a>(
+        &'a self,
+        group: &'a DuplicateGroup,
+        copy: usize,
+        ind: usize,
+    ) -> <Self as LeaperMode>::Element<'a> {
+        let selected = self.selected;
+        let display = self.display();
+        let path = Path::new(&group.paths[copy]);
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let ext = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let detail = format!(
+            "copy {} of {}, {} each - {}",
+            copy + 1,
+            group.paths.len(),
+            human_size(group.size),
+            path.parent()
+                .map(|dir| dir.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        );
+
+        let r = row![
+            self.mark_button(&group.paths[copy], ind),
+            text(FileKind::from_ext(&ext).name())
+                .size(display.text_size * 0.6)
+                .width(Length::Fixed(display.image_size())),
+            column![
+                text(name).size(display.text_size * 0.8),
+                text(detail).size(display.text_size * 0.5),
+            ]
+            .width(Length::Fill),
+        ]
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .spacing(display.entry_spacing)
+        .padding(display.entry_padding)
+        .align_y(Vertical::Center);
+
+        button(r)
+            .on_press(<Self as LeaperMode>::Msg::Open(ind))
+            .style(move |theme, status| style::list_button(theme, status, selected == ind, None))
+            .height(Length::Fixed(display.entry_height))
+            .width(Length::Fill)
+            .into()
+    }
+
+    /// Marks the file at `path` (shown at `ind`) for a [`BatchAction`] or unmarks it
+    fn mark_button(&self, path: &str, ind: usize) -> <Self as LeaperMode>::Element<'_> {
+        let display = self.display();
+        let glyph = match self.marked.contains(path) {
+            true => "●",
+            false => "○",
+        };
+
+        button(text(glyph).size(display.text_size * 0.6))
+            .on_press(<Self as LeaperMode>::Msg::ToggleMark(ind))
+            .style(button::text)
+            .padding(0)
+            .into()
+    }
+}
+
+/// What the finder's [`KeyBindings`] map to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyAction {
+    /// Enter while [`LeaperFinder::confirm`] asks
+    Confirm,
+    Cancel,
+    /// The marked files instead of the selected one, if there are any
+    Open,
+    /// Ctrl+Space, marks the selected file for a [`BatchAction`] or unmarks it
+    Mark,
+    Batch(BatchAction),
+    /// Tab or Right on a directory while browsing
+    BrowseInto,
+    /// Backspace on an empty partial name while browsing
+    BrowseUp,
+    /// Ctrl+D, lists the files with identical contents instead of the results
+    Duplicates,
+    /// Escape out of the duplicates
+    Back,
+    /// Ctrl+I, indexes [`LeaperFinder::index_root`]
+    Index,
+    /// Ctrl+S, goes through the [`FileSort`]s and back to the ranking
+    Sort,
+    /// Ctrl+F, shows or hides the type and size chips
+    Filters,
+    /// Ctrl+O, Ctrl+T or Ctrl+Shift+C on the selected file or directory
+    FileAction(FileAction),
+    Select(SelectionMove),
+    /// Escape with a non-empty query and `escape_clears` set
+    Clear,
+    Exit,
+}
+
+#[to_layer_message]
+#[derive(Debug, Clone)]
+pub enum LeaperFinderMsg {
+    Exit,
+
+    InitDB(DBResult<DB>),
+    /// `None` if the daemon isn't running
+    InitDaemon(Option<LeaperDaemonClient>),
+    /// Files passing the query they were loaded for
+    Candidates(SearchQuery, CoreResult<Vec<FileHit>>),
+    LiveFiles(Vec<LiveFile>),
+    /// Children of the directory of the path they were listed for
+    BrowseEntries(BrowsePath, CoreResult<Vec<BrowseEntry>>),
+    /// Index into the browsed entries, continues the path with a directory
+    BrowseInto(usize),
+    BrowseUp,
+
+    SearchInput(String),
+    Select(SelectionMove),
+
+    OpenSelected,
+    Open(usize),
+    /// On the selected file or directory
+    FileAction(FileAction),
+    ToggleDuplicates,
+    Duplicates(CoreResult<Vec<DuplicateGroup>>),
+    ToggleFilters,
+    /// Adds the search term to the search or removes it
+    ToggleFilter(String),
+    CycleSort,
+    /// Has the daemon index [`LeaperFinder::index_root`], the search runs again after
+    IndexNow,
+    PollIndex,
+    /// Entries of the running index handled so far, `None` if the daemon didn't say
+    IndexProgress(Option<u64>),
+    Indexed(Result<IndexSummary, String>),
+    /// Index into the results as shown
+    ToggleMark(usize),
+    /// Runs on the marked files, destructive ones once confirmed
+    Batch(BatchAction),
+    CancelBatch,
+    /// Paths that were moved to the trash
+    Trashed(Vec<String>),
+    ScrollToSelected,
+    ListScrolled(scrollable::Viewport),
+
+    IcedEvent(Event),
+}
@@ -1,6 +1,17 @@
+mod apps;
+mod calc;
+mod display;
+pub mod finder;
+mod list;
+mod power_menu;
+pub mod provider;
+
 use std::{
-    path::PathBuf,
-    sync::{Arc, Mutex},
+    collections::{HashMap, HashSet},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 use dashmap::DashMap;
@@ -8,14 +19,15 @@ use derive_more::Debug;
 use directories::ProjectDirs;
 use futures::SinkExt;
 use iced::{
-    Event, Length,
-    advanced::widget::{Id, operate, operation::scrollable::scroll_to},
+    Color, Event, Font, Length,
     alignment::{Horizontal, Vertical},
-    keyboard::{self, Key, key},
-    stream,
+    keyboard::{self, Key, Modifiers, key},
+    mouse, stream,
     widget::{
-        button, center, column, horizontal_rule, image, row, scrollable, svg, text, text_input,
+        Space, button, center, column, container, horizontal_rule, image, row, scrollable, svg,
+        text, text_input, tooltip,
     },
+    window,
 };
 use iced_aw::Spinner;
 use iced_fonts::{NERD_FONT, Nerd, nerd::icon_to_string};
@@ -25,40 +37,125 @@ use iced_layershell::{
     settings::{LayerShellSettings, Settings, StartMode},
     to_layer_message,
 };
-use itertools::Itertools;
 use tokio_stream::StreamExt;
 
 use daemon::LeaperDaemonClient;
 use db::{
-    DB, DBAction, DBResult, InstrumentedDBQuery,
-    apps::{AppWithIcon, GetAppWithIconsQuery, GetLiveAppIconUpdates, GetLiveAppWithIconsQuery},
-    init_db,
+    DB, DBEntryId, DBResult,
+    apps::{AppAction, AppWithIcon, CategoryCount},
+    init_db, init_mem_db,
 };
 use executor::LeaperExecutor;
+use leaper_core::{
+    CoreError, CoreResult,
+    catalog::{AppCatalog, LiveApp},
+    clipboard,
+    icons::{IconResolver, ResolvedIcon},
+    launch::Launcher,
+    opener,
+    power::PowerAction,
+    recall::QueryHistory,
+    search::AppSearch,
+    snapshot::AppSnapshot,
+    sort::{Favorites, Launches, QueryChoices},
+};
 use macros::lerror;
 use mode::{
     LeaperMode, LeaperModeTheme,
+    catcher::KeyCatcher,
     config::{LeaperAppModeConfigError, LeaperModeConfig},
+    keys::{Binding, KeyBindings, KeyLayout},
+    selection::SelectionMove,
+    state::WindowSizes,
+};
+use power::PowerCommand;
+use style::{
+    hints::hint_bar,
+    osk::{OskKey, OskLayout, on_screen_keyboard},
 };
 
-type AppsIcons = Vec<AppWithIcon>;
+use crate::{
+    apps::AppsProvider,
+    calc::CalcProvider,
+    display::DisplayProfile,
+    list::{self, ListScroll},
+    power_menu::PowerProvider,
+    provider::{ActionItem, Providers, ResultAction, ResultItem, ResultKind},
+};
 
-type InitAppsIconsResult = DBResult<AppsIcons>;
+/// Shared so the results, snapshots and launches don't copy whole entries
+type AppsIcons = Vec<Arc<AppWithIcon>>;
+
+type InitAppsIconsResult = CoreResult<Vec<AppWithIcon>>;
 
 #[derive(Default)]
 pub struct LeaperLauncher {
     config: LeaperModeConfig,
-    db: Option<DB>,
+    catalog: Option<AppCatalog>,
+    launcher: Launcher,
     daemon: Option<LeaperDaemonClient>,
+    snapshot_path: Option<PathBuf>,
 
     apps: AppsIcons,
-    filtered: AppsIcons,
+    /// Positions in [`Self::apps`] by id, kept up to date by [`Self::sort_apps`] and
+    /// [`Self::apply_live`]
+    app_index: HashMap<DBEntryId, usize>,
+    /// Whether [`Self::apps`] came from the DB already, not just the snapshot
+    apps_loaded: bool,
+    /// Live notifications that came in before the apps were loaded from the DB, applied
+    /// on top of them once they are so neither side gets lost or doubled
+    pending_live: Vec<LiveApp>,
+    /// Only set once nothing showed up within [`Self::SPINNER_DELAY`], so warm starts
+    /// don't flash it
+    show_spinner: bool,
+    /// Everything [`Self::providers`] came up with for the query, see [`Self::filtering`]
+    results: Vec<ResultItem>,
+    providers: Providers,
+    /// Registered in [`Self::providers`] too, kept to hand it the changed apps
+    apps_provider: AppsProvider,
+    /// Bumped with every query, results of the outdated ones are dropped
+    query_id: u64,
+    /// Query [`Self::results`] are for, they're stale while it's behind [`Self::query_id`]
+    results_id: u64,
+    /// Enter came before the results of the query did, runs the selected one once they're in
+    run_pending: bool,
+    launches: Launches,
 
     search: String,
-    matcher: nucleo::Matcher,
+    /// Shared with [`Self::apps_provider`], changing it copies it first while the provider
+    /// still holds on to it
+    app_search: Arc<AppSearch>,
+    query_history: QueryHistory,
+    /// Index of the past query in the search input, until it gets edited
+    recall: Option<usize>,
     selected: usize,
-
-    xpm_handles: Arc<Mutex<DashMap<PathBuf, image::Handle>>>,
+    /// Desktop actions of the selected app are listed below the apps (Alt+Right or the ⋯
+    /// button shows them), the keys move through and run those instead
+    actions_open: bool,
+    selected_action: usize,
+    details: bool,
+    osk: bool,
+    /// [`DisplayProfile::compact`] instead of the regular one (Ctrl+- toggles it)
+    compact: bool,
+
+    icons: IconResolver,
+    /// Icon paths [`Self::resolve_icons`] is working on, so they aren't sent off twice
+    resolving: HashSet<String>,
+    /// Decoded icons, filled while drawing. Pruned to the listed apps' icons whenever
+    /// the list changes, see [`Self::prune_handles`]
+    rgba_handles: DashMap<PathBuf, image::Handle>,
+    frame_handles: DashMap<(PathBuf, usize), image::Handle>,
+    /// To know which entries are on screen
+    list: ListScroll,
+    animation_start: Option<Instant>,
+    animation_elapsed: Duration,
+    /// Icon color of the selected entry, tagged with the index it was computed for
+    accent: Option<(usize, Color)>,
+
+    categories: Vec<CategoryCount>,
+    /// Only apps in this desktop entry category are listed
+    category: Option<String>,
+    show_categories: bool,
 }
 
 impl LeaperMode for LeaperLauncher {
@@ -77,6 +174,18 @@ impl LeaperMode for LeaperLauncher {
     type Msg = LeaperLauncherMsg;
 
     fn run() -> Result<(), Self::RunError> {
+        let project_dirs = Self::project_dirs();
+        let config = LeaperModeConfig::open(&project_dirs)?;
+        config.renderer.apply();
+        KeyLayout::init();
+
+        let (width, height) = config
+            .launcher
+            .remember_size
+            .then(|| WindowSizes::load(&project_dirs).get(Self::NAME))
+            .flatten()
+            .unwrap_or((Self::WINDOW_SIZE.0 as u32, Self::WINDOW_SIZE.1 as u32));
+
         let Settings {
             fonts,
             default_font,
@@ -92,7 +201,7 @@ impl LeaperMode for LeaperLauncher {
                 anchor: Anchor::empty(),
                 layer: Layer::Overlay,
                 exclusive_zone: 0,
-                size: Some((500, 800)),
+                size: Some((width, height)),
                 margin: (0, 0, 0, 0),
                 keyboard_interactivity: KeyboardInteractivity::Exclusive,
                 start_mode: StartMode::Active,
@@ -105,23 +214,19 @@ impl LeaperMode for LeaperLauncher {
             virtual_keyboard_support,
         };
 
-        let project_dirs = Self::project_dirs();
-        let config = LeaperModeConfig::open(&project_dirs)?;
-
-        iced_layershell::build_pattern::application(Self::title, Self::update, Self::view)
-            .settings(settings)
-            .theme(Self::theme)
-            .subscription(Self::subscription)
-            .font(iced_fonts::REQUIRED_FONT_BYTES)
-            .font(iced_fonts::NERD_FONT_BYTES)
-            .executor::<LeaperExecutor>()
-            .run_with(move || Self::init(project_dirs, config, ()))?;
+        mode::run_mode!(Self {
+            settings: settings,
+            window_size: (width as f32, height as f32),
+            fonts: [iced_fonts::REQUIRED_FONT_BYTES, iced_fonts::NERD_FONT_BYTES],
+            executor: LeaperExecutor,
+            init: move || Self::init(project_dirs, config, ()),
+        });
 
         Ok(())
     }
 
     fn init(
-        _project_dirs: ProjectDirs,
+        project_dirs: ProjectDirs,
         config: LeaperModeConfig,
         _args: Self::InitArgs,
     ) -> (Self, Self::Task)
@@ -129,51 +234,137 @@ impl LeaperMode for LeaperLauncher {
         Self: Sized,
     {
         let db_port = config.db_port;
+        let ephemeral = config.ephemeral;
+        // Ephemeral sessions don't leave anything behind, the snapshot included
+        let snapshot_path = (!ephemeral).then(|| AppSnapshot::path(project_dirs.cache_dir()));
+
+        let apps_provider = AppsProvider::default();
+        let mut providers = Providers::default();
+        providers.register(apps_provider.clone());
+
+        if config.launcher.calculator {
+            providers.register(CalcProvider);
+        }
+
+        if config.launcher.power_actions {
+            providers.register(PowerProvider(config.clone()));
+        }
+
         let launcher = Self {
+            snapshot_path: snapshot_path.clone(),
+            providers,
+            apps_provider,
+            app_search: Arc::new(AppSearch::new(config.launcher.clone())),
+            launcher: Launcher::new(&config),
+            icons: IconResolver::new(
+                config.launcher.max_icon_size,
+                config.launcher.animated_icons,
+            ),
+            osk: config.osk.enabled(),
+            compact: config.launcher.compact,
+            show_categories: config.launcher.show_categories,
+            list: ListScroll::new(Self::WINDOW_SIZE.1),
             config,
             ..Default::default()
         };
         let task = {
-            let init_db_task = Self::Task::perform(init_db(db_port), Self::Msg::InitDB);
-            let init_daemon_task =
-                Self::Task::perform(daemon::client::connect(), |res| match res {
-                    Ok(daemon) => Self::Msg::InitDaemon(daemon),
-                    Err(err) => {
-                        tracing::warn!("Failed to initialized daemon client: {err}");
-                        Self::Msg::Ignore
-                    }
-                });
-
-            Self::Task::batch([
-                text_input::focus(Self::SEARCH_ID),
-                init_db_task,
-                init_daemon_task,
-            ])
+            // Ephemeral sessions don't touch the daemon, apps are searched in-process
+            // once the in-memory DB is up
+            let init_tasks = match ephemeral {
+                true => Self::Task::perform(init_mem_db(), Self::Msg::InitDB),
+                false => Self::Task::batch([
+                    Self::Task::perform(init_db(db_port), Self::Msg::InitDB),
+                    Self::Task::perform(daemon::client::connect(), |res| match res {
+                        Ok(daemon) => Self::Msg::InitDaemon(daemon),
+                        Err(err) => {
+                            tracing::warn!("Failed to initialized daemon client: {err}");
+                            Self::Msg::Ignore
+                        }
+                    }),
+                ]),
+            };
+
+            let snapshot = snapshot_path.map(|path| {
+                Self::Task::perform(AppSnapshot::load(path), Self::Msg::InitedSnapshot)
+            });
+
+            Self::Task::batch(
+                [
+                    text_input::focus(Self::SEARCH_ID),
+                    Self::Task::perform(tokio::time::sleep(Self::SPINNER_DELAY), |_| {
+                        Self::Msg::ShowSpinner
+                    }),
+                    init_tasks,
+                ]
+                .into_iter()
+                .chain(snapshot),
+            )
         };
 
         (launcher, task)
     }
 
     fn view(&self) -> Self::Element<'_> {
-        column![self.search(), horizontal_rule(2), self.list()]
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .padding(20)
-            .spacing(10)
-            .into()
+        KeyCatcher::new(
+            column![self.search()]
+                .push_maybe(self.category_chips())
+                .push(horizontal_rule(2))
+                .push(self.list())
+                .push_maybe(self.actions_open.then(|| self.actions_panel()).flatten())
+                .push_maybe(
+                    self.details
+                        .then(|| self.selected_app())
+                        .flatten()
+                        .map(|app| self.app_details(app)),
+                )
+                .push_maybe(
+                    self.config
+                        .show_hints
+                        .then(|| hint_bar(Self::BINDINGS.hints(|action| self.available(action)))),
+                )
+                .push_maybe(
+                    self.osk
+                        .then(|| on_screen_keyboard(OskLayout::Qwerty, Self::Msg::OskKey)),
+                )
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .padding(20)
+                .spacing(10),
+            |event| {
+                Self::BINDINGS
+                    .catches(event, |action| self.available(action))
+                    .then(|| Self::Msg::IcedEvent(Event::Keyboard(event.clone())))
+            },
+        )
+        .into()
     }
 
     fn update(&mut self, msg: Self::Msg) -> Self::Task {
         match msg {
             Self::Msg::Exit => {
+                self.save_size();
                 return iced::exit();
             }
             Self::Msg::Ignore => {}
 
             Self::Msg::InitDB(db) => match db {
                 Ok(db) => {
-                    self.db = Some(db.clone());
-                    return Self::Task::done(Self::Msg::InitApps).map(Into::into);
+                    self.catalog = Some(AppCatalog::new(db.clone(), &self.config.launcher));
+
+                    let icons = self.config.icons;
+                    let search_in_process = self.config.ephemeral.then(|| {
+                        Self::Task::perform(
+                            async move { daemon::search_apps_in_process(db, icons) },
+                            |_| Self::Msg::Ignore,
+                        )
+                    });
+
+                    return Self::Task::batch(
+                        [Self::Task::done(Self::Msg::InitApps)]
+                            .into_iter()
+                            .chain(search_in_process),
+                    )
+                    .map(Into::into);
                 }
                 Err(err) => {
                     tracing::error!("Failed to initialize the database: {err}");
@@ -183,23 +374,56 @@ impl LeaperMode for LeaperLauncher {
             Self::Msg::InitDaemon(daemon) => self.daemon = Some(daemon),
 
             Self::Msg::InitApps => {
+                let catalog = self.catalog.clone().expect("db is available");
+
                 return Self::Task::batch([
+                    Self::Task::perform(catalog.clone().apps(), Self::Msg::InitedApps),
+                    Self::Task::perform(catalog.clone().launches(), Self::Msg::InitedLaunches),
+                    Self::Task::perform(catalog.clone().categories(), Self::Msg::InitedCategories),
+                    Self::Task::perform(catalog.clone().favorites(), Self::Msg::InitedFavorites),
                     Self::Task::perform(
-                        GetAppWithIconsQuery
-                            .instrumented_execute(self.db.clone().expect("db is available")),
-                        Self::Msg::InitedApps,
-                    )
-                    .map(Into::into),
+                        catalog.clone().query_choices(),
+                        Self::Msg::InitedQueryChoices,
+                    ),
+                    match self.config.launcher.query_history {
+                        true => Self::Task::perform(
+                            catalog.query_history(),
+                            Self::Msg::InitedQueryHistory,
+                        ),
+                        false => Self::Task::none(),
+                    },
                     Self::Task::done(Self::Msg::LoadApps),
                 ]);
             }
             Self::Msg::InitedApps(apps) => match apps {
                 Ok(apps) => {
-                    self.apps = apps;
+                    let mut apps = apps
+                        .into_iter()
+                        .filter(|app| !self.config.hides_app(&app.categories))
+                        .map(Arc::new)
+                        .collect::<AppsIcons>();
+                    self.app_search.sort(&mut apps, &self.launches);
+
+                    let icons = self.icons.clone();
+                    let first_page = apps
+                        .iter()
+                        .filter(|_| self.display().icons)
+                        .take(self.page_len())
+                        .filter_map(|app| app.icon.clone())
+                        .collect::<Vec<_>>();
 
-                    tracing::trace!(
-                        "Initialized apps list from cache [{} apps]",
-                        self.apps.len()
+                    return Self::Task::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || {
+                                icons.prefetch(&first_page, Self::PREFETCH_BUDGET)
+                            })
+                            .await
+                            .unwrap_or_default()
+                        },
+                        move |prefetched| {
+                            tracing::trace!("Prefetched {prefetched} icons of the first page");
+                            Self::Msg::ShowApps(apps)
+                        },
                     );
                 }
                 Err(err) => {
@@ -208,6 +432,158 @@ impl LeaperMode for LeaperLauncher {
                     return Self::Task::done(Self::Msg::Exit);
                 }
             },
+            Self::Msg::ShowApps(apps) => {
+                self.change_apps(|this| {
+                    this.apps = apps;
+                    this.app_search.sort(&mut this.apps, &this.launches);
+                });
+
+                // Apps the snapshot didn't have might match too
+                let mut refilter = self.filtering();
+
+                self.apps_loaded = true;
+
+                let pending = std::mem::take(&mut self.pending_live);
+
+                if !pending.is_empty() {
+                    tracing::trace!("Applying {} buffered live app updates", pending.len());
+
+                    refilter |= self.apply_live(pending);
+                }
+
+                tracing::trace!(
+                    "Initialized apps list from cache [{} apps]",
+                    self.apps.len()
+                );
+
+                let save_snapshot = self
+                    .snapshot_path
+                    .clone()
+                    .filter(|_| !self.apps.is_empty())
+                    .map(|path| {
+                        Self::Task::perform(AppSnapshot::save(path, self.apps.clone()), |res| {
+                            Self::Msg::Result(res.map_err(Into::into))
+                        })
+                    });
+
+                return Self::Task::batch(
+                    [self.accent_task(), self.resolve_icons()]
+                        .into_iter()
+                        .chain(save_snapshot)
+                        .chain(refilter.then(|| self.refilter())),
+                );
+            }
+
+            Self::Msg::InitedSnapshot(Some(apps)) if !self.apps_loaded => {
+                self.change_apps(|this| {
+                    this.apps = apps
+                        .into_iter()
+                        .filter(|app| !this.config.hides_app(&app.categories))
+                        .map(Arc::new)
+                        .collect();
+                    this.app_search.sort(&mut this.apps, &this.launches);
+                });
+
+                tracing::trace!("Showing {} apps from the last snapshot", self.apps.len());
+
+                return Self::Task::batch([self.accent_task(), self.resolve_icons()]);
+            }
+            Self::Msg::InitedSnapshot(_) => {}
+            Self::Msg::ShowSpinner => self.show_spinner = true,
+
+            Self::Msg::InitedLaunches(launches) => match launches {
+                Ok(launches) => {
+                    self.launches = launches;
+                    self.sort_apps();
+                }
+                Err(err) => tracing::warn!("Failed to load launch history: {err}"),
+            },
+
+            Self::Msg::InitedFavorites(favorites) => match favorites {
+                Ok(favorites) => {
+                    Arc::make_mut(&mut self.app_search).set_favorites(favorites);
+                    self.sort_apps();
+                }
+                Err(err) => tracing::warn!("Failed to load favorite apps: {err}"),
+            },
+            Self::Msg::ToggleFavorite(ind) => {
+                let (Some(app), Some(catalog)) = (
+                    self.visible_app(ind).map(|app| app.id.clone()),
+                    self.catalog.clone(),
+                ) else {
+                    return Self::Task::none();
+                };
+
+                self.selected = ind;
+
+                let mut favorites = self.app_search.favorites().clone();
+                let favorite = !favorites.remove(&app);
+
+                if favorite {
+                    favorites.insert(app.clone());
+                }
+
+                Arc::make_mut(&mut self.app_search).set_favorites(favorites);
+                // The selection follows the app to its new place
+                self.sort_apps();
+
+                return Self::Task::batch([
+                    Self::Task::perform(catalog.set_favorite(app, favorite), |res| {
+                        Self::Msg::Result(res.map_err(Into::into))
+                    }),
+                    Self::Task::done(Self::Msg::ScrollToSelected),
+                    self.accent_task(),
+                    self.resolve_icons(),
+                ]);
+            }
+
+            Self::Msg::InitedQueryChoices(choices) => match choices {
+                Ok(choices) => {
+                    Arc::make_mut(&mut self.app_search).set_choices(choices);
+                    self.apps_provider
+                        .update(&self.apps, &self.launches, &self.app_search);
+
+                    if !self.search.is_empty() {
+                        return self.refilter();
+                    }
+                }
+                Err(err) => tracing::warn!("Failed to load past search choices: {err}"),
+            },
+            Self::Msg::InitedQueryHistory(history) => match history {
+                Ok(history) => self.query_history = history,
+                Err(err) => tracing::warn!("Failed to load past search queries: {err}"),
+            },
+            Self::Msg::InitedCategories(categories) => match categories {
+                Ok(categories) => {
+                    self.categories = categories
+                        .into_iter()
+                        .filter(|category| {
+                            !self
+                                .config
+                                .hides_app(std::slice::from_ref(&category.category))
+                        })
+                        .collect()
+                }
+                Err(err) => tracing::warn!("Failed to load app categories: {err}"),
+            },
+            Self::Msg::SelectCategory(category) => {
+                self.category = category;
+                self.actions_open = false;
+
+                return Self::Task::batch([
+                    self.refilter(),
+                    Self::Task::done(Self::Msg::ScrollToSelected),
+                    self.accent_task(),
+                ]);
+            }
+            Self::Msg::ToggleCategories => {
+                self.show_categories = !self.show_categories;
+
+                // A hidden filter would be confusing
+                if !self.show_categories && self.category.is_some() {
+                    return Self::Task::done(Self::Msg::SelectCategory(None));
+                }
+            }
 
             Self::Msg::LoadApps => {
                 if let Some(daemon) = self.daemon.clone() {
@@ -227,168 +603,264 @@ impl LeaperMode for LeaperLauncher {
                 }
             }
 
-            Self::Msg::AddApp(app_with_icon) => {
-                let existing_ind = self
-                    .apps
-                    .iter()
-                    .enumerate()
-                    .find_map(|(ind, app)| (app.id == app_with_icon.id).then_some(ind));
-
-                match existing_ind {
-                    Some(ind) => {
-                        self.apps[ind] = app_with_icon;
-                    }
-                    None => {
-                        self.apps.push(app_with_icon);
-                        self.apps.sort_by_key(|x| x.name.clone());
-                    }
+            // The DB query might not have seen these yet, or already have them
+            Self::Msg::LiveApps(changes) if !self.apps_loaded => self.pending_live.extend(changes),
+            Self::Msg::LiveApps(changes) => {
+                if self.apply_live(changes) {
+                    return self.refilter();
                 }
+
+                return self.resolve_icons();
             }
 
             Self::Msg::SearchInput(new_search) => {
                 self.search = new_search;
+                self.recall = None;
+                self.run_pending = false;
+                self.actions_open = false;
 
-                self.filtered = match self.search.as_str() {
-                    "" => {
-                        self.selected = match self.apps.len() {
-                            0 => 0,
-                            len => self.selected.clamp(0, len - 1),
-                        };
+                return Self::Task::batch([self.refilter(), self.accent_task()]);
+            }
+            Self::Msg::Recall(recall) => {
+                self.search = recall
+                    .and_then(|ind| self.query_history.get(ind))
+                    .unwrap_or_default()
+                    .to_string();
+                self.recall = recall;
+                self.selected = 0;
 
-                        vec![]
+                return Self::Task::batch([
+                    self.refilter(),
+                    Self::Task::done(Self::Msg::ScrollToSelected),
+                    self.accent_task(),
+                ]);
+            }
+            Self::Msg::Select(movement) if self.actions_open => {
+                let len = self.selected_app().map_or(0, |app| app.actions.len());
+                self.selected_action = movement.apply(self.selected_action, len, len);
+            }
+            Self::Msg::Select(movement) => {
+                self.selected = movement.apply(self.selected, self.visible_len(), self.page_len());
+
+                return Self::Task::batch([
+                    Self::Task::done(Self::Msg::ScrollToSelected),
+                    self.accent_task(),
+                ]);
+            }
+
+            // Enter right after typing, the selection is still in the previous results
+            Self::Msg::RunSelectedApp if self.results_id != self.query_id => {
+                self.run_pending = true;
+            }
+            Self::Msg::RunSelectedApp => {
+                match (self.visible_entry(self.selected), self.actions_open) {
+                    (None, _) => {}
+                    (Some(ListEntry::Action(item)), _) => {
+                        return Self::Task::done(Self::Msg::RunResult(item.action.clone()));
                     }
-                    search => {
-                        self.selected = match self.filtered.len() {
-                            0 => 0,
-                            len => self.selected.clamp(0, len - 1),
-                        };
-
-                        self.apps
-                            .iter()
-                            .filter_map(|app| {
-                                self.matcher
-                                    .fuzzy_match(
-                                        nucleo::Utf32Str::new(&app.name, &mut vec![]),
-                                        nucleo::Utf32Str::new(&search.to_lowercase(), &mut vec![]),
-                                    )
-                                    .map(|score| (score, app))
-                            })
-                            .sorted_by_key(|(score, _)| *score)
-                            .rev()
-                            .map(|(_, app)| app.clone())
-                            .collect()
+                    (Some(ListEntry::App(_)), true) => {
+                        return Self::Task::done(Self::Msg::RunAction(self.selected_action));
                     }
-                };
-
-                self.selected = self.selected.clamp(
-                    0,
-                    match self.search.is_empty() {
-                        true => self.apps.len(),
-                        false => self.filtered.len(),
-                    } - 1,
-                );
+                    (Some(ListEntry::App(_)), false) => {
+                        return Self::Task::done(Self::Msg::RunApp(self.selected)).map(Into::into);
+                    }
+                }
             }
-            Self::Msg::SelectUp => {
-                let len = match self.search.is_empty() {
-                    true => self.apps.len(),
-                    false => self.filtered.len(),
-                };
+            Self::Msg::Results(id, results) if id == self.query_id => {
+                let had_action = self.action_first();
+
+                self.results = results
+                    .into_iter()
+                    .filter(|item| match &item.kind {
+                        ResultKind::App(app) => self.in_category(app),
+                        ResultKind::Action(_) => true,
+                    })
+                    .collect();
+                self.results_id = id;
+
+                // A result that isn't an app on top (e.g. arithmetic) is what Enter should
+                // act on as soon as it shows up
+                if !had_action && self.action_first() {
+                    self.selected = 0;
+                }
 
-                self.selected = match len == 0 {
-                    true => 0,
-                    false => match self.selected {
-                        0 => len - 1,
-                        x => x - 1,
-                    },
-                };
+                self.clamp_selected();
 
-                return Self::Task::done(Self::Msg::ScrollToSelected).map(Into::into);
+                return Self::Task::batch(
+                    [self.accent_task(), self.resolve_icons()]
+                        .into_iter()
+                        .chain(
+                            std::mem::take(&mut self.run_pending)
+                                .then(|| Self::Task::done(Self::Msg::RunSelectedApp)),
+                        ),
+                );
             }
-            Self::Msg::SelectDown => {
-                let len = match self.search.is_empty() {
-                    true => self.apps.len(),
-                    false => self.filtered.len(),
-                };
-
-                self.selected = match len == 0 {
-                    true => 0,
-                    false => match self.selected >= len - 1 {
-                        true => 0,
-                        false => self.selected + 1,
+            Self::Msg::Results(..) => {}
+            Self::Msg::RunResult(ResultAction::Power(action)) => {
+                return Self::Task::done(Self::Msg::RunPower(action));
+            }
+            Self::Msg::RunResult(action) => {
+                return Self::Task::perform(
+                    async move {
+                        match action {
+                            ResultAction::Copy(text) => clipboard::copy(text).await,
+                            ResultAction::Open(uri) => opener::open_uri(&uri).await,
+                            ResultAction::Power(_) => unreachable!("Handled above"),
+                        }
                     },
-                };
+                    |res| {
+                        if let Err(err) = res {
+                            tracing::error!("Failed to run the result: {err}");
+                        }
 
-                return Self::Task::done(Self::Msg::ScrollToSelected).map(Into::into);
+                        Self::Msg::Exit
+                    },
+                );
             }
-
-            Self::Msg::RunSelectedApp => match self.apps.is_empty() {
-                true => {}
-                false => return Self::Task::done(Self::Msg::RunApp(self.selected)).map(Into::into),
+            Self::Msg::RunApp(ind) => match self.visible_app(ind) {
+                Some(app) => return self.run_app(app.clone(), None),
+                None => tracing::warn!("Logic error!"),
             },
-            Self::Msg::RunApp(ind) => match {
-                match self.search.is_empty() {
-                    true => &self.apps,
-                    false => &self.filtered,
-                }
+            Self::Msg::ShowActions(show) => {
+                self.actions_open = show;
+                self.selected_action = 0;
             }
-            .get(ind)
+            Self::Msg::ToggleActions(ind) => {
+                self.actions_open = !(self.actions_open && self.selected == ind);
+                self.selected = ind;
+                self.selected_action = 0;
+            }
+            Self::Msg::RunAction(ind) => match self
+                .selected_app()
+                .and_then(|app| Some((app, app.actions.get(ind)?)))
             {
-                Some(app) => {
-                    tracing::trace!("Running {}: {:?}", app.name, app.exec);
+                Some((app, action)) => return self.run_app(app.clone(), Some(action.clone())),
+                None => tracing::warn!("Logic error!"),
+            },
 
-                    let cmd = &app.exec[0];
-                    let args = match app.exec.len() {
-                        1 => None,
-                        _ => Some(app.exec[1..].iter()),
-                    };
+            Self::Msg::RunPower(action) => match PowerCommand::new(action, &self.config) {
+                Some(command) => {
+                    return Self::Task::perform(command.run(), |res| {
+                        if let Err(err) = res {
+                            tracing::error!("Failed to perform the power action: {err}");
+                        }
 
-                    let mut cmd = std::process::Command::new(cmd);
+                        Self::Msg::Exit
+                    });
+                }
+                None => tracing::warn!("Logic error!"),
+            },
 
-                    if let Some(args) = args {
-                        cmd.args(args);
-                    }
+            Self::Msg::ToggleDetails => self.details = !self.details,
+            Self::Msg::ToggleCompact => {
+                self.compact = !self.compact;
+                self.list.reset();
 
-                    if let Err(err) = cmd.spawn() {
-                        tracing::error!("Failed to run the app {}: {err}", app.name)
+                // Entry heights changed, so did the offset of the selected one
+                return Self::Task::batch([
+                    Self::Task::done(Self::Msg::ScrollToSelected),
+                    self.accent_task(),
+                    self.resolve_icons(),
+                ]);
+            }
+            Self::Msg::OskKey(key) => {
+                let mut search = self.search.clone();
+
+                match key {
+                    OskKey::Char(ch) => search.push(ch),
+                    OskKey::Backspace => {
+                        search.pop();
                     }
+                    OskKey::Enter => return Self::Task::done(Self::Msg::RunSelectedApp),
+                }
 
-                    return Self::Task::done(Self::Msg::Exit);
+                return Self::Task::done(Self::Msg::SearchInput(search));
+            }
+
+            Self::Msg::AccentColor(ind, color) => {
+                // Selection could've moved on while the icon was being decoded
+                if ind == self.selected {
+                    self.accent = Some((ind, color));
                 }
-                None => tracing::warn!("Logic error!"),
-            },
+            }
+
+            Self::Msg::ListScrolled(viewport) => {
+                self.list.scrolled(viewport);
+                self.icons.evict_prefetched(
+                    self.on_screen()
+                        .filter_map(|ind| self.visible_app(ind))
+                        .filter_map(|app| app.icon.as_ref()),
+                );
+
+                return Self::Task::batch([Self::focus_search(), self.resolve_icons()]);
+            }
+            Self::Msg::IconsResolved(paths) => {
+                for path in paths {
+                    self.resolving.remove(&path);
+                }
+            }
+            Self::Msg::AnimationTick(now) => {
+                let start = *self.animation_start.get_or_insert(now);
+                self.animation_elapsed = now - start;
+            }
 
             Self::Msg::ScrollToSelected => {
-                if !self.apps.is_empty() {
-                    let y_offset =
-                        self.selected as f32 * (Self::APP_ENTRY_HEIGHT + Self::LIST_SPACING);
-
-                    return operate(scroll_to(
-                        Id::new(Self::LIST_ID),
-                        scrollable::AbsoluteOffset {
-                            x: 0.0,
-                            y: y_offset,
-                        },
-                    ));
+                if self.visible_len() > 0 {
+                    return self
+                        .list
+                        .scroll_to(Self::LIST_ID, self.selected, self.display());
                 }
             }
 
             Self::Msg::IcedEvent(event) => {
+                if let Event::Window(window::Event::Resized(size)) = event {
+                    self.list.resized(size);
+
+                    return self.resolve_icons();
+                }
+
+                // Only events the focused widget ignored end up here, so clicking anywhere
+                // but the input should give the focus back to it
+                if let Event::Mouse(mouse::Event::ButtonReleased(_)) = event {
+                    return Self::focus_search();
+                }
+
                 if let Event::Keyboard(event) = event
-                    && let keyboard::Event::KeyPressed { key, .. } = event
+                    && let keyboard::Event::KeyPressed {
+                        key,
+                        modifiers,
+                        text,
+                        ..
+                    } = event
                 {
+                    if let Some(action) = Self::BINDINGS
+                        .find(key.as_ref(), modifiers, |action| self.available(action))
+                    {
+                        return self.key_action(action);
+                    }
+
                     match key.as_ref() {
-                        Key::Named(key::Named::Escape) | Key::Character("q" | "Q") => {
-                            return Self::Task::done(Self::Msg::Exit);
+                        // The input lost focus, type into it anyway
+                        Key::Named(key::Named::Backspace) => {
+                            let mut search = self.search.clone();
+                            search.pop();
+
+                            return Self::Task::batch([
+                                Self::Task::done(Self::Msg::SearchInput(search)),
+                                Self::focus_search(),
+                            ]);
                         }
+                        _ if !modifiers.control() && !modifiers.alt() && !modifiers.logo() => {
+                            if let Some(text) =
+                                text.filter(|text| text.chars().all(|ch| !ch.is_control()))
+                            {
+                                let search = format!("{}{text}", self.search);
 
-                        Key::Named(key::Named::ArrowUp) => {
-                            return Self::Task::done(Self::Msg::SelectUp);
-                        }
-                        Key::Named(key::Named::ArrowDown) => {
-                            return Self::Task::done(Self::Msg::SelectDown);
-                        }
-                        Key::Named(key::Named::Enter) => {
-                            return Self::Task::done(Self::Msg::RunSelectedApp);
+                                return Self::Task::batch([
+                                    Self::Task::done(Self::Msg::SearchInput(search)),
+                                    Self::focus_search(),
+                                ]);
+                            }
                         }
 
                         _ => {}
@@ -416,28 +888,25 @@ impl LeaperMode for LeaperLauncher {
 
     fn subscription(&self) -> Self::Subscription {
         let iced_events = iced::event::listen().map(Self::Msg::IcedEvent);
+        let iced_events = match self.animating() {
+            true => Self::Subscription::batch([
+                iced_events,
+                iced::time::every(self.config.frame_interval()).map(Self::Msg::AnimationTick),
+            ]),
+            false => iced_events,
+        };
 
-        match &self.db {
-            Some(db) => {
-                let db = db.clone();
+        match &self.catalog {
+            Some(catalog) => {
+                let catalog = catalog.clone();
 
                 Self::Subscription::batch([
                     iced_events,
                     Self::Subscription::run_with_id(
                         "live_apps",
                         stream::channel(1, |mut msg_sender| async move {
-                            let app_icons_stream = GetLiveAppWithIconsQuery
-                                .instrumented_execute(db.clone())
-                                .await;
-                            let app_icons_updates_stream =
-                                GetLiveAppIconUpdates.instrumented_execute(db.clone()).await;
-
-                            let mut stream = match app_icons_stream
-                                .and_then(|x| app_icons_updates_stream.map(|y| (x, y)))
-                            {
-                                Ok((app_icons, app_icons_updates)) => {
-                                    app_icons.merge(app_icons_updates)
-                                }
+                            let stream = match catalog.live_apps().await {
+                                Ok(stream) => Box::pin(stream),
                                 Err(err) => {
                                     tracing::error!("{err}");
 
@@ -451,9 +920,17 @@ impl LeaperMode for LeaperLauncher {
                                 }
                             };
 
-                            while let Some(notification) = stream.next().await {
-                                let notification = match notification {
-                                    Ok(notification) => notification,
+                            // Notifications that arrived together (e.g. during initial indexing)
+                            // are applied in one update
+                            let mut batches =
+                                futures::StreamExt::ready_chunks(stream, Self::LIVE_BATCH_SIZE);
+
+                            while let Some(batch) = batches.next().await {
+                                let changes = match batch
+                                    .into_iter()
+                                    .collect::<CoreResult<Vec<_>>>()
+                                {
+                                    Ok(changes) => changes,
                                     Err(err) => {
                                         tracing::error!(
                                             "Failed to get notification from apps live table: {err}"
@@ -469,25 +946,18 @@ impl LeaperMode for LeaperLauncher {
                                     }
                                 };
 
-                                match notification.action {
-                                    DBAction::Create | DBAction::Update => {
-                                        if let Err(err) = msg_sender
-                                            .send(Self::Msg::AddApp(notification.data))
-                                            .await
-                                        {
-                                            tracing::error!(
-                                                "Failed to send add app from live app table subscription: {err}"
-                                            );
+                                if let Err(err) =
+                                    msg_sender.send(Self::Msg::LiveApps(changes)).await
+                                {
+                                    tracing::error!(
+                                        "Failed to send app changes from live app table subscription: {err}"
+                                    );
 
-                                            if let Err(err) = msg_sender.send(Self::Msg::Exit).await
-                                            {
-                                                tracing::error!(
-                                                    "Failed to send exit message from live app table subscription: {err}"
-                                                );
-                                            }
-                                        }
+                                    if let Err(err) = msg_sender.send(Self::Msg::Exit).await {
+                                        tracing::error!(
+                                            "Failed to send exit message from live app table subscription: {err}"
+                                        );
                                     }
-                                    _ => unreachable!(),
                                 }
                             }
                         }),
@@ -509,7 +979,652 @@ impl LeaperMode for LeaperLauncher {
 
 impl LeaperLauncher {
     pub const SEARCH_ID: &'static str = "app_search_input";
+    const LIVE_BATCH_SIZE: usize = 256;
     const LIST_ID: &'static str = "list";
+    const NAME: &'static str = "launcher";
+    const WINDOW_SIZE: (f32, f32) = (500.0, 800.0);
+    /// How long the list can stay empty on startup before the spinner shows up
+    const SPINNER_DELAY: Duration = Duration::from_millis(300);
+    /// How long the first icons may hold back the list on a cold start
+    const PREFETCH_BUDGET: Duration = Duration::from_millis(150);
+
+    fn page_len(&self) -> usize {
+        self.list.page_len(self.display())
+    }
+
+    fn on_screen(&self) -> Range<usize> {
+        self.list.on_screen(self.display(), self.visible_len())
+    }
+
+    /// Remembers the current size for the next run, see `launcher.remember_size`
+    fn save_size(&self) {
+        let Some(size) = self
+            .list
+            .window_size()
+            .filter(|_| self.config.launcher.remember_size && !self.config.ephemeral)
+        else {
+            return;
+        };
+
+        if let Err(err) = WindowSizes::save(
+            &Self::project_dirs(),
+            Self::NAME,
+            (size.width as u32, size.height as u32),
+        ) {
+            tracing::warn!("Failed to remember the launcher size: {err}");
+        }
+    }
+
+    fn animating(&self) -> bool {
+        self.display().icons
+            && self.config.launcher.animated_icons
+            && self
+                .on_screen()
+                .filter_map(|ind| self.visible_app(ind))
+                .any(|app| {
+                    matches!(
+                        self.icons.cached(app.icon.as_ref()),
+                        Some(ResolvedIcon::Animated(_))
+                    )
+                })
+    }
+
+    const BINDINGS: KeyBindings<KeyAction> = KeyBindings(&[
+        Binding::new(Key::Named(key::Named::Enter), KeyAction::Run, "run"),
+        Binding::new(Key::Character("p"), KeyAction::RunPower, "power").with(Modifiers::CTRL),
+        Binding::new(
+            Key::Named(key::Named::ArrowUp),
+            KeyAction::RecallOlder,
+            "history",
+        ),
+        Binding::new(
+            Key::Named(key::Named::ArrowDown),
+            KeyAction::RecallNewer,
+            "history",
+        ),
+        Binding::new(
+            Key::Named(key::Named::ArrowUp),
+            KeyAction::Select(SelectionMove::Up),
+            "select",
+        ),
+        Binding::new(
+            Key::Named(key::Named::ArrowDown),
+            KeyAction::Select(SelectionMove::Down),
+            "select",
+        ),
+        Binding::new(
+            Key::Named(key::Named::PageUp),
+            KeyAction::Select(SelectionMove::PageUp),
+            "page",
+        ),
+        Binding::new(
+            Key::Named(key::Named::PageDown),
+            KeyAction::Select(SelectionMove::PageDown),
+            "page",
+        ),
+        Binding::new(
+            Key::Named(key::Named::Home),
+            KeyAction::Select(SelectionMove::First),
+            "first/last",
+        ),
+        Binding::new(
+            Key::Named(key::Named::End),
+            KeyAction::Select(SelectionMove::Last),
+            "first/last",
+        ),
+        Binding::new(
+            Key::Named(key::Named::Tab),
+            KeyAction::NextCategory,
+            "category",
+        ),
+        Binding::new(
+            Key::Named(key::Named::Tab),
+            KeyAction::PrevCategory,
+            "category",
+        )
+        .with(Modifiers::SHIFT),
+        Binding::new(
+            Key::Named(key::Named::ArrowRight),
+            KeyAction::ShowActions,
+            "actions",
+        )
+        .with(Modifiers::ALT),
+        Binding::new(
+            Key::Named(key::Named::ArrowLeft),
+            KeyAction::HideActions,
+            "back",
+        )
+        .with(Modifiers::ALT),
+        Binding::new(
+            Key::Named(key::Named::Escape),
+            KeyAction::HideActions,
+            "back",
+        ),
+        Binding::new(
+            Key::Character("k"),
+            KeyAction::ToggleCategories,
+            "categories",
+        )
+        .with(Modifiers::CTRL),
+        Binding::new(Key::Character("d"), KeyAction::ToggleFavorite, "favorite")
+            .with(Modifiers::CTRL),
+        Binding::new(Key::Character("i"), KeyAction::ToggleDetails, "details")
+            .with(Modifiers::CTRL),
+        Binding::new(Key::Character("-"), KeyAction::ToggleCompact, "compact")
+            .with(Modifiers::CTRL),
+        Binding::new(Key::Named(key::Named::Escape), KeyAction::Clear, "clear"),
+        Binding::new(Key::Named(key::Named::Escape), KeyAction::Exit, "exit"),
+    ]);
+
+    /// Whether `action` does anything in the current state, unavailable ones are neither
+    /// handled nor hinted
+    fn available(&self, action: KeyAction) -> bool {
+        let clears = self.config.escape_clears && !self.search.is_empty();
+
+        match action {
+            KeyAction::Run => self.visible_len() > 0,
+            KeyAction::RunPower => self.first_power().is_some(),
+            KeyAction::ToggleFavorite => self.catalog.is_some() && self.selected_app().is_some(),
+            KeyAction::RecallOlder if self.actions_open => false,
+            KeyAction::RecallOlder => match self.recall {
+                Some(ind) => ind + 1 < self.query_history.len(),
+                // Only from the top of the list, Up moves the selection anywhere else
+                None => {
+                    !self.query_history.is_empty()
+                        && self.selected == 0
+                        && (self.search.is_empty() || self.visible_len() == 0)
+                }
+            },
+            KeyAction::RecallNewer => self.recall.is_some() && !self.actions_open,
+            KeyAction::Select(_) if self.actions_open => {
+                self.selected_app().is_some_and(|app| app.actions.len() > 1)
+            }
+            KeyAction::Select(_) => self.visible_len() > 1,
+            KeyAction::ShowActions => {
+                !self.actions_open
+                    && self
+                        .selected_app()
+                        .is_some_and(|app| !app.actions.is_empty())
+            }
+            KeyAction::HideActions => self.actions_open,
+            KeyAction::NextCategory | KeyAction::PrevCategory => {
+                self.show_categories && !self.categories.is_empty()
+            }
+            KeyAction::ToggleCategories | KeyAction::ToggleDetails | KeyAction::ToggleCompact => {
+                true
+            }
+            KeyAction::Clear => clears,
+            KeyAction::Exit => !clears,
+        }
+    }
+
+    fn key_action(&self, action: KeyAction) -> <Self as LeaperMode>::Task {
+        let msg = match action {
+            KeyAction::Run => LeaperLauncherMsg::RunSelectedApp,
+            KeyAction::RunPower => {
+                LeaperLauncherMsg::RunPower(self.first_power().expect("available"))
+            }
+            KeyAction::RecallOlder => {
+                LeaperLauncherMsg::Recall(Some(self.recall.map_or(0, |ind| ind + 1)))
+            }
+            KeyAction::RecallNewer => {
+                LeaperLauncherMsg::Recall(self.recall.and_then(|ind| ind.checked_sub(1)))
+            }
+            KeyAction::Select(movement) => LeaperLauncherMsg::Select(movement),
+            KeyAction::ShowActions => LeaperLauncherMsg::ShowActions(true),
+            KeyAction::HideActions => LeaperLauncherMsg::ShowActions(false),
+            KeyAction::NextCategory => {
+                LeaperLauncherMsg::SelectCategory(self.cycle_category(false))
+            }
+            KeyAction::PrevCategory => LeaperLauncherMsg::SelectCategory(self.cycle_category(true)),
+            KeyAction::ToggleCategories => LeaperLauncherMsg::ToggleCategories,
+            KeyAction::ToggleFavorite => LeaperLauncherMsg::ToggleFavorite(self.selected),
+            KeyAction::ToggleDetails => LeaperLauncherMsg::ToggleDetails,
+            KeyAction::ToggleCompact => LeaperLauncherMsg::ToggleCompact,
+            KeyAction::Clear => {
+                return <Self as LeaperMode>::Task::batch([
+                    <Self as LeaperMode>::Task::done(LeaperLauncherMsg::SearchInput(String::new())),
+                    Self::focus_search(),
+                ]);
+            }
+            KeyAction::Exit => LeaperLauncherMsg::Exit,
+        };
+
+        <Self as LeaperMode>::Task::done(msg)
+    }
+
+    fn focus_search() -> <Self as LeaperMode>::Task {
+        <Self as LeaperMode>::Task::batch([
+            text_input::focus(Self::SEARCH_ID),
+            text_input::move_cursor_to_end(Self::SEARCH_ID),
+        ])
+    }
+
+    /// Computes the accent of the selected entry in the background
+    fn accent_task(&mut self) -> <Self as LeaperMode>::Task {
+        self.accent = None;
+
+        if !self.display().icons || !self.config.launcher.accent_from_icon {
+            return <Self as LeaperMode>::Task::none();
+        }
+
+        let Some(icon) = self.selected_app().and_then(|app| app.icon.clone()) else {
+            return <Self as LeaperMode>::Task::none();
+        };
+
+        let icons = self.icons.clone();
+        let ind = self.selected;
+
+        <Self as LeaperMode>::Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || icons.accent(Some(&icon)))
+                    .await
+                    .ok()
+                    .flatten()
+            },
+            move |accent| match accent {
+                Some([r, g, b]) => LeaperLauncherMsg::AccentColor(ind, Color::from_rgb8(r, g, b)),
+                None => LeaperLauncherMsg::Ignore,
+            },
+        )
+    }
+
+    /// Resolves the icons on screen that [`IconResolver::cached`] doesn't know yet in the
+    /// background, `view` leaves their space blank until then
+    fn resolve_icons(&mut self) -> <Self as LeaperMode>::Task {
+        if !self.display().icons {
+            return <Self as LeaperMode>::Task::none();
+        }
+
+        let icons = self
+            .on_screen()
+            .filter_map(|ind| self.visible_app(ind))
+            .filter_map(|app| app.icon.clone())
+            .filter(|icon| {
+                !self.resolving.contains(&icon.path) && self.icons.cached(Some(icon)).is_none()
+            })
+            .collect::<Vec<_>>();
+
+        if icons.is_empty() {
+            return <Self as LeaperMode>::Task::none();
+        }
+
+        let paths = icons
+            .iter()
+            .map(|icon| icon.path.clone())
+            .collect::<Vec<_>>();
+        let resolver = self.icons.clone();
+
+        self.resolving.extend(paths.iter().cloned());
+
+        <Self as LeaperMode>::Task::perform(
+            async move {
+                let _ = tokio::task::spawn_blocking(move || {
+                    for icon in &icons {
+                        resolver.resolve(Some(icon));
+                    }
+                })
+                .await;
+
+                paths
+            },
+            LeaperLauncherMsg::IconsResolved,
+        )
+    }
+
+    /// Asks the providers about the current query, the results come back as
+    /// [`LeaperLauncherMsg::Results`]
+    fn refilter(&mut self) -> <Self as LeaperMode>::Task {
+        self.query_id += 1;
+
+        if !self.filtering() {
+            self.results.clear();
+            self.results_id = self.query_id;
+            self.clamp_selected();
+
+            return <Self as LeaperMode>::Task::none();
+        }
+
+        let query_id = self.query_id;
+
+        <Self as LeaperMode>::Task::perform(self.providers.query(&self.search), move |results| {
+            LeaperLauncherMsg::Results(query_id, results)
+        })
+    }
+
+    fn clamp_selected(&mut self) {
+        self.selected = match self.visible_len() {
+            0 => 0,
+            len => self.selected.clamp(0, len - 1),
+        };
+    }
+
+    /// Whether the top result isn't an app
+    fn action_first(&self) -> bool {
+        matches!(self.visible_entry(0), Some(ListEntry::Action(_)))
+    }
+
+    /// Best of the power actions among the results, what Ctrl+P runs
+    fn first_power(&self) -> Option<PowerAction> {
+        self.results.iter().find_map(|item| match &item.kind {
+            ResultKind::Action(ActionItem {
+                action: ResultAction::Power(action),
+                ..
+            }) => Some(*action),
+            _ => None,
+        })
+    }
+
+    fn selected_app(&self) -> Option<&Arc<AppWithIcon>> {
+        self.visible_app(self.selected)
+    }
+
+    /// Starts `app` (or one of its `action`s) and records the launch, then exits
+    fn run_app(
+        &self,
+        app: Arc<AppWithIcon>,
+        action: Option<AppAction>,
+    ) -> <Self as LeaperMode>::Task {
+        let launcher = self.launcher.clone();
+        let catalog = self.catalog.clone();
+        let query = (!self.search.is_empty()).then(|| self.search.clone());
+        let remember_query = self.config.launcher.query_history;
+
+        <Self as LeaperMode>::Task::perform(
+            async move {
+                let res = match &action {
+                    Some(action) => launcher.launch_action(&app, action).await,
+                    None => launcher.launch(&app).await,
+                };
+
+                if let Err(err) = res {
+                    tracing::error!("Failed to run the app {}: {err}", app.name);
+                    return;
+                }
+
+                if let Some(catalog) = catalog {
+                    let _ = catalog.clone().record_launch(app.id.clone()).await;
+
+                    if let Some(query) = query {
+                        if remember_query {
+                            let _ = catalog.clone().record_query(query.clone()).await;
+                        }
+
+                        let _ = catalog.record_choice(query, app.id.clone()).await;
+                    }
+                }
+            },
+            |_| LeaperLauncherMsg::Exit,
+        )
+    }
+
+    fn sort_apps(&mut self) {
+        self.change_apps(|this| this.app_search.sort(&mut this.apps, &this.launches));
+    }
+
+    /// Runs `change` on [`Self::apps`], the results get the changed entries of their
+    /// apps and the selection follows its app to wherever it ended up afterwards
+    fn change_apps<R>(&mut self, change: impl FnOnce(&mut Self) -> R) -> R {
+        let selected = self.selected_app().map(|app| app.id.clone());
+
+        let res = change(self);
+        self.reindex_apps();
+        self.apps_provider
+            .update(&self.apps, &self.launches, &self.app_search);
+        self.prune_handles();
+
+        let (apps, app_index) = (&self.apps, &self.app_index);
+
+        self.results.retain_mut(|item| match &mut item.kind {
+            ResultKind::App(app) => match app_index.get(&app.id) {
+                Some(ind) => {
+                    *app = apps[*ind].clone();
+                    true
+                }
+                None => false,
+            },
+            ResultKind::Action(_) => true,
+        });
+
+        if let Some(ind) = selected.and_then(|id| self.visible_position(&id)) {
+            self.selected = ind;
+        }
+
+        res
+    }
+
+    /// Drops the decoded icons no app in the list uses anymore
+    fn prune_handles(&self) {
+        let icons = self
+            .apps
+            .iter()
+            .filter_map(|app| app.icon.as_ref())
+            .map(|icon| Path::new(&icon.path))
+            .collect::<HashSet<_>>();
+
+        self.rgba_handles
+            .retain(|path, _| icons.contains(path.as_path()));
+        self.frame_handles
+            .retain(|(path, _), _| icons.contains(path.as_path()));
+    }
+
+    fn reindex_apps(&mut self) {
+        self.app_index = self
+            .apps
+            .iter()
+            .enumerate()
+            .map(|(ind, app)| (app.id.clone(), ind))
+            .collect();
+    }
+
+    /// Applies the live notifications of apps, returns whether the results need refiltering
+    fn apply_live(&mut self, changes: Vec<LiveApp>) -> bool {
+        let icon_order = AppCatalog::icon_order(&self.config.launcher);
+
+        // Coalesced by id first, so every app gets upserted once and its current icon
+        // is looked up before the list changes. Whatever came last for an app wins
+        let mut updates: Vec<AppWithIcon> = vec![];
+        let mut update_index = HashMap::new();
+        let mut removed = HashSet::new();
+
+        for change in changes {
+            let mut app = match change {
+                LiveApp::Changed(app) if self.config.hides_app(&app.categories) => {
+                    removed.insert(app.id);
+                    continue;
+                }
+                LiveApp::Changed(app) => app,
+                LiveApp::Removed(id) => {
+                    removed.insert(id);
+                    continue;
+                }
+            };
+
+            removed.remove(&app.id);
+
+            match update_index.get(&app.id) {
+                Some(&ind) => {
+                    let update: &mut AppWithIcon = &mut updates[ind];
+                    app.icon = icon_order.pick(update.icon.take(), app.icon);
+                    *update = app;
+                }
+                None => {
+                    update_index.insert(app.id.clone(), updates.len());
+                    updates.push(app);
+                }
+            }
+        }
+
+        updates.retain(|app| !removed.contains(&app.id));
+        removed.retain(|id| self.app_index.contains_key(id));
+
+        for app in &mut updates {
+            let current_icon = self
+                .app_index
+                .get(&app.id)
+                .and_then(|ind| self.apps.get(*ind))
+                .and_then(|current| current.icon.clone());
+            app.icon = icon_order.pick(current_icon, app.icon.take());
+        }
+
+        if updates.is_empty() && removed.is_empty() {
+            return false;
+        }
+
+        // Checked while the positions in the results still hold
+        let updates = updates
+            .into_iter()
+            .map(|app| {
+                let affects_results = self.affects_results(&app);
+                (app, affects_results)
+            })
+            .collect::<Vec<_>>();
+
+        let refilter = self.change_apps(|this| {
+            let mut refilter = false;
+
+            // The results lose them along with the list
+            if !removed.is_empty() {
+                this.apps.retain(|app| !removed.contains(&app.id));
+                this.reindex_apps();
+            }
+
+            for (app, affects_results) in updates {
+                // Stale after the first insert, upsert looks the app up again then
+                let existing = this.app_index.get(&app.id).copied();
+                let changed =
+                    this.app_search
+                        .upsert(&mut this.apps, &this.launches, Arc::new(app), existing);
+
+                refilter |= changed && affects_results;
+            }
+
+            refilter
+        });
+
+        // The selected app might have been one of the removed ones
+        self.clamp_selected();
+
+        refilter
+    }
+
+    /// Whether a change to `app` can alter the results
+    fn affects_results(&self, app: &AppWithIcon) -> bool {
+        let in_results = self
+            .results
+            .iter()
+            .any(|item| matches!(&item.kind, ResultKind::App(result) if result.id == app.id));
+
+        match (self.search.is_empty(), &self.category) {
+            (true, None) => false,
+            (true, Some(_)) => in_results || self.in_category(app),
+            (false, _) => in_results || self.app_search.matches(app, &self.search),
+        }
+    }
+
+    fn visible_len(&self) -> usize {
+        match self.filtering() {
+            false => self.apps.len(),
+            true => self.results.len(),
+        }
+    }
+
+    /// Entry `ind` of the list as shown
+    fn visible_entry(&self, ind: usize) -> Option<ListEntry<'_>> {
+        match self.filtering() {
+            false => self.apps.get(ind).map(ListEntry::App),
+            true => self.results.get(ind).map(|item| match &item.kind {
+                ResultKind::App(app) => ListEntry::App(app),
+                ResultKind::Action(action) => ListEntry::Action(action),
+            }),
+        }
+    }
+
+    /// App at `ind` of the list as shown, [`None`] for other results
+    fn visible_app(&self, ind: usize) -> Option<&Arc<AppWithIcon>> {
+        match self.visible_entry(ind)? {
+            ListEntry::App(app) => Some(app),
+            ListEntry::Action(_) => None,
+        }
+    }
+
+    /// Where the app with `id` is in the list as shown
+    fn visible_position(&self, id: &DBEntryId) -> Option<usize> {
+        match self.filtering() {
+            false => self.app_index.get(id).copied(),
+            true => self
+                .results
+                .iter()
+                .position(|item| matches!(&item.kind, ResultKind::App(app) if app.id == *id)),
+        }
+    }
+
+    /// Whether the list shows [`Self::results`] instead of every app
+    fn filtering(&self) -> bool {
+        !self.search.is_empty() || self.category.is_some()
+    }
+
+    fn in_category(&self, app: &AppWithIcon) -> bool {
+        self.category
+            .as_ref()
+            .is_none_or(|category| app.categories.contains(category))
+    }
+
+    /// Category after (or before) the selected one, wrapping around through "All"
+    fn cycle_category(&self, back: bool) -> Option<String> {
+        let chips = std::iter::once(None)
+            .chain(
+                self.categories
+                    .iter()
+                    .map(|category| Some(category.category.clone())),
+            )
+            .collect::<Vec<_>>();
+        let current = chips
+            .iter()
+            .position(|chip| *chip == self.category)
+            .unwrap_or_default();
+
+        let next = match back {
+            true => current.checked_sub(1).unwrap_or(chips.len() - 1),
+            false => (current + 1) % chips.len(),
+        };
+
+        chips[next].clone()
+    }
+
+    fn category_chips(&self) -> Option<<Self as LeaperMode>::Element<'_>> {
+        if !self.show_categories || self.categories.is_empty() {
+            return None;
+        }
+
+        let chip = |label: String, category: Option<String>| {
+            let selected = self.category == category;
+
+            button(text(label).size(14))
+                .on_press(<Self as LeaperMode>::Msg::SelectCategory(category))
+                .padding([4, 12])
+                .style(move |theme, status| style::chip(theme, status, selected))
+                .into()
+        };
+
+        let chips = std::iter::once(chip("All".into(), None)).chain(self.categories.iter().map(
+            |category| {
+                chip(
+                    format!("{} ({})", category.category, category.count),
+                    Some(category.category.clone()),
+                )
+            },
+        ));
+
+        Some(
+            scrollable(row(chips).spacing(5))
+                .direction(scrollable::Direction::Horizontal(
+                    scrollable::Scrollbar::new().width(2).scroller_width(2),
+                ))
+                .width(Length::Fill)
+                .into(),
+        )
+    }
 
     fn search(&self) -> <Self as LeaperMode>::Element<'_> {
         center(
@@ -529,37 +1644,33 @@ impl LeaperLauncher {
         .into()
     }
 
-    const LIST_SPACING: f32 = 5.0;
-
     fn list(&self) -> <Self as LeaperMode>::Element<'_> {
-        let (items, filtered) = match self.search.is_empty() {
-            true => (&self.apps, false),
-            false => (&self.filtered, true),
-        };
+        let (empty, filtered) = (self.visible_len() == 0, self.filtering());
+        let searching = self.results_id != self.query_id;
 
         let scrllbl = || {
-            scrollable(
-                column(items.iter().enumerate().map(|(ind, app)| {
-                    Self::app_entry(app, ind, self.selected, self.xpm_handles.clone())
-                }))
-                .spacing(Self::LIST_SPACING)
-                .align_x(Horizontal::Center),
+            list::view(
+                Self::LIST_ID,
+                self.display(),
+                (0..self.visible_len()).filter_map(|ind| {
+                    self.visible_entry(ind).map(|entry| match entry {
+                        ListEntry::Action(item) => self.action_entry(item, ind),
+                        ListEntry::App(app) => self.app_entry(app, ind),
+                    })
+                }),
+                <Self as LeaperMode>::Msg::ListScrolled,
             )
-            .id(scrollable::Id::new(Self::LIST_ID))
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .spacing(5)
-            .style(style::scrollable)
-            .into()
         };
 
         match filtered {
-            true => match items.is_empty() {
-                true => center(text("No matches found!").size(25)).into(),
-                false => scrllbl(),
+            true => match (empty, searching) {
+                // Only until the first results of the query are in
+                (true, true) => Space::new(Length::Fill, Length::Fill).into(),
+                (true, false) => center(text("No matches found!").size(25)).into(),
+                (false, _) => scrllbl(),
             },
-            false => match items.is_empty() {
-                true => center(
+            false => match (empty, self.show_spinner) {
+                (true, true) => center(
                     row![
                         Spinner::new().width(30).height(30),
                         text("Loading...").size(20)
@@ -568,123 +1679,278 @@ impl LeaperLauncher {
                     .spacing(10),
                 )
                 .into(),
-                false => scrllbl(),
+                // Most starts have something to show well before the spinner is due
+                (true, false) => Space::new(Length::Fill, Length::Fill).into(),
+                (false, _) => scrllbl(),
             },
         }
     }
 
-    const APP_ENTRY_HEIGHT: f32 = 60.0;
-    const APP_ENTRY_PADDING: [f32; 2] = [10.0, 5.0];
-    const APP_ENTRY_SPACING: f32 = 10.0;
-    const APP_ENTRY_IMAGE_SIZE: f32 = Self::APP_ENTRY_HEIGHT - Self::APP_ENTRY_PADDING[1] * 2.0;
-    const APP_ENTRY_TEXT_HEIGHT: f32 = Self::APP_ENTRY_IMAGE_SIZE * 0.5;
+    /// Desktop actions of the selected app, see [`Self::actions_open`]
+    fn actions_panel(&self) -> Option<<Self as LeaperMode>::Element<'_>> {
+        let app = self.selected_app()?;
+        let text_size = self.display().text_size;
+
+        let actions = app.actions.iter().enumerate().map(|(ind, action)| {
+            let selected = ind == self.selected_action;
+
+            button(text(&action.name).size(text_size))
+                .on_press(<Self as LeaperMode>::Msg::RunAction(ind))
+                .padding([5, 10])
+                .width(Length::Fill)
+                .style(move |theme, status| style::list_button(theme, status, selected, None))
+                .into()
+        });
+
+        Some(
+            container(column![text(&app.name).size(14)].extend(actions).spacing(5))
+                .width(Length::Fill)
+                .padding(10)
+                .style(style::tooltip)
+                .into(),
+        )
+    }
+
+    fn display(&self) -> DisplayProfile {
+        match self.compact {
+            true => DisplayProfile::compact(),
+            false => DisplayProfile::regular(&self.config),
+        }
+    }
 
     fn app_entry<'a>(
+        &'a self,
         app: &'a AppWithIcon,
         ind: usize,
-        selected: usize,
-        xpm_handles: Arc<Mutex<DashMap<PathBuf, image::Handle>>>,
     ) -> <Self as LeaperMode>::Element<'a> {
-        let r = match &app.icon {
-            Some(icon) => match icon.svg {
-                true => row![
-                    svg(&icon.path)
-                        .width(Self::APP_ENTRY_IMAGE_SIZE)
-                        .height(Self::APP_ENTRY_IMAGE_SIZE),
-                ],
-                false => match icon.xpm {
-                    true => {
-                        let xpm_handles = xpm_handles.lock().expect("Should be fine");
-                        let icon_path = PathBuf::from(&icon.path);
-
-                        let handle = match xpm_handles.contains_key(&icon_path) {
-                            true => xpm_handles.get(&icon_path),
-                            false => {
-                                let img = std::fs::read_to_string(&icon.path).ok().and_then(|s| {
-                                    let start = s.find('"').unwrap_or_default();
-                                    let end = s.rfind('"').unwrap_or_else(|| match s.is_empty() {
-                                        true => 0,
-                                        false => s.len() - 1,
-                                    });
-
-                                    let lines = &s[start..=end]
-                                        .lines()
-                                        .map(|line| line.trim_end_matches(',').trim_matches('"'))
-                                        .collect_vec();
-
-                                    ez_pixmap::RgbaImage::from(lines)
-                                        .inspect_err(|err| {
-                                            tracing::error!(
-                                                "Failed to parse pixmap at {:?}: {err}\n\nLines:\n{}",
-                                                icon.path,
-                                                lines.join("\n")
-                                            )
-                                        })
-                                        .ok()
-                                });
-
-                                let img_handle = img.map(|img| {
-                                    image::Handle::from_rgba(
-                                        img.width(),
-                                        img.height(),
-                                        img.data().to_vec(),
-                                    )
-                                });
-
-                                if let Some(handle) = img_handle {
-                                    xpm_handles.insert(icon_path.clone(), handle);
-                                }
-
-                                xpm_handles.get(&icon_path)
-                            }
-                        };
-
-                        match handle {
-                            Some(handle) => row![
-                                image(handle.clone())
-                                    .width(Self::APP_ENTRY_IMAGE_SIZE)
-                                    .height(Self::APP_ENTRY_IMAGE_SIZE)
-                            ],
-                            None => row![
-                                text(icon_to_string(Nerd::Error))
-                                    .font(NERD_FONT)
-                                    .align_x(Horizontal::Center)
-                                    .width(Self::APP_ENTRY_IMAGE_SIZE)
-                                    .height(Self::APP_ENTRY_IMAGE_SIZE)
-                                    .size(Self::APP_ENTRY_TEXT_HEIGHT)
-                            ],
-                        }
-                    }
-                    false => row![
-                        image(&icon.path)
-                            .width(Self::APP_ENTRY_IMAGE_SIZE)
-                            .height(Self::APP_ENTRY_IMAGE_SIZE),
-                    ],
-                },
-            },
-            None => row![
-                text(icon_to_string(Nerd::Question))
+        let selected = self.selected;
+        let display = self.display();
+        let (image_size, text_size) = (display.image_size(), display.text_size);
+        let accent = self
+            .accent
+            .filter(|(accent_ind, _)| *accent_ind == ind)
+            .map(|(_, color)| color);
+        let error_icon = |icon: Nerd| {
+            row![
+                text(icon_to_string(icon))
                     .font(NERD_FONT)
                     .align_x(Horizontal::Center)
-                    .width(Self::APP_ENTRY_IMAGE_SIZE)
-                    .height(Self::APP_ENTRY_IMAGE_SIZE)
-                    .size(Self::APP_ENTRY_TEXT_HEIGHT)
-            ],
+                    .width(image_size)
+                    .height(image_size)
+                    .size(text_size)
+            ]
+        };
+
+        // Never resolved (and so never decoded) without icons
+        let icon = app.icon.as_ref().filter(|_| display.icons);
+
+        let r = match self.icons.cached(icon) {
+            _ if !display.icons => row![],
+            // Still being resolved, see `resolve_icons`
+            None => row![Space::new(image_size, image_size)],
+            Some(ResolvedIcon::Svg(path)) => row![svg(path).width(image_size).height(image_size),],
+            Some(ResolvedIcon::Raster(path)) => {
+                row![image(path).width(image_size).height(image_size),]
+            }
+            Some(ResolvedIcon::Rgba(rgba)) => {
+                let icon_path = PathBuf::from(&app.icon.as_ref().expect("resolved from icon").path);
+                let handle = self
+                    .rgba_handles
+                    .entry(icon_path)
+                    .or_insert_with(|| {
+                        image::Handle::from_rgba(rgba.width, rgba.height, rgba.data.clone())
+                    })
+                    .clone();
+
+                row![image(handle).width(image_size).height(image_size)]
+            }
+            Some(ResolvedIcon::Animated(animation)) => {
+                let icon_path = PathBuf::from(&app.icon.as_ref().expect("resolved from icon").path);
+                let frame = match self.on_screen().contains(&ind) {
+                    true => animation.frame_at(self.animation_elapsed),
+                    false => 0,
+                };
+                let handle = self
+                    .frame_handles
+                    .entry((icon_path, frame))
+                    .or_insert_with(|| {
+                        let (rgba, _) = &animation.frames[frame];
+                        image::Handle::from_rgba(rgba.width, rgba.height, rgba.data.clone())
+                    })
+                    .clone();
+
+                row![image(handle).width(image_size).height(image_size)]
+            }
+            Some(ResolvedIcon::Broken) => error_icon(Nerd::Error),
+            Some(ResolvedIcon::Missing) => error_icon(Nerd::Question),
         }
-        .push(text(&app.name).size(Self::APP_ENTRY_TEXT_HEIGHT))
+        .push(text(&app.name).size(text_size).width(Length::Fill))
+        .push_maybe(self.favorite_star(app, ind, text_size))
+        .push_maybe((!app.actions.is_empty()).then(|| {
+            button(text("⋯").size(text_size * 0.8))
+                .on_press(<Self as LeaperMode>::Msg::ToggleActions(ind))
+                .style(button::text)
+                .padding(0)
+        }))
         .height(Length::Fill)
         .width(Length::Fill)
-        .spacing(Self::APP_ENTRY_SPACING)
-        .padding(Self::APP_ENTRY_PADDING)
+        .spacing(display.entry_spacing)
+        .padding(display.entry_padding)
         .align_y(Vertical::Center);
 
-        button(r)
+        let entry = button(r)
             .on_press(<Self as LeaperMode>::Msg::RunApp(ind))
-            .style(move |theme, status| style::list_button(theme, status, selected == ind))
-            .height(Length::Fixed(Self::APP_ENTRY_HEIGHT))
+            .style(move |theme, status| style::list_button(theme, status, selected == ind, accent))
+            .height(Length::Fixed(display.entry_height))
+            .width(Length::Fill);
+
+        tooltip(
+            entry,
+            self.app_details(app),
+            tooltip::Position::FollowCursor,
+        )
+        .into()
+    }
+
+    /// ★ on favorites and ☆ on the selected app otherwise, clicking it (un)pins the app
+    fn favorite_star<'a>(
+        &self,
+        app: &AppWithIcon,
+        ind: usize,
+        text_size: f32,
+    ) -> Option<<Self as LeaperMode>::Element<'a>> {
+        let favorite = self.app_search.favorites().contains(&app.id);
+        let star = match (favorite, self.catalog.is_some() && self.selected == ind) {
+            (true, _) => "★",
+            (false, true) => "☆",
+            (false, false) => return None,
+        };
+
+        Some(
+            button(text(star).size(text_size * 0.8))
+                .on_press_maybe(
+                    self.catalog
+                        .is_some()
+                        .then_some(<Self as LeaperMode>::Msg::ToggleFavorite(ind)),
+                )
+                .style(button::text)
+                .padding(0)
+                .into(),
+        )
+    }
+
+    fn action_entry<'a>(
+        &'a self,
+        item: &'a ActionItem,
+        ind: usize,
+    ) -> <Self as LeaperMode>::Element<'a> {
+        let selected = self.selected;
+        let display = self.display();
+        let text_size = display.text_size;
+
+        let r = row![
+            text(&item.glyph)
+                .font(match item.nerd_glyph {
+                    true => NERD_FONT,
+                    false => Font::DEFAULT,
+                })
+                .align_x(Horizontal::Center)
+                .width(display.image_size())
+                .size(text_size),
+            text(&item.title).size(text_size).width(Length::Fill),
+            text(&item.detail).size(text_size * 0.6),
+        ]
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .spacing(display.entry_spacing)
+        .padding(display.entry_padding)
+        .align_y(Vertical::Center);
+
+        button(r)
+            .on_press(<Self as LeaperMode>::Msg::RunResult(item.action.clone()))
+            .style(move |theme, status| style::list_button(theme, status, selected == ind, None))
+            .height(Length::Fixed(display.entry_height))
             .width(Length::Fill)
             .into()
     }
+
+    const APP_DETAILS_LABEL_WIDTH: f32 = 100.0;
+
+    fn app_details<'a>(&self, app: &'a AppWithIcon) -> <Self as LeaperMode>::Element<'a> {
+        let field = |label: &'static str, value: String| {
+            row![
+                text(label)
+                    .font(Font {
+                        weight: iced::font::Weight::Semibold,
+                        ..Default::default()
+                    })
+                    .width(Self::APP_DETAILS_LABEL_WIDTH),
+                text(value)
+            ]
+            .spacing(10)
+        };
+
+        let categories = match app.categories.is_empty() {
+            true => "-".into(),
+            false => app.categories.join(", "),
+        };
+        let icon = app
+            .icon
+            .as_ref()
+            .map(|icon| icon.path.clone())
+            .unwrap_or_else(|| "-".into());
+
+        container(
+            column![
+                field("Exec", app.exec.join(" ")),
+                field("Desktop file", app.desktop_entry_path.clone()),
+                field("Categories", categories),
+                field("Icon", icon),
+            ]
+            .push_maybe(app.terminal.then(|| field("Terminal", "Yes".into())))
+            .push_maybe(
+                self.launcher
+                    .discrete_gpu(app)
+                    .then(|| field("GPU", "Discrete (render offload)".into())),
+            )
+            .spacing(5),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(style::tooltip)
+        .into()
+    }
+}
+
+/// Entry of the list as shown, see [`LeaperLauncher::visible_entry`]
+enum ListEntry<'a> {
+    App(&'a Arc<AppWithIcon>),
+    Action(&'a ActionItem),
+}
+
+/// What the launcher's [`KeyBindings`] map to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyAction {
+    Run,
+    /// First of the matching power actions
+    RunPower,
+    /// Up/Down through past queries, see `launcher.query_history`
+    RecallOlder,
+    RecallNewer,
+    Select(SelectionMove),
+    /// Desktop actions of the selected app, Alt+Right to open, Alt+Left or Escape to close.
+    /// Tab and the bare arrows belong to the categories and the input
+    ShowActions,
+    HideActions,
+    NextCategory,
+    PrevCategory,
+    ToggleCategories,
+    ToggleFavorite,
+    ToggleDetails,
+    ToggleCompact,
+    /// Escape with a non-empty query and `escape_clears` set
+    Clear,
+    Exit,
 }
 
 #[to_layer_message]
@@ -698,18 +1964,55 @@ pub enum LeaperLauncherMsg {
 
     InitApps,
     InitedApps(InitAppsIconsResult),
+    /// DB apps, once the icons of the first page are decoded
+    ShowApps(AppsIcons),
+    InitedLaunches(CoreResult<Launches>),
+    InitedCategories(CoreResult<Vec<CategoryCount>>),
+    InitedQueryChoices(CoreResult<QueryChoices>),
+    InitedFavorites(CoreResult<Favorites>),
+    InitedQueryHistory(CoreResult<QueryHistory>),
+    /// Apps from the last run, shown until the DB ones arrive
+    InitedSnapshot(Option<Vec<AppWithIcon>>),
+    ShowSpinner,
     LoadApps,
 
-    AddApp(AppWithIcon),
+    LiveApps(Vec<LiveApp>),
 
     SearchInput(String),
+    /// Past query to put into the search input, `None` clears it
+    Recall(Option<usize>),
+
+    Select(SelectionMove),
+    SelectCategory(Option<String>),
+    /// Ctrl+K
+    ToggleCategories,
 
-    SelectUp,
-    SelectDown,
+    /// Results of the query with this id, see [`LeaperLauncher::refilter`]
+    Results(u64, Vec<ResultItem>),
 
     RunSelectedApp,
     RunApp(usize),
+    /// Runs the action of a result that isn't an app and exits
+    RunResult(ResultAction),
+    ShowActions(bool),
+    /// Click on the actions button of the app at this index of the list as shown
+    ToggleActions(usize),
+    /// Index into the actions of the selected app
+    RunAction(usize),
+    RunPower(PowerAction),
+    /// Ctrl+D or a click on the star, pins the app at this index of the list as shown to
+    /// the top of it or unpins it
+    ToggleFavorite(usize),
+    ToggleDetails,
+    /// Ctrl+-
+    ToggleCompact,
+    OskKey(OskKey),
     ScrollToSelected,
+    AccentColor(usize, Color),
+    ListScrolled(scrollable::Viewport),
+    /// These icon paths are resolved now, see [`LeaperLauncher::resolve_icons`]
+    IconsResolved(Vec<String>),
+    AnimationTick(Instant),
 
     IcedEvent(Event),
 
@@ -717,24 +2020,16 @@ pub enum LeaperLauncherMsg {
 }
 
 #[lerror]
-#[lerr(prefix = "[leaper-launcher]", result_name = LeaperLauncherResult)]
+#[lerr(prefix = "[leaper-launcher]", result_name = LeaperLauncherResult, common)]
 pub enum LeaperLauncherError {
     #[lerr(str = "Path {0:?} doesn't have a file name...")]
     NoFileName(PathBuf),
 
-    #[lerr(str = "Interrupted by parent")]
-    InterruptedByParent,
-    #[lerr(str = "Lost connection to the parent")]
-    LostConnectionToParent,
-
-    #[lerr(str = "[std::io] {0}")]
-    IO(#[lerr(from, wrap = Arc)] std::io::Error),
-
     #[lerr(str = "[iced_layershell] {0}")]
     LayerShell(#[lerr(from, wrap = Arc)] iced_layershell::Error),
+    #[lerr(str = "[iced] {0}")]
+    Iced(#[lerr(from, wrap = Arc)] iced::Error),
 
-    #[lerr(str = "[tokio::task::join] {0}")]
-    TokioJoin(#[lerr(from, wrap = Arc)] tokio::task::JoinError),
     #[lerr(str = "[tokio::sync::mpsc::send<PathBuf>] {0}")]
     TokioMpscSendPathBuf(#[lerr(from)] tokio::sync::mpsc::error::SendError<PathBuf>),
     #[lerr(str = "[tokio::mpmc::channel] {0}")]
@@ -747,6 +2042,8 @@ pub enum LeaperLauncherError {
     Config(#[lerr(from)] LeaperAppModeConfigError),
     #[lerr(str = "{0}")]
     DB(#[lerr(from, wrap = Arc)] db::DBError),
+    #[lerr(str = "{0}")]
+    Core(#[lerr(from)] CoreError),
 
     #[lerr(str = "[dynamic] {0}")]
     Dynamic(String),
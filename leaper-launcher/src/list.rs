@@ -0,0 +1,126 @@
+//! The scrollable list of equally high entries the launcher and the finder both show
+
+use std::ops::Range;
+
+use iced::{
+    Element, Length, Size, Task,
+    advanced::widget::{Id, operate, operation::scrollable::scroll_to},
+    alignment::Horizontal,
+    widget::{column, scrollable},
+};
+
+use mode::{LeaperModeTheme, selection::offset_to_show};
+
+use crate::display::DisplayProfile;
+
+/// Where the list is scrolled to and how big the window is, which entries are on screen
+/// and how far to scroll to one follow from that
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ListScroll {
+    /// Last reported scroll position of the list
+    viewport: Option<scrollable::Viewport>,
+    /// Last size the compositor gave the surface
+    window_size: Option<Size>,
+    /// Assumed until the first resize, see [`Self::new`]
+    initial_height: f32,
+}
+
+impl ListScroll {
+    pub fn new(initial_height: f32) -> Self {
+        Self {
+            initial_height,
+            ..Default::default()
+        }
+    }
+
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.viewport = Some(viewport);
+    }
+
+    /// Forgets the scroll position, after the entries changed their height
+    pub fn reset(&mut self) {
+        self.viewport = None;
+    }
+
+    pub fn resized(&mut self, size: Size) {
+        self.window_size = Some(size);
+    }
+
+    pub fn window_size(&self) -> Option<Size> {
+        self.window_size
+    }
+
+    /// Number of entries that fit on screen at once
+    pub fn page_len(&self, display: DisplayProfile) -> usize {
+        (self.window_height() / display.entry_stride()).ceil() as usize
+    }
+
+    /// Indices of the entries currently on screen out of `len` (roughly, before the first
+    /// scroll event the window height is assumed)
+    pub fn on_screen(&self, display: DisplayProfile, len: usize) -> Range<usize> {
+        let entry = display.entry_stride();
+        let (offset, height) = self.visible();
+
+        let first = (offset / entry).floor() as usize;
+        let last = ((offset + height) / entry).ceil() as usize;
+
+        first.min(len)..last.min(len)
+    }
+
+    /// Scrolls the list with `id` as little as possible to bring entry `ind` into view
+    pub fn scroll_to<M: Send + 'static>(
+        &self,
+        id: &'static str,
+        ind: usize,
+        display: DisplayProfile,
+    ) -> Task<M> {
+        let (offset, height) = self.visible();
+        let y = offset_to_show(
+            ind,
+            display.entry_stride(),
+            display.entry_height,
+            offset,
+            height,
+        );
+
+        operate(scroll_to(
+            Id::new(id),
+            scrollable::AbsoluteOffset { x: 0.0, y },
+        ))
+    }
+
+    fn window_height(&self) -> f32 {
+        self.window_size
+            .map(|size| size.height)
+            .unwrap_or(self.initial_height)
+    }
+
+    /// Offset and height of the part of the list on screen
+    fn visible(&self) -> (f32, f32) {
+        self.viewport
+            .map(|viewport| (viewport.absolute_offset().y, viewport.bounds().height))
+            .unwrap_or((0.0, self.window_height()))
+    }
+}
+
+/// The list with `id` laid out from `entries`, reporting where it's scrolled through
+/// `on_scroll`
+pub fn view<'a, M: 'a>(
+    id: &'static str,
+    display: DisplayProfile,
+    entries: impl IntoIterator<Item = Element<'a, M, LeaperModeTheme>>,
+    on_scroll: impl Fn(scrollable::Viewport) -> M + 'a,
+) -> Element<'a, M, LeaperModeTheme> {
+    scrollable(
+        column(entries)
+            .spacing(display.list_spacing)
+            .align_x(Horizontal::Center),
+    )
+    .id(scrollable::Id::new(id))
+    .on_scroll(on_scroll)
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .spacing(5)
+    .style(style::scrollable)
+    .into()
+}
@@ -0,0 +1,43 @@
+//! The power menu's actions as one of the [`ResultProvider`]s, typing "reboot" offers it
+
+use futures::future::BoxFuture;
+
+use mode::config::LeaperModeConfig;
+use power::PowerCommand;
+
+use crate::provider::{ActionItem, ResultAction, ResultItem, ResultKind, ResultProvider};
+
+/// Lists the allowed power actions a word of the query starts, see `launcher.power_actions`.
+/// Only the action named in full ranks above the apps
+pub struct PowerProvider(pub LeaperModeConfig);
+
+impl ResultProvider for PowerProvider {
+    fn name(&self) -> &'static str {
+        "power"
+    }
+
+    fn query(&self, query: &str) -> BoxFuture<'static, Vec<ResultItem>> {
+        let items = PowerCommand::matching(query, &self.0)
+            .into_iter()
+            .map(|command| {
+                let name = command.action().name();
+
+                ResultItem {
+                    score: match name.eq_ignore_ascii_case(query.trim()) {
+                        true => ResultItem::EXACT,
+                        false => 0,
+                    },
+                    kind: ResultKind::Action(ActionItem {
+                        glyph: command.icon().into(),
+                        nerd_glyph: true,
+                        title: name.into(),
+                        detail: "Power".into(),
+                        action: ResultAction::Power(command.action()),
+                    }),
+                }
+            })
+            .collect();
+
+        Box::pin(futures::future::ready(items))
+    }
+}
@@ -0,0 +1,164 @@
+//! Sources of the launcher's results. Each [`ResultProvider`] turns the query into
+//! [`ResultItem`]s, [`Providers`] asks all of them at once and merges their answers into
+//! the one list that's shown
+
+use std::{cmp::Reverse, sync::Arc};
+
+use futures::future::{BoxFuture, join_all};
+use itertools::Itertools;
+
+use db::apps::AppWithIcon;
+use leaper_core::power::PowerAction;
+
+/// Something that can answer the launcher's query, registered in [`Providers`]
+pub trait ResultProvider: Send + Sync {
+    /// Shown in the logs
+    fn name(&self) -> &'static str;
+
+    /// Results for `query`, best first. Called on every edit of the query, so anything
+    /// slow belongs in the returned future
+    fn query(&self, query: &str) -> BoxFuture<'static, Vec<ResultItem>>;
+}
+
+/// One of the results, see [`ResultProvider::query`]
+#[derive(Debug, Clone)]
+pub struct ResultItem {
+    /// Results of all providers are ranked against each other by this, higher first.
+    /// Fuzzy matches score up to [`u16::MAX`]
+    pub score: u32,
+    pub kind: ResultKind,
+}
+
+impl ResultItem {
+    /// Above any fuzzy match, for results the query was meant for exactly (e.g. arithmetic)
+    pub const EXACT: u32 = u16::MAX as u32 + 1;
+}
+
+#[derive(Debug, Clone)]
+pub enum ResultKind {
+    App(Arc<AppWithIcon>),
+    Action(ActionItem),
+}
+
+/// Any result that isn't an app, shown as a glyph, a title and a detail
+#[derive(Debug, Clone)]
+pub struct ActionItem {
+    pub glyph: String,
+    /// [`Self::glyph`] is a Nerd Font icon
+    pub nerd_glyph: bool,
+    pub title: String,
+    pub detail: String,
+    /// What Enter (or a click) does with it
+    pub action: ResultAction,
+}
+
+/// Runs the same way for every provider, so new ones don't need their own messages
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResultAction {
+    /// Puts the text into the clipboard
+    Copy(String),
+    /// Opens a URL or a path with its default handler
+    Open(String),
+    /// Runs it the way the power menu would
+    Power(PowerAction),
+}
+
+/// The registered providers, in the order their equally scored results are listed
+#[derive(Default, Clone)]
+pub struct Providers(Vec<Arc<dyn ResultProvider>>);
+
+impl Providers {
+    pub fn register(&mut self, provider: impl ResultProvider + 'static) {
+        tracing::trace!("Registered the {} result provider", provider.name());
+
+        self.0.push(Arc::new(provider));
+    }
+
+    /// Asks every provider at once, their results merged best first
+    pub fn query(&self, query: &str) -> BoxFuture<'static, Vec<ResultItem>> {
+        let queries = self
+            .0
+            .iter()
+            .map(|provider| provider.query(query))
+            .collect::<Vec<_>>();
+
+        Box::pin(async move {
+            // Stable, so each provider's own order holds among equal scores
+            join_all(queries)
+                .await
+                .into_iter()
+                .flatten()
+                .sorted_by_key(|item| Reverse(item.score))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    /// Answers every query with the same titles and scores
+    struct Fixed(&'static [(&'static str, u32)]);
+
+    impl ResultProvider for Fixed {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn query(&self, _query: &str) -> BoxFuture<'static, Vec<ResultItem>> {
+            let items = self
+                .0
+                .iter()
+                .map(|(title, score)| ResultItem {
+                    score: *score,
+                    kind: ResultKind::Action(ActionItem {
+                        glyph: String::new(),
+                        nerd_glyph: false,
+                        title: title.to_string(),
+                        detail: String::new(),
+                        action: ResultAction::Copy(title.to_string()),
+                    }),
+                })
+                .collect();
+
+            Box::pin(async move { items })
+        }
+    }
+
+    fn titles(providers: &Providers) -> Vec<String> {
+        block_on(providers.query("q"))
+            .into_iter()
+            .map(|item| match item.kind {
+                ResultKind::Action(action) => action.title,
+                ResultKind::App(app) => app.name.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merges_best_first() {
+        let mut providers = Providers::default();
+        providers.register(Fixed(&[("a1", 30), ("a2", 10)]));
+        providers.register(Fixed(&[("b1", ResultItem::EXACT), ("b2", 20)]));
+
+        assert_eq!(titles(&providers), ["b1", "a1", "b2", "a2"]);
+    }
+
+    #[test]
+    fn equal_scores_keep_registration_order() {
+        let mut providers = Providers::default();
+        providers.register(Fixed(&[("a1", 5), ("a2", 5)]));
+        providers.register(Fixed(&[("b1", 5)]));
+        providers.register(Fixed(&[("c1", 5), ("c2", 7)]));
+
+        assert_eq!(titles(&providers), ["c2", "a1", "a2", "b1", "c1"]);
+    }
+
+    #[test]
+    fn no_providers_no_results() {
+        assert!(titles(&Providers::default()).is_empty());
+    }
+}
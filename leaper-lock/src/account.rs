@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use zbus::{Connection, proxy, zvariant::OwnedObjectPath};
+
+use crate::LeaperLockResult;
+
+#[proxy(
+    interface = "org.freedesktop.Accounts",
+    default_service = "org.freedesktop.Accounts",
+    default_path = "/org/freedesktop/Accounts"
+)]
+trait Accounts {
+    fn find_user_by_name(&self, name: &str) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.Accounts.User",
+    default_service = "org.freedesktop.Accounts"
+)]
+trait AccountsUser {
+    #[zbus(property)]
+    fn real_name(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn icon_file(&self) -> zbus::Result<String>;
+}
+
+/// What's shown above the password field
+#[derive(Debug, Clone)]
+pub struct UserInfo {
+    pub real_name: Option<String>,
+    pub avatar: Option<PathBuf>,
+}
+
+impl UserInfo {
+    /// Asks AccountsService first, then falls back to the GECOS field and `~/.face`
+    pub async fn load(user_name: String) -> Self {
+        let accounts = Self::from_accounts_service(&user_name)
+            .await
+            .inspect_err(|err| tracing::debug!("AccountsService unavailable: {err}"))
+            .ok();
+
+        let (real_name, avatar) = accounts
+            .map(|info| (info.real_name, info.avatar))
+            .unwrap_or_default();
+
+        Self {
+            real_name: real_name.or_else(|| Self::gecos_name(&user_name)),
+            avatar: avatar.or_else(Self::face_file),
+        }
+    }
+
+    async fn from_accounts_service(user_name: &str) -> LeaperLockResult<Self> {
+        let connection = Connection::system().await?;
+        let user_path = AccountsProxy::new(&connection)
+            .await?
+            .find_user_by_name(user_name)
+            .await?;
+        let user = AccountsUserProxy::builder(&connection)
+            .path(user_path)?
+            .build()
+            .await?;
+
+        let real_name = user.real_name().await?;
+        let icon_file = PathBuf::from(user.icon_file().await?);
+
+        Ok(Self {
+            real_name: (!real_name.is_empty()).then_some(real_name),
+            avatar: icon_file.is_file().then_some(icon_file),
+        })
+    }
+
+    fn gecos_name(user_name: &str) -> Option<String> {
+        let user = nix::unistd::User::from_name(user_name).ok()??;
+        let gecos = user.gecos.to_string_lossy().into_owned();
+        let name = gecos.split(',').next()?.trim();
+
+        (!name.is_empty()).then(|| name.to_string())
+    }
+
+    fn face_file() -> Option<PathBuf> {
+        let face = PathBuf::from(std::env::var("HOME").ok()?).join(".face");
+        face.is_file().then_some(face)
+    }
+}
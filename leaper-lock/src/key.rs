@@ -0,0 +1,130 @@
+//! Unlocking with a security key (FIDO2/U2F) through a PAM service of its own, e.g. one
+//! with `auth required pam_u2f.so cue` that waits for the key to be touched
+
+use std::time::Duration;
+
+use iced::futures::{SinkExt, Stream, StreamExt, channel::mpsc};
+use nonstick::{AuthnFlags, ConversationAdapter, Transaction};
+
+use crate::LeaperLockResult;
+
+#[derive(Debug, Clone)]
+pub enum KeyEvent {
+    /// What the PAM module asks for, usually to touch the key
+    Prompt(String),
+    Done(Result<(), String>),
+}
+
+/// Where the security key unlock is at, shown instead of the password field while it runs
+#[derive(Debug, Clone, Default)]
+pub enum KeyState {
+    #[default]
+    Idle,
+    /// The latest prompt of the PAM module, if it sent one yet
+    Waiting(Option<String>),
+    Failed(String),
+}
+
+impl KeyState {
+    pub fn waiting(&self) -> bool {
+        matches!(self, Self::Waiting(_))
+    }
+}
+
+/// Runs the `service` PAM stack for `user_name`, sending its prompts along until it's done
+pub fn authenticate(service: String, user_name: String) -> impl Stream<Item = KeyEvent> {
+    iced::stream::channel(4, move |mut output| async move {
+        let (events, mut events_rx) = mpsc::unbounded();
+        let adapter = KeyAuthAdapter {
+            user_name: user_name.clone(),
+            events: events.clone(),
+        };
+
+        tokio::task::spawn_blocking(move || {
+            let res = run_pam(&service, user_name, adapter).map_err(|err| err.to_string());
+            let _ = events.unbounded_send(KeyEvent::Done(res));
+        });
+
+        while let Some(event) = events_rx.next().await {
+            let done = matches!(event, KeyEvent::Done(_));
+
+            if output.send(event).await.is_err() || done {
+                return;
+            }
+        }
+
+        // The blocking task went away without a result
+        let _ = output
+            .send(KeyEvent::Done(Err("Security key check stopped".into())))
+            .await;
+    })
+}
+
+/// Stands in for the PAM module in demo mode, any "touch" succeeds after a moment
+pub fn demo_authenticate() -> impl Stream<Item = KeyEvent> {
+    iced::stream::channel(2, move |mut output| async move {
+        let _ = output
+            .send(KeyEvent::Prompt("Touch your security key (demo)".into()))
+            .await;
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let _ = output.send(KeyEvent::Done(Ok(()))).await;
+    })
+}
+
+fn run_pam(service: &str, user_name: String, adapter: KeyAuthAdapter) -> LeaperLockResult<()> {
+    let mut auth = nonstick::TransactionBuilder::new_with_service(service)
+        .username(user_name)
+        .build(adapter.into_conversation())?;
+
+    auth.authenticate(AuthnFlags::empty())?;
+    auth.account_management(AuthnFlags::empty())?;
+
+    Ok(())
+}
+
+struct KeyAuthAdapter {
+    user_name: String,
+    events: mpsc::UnboundedSender<KeyEvent>,
+}
+
+impl KeyAuthAdapter {
+    fn show(&self, message: impl AsRef<std::ffi::OsStr>) {
+        let message = message.as_ref().to_string_lossy().trim().to_string();
+
+        if !message.is_empty() {
+            let _ = self.events.unbounded_send(KeyEvent::Prompt(message));
+        }
+    }
+}
+
+impl ConversationAdapter for KeyAuthAdapter {
+    fn prompt(
+        &self,
+        _request: impl AsRef<std::ffi::OsStr>,
+    ) -> nonstick::Result<std::ffi::OsString> {
+        Ok((&self.user_name).into())
+    }
+
+    /// Keys with a PIN of their own aren't supported, there's nothing to answer with
+    fn masked_prompt(
+        &self,
+        request: impl AsRef<std::ffi::OsStr>,
+    ) -> nonstick::Result<std::ffi::OsString> {
+        tracing::warn!(
+            "[leaper-lock-key] Can't answer {:?}, only presence checks are supported",
+            request.as_ref()
+        );
+
+        Err(nonstick::ErrorCode::ConversationError)
+    }
+
+    fn error_msg(&self, message: impl AsRef<std::ffi::OsStr>) {
+        tracing::error!("[leaper-lock-key] {}", message.as_ref().to_string_lossy());
+        self.show(message);
+    }
+
+    fn info_msg(&self, message: impl AsRef<std::ffi::OsStr>) {
+        tracing::info!("[leaper-lock-key] {}", message.as_ref().to_string_lossy());
+        self.show(message);
+    }
+}
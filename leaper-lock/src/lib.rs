@@ -1,11 +1,22 @@
-use std::{sync::Arc, time::Duration};
+mod account;
+mod key;
+mod pin;
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use directories::ProjectDirs;
 use iced::{
     Length,
     alignment::{Horizontal, Vertical},
+    futures::StreamExt,
     keyboard,
-    widget::{button, center, column, container, row, text, text_input},
+    widget::{button, center, column, container, image, row, text, text_input},
 };
 use iced_aw::Spinner;
 use iced_fonts::{NERD_FONT, NERD_FONT_BYTES, Nerd, REQUIRED_FONT_BYTES, nerd::icon_to_string};
@@ -15,16 +26,41 @@ use macros::lerror;
 use mode::{
     LeaperModeMultiWindow,
     config::{LeaperAppModeConfigError, LeaperModeConfig},
+    wayland::{SESSION_LOCK, WaylandError, WaylandGlobals},
 };
 use nonstick::{AuthnFlags, ConversationAdapter, Transaction};
+use style::osk::{OskKey, OskLayout, on_screen_keyboard};
+
+use crate::{
+    account::UserInfo,
+    key::{KeyEvent, KeyState},
+    pin::PinStore,
+};
+
+static DEMO: AtomicBool = AtomicBool::new(false);
 
 pub struct LeaperLock {
     config: LeaperModeConfig,
 
     user_name: String,
+    user_info: Option<UserInfo>,
     password: String,
 
+    pin: Option<PinStore>,
+    pin_input: String,
+    pin_failures: u8,
+
+    key: KeyState,
+
     auth_in_progress: bool,
+    /// Bumped on resume, results of checks started before it are dropped
+    auth_attempt: u64,
+    osk: bool,
+    /// A lock surface got opened, which the compositor only does for a lock it granted.
+    /// LockedHint isn't set before that, see [`LeaperLock::set_locked_hint`]
+    locked: bool,
+    /// See [`LeaperLock::start_demo`]
+    demo: bool,
 }
 
 impl LeaperModeMultiWindow for LeaperLock {
@@ -36,10 +72,19 @@ impl LeaperModeMultiWindow for LeaperLock {
         let project_dirs =
             ProjectDirs::from("com", "tukanoid", "leaper").ok_or(Self::RunError::NoProjectDirs)?;
         let config = LeaperModeConfig::open(&project_dirs)?;
+        config.renderer.apply();
 
         let uid = nix::unistd::Uid::current();
         let user = nix::unistd::User::from_uid(uid)?.ok_or(LeaperLockError::NoUserFound)?;
 
+        if DEMO.load(Ordering::Relaxed) {
+            return Self::run_demo(project_dirs, config, user.name);
+        }
+
+        if !WaylandGlobals::query()?.has(SESSION_LOCK) {
+            return Err(LeaperLockError::NoSessionLock);
+        }
+
         iced_sessionlock::build_pattern::application(Self::update, Self::view)
             .subscription(Self::subscription)
             .theme(Self::theme)
@@ -51,22 +96,43 @@ impl LeaperModeMultiWindow for LeaperLock {
     }
 
     fn init(
-        _project_dirs: ProjectDirs,
+        project_dirs: ProjectDirs,
         config: LeaperModeConfig,
         user_name: Self::InitArgs,
     ) -> (Self, Self::Task)
     where
         Self: Sized,
     {
+        let demo = DEMO.load(Ordering::Relaxed);
+        let task = match config.lock.show_user {
+            true => Self::Task::perform(UserInfo::load(user_name.clone()), LeaperLockMsg::UserInfo),
+            false => Self::Task::none(),
+        };
+        let task = match config.lock.security_key.enabled && config.lock.security_key.auto_start {
+            true => Self::Task::batch([task, Self::Task::done(LeaperLockMsg::UseKey)]),
+            false => task,
+        };
+        let osk = config.osk.enabled();
+        let pin = Some(PinStore::new(&project_dirs)).filter(PinStore::is_set);
         let lock = Self {
             config,
 
             user_name,
+            user_info: None,
             password: String::new(),
 
+            pin,
+            pin_input: String::new(),
+            pin_failures: 0,
+
+            key: KeyState::Idle,
+
             auth_in_progress: false,
+            auth_attempt: 0,
+            osk,
+            locked: false,
+            demo,
         };
-        let task = Self::Task::none();
 
         (lock, task)
     }
@@ -93,41 +159,24 @@ impl LeaperModeMultiWindow for LeaperLock {
 
                     style
                 }),
-                row![
-                    text_input("Enter you password...", &self.password)
-                        .width(Length::Fill)
-                        .size(20)
-                        .padding(10.0)
-                        .on_input_maybe(
-                            (!self.auth_in_progress).then_some(LeaperLockMsg::EnterPassword)
-                        )
-                        .on_submit_maybe(
-                            (!self.auth_in_progress).then_some(LeaperLockMsg::ConfirmPassword)
-                        )
-                        .secure(true)
-                        .style(style::text_input),
-                    button(
-                        text(icon_to_string(Nerd::TriangleRight))
-                            .font(NERD_FONT)
-                            .size(25.0)
-                            .align_x(Horizontal::Center)
-                            .align_y(Vertical::Center)
-                    )
-                    .width(40.0)
-                    .height(40.0)
-                    .style(style::grid_button)
-                    .on_press_maybe(
-                        (!self.auth_in_progress).then_some(LeaperLockMsg::ConfirmPassword)
-                    )
-                ]
-                .push_maybe(
-                    self.auth_in_progress
-                        .then(|| Spinner::new().width(20).height(20))
-                )
-                .width(600.0)
-                .spacing(15)
-                .align_y(Vertical::Center),
             ]
+            .push_maybe(self.user_info.as_ref().map(|info| self.user_greeting(info)))
+            .push(match (self.key.waiting(), self.pin_mode()) {
+                (true, _) => self.key_prompt(),
+                (false, true) => self.pin_input(),
+                (false, false) => self.password_input(),
+            })
+            .push_maybe(self.key_status())
+            .push_maybe(
+                ((self.osk || self.pin_mode()) && !self.auth_in_progress).then(|| {
+                    let layout = match self.pin_mode() {
+                        true => OskLayout::PinPad,
+                        false => OskLayout::Qwerty,
+                    };
+
+                    on_screen_keyboard(layout, LeaperLockMsg::OskKey)
+                }),
+            )
             .align_x(Horizontal::Center)
             .spacing(50),
         )
@@ -137,21 +186,131 @@ impl LeaperModeMultiWindow for LeaperLock {
     fn update(&mut self, msg: Self::Msg) -> Self::Task {
         match msg {
             LeaperLockMsg::SecondTick => {}
+            LeaperLockMsg::Resumed => {
+                // The clock redraws along with this message, in-flight PAM/PIN checks
+                // might be stuck on a conversation from before the suspend
+                tracing::info!("Resumed from suspend, resetting authentication");
+
+                self.auth_attempt += 1;
+                self.auth_in_progress = false;
+                self.password.clear();
+                self.pin_input.clear();
+
+                if self.key.waiting() {
+                    self.key = KeyState::Idle;
+
+                    if self.config.lock.security_key.auto_start {
+                        return Self::Task::done(Self::Msg::UseKey);
+                    }
+                }
+            }
+            LeaperLockMsg::UserInfo(info) => self.user_info = Some(info),
             LeaperLockMsg::FailedLock(err) => {
                 self.auth_in_progress = false;
                 tracing::error!("{err}");
             }
 
             LeaperLockMsg::EnterPassword(new_pass) => self.password = new_pass,
+            // The keyboard is hidden while checking, a stale tap shouldn't start another one
+            LeaperLockMsg::OskKey(_) if self.auth_in_progress => {}
+            LeaperLockMsg::OskKey(key) => {
+                let (mut input, enter, confirm): (String, fn(String) -> Self::Msg, _) =
+                    match self.pin_mode() {
+                        true => (
+                            self.pin_input.clone(),
+                            Self::Msg::EnterPin,
+                            Self::Msg::ConfirmPin,
+                        ),
+                        false => (
+                            self.password.clone(),
+                            Self::Msg::EnterPassword,
+                            Self::Msg::ConfirmPassword,
+                        ),
+                    };
+
+                // Through the same checks as typing
+                return match key {
+                    OskKey::Char(ch) => {
+                        input.push(ch);
+                        Self::Task::done(enter(input))
+                    }
+                    OskKey::Backspace => {
+                        input.pop();
+                        Self::Task::done(enter(input))
+                    }
+                    OskKey::Enter => Self::Task::done(confirm),
+                };
+            }
+
+            LeaperLockMsg::EnterPin(new_pin) => {
+                if new_pin.len() <= PinStore::MAX_LEN
+                    && new_pin.chars().all(|ch| ch.is_ascii_digit())
+                {
+                    self.pin_input = new_pin;
+                }
+            }
+            LeaperLockMsg::ConfirmPin => {
+                let Some(pin) = self.pin.clone() else {
+                    return Self::Task::none();
+                };
+                let input = std::mem::take(&mut self.pin_input);
+                let attempt = self.auth_attempt;
+
+                self.auth_in_progress = true;
+
+                return Self::Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || pin.verify(&input))
+                            .await
+                            .map_err(|err| LeaperLockError::PinTask(err.to_string()))?
+                    },
+                    move |res| LeaperLockMsg::PinChecked(attempt, res),
+                );
+            }
+            LeaperLockMsg::PinChecked(attempt, _)
+            | LeaperLockMsg::PasswordChecked(attempt, _)
+            | LeaperLockMsg::Key(attempt, _)
+                if attempt != self.auth_attempt =>
+            {
+                tracing::debug!("Dropping the result of a check started before the suspend");
+            }
+            LeaperLockMsg::PinChecked(_, res) => {
+                self.auth_in_progress = false;
+
+                match res {
+                    Ok(true) => return Self::Task::done(Self::Msg::UnLock),
+                    Ok(false) => {
+                        self.pin_failures += 1;
+                        tracing::warn!(
+                            "Wrong PIN [{}/{}]",
+                            self.pin_failures,
+                            self.config.lock.pin_attempts
+                        );
+                    }
+                    Err(err) => {
+                        tracing::error!("Failed to check the PIN, falling back to password: {err}");
+                        self.pin = None;
+                    }
+                }
+            }
+            LeaperLockMsg::UsePassword => self.pin = None,
             LeaperLockMsg::ConfirmPassword => {
                 let auth_adapter = LeaperAuthAdapter {
                     user_name: self.user_name.clone(),
                     password: self.password.clone(),
                 };
                 let user_name = self.user_name.clone();
+                let attempt = self.auth_attempt;
 
                 self.auth_in_progress = true;
 
+                if self.demo {
+                    return Self::Task::perform(
+                        Self::demo_authenticate(self.password.clone()),
+                        move |res| LeaperLockMsg::PasswordChecked(attempt, res),
+                    );
+                }
+
                 return Self::Task::perform(
                     async move {
                         let mut auth =
@@ -164,25 +323,98 @@ impl LeaperModeMultiWindow for LeaperLock {
 
                         LeaperLockResult::Ok(())
                     },
-                    |res| match res {
-                        Ok(_) => LeaperLockMsg::UnLock,
-                        Err(err) => LeaperLockMsg::FailedLock(err.to_string()),
+                    move |res| {
+                        LeaperLockMsg::PasswordChecked(attempt, res.map_err(|err| err.to_string()))
                     },
                 );
             }
+            LeaperLockMsg::UseKey => {
+                if !self.config.lock.security_key.enabled || self.auth_in_progress {
+                    return Self::Task::none();
+                }
+
+                let attempt = self.auth_attempt;
+
+                self.key = KeyState::Waiting(None);
+                self.auth_in_progress = true;
+
+                let events = match self.demo {
+                    true => key::demo_authenticate().boxed(),
+                    false => key::authenticate(
+                        self.config.lock.security_key.pam_service.clone(),
+                        self.user_name.clone(),
+                    )
+                    .boxed(),
+                };
 
+                return Self::Task::run(events, move |event| LeaperLockMsg::Key(attempt, event));
+            }
+            LeaperLockMsg::CancelKey => {
+                // The PAM check can't be interrupted, its result gets dropped instead
+                self.auth_attempt += 1;
+                self.auth_in_progress = false;
+                self.key = KeyState::Idle;
+            }
+            LeaperLockMsg::Key(_, KeyEvent::Prompt(prompt)) => {
+                self.key = KeyState::Waiting(Some(prompt));
+            }
+            LeaperLockMsg::Key(_, KeyEvent::Done(res)) => {
+                self.auth_in_progress = false;
+
+                match res {
+                    Ok(()) => {
+                        self.key = KeyState::Idle;
+                        return Self::Task::done(Self::Msg::UnLock);
+                    }
+                    Err(err) => {
+                        tracing::warn!("Security key check failed: {err}");
+                        self.key = KeyState::Failed(err);
+                    }
+                }
+            }
+            LeaperLockMsg::PasswordChecked(_, res) => match res {
+                Ok(()) => return Self::Task::done(Self::Msg::UnLock),
+                Err(err) => return Self::Task::done(Self::Msg::FailedLock(err)),
+            },
+
+            LeaperLockMsg::IcedEvent(iced::Event::Window(iced::window::Event::Opened {
+                ..
+            })) if !self.demo && !self.locked => {
+                self.locked = true;
+                return Self::Task::future(Self::set_locked_hint(true)).discard();
+            }
             LeaperLockMsg::IcedEvent(ev) => {
+                if self.demo
+                    && let iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                        key: keyboard::Key::Named(keyboard::key::Named::Escape),
+                        ..
+                    }) = ev
+                {
+                    return iced::exit();
+                }
+
                 if !self.auth_in_progress
                     && let iced::Event::Keyboard(keyboard::Event::KeyPressed {
                         key: keyboard::Key::Named(keyboard::key::Named::Enter),
                         ..
                     }) = ev
                 {
-                    return Self::Task::done(Self::Msg::ConfirmPassword);
+                    return Self::Task::done(match self.pin_mode() {
+                        true => Self::Msg::ConfirmPin,
+                        false => Self::Msg::ConfirmPassword,
+                    });
                 }
             }
 
-            LeaperLockMsg::UnLock => return Self::Task::done(msg),
+            LeaperLockMsg::UnLock if self.demo => {
+                tracing::info!("Unlocked, closing the demo");
+                return iced::exit();
+            }
+            LeaperLockMsg::UnLock => {
+                return Self::Task::future(Self::set_locked_hint(false))
+                    .discard()
+                    .chain(Self::Task::done(msg));
+            }
         }
 
         Self::Task::none()
@@ -195,7 +427,11 @@ impl LeaperModeMultiWindow for LeaperLock {
                 "second-timer",
                 iced::stream::channel(1, move |mut sender| async move {
                     loop {
-                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        // Woken up right after the second changes, so the clock redraws
+                        // once per second and never lags behind
+                        let into_second = chrono::Local::now().timestamp_subsec_millis() as u64;
+                        tokio::time::sleep(Duration::from_millis(1000 - into_second.min(999)))
+                            .await;
 
                         if let Err(err) = sender.start_send(LeaperLockMsg::SecondTick) {
                             tracing::error!(
@@ -205,6 +441,14 @@ impl LeaperModeMultiWindow for LeaperLock {
                     }
                 }),
             ),
+            Self::Subscription::run_with_id(
+                "sleep-watch",
+                iced::stream::channel(1, move |mut sender| async move {
+                    if let Err(err) = Self::watch_sleep(&mut sender).await {
+                        tracing::warn!("Not watching for suspend/resume: {err}");
+                    }
+                }),
+            ),
         ])
     }
 
@@ -217,6 +461,288 @@ impl LeaperModeMultiWindow for LeaperLock {
     }
 }
 
+impl LeaperLock {
+    /// Accepted by the stubbed authentication of the demo mode
+    pub const DEMO_PASSWORD: &'static str = "demo";
+    const DEMO_WINDOW_SIZE: (f32, f32) = (1280.0, 800.0);
+
+    /// Runs the lock UI in a regular window without locking the session,
+    /// PAM is stubbed to only accept [`Self::DEMO_PASSWORD`] and Escape closes the window
+    pub fn start_demo() {
+        DEMO.store(true, Ordering::Relaxed);
+    }
+
+    fn run_demo(
+        project_dirs: ProjectDirs,
+        config: LeaperModeConfig,
+        user_name: String,
+    ) -> LeaperLockResult<()> {
+        tracing::warn!(
+            "Running the lock in demo mode, the session is NOT locked (password: {:?})",
+            Self::DEMO_PASSWORD
+        );
+
+        iced::application(Self::title, Self::update, Self::demo_view)
+            .window(iced::window::Settings {
+                size: Self::DEMO_WINDOW_SIZE.into(),
+                platform_specific: iced::window::settings::PlatformSpecific {
+                    application_id: "com.tukanoid.leaper.lock-demo".into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .subscription(Self::subscription)
+            .theme(Self::theme)
+            .font(REQUIRED_FONT_BYTES)
+            .font(NERD_FONT_BYTES)
+            .run_with(|| Self::init(project_dirs, config, user_name))?;
+
+        Ok(())
+    }
+
+    /// The lock only has one window in demo mode, its id doesn't matter to [`Self::view`]
+    fn demo_view(&self) -> <Self as LeaperModeMultiWindow>::Element<'_> {
+        self.view(iced::window::Id::unique())
+    }
+
+    /// Stands in for the PAM conversation, with a delay so the spinner shows up
+    async fn demo_authenticate(password: String) -> Result<(), String> {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        match password == Self::DEMO_PASSWORD {
+            true => Ok(()),
+            false => Err(format!(
+                "Wrong password, the demo only accepts {:?}",
+                Self::DEMO_PASSWORD
+            )),
+        }
+    }
+
+    /// Tells logind whether the session is locked, power actions waiting for the lock
+    /// (`power.lock_first`) watch for it
+    async fn set_locked_hint(locked: bool) {
+        let res = async {
+            let connection = zbus::Connection::system().await?;
+            connection
+                .call_method(
+                    Some("org.freedesktop.login1"),
+                    "/org/freedesktop/login1/session/auto",
+                    Some("org.freedesktop.login1.Session"),
+                    "SetLockedHint",
+                    &(locked,),
+                )
+                .await?;
+
+            LeaperLockResult::Ok(())
+        };
+
+        if let Err(err) = res.await {
+            tracing::warn!("Failed to set the session LockedHint to {locked}: {err}");
+        }
+    }
+
+    /// Sends [`LeaperLockMsg::Resumed`] whenever logind reports the system woke up
+    async fn watch_sleep(
+        sender: &mut iced::futures::channel::mpsc::Sender<LeaperLockMsg>,
+    ) -> LeaperLockResult<()> {
+        let connection = zbus::Connection::system().await?;
+        let rule = zbus::MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .sender("org.freedesktop.login1")?
+            .interface("org.freedesktop.login1.Manager")?
+            .member("PrepareForSleep")?
+            .build();
+        let mut signals = zbus::MessageStream::for_match_rule(rule, &connection, None).await?;
+
+        while let Some(signal) = signals.next().await {
+            let going_to_sleep: bool = signal?.body().deserialize()?;
+
+            if !going_to_sleep && let Err(err) = sender.start_send(LeaperLockMsg::Resumed) {
+                tracing::error!("Failed to send Resumed message to main thread: {err}");
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_pin() -> LeaperLockResult<()> {
+        let project_dirs =
+            ProjectDirs::from("com", "tukanoid", "leaper").ok_or(LeaperLockError::NoProjectDirs)?;
+
+        let pin = rpassword::prompt_password("New PIN: ")?;
+
+        if rpassword::prompt_password("Repeat PIN: ")? != pin {
+            return Err(LeaperLockError::PinMismatch);
+        }
+
+        PinStore::new(&project_dirs).set(&pin)?;
+        println!("PIN set, it can be used to unlock instead of the password");
+
+        Ok(())
+    }
+
+    pub fn clear_pin() -> LeaperLockResult<()> {
+        let project_dirs =
+            ProjectDirs::from("com", "tukanoid", "leaper").ok_or(LeaperLockError::NoProjectDirs)?;
+
+        PinStore::new(&project_dirs).clear()
+    }
+
+    /// PIN unlock is offered until it fails `pin_attempts` times
+    fn pin_mode(&self) -> bool {
+        self.pin.is_some() && self.pin_failures < self.config.lock.pin_attempts
+    }
+
+    fn password_input(&self) -> <Self as LeaperModeMultiWindow>::Element<'_> {
+        row![
+            text_input("Enter you password...", &self.password)
+                .width(Length::Fill)
+                .size(20)
+                .padding(10.0)
+                .on_input_maybe((!self.auth_in_progress).then_some(LeaperLockMsg::EnterPassword))
+                .on_submit_maybe((!self.auth_in_progress).then_some(LeaperLockMsg::ConfirmPassword))
+                .secure(true)
+                .style(style::text_input),
+            Self::confirm_button(
+                (!self.auth_in_progress).then_some(LeaperLockMsg::ConfirmPassword)
+            )
+        ]
+        .push_maybe(
+            self.auth_in_progress
+                .then(|| Spinner::new().width(20).height(20)),
+        )
+        .width(600.0)
+        .spacing(15)
+        .align_y(Vertical::Center)
+        .into()
+    }
+
+    fn pin_input(&self) -> <Self as LeaperModeMultiWindow>::Element<'_> {
+        let attempts_left = self.config.lock.pin_attempts - self.pin_failures;
+
+        column![
+            row![
+                text_input("Enter your PIN...", &self.pin_input)
+                    .width(Length::Fill)
+                    .size(20)
+                    .padding(10.0)
+                    .on_input_maybe((!self.auth_in_progress).then_some(LeaperLockMsg::EnterPin))
+                    .on_submit_maybe((!self.auth_in_progress).then_some(LeaperLockMsg::ConfirmPin))
+                    .secure(true)
+                    .style(style::text_input),
+                Self::confirm_button((!self.auth_in_progress).then_some(LeaperLockMsg::ConfirmPin))
+            ]
+            .push_maybe(
+                self.auth_in_progress
+                    .then(|| Spinner::new().width(20).height(20)),
+            )
+            .spacing(15)
+            .align_y(Vertical::Center),
+            row![
+                text(format!("{attempts_left} attempts left")).size(16),
+                button(text("Use password").size(16))
+                    .style(button::text)
+                    .on_press_maybe((!self.auth_in_progress).then_some(LeaperLockMsg::UsePassword))
+            ]
+            .spacing(15)
+            .align_y(Vertical::Center),
+        ]
+        .width(300.0)
+        .spacing(10)
+        .align_x(Horizontal::Center)
+        .into()
+    }
+
+    /// Replaces the password field while waiting for the key
+    fn key_prompt(&self) -> <Self as LeaperModeMultiWindow>::Element<'_> {
+        let prompt = match &self.key {
+            KeyState::Waiting(Some(prompt)) => prompt.as_str(),
+            _ => "Touch your security key",
+        };
+
+        column![
+            row![
+                text(icon_to_string(Nerd::Key)).font(NERD_FONT).size(30),
+                text(prompt).size(20),
+                Spinner::new().width(20).height(20),
+            ]
+            .spacing(15)
+            .align_y(Vertical::Center),
+            button(text("Use password").size(16))
+                .style(button::text)
+                .on_press(LeaperLockMsg::CancelKey),
+        ]
+        .spacing(10)
+        .align_x(Horizontal::Center)
+        .into()
+    }
+
+    /// Offers the security key under the password/PIN field, along with why it failed last
+    fn key_status(&self) -> Option<<Self as LeaperModeMultiWindow>::Element<'_>> {
+        if !self.config.lock.security_key.enabled || self.key.waiting() {
+            return None;
+        }
+
+        let failed = match &self.key {
+            KeyState::Failed(err) => Some(text(err).size(16)),
+            _ => None,
+        };
+
+        Some(
+            row![]
+                .push_maybe(failed)
+                .push(
+                    button(text("Use security key").size(16))
+                        .style(button::text)
+                        .on_press_maybe((!self.auth_in_progress).then_some(LeaperLockMsg::UseKey)),
+                )
+                .spacing(15)
+                .align_y(Vertical::Center)
+                .into(),
+        )
+    }
+
+    fn confirm_button<'a>(
+        on_press: Option<LeaperLockMsg>,
+    ) -> <Self as LeaperModeMultiWindow>::Element<'a> {
+        button(
+            text(icon_to_string(Nerd::TriangleRight))
+                .font(NERD_FONT)
+                .size(25.0)
+                .align_x(Horizontal::Center)
+                .align_y(Vertical::Center),
+        )
+        .width(40.0)
+        .height(40.0)
+        .style(style::grid_button)
+        .on_press_maybe(on_press)
+        .into()
+    }
+
+    fn user_greeting<'a>(
+        &'a self,
+        info: &'a UserInfo,
+    ) -> <Self as LeaperModeMultiWindow>::Element<'a> {
+        let name = info.real_name.as_deref().unwrap_or(&self.user_name);
+
+        column![]
+            .push_maybe(info.avatar.as_ref().map(|avatar| {
+                container(image(avatar).width(120).height(120))
+                    .style(|theme| {
+                        let mut style = container::bordered_box(theme);
+                        style.border = style.border.rounded(60.0).width(2);
+
+                        style
+                    })
+                    .padding(4)
+            }))
+            .push(text(format!("Welcome back, {name}")).size(30))
+            .align_x(Horizontal::Center)
+            .spacing(15)
+            .into()
+    }
+}
+
 pub struct LeaperAuthAdapter {
     user_name: String,
     password: String,
@@ -250,29 +776,63 @@ impl nonstick::ConversationAdapter for LeaperAuthAdapter {
 #[derive(Debug, Clone)]
 pub enum LeaperLockMsg {
     SecondTick,
+    /// logind's PrepareForSleep(false)
+    Resumed,
     FailedLock(String),
+    UserInfo(UserInfo),
 
     EnterPassword(String),
     ConfirmPassword,
+    /// Tagged with [`LeaperLock::auth_attempt`] at the time the check started
+    PasswordChecked(u64, Result<(), String>),
+    OskKey(OskKey),
+
+    EnterPin(String),
+    ConfirmPin,
+    PinChecked(u64, LeaperLockResult<bool>),
+    UsePassword,
+
+    /// Starts waiting for the security key, see `lock.security_key`
+    UseKey,
+    CancelKey,
+    Key(u64, KeyEvent),
 
     IcedEvent(iced::Event),
 }
 
 #[lerror]
-#[lerr(prefix = "[leaper-lock]", result_name = LeaperLockResult)]
+#[lerr(prefix = "[leaper-lock]", result_name = LeaperLockResult, common)]
 pub enum LeaperLockError {
+    #[lerr(str = "[iced] {0}")]
+    Iced(#[lerr(from, wrap = Arc)] iced::Error),
     #[lerr(str = "[iced_sessionlock] {0}")]
     SessionLock(#[lerr(from, wrap = Arc)] iced_sessionlock::Error),
     #[lerr(str = "[nonstick] {0}")]
     Nonstick(#[lerr(from)] nonstick::ErrorCode),
     #[lerr(str = "[nix] {0}")]
     Nix(#[lerr(from)] nix::Error),
+    #[lerr(str = "[zbus] {0}")]
+    ZBus(#[lerr(from)] zbus::Error),
+    #[lerr(str = "[argon2] {0}")]
+    PasswordHash(#[lerr(from)] argon2::password_hash::Error),
 
     #[lerr(str = "{0}")]
     Config(#[lerr(from)] LeaperAppModeConfigError),
+    #[lerr(str = "{0}")]
+    Wayland(#[lerr(from)] WaylandError),
 
+    #[lerr(str = "The compositor doesn't support ext-session-lock-v1, \
+        use a compositor that implements it or your desktop's own locker \
+        (e.g. `loginctl lock-session`)")]
+    NoSessionLock,
     #[lerr(str = "No ProjectDirs!")]
     NoProjectDirs,
     #[lerr(str = "No User found!")]
     NoUserFound,
+    #[lerr(str = "PIN must be 4 to 12 digits")]
+    InvalidPin,
+    #[lerr(str = "PINs don't match")]
+    PinMismatch,
+    #[lerr(str = "PIN check task failed: {0}")]
+    PinTask(String),
 }
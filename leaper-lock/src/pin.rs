@@ -0,0 +1,82 @@
+use std::{os::unix::fs::OpenOptionsExt, path::PathBuf};
+
+use argon2::{
+    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
+    password_hash::{SaltString, rand_core::OsRng},
+};
+use directories::ProjectDirs;
+
+use leaper_error::IntoContext;
+
+use crate::{LeaperLockError, LeaperLockResult};
+
+/// Argon2 hash of the quick unlock PIN, kept in the state dir
+/// and readable only by the user
+#[derive(Debug, Clone)]
+pub struct PinStore {
+    path: PathBuf,
+}
+
+impl PinStore {
+    pub const MIN_LEN: usize = 4;
+    pub const MAX_LEN: usize = 12;
+
+    pub fn new(dirs: &ProjectDirs) -> Self {
+        let dir = dirs.state_dir().unwrap_or(dirs.data_local_dir());
+
+        Self {
+            path: dir.join("pin"),
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.path.is_file()
+    }
+
+    pub fn set(&self, pin: &str) -> LeaperLockResult<()> {
+        if !(Self::MIN_LEN..=Self::MAX_LEN).contains(&pin.len())
+            || !pin.chars().all(|ch| ch.is_ascii_digit())
+        {
+            return Err(LeaperLockError::InvalidPin);
+        }
+
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default().hash_password(pin.as_bytes(), &salt)?;
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .err_context::<LeaperLockError>(format!("Creating {dir:?}"))?;
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&self.path)
+            .err_context::<LeaperLockError>(format!("Opening {:?}", self.path))?;
+        std::io::Write::write_all(&mut file, hash.to_string().as_bytes())
+            .err_context::<LeaperLockError>(format!("Writing {:?}", self.path))?;
+
+        Ok(())
+    }
+
+    pub fn clear(&self) -> LeaperLockResult<()> {
+        if self.is_set() {
+            std::fs::remove_file(&self.path)
+                .err_context::<LeaperLockError>(format!("Removing {:?}", self.path))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn verify(&self, pin: &str) -> LeaperLockResult<bool> {
+        let stored = std::fs::read_to_string(&self.path)
+            .err_context::<LeaperLockError>(format!("Reading {:?}", self.path))?;
+        let hash = PasswordHash::new(stored.trim())?;
+
+        Ok(Argon2::default()
+            .verify_password(pin.as_bytes(), &hash)
+            .is_ok())
+    }
+}
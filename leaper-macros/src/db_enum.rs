@@ -0,0 +1,35 @@
+use darling::{FromDeriveInput, FromVariant, ast::Data, util::Ignored};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Ident;
+
+use crate::util::DeriveInputUtil;
+
+#[derive(FromDeriveInput)]
+#[darling(supports(enum_unit))]
+pub struct DBEnum {
+    ident: Ident,
+    data: Data<DBEnumVariant, Ignored>,
+}
+
+impl DeriveInputUtil for DBEnum {
+    fn gen_(&self) -> manyhow::Result<TokenStream> {
+        let Self { ident, data } = self;
+
+        let variants = match data {
+            Data::Enum(items) => items.iter().map(|var| &var.ident),
+            Data::Struct(_) => unreachable!(),
+        };
+
+        Ok(quote! {
+            impl ::db::DBEnum for #ident {
+                const ALL: &'static [Self] = &[#(Self::#variants),*];
+            }
+        })
+    }
+}
+
+#[derive(FromVariant)]
+struct DBEnumVariant {
+    ident: Ident,
+}
@@ -17,6 +17,8 @@ pub struct LError {
     data: Data<LErrorVariant, Ignored>,
     result_name: Option<Ident>,
     prefix: Option<LitStr>,
+    /// Adds the `leaper_error` common variants and context support
+    common: Flag,
 }
 
 impl DeriveInputUtil for LError {
@@ -27,6 +29,7 @@ impl DeriveInputUtil for LError {
             data,
             result_name,
             prefix,
+            common,
         } = self;
 
         let variants = match data {
@@ -41,19 +44,84 @@ impl DeriveInputUtil for LError {
             .as_ref()
             .map(|ty| quote! { #vis type #ty<T> = Result<T, #ident>; });
 
+        let (common_vars, common_impls) = match common.is_present() {
+            true => {
+                let (vars, impls) = gen_common(ident, prefix);
+                (Some(vars), Some(impls))
+            }
+            false => (None, None),
+        };
+
         Ok(quote! {
             #[derive(Debug, Clone, thiserror::Error)]
             #vis enum #ident {
-                #(#enum_vars),*
+                #(#enum_vars,)*
+                #common_vars
             }
 
             #(#froms)*
 
+            #common_impls
+
             #result_ty
         })
     }
 }
 
+/// `Common`/`Context` variants and the conversions from everything `CommonError` covers
+fn gen_common(ident: &Ident, prefix: &Option<LitStr>) -> (TokenStream, TokenStream) {
+    let common_str = match prefix {
+        Some(prefix) => LitStr::new(&format!("{} {{0}}", prefix.value()), prefix.span()),
+        None => LitStr::new("{0}", ident.span()),
+    };
+
+    let vars = quote! {
+        #[error(#common_str)]
+        Common(#[source] ::leaper_error::CommonError),
+        #[error("{context}")]
+        Context {
+            context: String,
+            source: Box<#ident>,
+        },
+    };
+
+    let sources = [
+        quote!(::leaper_error::CommonError),
+        quote!(std::io::Error),
+        quote!(std::sync::Arc<std::io::Error>),
+        quote!(::leaper_error::JoinError),
+        quote!(std::sync::Arc<::leaper_error::JoinError>),
+    ];
+    let impls = quote! {
+        #(
+            impl From<#sources> for #ident {
+                fn from(val: #sources) -> Self {
+                    Self::Common(val.into())
+                }
+            }
+        )*
+
+        impl ::leaper_error::LeaperError for #ident {
+            fn context(self, context: String) -> Self {
+                Self::Context {
+                    context,
+                    source: Box::new(self),
+                }
+            }
+
+            fn common(&self) -> Option<&::leaper_error::CommonError> {
+                match self {
+                    Self::Common(err) => Some(err),
+                    Self::Context { source, .. } => source.common(),
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    (vars, impls)
+}
+
 #[derive(FromVariant)]
 #[darling(attributes(lerr))]
 struct LErrorVariant {
@@ -131,8 +199,8 @@ impl LErrorField {
             ident,
             ty,
             wrap,
+            from,
             backtrace,
-            ..
         } = self;
 
         let name = ident.as_ref().map(|i| quote!(#i:));
@@ -141,9 +209,12 @@ impl LErrorField {
             None => quote!(#ty),
         };
         let backtrace = backtrace.is_present().then(|| quote!(#[backtrace]));
+        // So what it was converted from shows up in reports' chains
+        let source = from.is_present().then(|| quote!(#[source]));
 
         quote! {
             #backtrace
+            #source
             #vis #name #ty
         }
     }
@@ -194,3 +265,142 @@ impl LErrorField {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::*;
+
+    fn parse(input: TokenStream) -> darling::Result<LError> {
+        LError::from_derive_input(&syn::parse2(input).unwrap())
+    }
+
+    fn expand(input: TokenStream) -> String {
+        parse(input)
+            .ok()
+            .and_then(|err| err.gen_().ok())
+            .expect("valid lerror input")
+            .to_string()
+    }
+
+    #[test]
+    fn prefix_is_prepended() {
+        let out = expand(quote! {
+            #[lerr(prefix = "[x]")]
+            enum E {
+                #[lerr(str = "boom")]
+                Boom,
+            }
+        });
+
+        assert!(out.contains(r#"# [error ("[x] boom")]"#), "{out}");
+    }
+
+    #[test]
+    fn result_alias() {
+        let out = expand(quote! {
+            #[lerr(result_name = EResult)]
+            pub enum E {
+                #[lerr(str = "boom")]
+                Boom,
+            }
+        });
+
+        assert!(
+            out.contains("pub type EResult < T > = Result < T , E > ;"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn wrap_generates_both_froms() {
+        let out = expand(quote! {
+            enum E {
+                #[lerr(str = "{0}")]
+                IO(#[lerr(from, wrap = Arc)] std::io::Error),
+            }
+        });
+
+        assert!(
+            out.contains("IO (# [source] Arc < std :: io :: Error >)"),
+            "{out}"
+        );
+        assert!(
+            out.contains("impl From < std :: io :: Error > for E"),
+            "{out}"
+        );
+        assert!(
+            out.contains("impl From < Arc < std :: io :: Error > > for E"),
+            "{out}"
+        );
+        assert!(out.contains("Self :: IO (Arc :: new (val))"), "{out}");
+    }
+
+    #[test]
+    fn from_without_wrap() {
+        let out = expand(quote! {
+            enum E {
+                #[lerr(str = "{inner}")]
+                Named { #[lerr(from)] inner: String },
+            }
+        });
+
+        assert!(out.contains("# [source] inner : String"), "{out}");
+        assert!(out.contains("impl From < String > for E"), "{out}");
+        assert!(out.contains("Self :: Named { inner : val }"), "{out}");
+        assert_eq!(out.matches("impl From").count(), 1, "{out}");
+    }
+
+    #[test]
+    fn no_from_no_impl() {
+        let out = expand(quote! {
+            enum E {
+                #[lerr(str = "{0}")]
+                Msg(String),
+            }
+        });
+
+        assert!(!out.contains("impl From"), "{out}");
+        assert!(!out.contains("source"), "{out}");
+    }
+
+    #[test]
+    fn missing_str() {
+        let err = parse(quote! {
+            enum E {
+                Boom,
+            }
+        })
+        .err()
+        .expect("variant without a message");
+
+        assert!(err.to_string().contains("str"), "{err}");
+    }
+
+    #[test]
+    fn unknown_field_attr() {
+        let err = parse(quote! {
+            enum E {
+                #[lerr(str = "{0}")]
+                IO(#[lerr(form)] std::io::Error),
+            }
+        })
+        .err()
+        .expect("misspelled `from`");
+
+        assert!(err.to_string().contains("form"), "{err}");
+    }
+
+    #[test]
+    fn structs_are_rejected() {
+        assert!(
+            parse(quote! {
+                struct E {
+                    msg: String,
+                }
+            })
+            .is_err()
+        );
+    }
+}
@@ -1,10 +1,30 @@
+mod db_enum;
 mod errors;
 mod util;
 
 use proc_macro2::TokenStream;
 
-use crate::{errors::LError, util::DeriveInputUtil};
+use crate::{db_enum::DBEnum, errors::LError, util::DeriveInputUtil};
 
+/// Error enum with a `thiserror` message per variant (`#[lerr(str = "...")]`) and `From`
+/// impls for the fields marked `#[lerr(from)]`. Errors are `Clone`, sources that aren't
+/// get wrapped, e.g. in an `Arc`:
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// #[leaper_macros::lerror]
+/// #[lerr(prefix = "[example]", result_name = ExampleResult)]
+/// enum ExampleError {
+///     #[lerr(str = "[std::io] {0}")]
+///     IO(#[lerr(from, wrap = Arc)] std::io::Error),
+/// }
+///
+/// let err: ExampleError = std::io::Error::other("io").into();
+/// assert_eq!(err.to_string(), "[example] [std::io] io");
+/// ```
+///
+/// What it rejects is in `tests/ui/fail`
 #[manyhow::manyhow]
 #[proc_macro_attribute]
 pub fn lerror(_attr: TokenStream, input: TokenStream) -> manyhow::Result<TokenStream> {
@@ -13,3 +33,13 @@ pub fn lerror(_attr: TokenStream, input: TokenStream) -> manyhow::Result<TokenSt
 
     Ok(res)
 }
+
+/// Lists the variants of a unit enum for `db::DBEnum`
+#[manyhow::manyhow]
+#[proc_macro_derive(DBEnum)]
+pub fn db_enum(input: TokenStream) -> manyhow::Result<TokenStream> {
+    let db_enum = DBEnum::parse(input)?;
+    let res = db_enum.gen_()?;
+
+    Ok(res)
+}
@@ -0,0 +1,9 @@
+use leaper_macros::DBEnum;
+
+#[derive(Clone, Copy, DBEnum)]
+enum Kind {
+    Image,
+    Other(u8),
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use leaper_macros::DBEnum;
+
+#[derive(Clone, Copy, DBEnum)]
+struct Kind;
+
+fn main() {}
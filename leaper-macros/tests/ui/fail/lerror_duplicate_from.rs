@@ -0,0 +1,11 @@
+use leaper_macros::lerror;
+
+#[lerror]
+enum Error {
+    #[lerr(str = "{0}")]
+    First(#[lerr(from)] std::fmt::Error),
+    #[lerr(str = "{0}")]
+    Second(#[lerr(from)] std::fmt::Error),
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use leaper_macros::lerror;
+
+#[lerror]
+enum Error {
+    Failed(String),
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use leaper_macros::lerror;
+
+#[lerror]
+struct Error(String);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use leaper_macros::lerror;
+
+#[lerror]
+enum Error {
+    #[lerr(str = "[std::io] {0}")]
+    IO(#[lerr(from)] std::io::Error),
+}
+
+fn main() {}
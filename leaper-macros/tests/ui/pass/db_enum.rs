@@ -0,0 +1,19 @@
+// The derive implements `::db::DBEnum`, this crate stands in for leaper-db
+extern crate self as db;
+
+use leaper_macros::DBEnum;
+
+pub trait DBEnum: Copy + 'static {
+    const ALL: &'static [Self];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, DBEnum)]
+enum Kind {
+    Image,
+    Video,
+    Other,
+}
+
+fn main() {
+    assert_eq!(Kind::ALL, [Kind::Image, Kind::Video, Kind::Other]);
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use leaper_macros::lerror;
+
+#[lerror]
+#[lerr(prefix = "[test]", result_name = TestResult)]
+pub enum TestError {
+    #[lerr(str = "[std::io] {0}")]
+    IO(#[lerr(from, wrap = Arc)] std::io::Error),
+    #[lerr(str = "[std::fmt] {0}")]
+    Fmt(#[lerr(from)] std::fmt::Error),
+    #[lerr(str = "[std::num] {inner}")]
+    Named {
+        #[lerr(from, wrap = Arc)]
+        inner: std::num::ParseIntError,
+    },
+    #[lerr(str = "{0} and {1}")]
+    NoFrom(String, usize),
+    #[lerr(str = "unit")]
+    Unit,
+}
+
+fn assert_clone<T: Clone>() {}
+
+fn main() {
+    assert_clone::<TestError>();
+
+    let _: TestError = std::io::Error::other("io").into();
+    let _: TestError = Arc::new(std::io::Error::other("io")).into();
+    let _: TestError = std::fmt::Error.into();
+
+    let parse_err = "x".parse::<u8>().unwrap_err();
+    let _: TestError = parse_err.clone().into();
+    let _: TestError = Arc::new(parse_err).into();
+
+    let res: TestResult<()> = Err(TestError::NoFrom("a".into(), 1));
+    assert_eq!(res.unwrap_err().to_string(), "[test] a and 1");
+    assert_eq!(TestError::Unit.to_string(), "[test] unit");
+}
@@ -0,0 +1,15 @@
+use leaper_macros::lerror;
+
+#[lerror]
+enum Error {
+    #[lerr(str = "failed: {0}")]
+    Failed(#[lerr(from)] std::fmt::Error),
+}
+
+fn main() {
+    let err: Error = std::fmt::Error.into();
+    assert_eq!(
+        err.to_string(),
+        "failed: an error occurred when formatting an argument"
+    );
+}
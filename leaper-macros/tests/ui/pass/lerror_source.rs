@@ -0,0 +1,21 @@
+use std::{error::Error as _, sync::Arc};
+
+use leaper_macros::lerror;
+
+#[lerror]
+#[lerr(prefix = "[test]")]
+enum Error {
+    #[lerr(str = "[std::io] {0}")]
+    IO(#[lerr(from, wrap = Arc)] std::io::Error),
+    #[lerr(str = "{0}")]
+    NoFrom(String),
+}
+
+fn main() {
+    let err: Error = std::io::Error::other("io").into();
+    let source = err.source().expect("from fields are the source");
+    assert!(source.is::<Arc<std::io::Error>>());
+    assert_eq!(source.to_string(), "io");
+
+    assert!(Error::NoFrom("msg".into()).source().is_none());
+}
@@ -0,0 +1,120 @@
+use iced::window::{Level, Position, settings::PlatformSpecific};
+
+use crate::wayland::{LAYER_SHELL, WaylandGlobals};
+
+/// Surface backend a mode gets run with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    LayerShell,
+    /// Regular wayland window through iced's winit backend
+    Window,
+    /// Override-redirect X11 window through iced's winit backend
+    X11,
+}
+
+impl Backend {
+    /// Picks the layer-shell backend when the compositor advertises [`LAYER_SHELL`],
+    /// falling back to a regular window otherwise (e.g. on GNOME),
+    /// or an X11 window when there is no wayland display at all
+    pub fn detect() -> Self {
+        if std::env::var_os("WAYLAND_DISPLAY").is_none_or(|display| display.is_empty()) {
+            tracing::info!("WAYLAND_DISPLAY is not set, running as an X11 window");
+            return Self::X11;
+        }
+
+        match WaylandGlobals::query() {
+            Ok(globals) if globals.has(LAYER_SHELL) => Self::LayerShell,
+            Ok(_) => {
+                tracing::warn!(
+                    "The compositor doesn't support {LAYER_SHELL} (wlr-layer-shell), \
+                    falling back to a regular window. \
+                    For the intended overlay experience use a compositor that supports it \
+                    (sway, Hyprland, river, niri, KDE Plasma, ...)"
+                );
+                Self::Window
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to query the wayland compositor globals ({err}), \
+                    falling back to a regular window"
+                );
+                Self::Window
+            }
+        }
+    }
+
+    /// Window settings resembling the layer-shell surface as close as a regular window can
+    pub fn window_settings(&self, id: &str, size: (f32, f32)) -> iced::window::Settings {
+        iced::window::Settings {
+            size: size.into(),
+            position: Position::Centered,
+            resizable: false,
+            decorations: false,
+            level: Level::AlwaysOnTop,
+            platform_specific: PlatformSpecific {
+                application_id: id.into(),
+                override_redirect: matches!(self, Self::X11),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// Runs a [`LeaperMode`](crate::LeaperMode) on the [`Backend`] detected for the current session,
+/// `settings` are used for the layer-shell surface, `window_size` for the regular window fallbacks
+#[macro_export]
+macro_rules! run_mode {
+    (
+        $mode:ty {
+            settings: $settings:expr,
+            window_size: $window_size:expr,
+            $(fonts: [$($font:expr),* $(,)?],)?
+            $(executor: $executor:ty,)?
+            init: $init:expr $(,)?
+        }
+    ) => {{
+        let settings: iced_layershell::build_pattern::MainSettings = $settings;
+
+        match $crate::backend::Backend::detect() {
+            $crate::backend::Backend::LayerShell => {
+                iced_layershell::build_pattern::application(
+                    <$mode as $crate::LeaperMode>::title,
+                    <$mode as $crate::LeaperMode>::update,
+                    <$mode as $crate::LeaperMode>::view,
+                )
+                .settings(settings)
+                .theme(<$mode as $crate::LeaperMode>::theme)
+                .subscription(<$mode as $crate::LeaperMode>::subscription)
+                $($(.font($font))*)?
+                $(.executor::<$executor>())?
+                .run_with($init)?
+            }
+            backend => {
+                let init = $init;
+                let window_settings = backend.window_settings(
+                    settings.id.as_deref().unwrap_or("com.tukanoid.leaper"),
+                    $window_size,
+                );
+
+                iced::application(
+                    <$mode as $crate::LeaperMode>::title,
+                    <$mode as $crate::LeaperMode>::update,
+                    <$mode as $crate::LeaperMode>::view,
+                )
+                .window(window_settings)
+                .theme(<$mode as $crate::LeaperMode>::theme)
+                .subscription(<$mode as $crate::LeaperMode>::subscription)
+                $($(.font($font))*)?
+                $(.executor::<$executor>())?
+                .run_with(move || {
+                    let (state, task) = init();
+
+                    // Override-redirect windows don't get focused by the window manager
+                    let focus = iced::window::get_oldest().and_then(iced::window::gain_focus);
+
+                    (state, iced::Task::batch([task, focus]))
+                })?
+            }
+        }
+    }};
+}
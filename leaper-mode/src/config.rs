@@ -1,12 +1,28 @@
-use std::{io::Write, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use smart_default::SmartDefault;
 
+use leaper_error::IntoContext;
 use macros::lerror;
 
-use crate::LeaperModeTheme;
+use crate::{LeaperModeTheme, renderer::Renderer, terminal::Terminal};
+
+/// Set by `--kiosk`, forces kiosk mode regardless of the config file
+static FORCE_KIOSK: AtomicBool = AtomicBool::new(false);
+/// Set by `--ephemeral`, see [`LeaperModeConfig::ephemeral`]
+static FORCE_EPHEMERAL: AtomicBool = AtomicBool::new(false);
+/// Set by `--renderer`, overrides [`LeaperModeConfig::renderer`]
+static FORCE_RENDERER: Mutex<Option<Renderer>> = Mutex::new(None);
 
 #[derive(SmartDefault, Serialize, Deserialize)]
 #[serde(default)]
@@ -14,34 +30,155 @@ pub struct LeaperModeConfig {
     #[serde(serialize_with = "ser_theme", deserialize_with = "de_theme")]
     #[default(LeaperModeTheme::TokyoNight)]
     pub theme: LeaperModeTheme,
+    pub launcher: LauncherConfig,
+    pub runner: RunnerConfig,
     pub power: PowerConfig,
+    pub lock: LockConfig,
+    pub kiosk: KioskConfig,
+    pub daemon: DaemonConfig,
+    /// On-screen keyboard for touch devices
+    pub osk: OskMode,
+    /// Switch to `gl` or `software` when the GPU driver makes the modes crash
+    pub renderer: Renderer,
+    pub log: LogConfig,
+    /// Cap for animation redraws, lower saves battery. Static screens only redraw
+    /// when something changes either way
+    #[default = 30]
+    pub max_fps: u32,
+    /// Command opening a terminal (e.g. `["foot"]`), for `Terminal=true` apps and started
+    /// in the target directory. Falls back to `$TERMINAL` and then the first known
+    /// terminal that's installed when empty, see [`Terminal::resolve`]
+    pub terminal: Vec<String>,
+    /// Discover, load and draw app icons. Off gives a text-only launcher with narrower
+    /// entries, and the daemon skips walking the icon directories
+    #[default = true]
+    pub icons: bool,
+    /// Escape clears a non-empty query first and only exits on the next press
+    #[default = true]
+    pub escape_clears: bool,
+    /// Bar at the bottom listing the shortcuts available right now
+    #[default = true]
+    pub show_hints: bool,
     #[default = 8000]
     pub db_port: u16,
+    /// Keep the index in memory and search for apps in-process instead of using the daemon,
+    /// nothing gets written to disk
+    pub ephemeral: bool,
 }
 
 impl LeaperModeConfig {
     pub fn open(dirs: &ProjectDirs) -> LeaperModeConfigResult<Self> {
         let config_dir = dirs.config_local_dir();
 
-        if !config_dir.exists() {
-            std::fs::create_dir_all(config_dir)?;
+        if !config_dir.exists() && !FORCE_EPHEMERAL.load(Ordering::Relaxed) {
+            std::fs::create_dir_all(config_dir)
+                .err_context::<LeaperAppModeConfigError>(format!("Creating {config_dir:?}"))?;
         }
 
-        let config_file_path = config_dir.join("config.toml");
+        let config_file_path = Self::path(dirs);
 
         let res = match config_file_path.exists() {
-            true => toml::from_str(&std::fs::read_to_string(config_file_path)?)?,
+            true => {
+                let contents = std::fs::read_to_string(&config_file_path)
+                    .err_context::<LeaperAppModeConfigError>(format!(
+                    "Reading {config_file_path:?}"
+                ))?;
+
+                toml::from_str(&contents).err_context::<LeaperAppModeConfigError>(format!(
+                    "Parsing {config_file_path:?}"
+                ))?
+            }
+            false if FORCE_EPHEMERAL.load(Ordering::Relaxed) => Default::default(),
             false => {
                 let config = Default::default();
 
-                let mut file = std::fs::File::create(config_file_path)?;
-                file.write_all(toml::to_string_pretty(&config)?.as_bytes())?;
+                std::fs::write(&config_file_path, toml::to_string_pretty(&config)?)
+                    .err_context::<LeaperAppModeConfigError>(format!(
+                        "Writing {config_file_path:?}"
+                    ))?;
 
                 config
             }
         };
 
-        Ok(res)
+        Ok(Self::apply_overrides(res))
+    }
+
+    /// Enables kiosk mode for every config opened by this process afterwards
+    pub fn force_kiosk() {
+        FORCE_KIOSK.store(true, Ordering::Relaxed);
+    }
+
+    /// Enables ephemeral mode for every config opened by this process afterwards
+    pub fn force_ephemeral() {
+        FORCE_EPHEMERAL.store(true, Ordering::Relaxed);
+    }
+
+    /// See [`Self::terminal`]
+    pub fn resolve_terminal(&self) -> Terminal {
+        Terminal::resolve(&self.terminal)
+    }
+
+    /// Time between animation frames, see [`Self::max_fps`]
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.max_fps.clamp(1, 240)
+    }
+
+    /// Uses `renderer` for every config opened by this process afterwards
+    pub fn force_renderer(renderer: Renderer) {
+        *FORCE_RENDERER.lock().unwrap() = Some(renderer);
+    }
+
+    fn apply_overrides(mut config: Self) -> Self {
+        if let Some(renderer) = *FORCE_RENDERER.lock().unwrap() {
+            config.renderer = renderer;
+        }
+
+        if FORCE_KIOSK.load(Ordering::Relaxed) {
+            config.kiosk.enabled = true;
+        }
+
+        if FORCE_EPHEMERAL.load(Ordering::Relaxed) {
+            config.ephemeral = true;
+        }
+
+        config
+    }
+
+    /// Whether the `capability` is available with the current restrictions
+    pub fn allows(&self, capability: Capability) -> bool {
+        !self.kiosk.enabled || matches!(capability, Capability::Lock)
+    }
+
+    /// Whether an app with these categories should be hidden from the launcher
+    pub fn hides_app(&self, categories: &[String]) -> bool {
+        self.kiosk.enabled
+            && categories
+                .iter()
+                .any(|category| self.kiosk.hidden_categories.contains(category))
+    }
+
+    /// Replaces the config file with `contents` after validating them,
+    /// keeping the previous config next to it as `config.toml.bak`
+    pub fn replace(dirs: &ProjectDirs, contents: &str) -> LeaperModeConfigResult<Self> {
+        let config = toml::from_str(contents)?;
+        let config_file_path = Self::path(dirs);
+
+        if config_file_path.exists() {
+            let backup = config_file_path.with_extension("toml.bak");
+
+            std::fs::copy(&config_file_path, &backup)
+                .err_context::<LeaperAppModeConfigError>(format!("Backing up to {backup:?}"))?;
+        }
+
+        std::fs::write(&config_file_path, contents)
+            .err_context::<LeaperAppModeConfigError>(format!("Writing {config_file_path:?}"))?;
+
+        Ok(config)
+    }
+
+    pub fn path(dirs: &ProjectDirs) -> PathBuf {
+        dirs.config_local_dir().join("config.toml")
     }
 }
 macro_rules! serde_theme {
@@ -130,15 +267,368 @@ serde_theme!(LeaperModeTheme => [
     Ferra
 ]);
 
+#[derive(SmartDefault, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LauncherConfig {
+    /// Ordering of results with equal match scores
+    pub tie_break: SortOrder,
+    /// Ordering of the list when the search query is empty
+    pub empty_query: SortOrder,
+    /// Locales whose scripts get transliterated to latin before matching,
+    /// so app names can be searched in either script (supported: ru, uk, be, el)
+    pub transliteration: Vec<String>,
+    /// Locale used for alphabetical ordering (e.g. "de", "sv-SE"),
+    /// taken from LC_ALL/LC_COLLATE/LANG when not set
+    pub collation: Option<String>,
+    /// Largest icon width/height sent to the GPU, bigger icons get downscaled first.
+    /// Defaults to a limit every device supports
+    pub max_icon_size: Option<u32>,
+    /// Icon formats (file extensions) to pick from when an app has several, best first,
+    /// e.g. `["png", "svg"]` never uses XPMs. Empty allows every format
+    pub icon_formats: Vec<String>,
+    /// Preferred icon width range in px, `[min, max]`, for picking between rasters
+    #[default([48, 128])]
+    pub icon_size: [u32; 2],
+    /// Height of the list entries: "small", "medium", "large" or a number of pixels.
+    /// Icons and names scale along with it
+    pub entry_size: EntrySize,
+    /// Start with the compact list: names only, small entries (Ctrl+- toggles it)
+    pub compact: bool,
+    /// Play animated GIF icons of the entries on screen, costs a redraw per frame
+    pub animated_icons: bool,
+    /// Tint the selected entry with the dominant color of its icon
+    pub accent_from_icon: bool,
+    /// Show the category filter chips above the list on start (Ctrl+K toggles them)
+    pub show_categories: bool,
+    /// Remember search queries, Up/Down recall them while the query is empty or finds nothing
+    #[default = true]
+    pub query_history: bool,
+    /// Offer the power menu actions matching the query (e.g. "reb" for Reboot) among the
+    /// results, the one named in full on top. Ctrl+P runs the first one
+    #[default = true]
+    pub power_actions: bool,
+    /// Show the result of a query that's arithmetic (e.g. "12*37+5") as the first entry,
+    /// Enter copies it
+    #[default = true]
+    pub calculator: bool,
+    /// List entries limited to other desktops with OnlyShowIn/NotShowIn too
+    pub show_all_desktops: bool,
+    /// Desktop names OnlyShowIn/NotShowIn are checked against (e.g. `["GNOME"]`),
+    /// `XDG_CURRENT_DESKTOP` when empty
+    pub current_desktop: Vec<String>,
+    /// List entries marked NoDisplay (helpers like "Avahi Zeroconf Browser") too.
+    /// Hidden entries are never listed
+    pub show_no_display: bool,
+    /// Come back with the size the launcher had when it was last closed (per output setup)
+    #[default = true]
+    pub remember_size: bool,
+    /// GPU per desktop file id, overriding the entry's PrefersNonDefaultGPU,
+    /// e.g. `{ steam = "discrete", "org.gnome.Maps" = "default" }`
+    pub gpu: HashMap<String, GpuPreference>,
+}
+
+/// Which GPU an app renders on, on laptops with two of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GpuPreference {
+    Default,
+    /// Offloaded to the discrete one (DRI_PRIME or NVIDIA PRIME render offload)
+    Discrete,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    #[default]
+    Alphabetical,
+    Frecency,
+    LastLaunched,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EntrySize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+    #[serde(untagged)]
+    Pixels(u16),
+}
+
+impl EntrySize {
+    /// Anything smaller leaves no room for the name
+    const MIN_PIXELS: u16 = 24;
+
+    pub fn height(self) -> f32 {
+        match self {
+            Self::Small => 44.0,
+            Self::Medium => 60.0,
+            Self::Large => 80.0,
+            Self::Pixels(px) => px.max(Self::MIN_PIXELS) as f32,
+        }
+    }
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunnerConfig {
+    /// Commands with `{placeholder}`s, picked by typing their name in the runner
+    pub templates: Vec<CommandTemplate>,
+    /// Hand every command to the daemon, which keeps its output for `leaper jobs`.
+    /// Ctrl+Enter does the same for a single command
+    pub detach: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandTemplate {
+    pub name: String,
+    /// e.g. `ssh {host}`, every placeholder is asked for before running it
+    pub command: String,
+}
+
+#[derive(SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockConfig {
+    /// Show the user's avatar and real name above the password field
+    #[default = true]
+    pub show_user: bool,
+    /// Failed PIN attempts before falling back to the full password
+    #[default = 3]
+    pub pin_attempts: u8,
+    pub security_key: SecurityKeyConfig,
+}
+
+/// Unlocking by touching a FIDO2/U2F key. The check runs a PAM service of its own, which
+/// has to be set up with a module waiting for the key (e.g. `auth required pam_u2f.so`)
+#[derive(SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityKeyConfig {
+    pub enabled: bool,
+    #[default = "leaper-lock-key"]
+    pub pam_service: String,
+    /// Wait for the key as soon as the screen locks instead of on "Use security key"
+    #[default = true]
+    pub auto_start: bool,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OskMode {
+    #[default]
+    Off,
+    On,
+    /// Show it only when a touchscreen is connected
+    Auto,
+}
+
+impl OskMode {
+    pub fn enabled(&self) -> bool {
+        match self {
+            Self::Off => false,
+            Self::On => true,
+            Self::Auto => crate::input::touchscreen_present(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Send logs to the systemd journal, with their priorities and fields
+    pub journald: JournaldMode,
+    /// Keep the pretty stderr output while logging to the journal
+    pub keep_stderr: bool,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JournaldMode {
+    Off,
+    On,
+    /// Only when stderr already goes to the journal, i.e. when running as a systemd service
+    #[default]
+    Auto,
+}
+
+impl JournaldMode {
+    pub fn enabled(&self) -> bool {
+        match self {
+            Self::Off => false,
+            Self::On => true,
+            Self::Auto => std::env::var_os("JOURNAL_STREAM").is_some(),
+        }
+    }
+}
+
+#[derive(SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DaemonConfig {
+    /// Minutes between automatic re-indexing runs, 0 disables them
+    #[default = 60]
+    pub reindex_interval: u64,
+    /// Up to this many seconds are randomly added to each interval,
+    /// so runs don't line up with other periodic work
+    #[default = 300]
+    pub reindex_jitter: u64,
+    /// Directories re-indexed on every run, on top of the app and icon search
+    pub reindex_paths: Vec<PathBuf>,
+    /// Shortcuts registered through the GlobalShortcuts portal, for compositors that support it
+    pub shortcuts: Vec<Shortcut>,
+    /// Serve the read-only HTTP API on `$XDG_RUNTIME_DIR/leaper/api.sock`
+    /// (e.g. `curl --unix-socket`), only the user can connect to it
+    pub api: bool,
+    /// Hash the contents of indexed files in the background after each re-index,
+    /// so duplicates can be found
+    pub hash_files: bool,
+    /// Files bigger than this many MiB are never hashed
+    #[default = 64]
+    pub hash_max_size: u64,
+    /// Index removable drives when they get mounted. Their files are hidden from results
+    /// while unmounted either way
+    pub index_removable: bool,
+    /// Serve org.freedesktop.ScreenSaver, so video players and browsers can keep the
+    /// idle lock off. Only for setups where nothing else serves it (hypridle and most
+    /// desktops do), skipped if something already does
+    pub screensaver: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shortcut {
+    /// Preferred trigger in the XDG shortcuts format (e.g. "LOGO+space"),
+    /// the compositor can still ask the user to confirm or pick another one
+    pub trigger: String,
+    pub mode: ShortcutMode,
+}
+
+/// Mode toggled by a [`Shortcut`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShortcutMode {
+    Launcher,
+    Runner,
+    Power,
+    Lock,
+}
+
+impl ShortcutMode {
+    /// Subcommand of the leaper binary running the mode
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Launcher => "launcher",
+            Self::Runner => "runner",
+            Self::Power => "power",
+            Self::Lock => "lock",
+        }
+    }
+}
+
+/// Restricted mode for shared/kiosk machines
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KioskConfig {
+    pub enabled: bool,
+    /// Desktop entry categories (e.g. "System", "Development") hidden from the launcher
+    pub hidden_categories: Vec<String>,
+}
+
+/// Features that can be taken away by [`KioskConfig`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    Runner,
+    Lock,
+    SwitchUser,
+    LogOut,
+    Hibernate,
+    Reboot,
+    Shutdown,
+}
+
 #[derive(SmartDefault, Serialize, Deserialize)]
 pub struct PowerConfig {
     pub actions: Actions,
+    #[serde(default)]
+    pub events: PowerEvents,
+    #[serde(default)]
+    pub layout: PowerLayout,
+    #[serde(default)]
+    pub lock_first: LockFirst,
+}
+
+/// Actions that lock the session first and wait for the lock screen to be up (logind's
+/// `LockedHint`), so e.g. resuming from hibernation lands on the lock screen
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockFirst {
+    pub switch_user: bool,
+    pub hibernate: bool,
+}
+
+/// How the power menu buttons are laid out, smaller screens fit a grid of smaller buttons
+#[derive(SmartDefault, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerLayout {
+    pub arrangement: PowerArrangement,
+    /// Width and height of a button in px, icons and labels scale along
+    #[default = 200]
+    pub button_size: u16,
+    /// Action names under the icons
+    #[default = true]
+    pub labels: bool,
+    /// Shortcut keys under the names
+    #[default = true]
+    pub shortcuts: bool,
+    /// Nerd Font glyphs replacing the default icons, e.g. `shutdown = "󰐥"`
+    pub icons: PowerIcons,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PowerArrangement {
+    /// All buttons side by side
+    #[default]
+    Row,
+    /// Two buttons per row
+    Grid,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerIcons {
+    pub lock: Option<String>,
+    pub switch_user: Option<String>,
+    pub log_out: Option<String>,
+    pub hibernate: Option<String>,
+    pub reboot: Option<String>,
+    pub shutdown: Option<String>,
+}
+
+/// Hardware events leaper-daemon handles instead of logind
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerEvents {
+    pub lid_close: EventAction,
+    pub power_key: EventAction,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "kebab-case")]
+pub enum EventAction {
+    /// Leave the event to logind (HandleLidSwitch/HandlePowerKey in logind.conf)
+    #[default]
+    System,
+    /// Inhibit the event without doing anything
+    Ignore,
+    Lock,
+    PowerMenu,
+    Cmd(Vec<String>),
 }
 
 #[derive(Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Actions {
     pub lock: ActionMethod,
+    pub switch_user: ActionMethod,
     pub log_out: ActionMethod,
     pub hibernate: ActionMethod,
     pub reboot: ActionMethod,
@@ -154,10 +644,8 @@ pub enum ActionMethod {
 }
 
 #[lerror]
-#[lerr(prefix = "[leaper_mode::config]", result_name = LeaperModeConfigResult)]
+#[lerr(prefix = "[leaper_mode::config]", result_name = LeaperModeConfigResult, common)]
 pub enum LeaperAppModeConfigError {
-    #[lerr(str = "[std::io] {0}")]
-    IO(#[lerr(from, wrap = Arc)] std::io::Error),
     #[lerr(str = "[toml::de] {0}")]
     TomlDeser(#[lerr(from)] toml::de::Error),
     #[lerr(str = "[toml::ser] {0}")]
@@ -0,0 +1,17 @@
+/// `INPUT_PROP_DIRECT` from linux/input-event-codes.h,
+/// set for devices where input maps directly to the screen (touchscreens, drawing tablets)
+const INPUT_PROP_DIRECT: u64 = 1 << 0x01;
+
+/// Whether any input device advertised by the kernel is a touchscreen
+pub fn touchscreen_present() -> bool {
+    let Ok(devices) = std::fs::read_dir("/sys/class/input") else {
+        return false;
+    };
+
+    devices
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("input"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("properties")).ok())
+        .filter_map(|props| u64::from_str_radix(props.trim(), 16).ok())
+        .any(|props| props & INPUT_PROP_DIRECT != 0)
+}
@@ -0,0 +1,235 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{LazyLock, OnceLock},
+};
+
+use iced::keyboard::{self, Key, Modifiers, key::Named};
+use xkbcommon::xkb;
+
+use crate::wayland::KeyboardKeymap;
+
+static LAYOUT: OnceLock<KeyLayout> = OnceLock::new();
+static NO_LAYOUT: LazyLock<KeyLayout> = LazyLock::new(KeyLayout::default);
+
+/// Key combination bound to an action of a mode. The same list drives both the key
+/// handling and the hint bar, so the hints can't drift from what the keys actually do
+#[derive(Debug, Clone, Copy)]
+pub struct Binding<A> {
+    pub key: Key<&'static str>,
+    pub modifiers: Modifiers,
+    pub action: A,
+    /// Short description for the hint bar, bindings sharing it are shown together
+    pub hint: &'static str,
+}
+
+impl<A: Copy> Binding<A> {
+    pub const fn new(key: Key<&'static str>, action: A, hint: &'static str) -> Self {
+        Self {
+            key,
+            modifiers: Modifiers::empty(),
+            action,
+            hint,
+        }
+    }
+
+    pub const fn with(self, modifiers: Modifiers) -> Self {
+        Self { modifiers, ..self }
+    }
+
+    /// Characters are matched case-insensitively, ignoring Shift unless it's part of the binding.
+    /// Their [`KeyLayout::local`] counterparts trigger them too
+    pub fn matches(&self, key: Key<&str>, modifiers: Modifiers) -> bool {
+        let key_matches = match (self.key, key) {
+            (Key::Character(bound), Key::Character(pressed)) => {
+                bound.eq_ignore_ascii_case(pressed)
+                    || KeyLayout::get().local(bound) == pressed.to_lowercase()
+            }
+            (Key::Named(bound), Key::Named(pressed)) => bound == pressed,
+            _ => false,
+        };
+        let shift_matches = match self.key {
+            Key::Character(_) if !self.modifiers.shift() => true,
+            _ => self.modifiers.shift() == modifiers.shift(),
+        };
+
+        key_matches
+            && shift_matches
+            && self.modifiers.control() == modifiers.control()
+            && self.modifiers.alt() == modifiers.alt()
+            && self.modifiers.logo() == modifiers.logo()
+    }
+
+    /// Bare characters (and Space) type into a focused input, so they're left to it
+    pub fn typed(&self) -> bool {
+        matches!(self.key, Key::Character(_) | Key::Named(Named::Space))
+            && !self.modifiers.control()
+            && !self.modifiers.alt()
+            && !self.modifiers.logo()
+    }
+
+    /// e.g. "Ctrl+K", "Shift+Tab", characters as the active layout types them ("Ctrl+Л")
+    pub fn label(&self) -> String {
+        let modifiers = [
+            (self.modifiers.control(), "Ctrl+"),
+            (self.modifiers.alt(), "Alt+"),
+            (self.modifiers.logo(), "Super+"),
+            (self.modifiers.shift(), "Shift+"),
+        ]
+        .into_iter()
+        .filter_map(|(set, label)| set.then_some(label))
+        .collect::<String>();
+
+        let key = match self.key {
+            Key::Character(ch) => KeyLayout::get().local(ch).to_uppercase(),
+            Key::Named(Named::Escape) => "Esc".into(),
+            Key::Named(Named::ArrowUp) => "↑".into(),
+            Key::Named(Named::ArrowDown) => "↓".into(),
+            Key::Named(Named::ArrowLeft) => "←".into(),
+            Key::Named(Named::ArrowRight) => "→".into(),
+            Key::Named(named) => format!("{named:?}"),
+            Key::Unidentified => "?".into(),
+        };
+
+        format!("{modifiers}{key}")
+    }
+}
+
+/// Every binding of a mode, in the order they're shown in the hint bar
+pub struct KeyBindings<A: 'static>(pub &'static [Binding<A>]);
+
+impl<A: Copy> KeyBindings<A> {
+    /// First binding for the pressed keys whose action is `available` right now
+    pub fn find(
+        &self,
+        key: Key<&str>,
+        modifiers: Modifiers,
+        available: impl Fn(A) -> bool,
+    ) -> Option<A> {
+        self.0
+            .iter()
+            .find(|binding| binding.matches(key, modifiers) && available(binding.action))
+            .map(|binding| binding.action)
+    }
+
+    /// Whether a binding that doesn't type anything takes this key press, for the
+    /// [`KeyCatcher`](crate::catcher::KeyCatcher) to handle it before the focused input
+    pub fn catches(&self, event: &keyboard::Event, available: impl Fn(A) -> bool) -> bool {
+        let keyboard::Event::KeyPressed { key, modifiers, .. } = event else {
+            return false;
+        };
+
+        self.0.iter().any(|binding| {
+            !binding.typed()
+                && binding.matches(key.as_ref(), *modifiers)
+                && available(binding.action)
+        })
+    }
+
+    /// (keys, hint) pairs of the `available` actions, e.g. ("Tab/Shift+Tab", "category")
+    pub fn hints(&self, available: impl Fn(A) -> bool) -> Vec<(String, &'static str)> {
+        let mut hints: Vec<(String, &'static str)> = vec![];
+
+        for binding in self.0.iter().filter(|binding| available(binding.action)) {
+            match hints.iter_mut().find(|(_, hint)| *hint == binding.hint) {
+                Some((keys, _)) => {
+                    keys.push('/');
+                    keys.push_str(&binding.label());
+                }
+                None => hints.push((binding.label(), binding.hint)),
+            }
+        }
+
+        hints
+    }
+}
+
+/// Binding characters (written for a US layout) the active keyboard layout can't type
+/// without modifiers, mapped to what the same physical key types in it instead, e.g. "k"
+/// to "л" on a Russian layout or ";" to "ö" on a German one
+#[derive(Debug, Default)]
+pub struct KeyLayout(HashMap<String, String>);
+
+impl KeyLayout {
+    /// Loads the compositor's keymap, once at startup before the first view. It takes a
+    /// roundtrip to the compositor, which no view or key press should wait on
+    pub fn init() {
+        LAYOUT.get_or_init(|| {
+            Self::load().unwrap_or_else(|| {
+                tracing::debug!("No keymap to localize the key labels with");
+                Self::default()
+            })
+        });
+    }
+
+    /// The layout [`Self::init`] loaded, nothing is translated without it
+    pub fn get() -> &'static Self {
+        LAYOUT.get().unwrap_or(&NO_LAYOUT)
+    }
+
+    /// Lowercase character typed for `bound` in the active layout
+    pub fn local(&self, bound: &str) -> String {
+        let bound = bound.to_lowercase();
+
+        self.0.get(&bound).cloned().unwrap_or(bound)
+    }
+
+    fn load() -> Option<Self> {
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let us =
+            xkb::Keymap::new_from_names(&context, "", "", "us", "", None, xkb::COMPILE_NO_FLAGS)?;
+        let active = Self::active_keymap(&context)?;
+
+        Some(Self::translations(&us, &active))
+    }
+
+    /// The compositor's keymap, or the one from XKB_DEFAULT_LAYOUT and co. without Wayland
+    fn active_keymap(context: &xkb::Context) -> Option<xkb::Keymap> {
+        let from_env =
+            || xkb::Keymap::new_from_names(context, "", "", "", "", None, xkb::COMPILE_NO_FLAGS);
+
+        match KeyboardKeymap::query() {
+            Ok(Some(keymap)) => xkb::Keymap::new_from_string(
+                context,
+                keymap,
+                xkb::KEYMAP_FORMAT_TEXT_V1,
+                xkb::COMPILE_NO_FLAGS,
+            ),
+            Ok(None) => from_env(),
+            Err(err) => {
+                tracing::debug!("Using the keymap from the environment: {err}");
+                from_env()
+            }
+        }
+    }
+
+    /// Only the first layout of the keymap counts, which one is active isn't known
+    /// without keyboard focus
+    fn translations(us: &xkb::Keymap, active: &xkb::Keymap) -> Self {
+        let keys = |keymap: &xkb::Keymap| {
+            (keymap.min_keycode().raw()..=keymap.max_keycode().raw()).map(xkb::Keycode::new)
+        };
+        let typed = |keymap: &xkb::Keymap, key| {
+            keymap
+                .key_get_syms_by_level(key, 0, 0)
+                .first()
+                .and_then(|sym| sym.key_char())
+                .filter(|ch| !ch.is_control() && !ch.is_whitespace())
+                .map(|ch| ch.to_lowercase().to_string())
+        };
+
+        let available = keys(active)
+            .filter_map(|key| typed(active, key))
+            .collect::<HashSet<_>>();
+
+        Self(
+            keys(us)
+                .filter_map(|key| {
+                    let bound = typed(us, key)?;
+                    let local = typed(active, key)?;
+
+                    (!available.contains(&bound) && local != bound).then_some((bound, local))
+                })
+                .collect(),
+        )
+    }
+}
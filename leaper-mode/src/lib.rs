@@ -1,6 +1,15 @@
 #![feature(associated_type_defaults)]
 
+pub mod backend;
+pub mod catcher;
 pub mod config;
+pub mod input;
+pub mod keys;
+pub mod renderer;
+pub mod selection;
+pub mod state;
+pub mod terminal;
+pub mod wayland;
 
 use directories::ProjectDirs;
 
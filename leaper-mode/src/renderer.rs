@@ -0,0 +1,83 @@
+use std::{path::Path, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Which iced renderer the modes draw with
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Renderer {
+    /// wgpu, falling back to software rendering when it can't start.
+    /// Without any Vulkan driver installed wgpu is pointed at OpenGL right away
+    #[default]
+    Auto,
+    Wgpu,
+    /// wgpu on top of OpenGL, for broken Vulkan drivers
+    Gl,
+    /// tiny-skia on the CPU, works everywhere
+    Software,
+}
+
+impl Renderer {
+    /// Vulkan loader manifest directories, no manifests means no usable driver
+    const VULKAN_ICD_DIRS: [&str; 3] = [
+        "/usr/share/vulkan/icd.d",
+        "/etc/vulkan/icd.d",
+        "/run/opengl-driver/share/vulkan/icd.d",
+    ];
+
+    /// Points iced's renderer selection (`ICED_BACKEND`, `WGPU_BACKEND`) at `self`.
+    /// Variables already set in the environment win, so they can still be used for debugging.
+    /// Has to run before the mode starts any threads
+    pub fn apply(self) {
+        let (iced_backend, wgpu_backend) = match self {
+            Self::Auto => (None, (!Self::vulkan_available()).then_some("gl")),
+            Self::Wgpu => (Some("wgpu"), None),
+            Self::Gl => (Some("wgpu"), Some("gl")),
+            Self::Software => (Some("tiny-skia"), None),
+        };
+
+        for (var, value) in [
+            ("ICED_BACKEND", iced_backend),
+            ("WGPU_BACKEND", wgpu_backend),
+        ] {
+            let Some(value) = value else {
+                continue;
+            };
+
+            if std::env::var_os(var).is_some() {
+                tracing::debug!("{var} is already set, not overriding it with {value:?}");
+                continue;
+            }
+
+            tracing::debug!("Using {var}={value} for the {self:?} renderer");
+
+            // SAFETY: modes apply the renderer at the very start of `run`,
+            // before iced or anything else spawns threads
+            unsafe { std::env::set_var(var, value) };
+        }
+    }
+
+    fn vulkan_available() -> bool {
+        Self::VULKAN_ICD_DIRS.iter().any(|dir| {
+            std::fs::read_dir(Path::new(dir))
+                .map(|mut entries| entries.next().is_some())
+                .unwrap_or_default()
+        })
+    }
+}
+
+impl FromStr for Renderer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "wgpu" => Ok(Self::Wgpu),
+            "gl" => Ok(Self::Gl),
+            "software" => Ok(Self::Software),
+            _ => Err(format!(
+                "unknown renderer {s:?}, expected one of: auto, wgpu, gl, software"
+            )),
+        }
+    }
+}
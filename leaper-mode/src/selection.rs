@@ -0,0 +1,61 @@
+/// Keyboard moves through a list of results, shared by the modes listing something
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionMove {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+}
+
+impl SelectionMove {
+    /// Index selected after the move, in a list of `len` entries of which `page` fit on
+    /// screen. Single steps wrap around, page steps stop at the ends
+    pub fn apply(self, selected: usize, len: usize, page: usize) -> usize {
+        if len == 0 {
+            return 0;
+        }
+
+        let last = len - 1;
+        // The entry at the edge of the old page stays in view
+        let page = page.saturating_sub(1).max(1);
+
+        match self {
+            Self::Up => match selected {
+                0 => last,
+                x => x - 1,
+            },
+            Self::Down => match selected >= last {
+                true => 0,
+                false => selected + 1,
+            },
+            Self::PageUp => selected.saturating_sub(page),
+            Self::PageDown => (selected + page).min(last),
+            Self::First => 0,
+            Self::Last => last,
+        }
+    }
+}
+
+/// Scroll offset that brings entry `selected` into view, moving as little as possible
+/// from `offset`. Entries are `stride` apart and `entry_height` high, `height` is the
+/// height of the visible part of the list
+pub fn offset_to_show(
+    selected: usize,
+    stride: f32,
+    entry_height: f32,
+    offset: f32,
+    height: f32,
+) -> f32 {
+    let top = selected as f32 * stride;
+    let bottom = top + entry_height;
+
+    if top < offset {
+        top
+    } else if bottom > offset + height {
+        (bottom - height).max(0.0)
+    } else {
+        offset
+    }
+}
@@ -0,0 +1,71 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::LazyLock};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::wayland::OutputNames;
+
+/// Connected outputs joined together ("DP-1+eDP-1"), so a docked and an undocked
+/// laptop remember their sizes separately
+static OUTPUT_SETUP: LazyLock<String> = LazyLock::new(|| match OutputNames::query() {
+    Ok(names) if !names.is_empty() => names.join("+"),
+    Ok(_) => "default".into(),
+    Err(err) => {
+        tracing::debug!("Couldn't get the output names ({err}), sizes won't be per output");
+        "default".into()
+    }
+});
+
+/// Surface sizes the modes were last shown with, per mode and output setup.
+/// Kept in the state dir, it's not something to edit by hand
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSizes {
+    sizes: BTreeMap<String, [u32; 2]>,
+}
+
+impl WindowSizes {
+    fn path(dirs: &ProjectDirs) -> PathBuf {
+        dirs.state_dir()
+            .unwrap_or(dirs.data_local_dir())
+            .join("windows.toml")
+    }
+
+    pub fn load(dirs: &ProjectDirs) -> Self {
+        std::fs::read_to_string(Self::path(dirs))
+            .ok()
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, mode: &str) -> Option<(u32, u32)> {
+        self.sizes
+            .get(&Self::key(mode))
+            .map(|[width, height]| (*width, *height))
+    }
+
+    /// Remembers `size` for `mode` on the current output setup
+    pub fn save(
+        dirs: &ProjectDirs,
+        mode: &str,
+        (width, height): (u32, u32),
+    ) -> std::io::Result<()> {
+        let mut sizes = Self::load(dirs);
+        sizes.sizes.insert(Self::key(mode), [width, height]);
+
+        let path = Self::path(dirs);
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        std::fs::write(
+            path,
+            toml::to_string_pretty(&sizes).map_err(std::io::Error::other)?,
+        )
+    }
+
+    fn key(mode: &str) -> String {
+        format!("{mode}@{}", *OUTPUT_SETUP)
+    }
+}
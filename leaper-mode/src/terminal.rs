@@ -0,0 +1,89 @@
+use std::path::Path;
+
+/// Terminals looked for in `$PATH` when neither `terminal` nor `$TERMINAL` is set, best
+/// first, along with the arguments that make them run a command
+pub const KNOWN_TERMINALS: &[(&str, &[&str])] = &[
+    ("xdg-terminal-exec", &[]),
+    ("foot", &[]),
+    ("kitty", &[]),
+    ("alacritty", &["-e"]),
+    ("wezterm", &["start", "--"]),
+    ("ghostty", &["-e"]),
+    ("konsole", &["-e"]),
+    ("gnome-terminal", &["--"]),
+    ("xfce4-terminal", &["-x"]),
+    ("xterm", &["-e"]),
+];
+
+/// Terminal emulator that `Terminal=true` apps and "Open terminal here" run in
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Terminal {
+    /// Program and its own arguments, empty runs commands without a terminal
+    pub command: Vec<String>,
+    /// Go between [`Self::command`] and the command run in the terminal
+    pub exec_args: Vec<String>,
+}
+
+impl Terminal {
+    /// Used as the last resort, even when it's not installed
+    const FALLBACK: &str = "xdg-terminal-exec";
+    /// Unknown terminals are assumed to take the command after `-e`, like xterm
+    const DEFAULT_EXEC_ARGS: &[&str] = &["-e"];
+
+    /// `configured` (the `terminal` config), `$TERMINAL`, then the first of the
+    /// [`KNOWN_TERMINALS`] that's installed
+    pub fn resolve(configured: &[String]) -> Self {
+        let command = match configured.is_empty() {
+            false => configured.to_vec(),
+            true => vec![
+                std::env::var("TERMINAL")
+                    .ok()
+                    .filter(|term| !term.is_empty())
+                    .or_else(|| {
+                        KNOWN_TERMINALS
+                            .iter()
+                            .map(|(program, _)| *program)
+                            .find(|program| Self::installed(program))
+                            .map(Into::into)
+                    })
+                    .unwrap_or_else(|| Self::FALLBACK.into()),
+            ],
+        };
+
+        let program = command
+            .first()
+            .and_then(|program| Path::new(program).file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let exec_args = KNOWN_TERMINALS
+            .iter()
+            .find(|(known, _)| *known == program)
+            .map(|(_, args)| *args)
+            .unwrap_or(Self::DEFAULT_EXEC_ARGS)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        Self { command, exec_args }
+    }
+
+    /// `exec` run inside the terminal
+    pub fn wrap(&self, exec: &[String]) -> Vec<String> {
+        if self.command.is_empty() {
+            return exec.to_vec();
+        }
+
+        self.command
+            .iter()
+            .chain(&self.exec_args)
+            .chain(exec)
+            .cloned()
+            .collect()
+    }
+
+    fn installed(program: &str) -> bool {
+        std::env::var_os("PATH").is_some_and(|paths| {
+            std::env::split_paths(&paths).any(|dir| dir.join(program).is_file())
+        })
+    }
+}
@@ -0,0 +1,221 @@
+use std::{io::Read, sync::Arc};
+
+use wayland_client::{
+    Connection, Dispatch, QueueHandle, WEnum,
+    protocol::{wl_display::WlDisplay, wl_keyboard, wl_output, wl_registry, wl_seat},
+};
+
+use macros::lerror;
+
+pub const LAYER_SHELL: &str = "zwlr_layer_shell_v1";
+pub const SESSION_LOCK: &str = "ext_session_lock_manager_v1";
+
+/// Interfaces of the globals advertised by the running compositor
+#[derive(Debug, Default)]
+pub struct WaylandGlobals(Vec<String>);
+
+impl WaylandGlobals {
+    pub fn query() -> WaylandResult<Self> {
+        let connection = Connection::connect_to_env()?;
+        let display: WlDisplay = connection.display();
+
+        let mut queue = connection.new_event_queue();
+        let _registry = display.get_registry(&queue.handle(), ());
+
+        let mut globals = Self::default();
+        queue.roundtrip(&mut globals)?;
+
+        Ok(globals)
+    }
+
+    pub fn has(&self, interface: &str) -> bool {
+        self.0.iter().any(|global| global == interface)
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for WaylandGlobals {
+    fn event(
+        state: &mut Self,
+        _proxy: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global { interface, .. } = event {
+            state.0.push(interface);
+        }
+    }
+}
+
+/// Names of the connected outputs (e.g. "eDP-1", "DP-2"), sorted
+#[derive(Debug, Default)]
+pub struct OutputNames(Vec<String>);
+
+impl OutputNames {
+    /// `wl_output.name` only exists since version 4, older compositors report no names
+    const MIN_VERSION: u32 = 4;
+
+    pub fn query() -> WaylandResult<Vec<String>> {
+        let connection = Connection::connect_to_env()?;
+        let display: WlDisplay = connection.display();
+
+        let mut queue = connection.new_event_queue();
+        let _registry = display.get_registry(&queue.handle(), ());
+
+        let mut outputs = Self::default();
+        // Once for the globals (binding the outputs), once for the output names
+        queue.roundtrip(&mut outputs)?;
+        queue.roundtrip(&mut outputs)?;
+
+        outputs.0.sort();
+
+        Ok(outputs.0)
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for OutputNames {
+    fn event(
+        _state: &mut Self,
+        proxy: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _data: &(),
+        _conn: &Connection,
+        qhandle: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+            && interface == "wl_output"
+            && version >= Self::MIN_VERSION
+        {
+            proxy.bind::<wl_output::WlOutput, _, _>(name, Self::MIN_VERSION, qhandle, ());
+        }
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, ()> for OutputNames {
+    fn event(
+        state: &mut Self,
+        _proxy: &wl_output::WlOutput,
+        event: wl_output::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event {
+            state.0.push(name);
+        }
+    }
+}
+
+/// Keymap the compositor hands to keyboards, in the XKB text format
+#[derive(Debug, Default)]
+pub struct KeyboardKeymap {
+    seat: Option<wl_seat::WlSeat>,
+    keyboard: Option<wl_keyboard::WlKeyboard>,
+    keymap: Option<String>,
+}
+
+impl KeyboardKeymap {
+    /// `None` if the first seat has no keyboard
+    pub fn query() -> WaylandResult<Option<String>> {
+        let connection = Connection::connect_to_env()?;
+        let display: WlDisplay = connection.display();
+
+        let mut queue = connection.new_event_queue();
+        let _registry = display.get_registry(&queue.handle(), ());
+
+        let mut state = Self::default();
+        // Once each for the globals (binding the seat), the seat capabilities (getting
+        // the keyboard) and the keymap
+        for _ in 0..3 {
+            queue.roundtrip(&mut state)?;
+        }
+
+        Ok(state.keymap)
+    }
+
+    fn read(fd: std::os::fd::OwnedFd, size: u32) -> std::io::Result<String> {
+        let mut keymap = vec![0; size as usize];
+        std::fs::File::from(fd).read_exact(&mut keymap)?;
+
+        Ok(String::from_utf8_lossy(&keymap)
+            .trim_end_matches('\0')
+            .to_string())
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for KeyboardKeymap {
+    fn event(
+        state: &mut Self,
+        proxy: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _data: &(),
+        _conn: &Connection,
+        qhandle: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name, interface, ..
+        } = event
+            && interface == "wl_seat"
+            && state.seat.is_none()
+        {
+            state.seat = Some(proxy.bind::<wl_seat::WlSeat, _, _>(name, 1, qhandle, ()));
+        }
+    }
+}
+
+impl Dispatch<wl_seat::WlSeat, ()> for KeyboardKeymap {
+    fn event(
+        state: &mut Self,
+        proxy: &wl_seat::WlSeat,
+        event: wl_seat::Event,
+        _data: &(),
+        _conn: &Connection,
+        qhandle: &QueueHandle<Self>,
+    ) {
+        if let wl_seat::Event::Capabilities {
+            capabilities: WEnum::Value(capabilities),
+        } = event
+            && capabilities.contains(wl_seat::Capability::Keyboard)
+            && state.keyboard.is_none()
+        {
+            state.keyboard = Some(proxy.get_keyboard(qhandle, ()));
+        }
+    }
+}
+
+impl Dispatch<wl_keyboard::WlKeyboard, ()> for KeyboardKeymap {
+    fn event(
+        state: &mut Self,
+        _proxy: &wl_keyboard::WlKeyboard,
+        event: wl_keyboard::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        if let wl_keyboard::Event::Keymap {
+            format: WEnum::Value(wl_keyboard::KeymapFormat::XkbV1),
+            fd,
+            size,
+        } = event
+        {
+            match Self::read(fd, size) {
+                Ok(keymap) => state.keymap = Some(keymap),
+                Err(err) => tracing::warn!("Failed to read the keymap: {err}"),
+            }
+        }
+    }
+}
+
+#[lerror]
+#[lerr(prefix = "[leaper_mode::wayland]", result_name = WaylandResult)]
+pub enum WaylandError {
+    #[lerr(str = "[wayland_client::connect] {0}")]
+    Connect(#[lerr(from, wrap = Arc)] wayland_client::ConnectError),
+    #[lerr(str = "[wayland_client::dispatch] {0}")]
+    Dispatch(#[lerr(from, wrap = Arc)] wayland_client::DispatchError),
+}
@@ -0,0 +1,112 @@
+/// What the field codes of a desktop entry Exec line (`%u`, `%i`, `%c`, ...) get filled
+/// in with. Nothing is ever opened through the launcher, so the file and URL codes go
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecFields<'a> {
+    /// Translated Name, for `%c`
+    pub name: &'a str,
+    /// Icon key, `%i` becomes `--icon <icon>` (or nothing without one)
+    pub icon: Option<&'a str>,
+    /// Location of the desktop file, for `%k`
+    pub desktop_file: &'a str,
+}
+
+impl ExecFields<'_> {
+    /// Applies the field codes to the already split (unquoted) Exec arguments.
+    /// Arguments that only make sense with a file or URL (e.g. `--url=%u`) are dropped
+    /// along with the code, unknown and deprecated codes are removed
+    pub fn expand(&self, args: Vec<String>) -> Vec<String> {
+        let mut expanded = Vec::with_capacity(args.len());
+
+        for arg in args {
+            if arg == "%i" {
+                if let Some(icon) = self.icon {
+                    expanded.extend(["--icon".to_string(), icon.to_string()]);
+                }
+
+                continue;
+            }
+
+            if let Some(arg) = self.expand_arg(&arg) {
+                expanded.push(arg);
+            }
+        }
+
+        expanded
+    }
+
+    /// `None` if the argument has to go
+    fn expand_arg(&self, arg: &str) -> Option<String> {
+        let mut out = String::with_capacity(arg.len());
+        let mut chars = arg.chars();
+        let mut dropped_code = false;
+
+        while let Some(ch) = chars.next() {
+            if ch != '%' {
+                out.push(ch);
+                continue;
+            }
+
+            match chars.next() {
+                Some('%') => out.push('%'),
+                Some('c') => out.push_str(self.name),
+                Some('k') => out.push_str(self.desktop_file),
+                Some('i') => out.push_str(self.icon.unwrap_or_default()),
+                Some('f' | 'F' | 'u' | 'U') => return None,
+                // %d, %D, %n, %N, %v and %m are deprecated, anything else is invalid
+                Some(_) => dropped_code = true,
+                None => out.push('%'),
+            }
+        }
+
+        // A lone deprecated code shouldn't leave an empty argument behind
+        (!(dropped_code && out.is_empty())).then_some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELDS: ExecFields = ExecFields {
+        name: "Firefox",
+        icon: Some("firefox"),
+        desktop_file: "/usr/share/applications/firefox.desktop",
+    };
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn file_codes_are_stripped() {
+        assert_eq!(FIELDS.expand(args(&["firefox", "%u"])), ["firefox"]);
+        assert_eq!(
+            FIELDS.expand(args(&["code", "--new-window", "%F"])),
+            ["code", "--new-window"]
+        );
+        assert_eq!(FIELDS.expand(args(&["app", "--url=%u"])), ["app"]);
+    }
+
+    #[test]
+    fn codes_are_filled() {
+        assert_eq!(
+            FIELDS.expand(args(&["app", "%i", "--class=%c", "%k"])),
+            [
+                "app",
+                "--icon",
+                "firefox",
+                "--class=Firefox",
+                "/usr/share/applications/firefox.desktop"
+            ]
+        );
+        assert_eq!(ExecFields::default().expand(args(&["app", "%i"])), ["app"]);
+    }
+
+    #[test]
+    fn literal_and_deprecated_codes() {
+        assert_eq!(
+            FIELDS.expand(args(&["printf", "100%%", "%d", "50%"])),
+            ["printf", "100%", "50%"]
+        );
+    }
+}
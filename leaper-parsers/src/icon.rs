@@ -0,0 +1,90 @@
+use nom::{
+    IResult, Parser,
+    character::complete::{char, u32},
+    combinator::opt,
+    sequence::{pair, preceded, separated_pair},
+};
+
+use crate::parse_all;
+
+/// Size of an icon, as encoded in icon theme directories (`48x48`, `32x32@2`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IconDims {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl IconDims {
+    /// Looks for the first `WxH[@scale]` component of `path`
+    pub fn from_path(path: &str) -> Option<Self> {
+        path.split('/')
+            .find_map(|component| parse_all(dims, component).ok())
+    }
+}
+
+fn dims(input: &str) -> IResult<&str, IconDims> {
+    let (input, ((width, height), scale)) = pair(
+        separated_pair(u32, char('x'), u32),
+        opt(preceded(char('@'), u32)),
+    )
+    .parse(input)?;
+
+    let scale = scale.unwrap_or(1) as usize;
+
+    Ok((
+        input,
+        IconDims {
+            width: width as usize * scale,
+            height: height as usize * scale,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_paths() {
+        assert_eq!(
+            IconDims::from_path("/usr/share/icons/hicolor/48x48/apps/firefox.png"),
+            Some(IconDims {
+                width: 48,
+                height: 48
+            })
+        );
+        assert_eq!(
+            IconDims::from_path("/usr/share/icons/Papirus/16x24/apps/foo.svg"),
+            Some(IconDims {
+                width: 16,
+                height: 24
+            })
+        );
+    }
+
+    #[test]
+    fn scaled() {
+        assert_eq!(
+            IconDims::from_path("/usr/share/icons/hicolor/32x32@2/apps/foo.png"),
+            Some(IconDims {
+                width: 64,
+                height: 64
+            })
+        );
+    }
+
+    #[test]
+    fn no_dims() {
+        assert_eq!(
+            IconDims::from_path("/usr/share/icons/hicolor/scalable/apps/foo.svg"),
+            None
+        );
+        assert_eq!(IconDims::from_path("/usr/share/pixmaps/xterm.xpm"), None);
+        // Only whole components count
+        assert_eq!(
+            IconDims::from_path("/home/me/1920x1080-wallpapers/a.png"),
+            None
+        );
+        assert_eq!(IconDims::from_path("/icons/48x/a.png"), None);
+    }
+}
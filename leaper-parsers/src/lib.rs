@@ -0,0 +1,70 @@
+pub mod exec;
+pub mod icon;
+pub mod query;
+pub mod template;
+
+use nom::Parser;
+
+use macros::lerror;
+
+/// Runs `parser` over the whole `input`, anything left unparsed is an error
+pub fn parse_all<'a, O>(
+    parser: impl Parser<&'a str, Output = O, Error = nom::error::Error<&'a str>>,
+    input: &'a str,
+) -> ParseResult<O> {
+    nom::combinator::all_consuming(parser)
+        .parse(input)
+        .map(|(_, out)| out)
+        .map_err(|err| match err {
+            nom::Err::Error(err) | nom::Err::Failure(err) => ParseError::at(input, err.input),
+            nom::Err::Incomplete(_) => ParseError::at(input, ""),
+        })
+}
+
+#[lerror]
+#[lerr(prefix = "[leaper_parsers]", result_name = ParseResult)]
+pub enum ParseError {
+    #[lerr(str = "Unexpected input at column {column}: {rest:?}")]
+    Unexpected {
+        input: String,
+        rest: String,
+        /// Byte offset of `rest` in `input`
+        column: usize,
+    },
+}
+
+impl ParseError {
+    /// `rest` has to be a suffix of `input`, which is what nom errors point at
+    fn at(input: &str, rest: &str) -> Self {
+        Self::Unexpected {
+            input: input.into(),
+            rest: rest.into(),
+            column: input.len() - rest.len(),
+        }
+    }
+
+    /// The input with a caret under where parsing failed, for showing next to a search box
+    pub fn report(&self) -> String {
+        match self {
+            Self::Unexpected { input, column, .. } => {
+                format!("{input}\n{}^", " ".repeat(input[..*column].chars().count()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::bytes::complete::tag;
+
+    use super::*;
+
+    #[test]
+    fn parse_all_rejects_leftovers() {
+        assert_eq!(parse_all(tag("abc"), "abc").unwrap(), "abc");
+
+        let err = parse_all(tag("abc"), "abcdef").unwrap_err();
+        assert!(matches!(&err, ParseError::Unexpected { column: 3, rest, .. } if rest == "def"));
+        assert_eq!(err.report(), "abcdef\n   ^");
+    }
+}
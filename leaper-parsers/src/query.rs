@@ -0,0 +1,211 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::{tag, take_till1},
+    character::complete::{char, digit1, multispace0, multispace1, one_of},
+    combinator::{cut, map, map_res, opt, recognize},
+    multi::separated_list0,
+    sequence::{delimited, pair, preceded},
+};
+
+use crate::{ParseResult, parse_all};
+
+/// Search string split into filters, e.g. `report ext:pdf in:~/docs before:2024-01-01`
+pub fn parse(input: &str) -> ParseResult<Vec<Term>> {
+    parse_all(
+        delimited(multispace0, separated_list0(multispace1, term), multispace0),
+        input,
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    /// Lowercase word that has to appear in the file name
+    Text(String),
+    /// `ext:pdf`
+    Ext(String),
+    /// `in:~/docs`
+    In(PathBuf),
+    /// `type:img`
+    Kind(String),
+    /// `size:>10M`, `size:<1G`, `size:512K`
+    Size(SizeCmp, u64),
+    /// `before:2024-01-01`, unix timestamp (seconds)
+    Before(i64),
+    /// `after:2024-01-01`, unix timestamp (seconds)
+    After(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeCmp {
+    Less,
+    Equal,
+    Greater,
+}
+
+fn term(input: &str) -> IResult<&str, Term> {
+    alt((
+        preceded(
+            tag("ext:"),
+            cut(map(value, |ext| {
+                Term::Ext(ext.trim_start_matches('.').to_lowercase())
+            })),
+        ),
+        preceded(
+            tag("in:"),
+            cut(map(value, |dir| Term::In(expand_home(dir)))),
+        ),
+        preceded(
+            tag("type:"),
+            cut(map(value, |kind| Term::Kind(kind.to_lowercase()))),
+        ),
+        preceded(
+            tag("size:"),
+            cut(map(pair(size_cmp, size), |(cmp, size)| {
+                Term::Size(cmp, size)
+            })),
+        ),
+        preceded(tag("before:"), cut(map(date, Term::Before))),
+        preceded(tag("after:"), cut(map(date, Term::After))),
+        map(value, |word| Term::Text(word.to_lowercase())),
+    ))
+    .parse(input)
+}
+
+fn value(input: &str) -> IResult<&str, &str> {
+    take_till1(char::is_whitespace).parse(input)
+}
+
+fn size_cmp(input: &str) -> IResult<&str, SizeCmp> {
+    map(opt(one_of("<>")), |cmp| match cmp {
+        Some('<') => SizeCmp::Less,
+        Some(_) => SizeCmp::Greater,
+        None => SizeCmp::Equal,
+    })
+    .parse(input)
+}
+
+/// `10`, `512K`, `10M`, `1.5G`, with an optional trailing `B`/`iB`, in binary units
+pub fn size(input: &str) -> IResult<&str, u64> {
+    let number = map_res(
+        recognize(pair(digit1, opt(pair(char('.'), digit1)))),
+        str::parse::<f64>,
+    );
+    let unit = map(
+        pair(
+            opt(one_of("kKmMgGtT")),
+            opt(alt((tag("iB"), tag("B"), tag("b")))),
+        ),
+        |(unit, _)| match unit.map(|unit| unit.to_ascii_uppercase()) {
+            Some('K') => 1u64 << 10,
+            Some('M') => 1 << 20,
+            Some('G') => 1 << 30,
+            Some('T') => 1 << 40,
+            _ => 1,
+        },
+    );
+
+    map(pair(number, unit), |(number, unit)| {
+        (number * unit as f64) as u64
+    })
+    .parse(input)
+}
+
+/// `YYYY-MM-DD`, as the unix timestamp of its start (UTC)
+pub fn date(input: &str) -> IResult<&str, i64> {
+    map_res(value, |date| {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(|date| date.and_time(Default::default()).and_utc().timestamp())
+    })
+    .parse(input)
+}
+
+fn expand_home(dir: &str) -> PathBuf {
+    match dir.strip_prefix('~') {
+        Some(rest) => match std::env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(rest.trim_start_matches('/')),
+            None => dir.into(),
+        },
+        None => dir.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParseError;
+
+    use super::*;
+
+    #[test]
+    fn free_text() {
+        assert_eq!(
+            parse("  Quarterly   Report ").unwrap(),
+            vec![Term::Text("quarterly".into()), Term::Text("report".into())]
+        );
+        assert_eq!(parse("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn filters() {
+        assert_eq!(
+            parse("ext:.PDF in:/srv/docs type:Doc report").unwrap(),
+            vec![
+                Term::Ext("pdf".into()),
+                Term::In("/srv/docs".into()),
+                Term::Kind("doc".into()),
+                Term::Text("report".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn home_dir() {
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+
+        assert_eq!(
+            parse("in:~/docs").unwrap(),
+            vec![Term::In(home.join("docs"))]
+        );
+        assert_eq!(parse("in:~").unwrap(), vec![Term::In(home)]);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(
+            parse("size:>10M size:<1.5GiB size:512").unwrap(),
+            vec![
+                Term::Size(SizeCmp::Greater, 10 << 20),
+                Term::Size(SizeCmp::Less, 3 << 29),
+                Term::Size(SizeCmp::Equal, 512),
+            ]
+        );
+        assert_eq!(size("4kb").unwrap(), ("", 4096));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(
+            parse("before:2024-01-01 after:1970-01-02").unwrap(),
+            vec![Term::Before(1_704_067_200), Term::After(86_400)]
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_bad_filter() {
+        let err = parse("report before:yesterday").unwrap_err();
+        assert!(matches!(&err, ParseError::Unexpected { column: 14, .. }));
+        assert_eq!(err.report(), "report before:yesterday\n              ^");
+
+        assert!(matches!(
+            parse("size:>lots").unwrap_err(),
+            ParseError::Unexpected { column: 6, .. }
+        ));
+        assert!(matches!(
+            parse("size:10X").unwrap_err(),
+            ParseError::Unexpected { column: 7, .. }
+        ));
+    }
+}
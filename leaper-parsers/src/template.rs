@@ -0,0 +1,119 @@
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::{is_not, tag, take_while1},
+    combinator::{map, value},
+    multi::many0,
+    sequence::delimited,
+};
+
+use crate::{ParseResult, parse_all};
+
+/// Command with `{name}` placeholders filled in by the user before running it
+/// (`ssh {host}`, `man {page}`). `{{` and `}}` are literal braces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template(Vec<Segment>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Text(String),
+    Placeholder(String),
+}
+
+impl Template {
+    pub fn parse(input: &str) -> ParseResult<Self> {
+        parse_all(many0(segment), input).map(|segments| Self(merge_text(segments)))
+    }
+
+    /// Placeholder names in order of first appearance, repeated ones are asked for once
+    pub fn placeholders(&self) -> Vec<&str> {
+        let mut names = Vec::<&str>::new();
+
+        for segment in &self.0 {
+            if let Segment::Placeholder(name) = segment
+                && !names.contains(&name.as_str())
+            {
+                names.push(name);
+            }
+        }
+
+        names
+    }
+
+    /// Substitutes every placeholder with what `value` returns for its name
+    pub fn render<'a>(&self, value: impl Fn(&str) -> &'a str) -> String {
+        self.0
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.as_str(),
+                Segment::Placeholder(name) => value(name),
+            })
+            .collect()
+    }
+}
+
+fn segment(input: &str) -> IResult<&str, Segment> {
+    alt((
+        map(placeholder, |name: &str| Segment::Placeholder(name.into())),
+        map(
+            alt((value("{", tag("{{")), value("}", tag("}}")), is_not("{}"))),
+            |text: &str| Segment::Text(text.into()),
+        ),
+    ))
+    .parse(input)
+}
+
+fn placeholder(input: &str) -> IResult<&str, &str> {
+    delimited(
+        tag("{"),
+        take_while1(|ch: char| ch.is_alphanumeric() || ch == '_' || ch == '-'),
+        tag("}"),
+    )
+    .parse(input)
+}
+
+/// Escapes and plain text get parsed as separate segments
+fn merge_text(segments: Vec<Segment>) -> Vec<Segment> {
+    segments
+        .into_iter()
+        .fold(Vec::new(), |mut merged, segment| {
+            match (merged.last_mut(), segment) {
+                (Some(Segment::Text(prev)), Segment::Text(text)) => prev.push_str(&text),
+                (_, segment) => merged.push(segment),
+            }
+
+            merged
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders() {
+        let template = Template::parse("scp {file} {host}:{file}").unwrap();
+
+        assert_eq!(template.placeholders(), ["file", "host"]);
+        assert_eq!(
+            template.render(|name| match name {
+                "file" => "notes.txt",
+                _ => "box",
+            }),
+            "scp notes.txt box:notes.txt"
+        );
+    }
+
+    #[test]
+    fn escaped_braces() {
+        let template = Template::parse("awk '{{print ${col}}}'").unwrap();
+
+        assert_eq!(template.placeholders(), ["col"]);
+        assert_eq!(template.render(|_| "2"), "awk '{print $2}'");
+    }
+
+    #[test]
+    fn unclosed_placeholder() {
+        assert!(Template::parse("ssh {host").is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use iced_fonts::{Nerd, nerd::icon_to_string};
+
+use leaper_core::power::{PowerAction, PowerActions};
+use mode::config::{ActionMethod, LeaperModeConfig};
+
+use crate::{LeaperPower, LeaperPowerResult};
+
+/// A power menu action along with how the config says to run it, so other modes (e.g.
+/// the launcher) can offer the same actions without going through the power menu
+#[derive(Debug, Clone)]
+pub struct PowerCommand {
+    action: PowerAction,
+    icon: String,
+    method: ActionMethod,
+    lock_first: bool,
+}
+
+impl PowerCommand {
+    /// `None` if kiosk mode took the action away
+    pub fn new(action: PowerAction, config: &LeaperModeConfig) -> Option<Self> {
+        if !config.allows(LeaperPower::capability(action)) {
+            return None;
+        }
+
+        let icon = match action.icon(&config.power.layout.icons) {
+            Some(icon) => icon.to_string(),
+            None => icon_to_string(Self::default_icon(action)),
+        };
+
+        Some(Self {
+            action,
+            icon,
+            method: action.method(&config.power.actions).clone(),
+            lock_first: action.lock_first(&config.power.lock_first),
+        })
+    }
+
+    /// Allowed actions whose name starts with a word of `query`, in power menu order
+    pub fn matching(query: &str, config: &LeaperModeConfig) -> Vec<Self> {
+        let query = query.trim().to_lowercase();
+
+        if query.is_empty() {
+            return vec![];
+        }
+
+        LeaperPower::BUTTONS
+            .iter()
+            .filter(|(.., action)| {
+                action
+                    .name()
+                    .to_lowercase()
+                    .split_whitespace()
+                    .any(|word| word.starts_with(&query))
+                    || action.name().to_lowercase().starts_with(&query)
+            })
+            .filter_map(|(.., action)| Self::new(*action, config))
+            .collect()
+    }
+
+    pub fn action(&self) -> PowerAction {
+        self.action
+    }
+
+    /// Nerd Font glyph, the configured one if there is one
+    pub fn icon(&self) -> &str {
+        &self.icon
+    }
+
+    /// Connects to logind just for this one action
+    pub async fn run(self) -> LeaperPowerResult<()> {
+        self.run_with(PowerActions::connect().await?).await
+    }
+
+    pub async fn run_with(self, actions: PowerActions) -> LeaperPowerResult<()> {
+        actions
+            .perform(self.action, self.method, self.lock_first)
+            .await?;
+
+        Ok(())
+    }
+
+    fn default_icon(action: PowerAction) -> Nerd {
+        LeaperPower::BUTTONS
+            .iter()
+            .find(|(.., button)| *button == action)
+            .map(|(icon, ..)| *icon)
+            .unwrap_or(Nerd::Power)
+    }
+}
@@ -1,3 +1,5 @@
+mod command;
+
 use std::sync::Arc;
 
 use directories::ProjectDirs;
@@ -5,7 +7,7 @@ use iced::{
     Event, Font,
     alignment::Horizontal,
     keyboard::{self, Key, key},
-    widget::{button, center, column, row, text},
+    widget::{Column, Row, button, center, column, text},
 };
 use iced_fonts::{NERD_FONT, Nerd, nerd::icon_to_string};
 use iced_layershell::{
@@ -14,49 +16,23 @@ use iced_layershell::{
     settings::{LayerShellSettings, Settings, StartMode},
     to_layer_message,
 };
-use logind_zbus::{manager::ManagerProxy, session::SessionProxy};
-use zbus::{Connection, connection};
 
+use leaper_core::{
+    CoreError, CoreResult,
+    power::{PowerAction, PowerActions},
+};
 use macros::lerror;
 use mode::{
     LeaperMode, LeaperModeTheme,
-    config::{ActionMethod, LeaperAppModeConfigError, LeaperModeConfig},
+    config::{Capability, LeaperAppModeConfigError, LeaperModeConfig, PowerArrangement},
 };
 
-macro_rules! logind_fns {
-    (
-        $(
-            $root:ident => [
-                $(
-                    $fn:ident [$context:literal] $((
-                        $($param:expr),+
-                        $(,)?
-                    ))?
-                ),+
-                $(,)?
-            ]
-        ),+
-        $(,)?
-    ) => {
-        $($(
-            async fn $fn(connection: Option<Connection>) -> LeaperPowerResult<()> {
-                let Some(connection) = connection else {
-                    return Err(LeaperPowerError::NoDBusConnection);
-                };
-
-                Ok(Self::$root(&connection)
-                    .await?
-                    .$fn($($($param),+)?)
-                    .await?)
-            }
-        )+)+
-    }
-}
+pub use crate::command::PowerCommand;
 
 #[derive(Default)]
 pub struct LeaperPower {
     config: LeaperModeConfig,
-    connection: Option<Connection>,
+    actions: Option<PowerActions>,
 }
 
 impl LeaperMode for LeaperPower {
@@ -67,6 +43,7 @@ impl LeaperMode for LeaperPower {
     fn run() -> Result<(), Self::RunError> {
         let project_dirs = Self::project_dirs();
         let config = LeaperModeConfig::open(&project_dirs)?;
+        config.renderer.apply();
 
         let Settings {
             fonts,
@@ -96,13 +73,12 @@ impl LeaperMode for LeaperPower {
             virtual_keyboard_support,
         };
 
-        iced_layershell::build_pattern::application(Self::title, Self::update, Self::view)
-            .settings(settings)
-            .theme(Self::theme)
-            .subscription(Self::subscription)
-            .font(iced_fonts::REQUIRED_FONT_BYTES)
-            .font(iced_fonts::NERD_FONT_BYTES)
-            .run_with(move || Self::init(project_dirs, config, ()))?;
+        mode::run_mode!(Self {
+            settings: settings,
+            window_size: (1480.0, 320.0),
+            fonts: [iced_fonts::REQUIRED_FONT_BYTES, iced_fonts::NERD_FONT_BYTES],
+            init: move || Self::init(project_dirs, config, ()),
+        });
 
         Ok(())
     }
@@ -117,9 +93,9 @@ impl LeaperMode for LeaperPower {
     {
         let power = Self {
             config,
-            connection: None,
+            actions: None,
         };
-        let task = Self::Task::done(Self::Msg::ConnectZbus);
+        let task = Self::Task::perform(PowerActions::connect(), Self::Msg::Connected);
 
         (power, task)
     }
@@ -128,57 +104,25 @@ impl LeaperMode for LeaperPower {
         match msg {
             Self::Msg::Exit => return iced::exit(),
 
-            Self::Msg::ConnectZbus => {
-                return Self::Task::perform(LeaperPower::zbus_connect(), |res| {
-                    Self::Msg::ZbusConnected(res)
-                });
-            }
-            Self::Msg::ZbusConnected(connection) => match connection {
-                Ok(connection) => self.connection = Some(connection),
-                Err(e) => {
-                    tracing::error!("{}", e);
+            Self::Msg::Connected(actions) => match actions {
+                Ok(actions) => self.actions = Some(actions),
+                Err(err) => {
+                    tracing::error!("{err}");
                     return Self::Task::done(Self::Msg::Exit);
                 }
             },
-            Self::Msg::Lock => {
-                return Self::action_task(
-                    "Lock",
-                    self.config.power.actions.lock.clone(),
-                    self.connection.clone(),
-                    Self::lock,
-                );
-            }
-            Self::Msg::LogOut => {
-                return Self::action_task(
-                    "Log Out",
-                    self.config.power.actions.log_out.clone(),
-                    self.connection.clone(),
-                    Self::terminate,
-                );
-            }
-            Self::Msg::Hibernate => {
-                return Self::action_task(
-                    "Hibernate",
-                    self.config.power.actions.hibernate.clone(),
-                    self.connection.clone(),
-                    Self::hibernate,
-                );
-            }
-            Self::Msg::Reboot => {
-                return Self::action_task(
-                    "Reboot",
-                    self.config.power.actions.reboot.clone(),
-                    self.connection.clone(),
-                    Self::reboot,
-                );
-            }
-            Self::Msg::Shutdown => {
-                return Self::action_task(
-                    "Shutdown",
-                    self.config.power.actions.shutdown.clone(),
-                    self.connection.clone(),
-                    Self::power_off,
-                );
+            Self::Msg::Action(action) => {
+                let Some(command) = PowerCommand::new(action, &self.config) else {
+                    tracing::warn!("{} is disabled in kiosk mode", action.name());
+                    return Self::Task::none();
+                };
+
+                let Some(actions) = self.actions.clone() else {
+                    tracing::error!("{}", LeaperPowerError::NoDBusConnection);
+                    return Self::Task::none();
+                };
+
+                return Self::Task::perform(command.run_with(actions), Self::Msg::ActionResult);
             }
             Self::Msg::ActionResult(result) => {
                 if let Err(err) = result {
@@ -196,11 +140,14 @@ impl LeaperMode for LeaperPower {
                         Key::Named(key::Named::Escape) | Key::Character("q" | "Q") => {
                             return Self::Task::done(Self::Msg::Exit);
                         }
-                        Key::Character("L" | "l") => return Self::Task::done(Self::Msg::Lock),
-                        Key::Character("O" | "o") => return Self::Task::done(Self::Msg::LogOut),
-                        Key::Character("H" | "h") => return Self::Task::done(Self::Msg::Hibernate),
-                        Key::Character("R" | "r") => return Self::Task::done(Self::Msg::Reboot),
-                        Key::Character("S" | "s") => return Self::Task::done(Self::Msg::Shutdown),
+                        Key::Character(ch) => {
+                            if let Some((.., action)) = Self::BUTTONS
+                                .iter()
+                                .find(|(_, shortcut, _)| shortcut.eq_ignore_ascii_case(ch))
+                            {
+                                return Self::Task::done(Self::Msg::Action(*action));
+                            }
+                        }
                         _ => (),
                     }
                 }
@@ -219,43 +166,75 @@ impl LeaperMode for LeaperPower {
     }
 
     fn view(&self) -> Self::Element<'_> {
-        let power_btn = |icon: Nerd, str: &'static str, shortcut: &'static str, msg: Self::Msg| {
-            button(center(
-                column![
-                    text(icon_to_string(icon)).font(NERD_FONT).size(80),
-                    text(str)
-                        .font(Font {
-                            weight: iced::font::Weight::Semibold,
-                            ..Default::default()
-                        })
-                        .size(30),
-                    text(format!("[{shortcut}]"))
-                        .font(Font {
-                            weight: iced::font::Weight::Semibold,
-                            ..Default::default()
-                        })
-                        .size(20.0)
-                ]
+        let layout = &self.config.power.layout;
+        let size = layout.button_size as f32;
+        let semibold = Font {
+            weight: iced::font::Weight::Semibold,
+            ..Default::default()
+        };
+
+        let power_btn = |icon: Nerd, action: PowerAction, shortcut: &'static str| {
+            let icon = action
+                .icon(&layout.icons)
+                .map(ToString::to_string)
+                .unwrap_or_else(|| icon_to_string(icon));
+
+            let mut content = column![text(icon).font(NERD_FONT).size(size * 0.4)]
                 .align_x(Horizontal::Center)
-                .spacing(5),
-            ))
-            .width(200)
-            .height(200)
-            .style(style::grid_button)
-            .on_press(msg)
+                .spacing(size * 0.025);
+
+            if layout.labels {
+                content = content.push(text(action.name()).font(semibold).size(size * 0.15));
+            }
+
+            if layout.shortcuts {
+                content = content.push(
+                    text(format!("[{shortcut}]"))
+                        .font(semibold)
+                        .size(size * 0.1),
+                );
+            }
+
+            button(center(content))
+                .width(size)
+                .height(size)
+                .style(style::grid_button)
+                .on_press(Self::Msg::Action(action))
         };
 
-        center(
-            row![
-                power_btn(Nerd::AccountLock, "Lock", "L", Self::Msg::Lock),
-                power_btn(Nerd::Logout, "Log Out", "O", Self::Msg::LogOut),
-                power_btn(Nerd::Snowflake, "Hibernate", "H", Self::Msg::Hibernate),
-                power_btn(Nerd::RotateLeft, "Reboot", "R", Self::Msg::Reboot),
-                power_btn(Nerd::Power, "Shutdown", "S", Self::Msg::Shutdown)
-            ]
-            .spacing(40.0),
-        )
-        .into()
+        let buttons = Self::BUTTONS
+            .iter()
+            .filter(|(.., action)| self.config.allows(Self::capability(*action)))
+            .map(|(icon, shortcut, action)| -> Self::Element<'_> {
+                power_btn(*icon, *action, *shortcut).into()
+            });
+
+        let spacing = size * 0.2;
+
+        match layout.arrangement {
+            PowerArrangement::Row => center(Row::with_children(buttons).spacing(spacing)).into(),
+            PowerArrangement::Grid => {
+                let mut rows = vec![];
+                let mut row = Row::new().spacing(spacing);
+
+                for (ind, button) in buttons.enumerate() {
+                    if ind > 0 && ind % Self::GRID_COLUMNS == 0 {
+                        rows.push(std::mem::replace(&mut row, Row::new().spacing(spacing)).into());
+                    }
+
+                    row = row.push(button);
+                }
+
+                rows.push(row.into());
+
+                center(
+                    Column::with_children(rows)
+                        .spacing(spacing)
+                        .align_x(Horizontal::Center),
+                )
+                .into()
+            }
+        }
     }
 
     fn subscription(&self) -> Self::Subscription {
@@ -272,70 +251,28 @@ impl LeaperMode for LeaperPower {
 }
 
 impl LeaperPower {
-    async fn cmd(action: impl Into<String>, args: Vec<String>) -> LeaperPowerResult<()> {
-        let program = args
-            .first()
-            .ok_or_else(|| LeaperPowerError::ActionCMDEmpty(action.into()))?;
-
-        let mut cmd = tokio::process::Command::new(program);
-
-        if args.len() > 1 {
-            cmd.args(&args[1..]);
-        }
-
-        let mut process = cmd.spawn().map_err(Arc::new)?;
-        process.wait().await.map_err(Arc::new)?;
-
-        Ok(())
-    }
-
-    pub async fn zbus_connect() -> LeaperPowerResult<Connection> {
-        Ok(connection::Builder::system()?
-            .internal_executor(false)
-            .build()
-            .await?)
-    }
+    /// Buttons per row of [`PowerArrangement::Grid`]
+    const GRID_COLUMNS: usize = 2;
+
+    const BUTTONS: [(Nerd, &'static str, PowerAction); 6] = [
+        (Nerd::AccountLock, "L", PowerAction::Lock),
+        (Nerd::AccountSwitch, "U", PowerAction::SwitchUser),
+        (Nerd::Logout, "O", PowerAction::LogOut),
+        (Nerd::Snowflake, "H", PowerAction::Hibernate),
+        (Nerd::RotateLeft, "R", PowerAction::Reboot),
+        (Nerd::Power, "S", PowerAction::Shutdown),
+    ];
 
-    fn action_task<DF>(
-        action: &'static str,
-        method: ActionMethod,
-        connection: Option<Connection>,
-        dbus_fn: impl Fn(Option<Connection>) -> DF,
-    ) -> <Self as LeaperMode>::Task
-    where
-        DF: Future<Output = LeaperPowerResult<()>> + Send + 'static,
-    {
-        match method {
-            ActionMethod::Dbus => <Self as LeaperMode>::Task::perform(dbus_fn(connection), |res| {
-                <Self as LeaperMode>::Msg::ActionResult(res)
-            }),
-            ActionMethod::Cmd(args) => {
-                <Self as LeaperMode>::Task::perform(Self::cmd(action, args), |res| {
-                    <Self as LeaperMode>::Msg::ActionResult(res)
-                })
-            }
+    fn capability(action: PowerAction) -> Capability {
+        match action {
+            PowerAction::Lock => Capability::Lock,
+            PowerAction::SwitchUser => Capability::SwitchUser,
+            PowerAction::LogOut => Capability::LogOut,
+            PowerAction::Hibernate => Capability::Hibernate,
+            PowerAction::Reboot => Capability::Reboot,
+            PowerAction::Shutdown => Capability::Shutdown,
         }
     }
-
-    async fn get_logind_manager(connection: &'_ Connection) -> LeaperPowerResult<ManagerProxy<'_>> {
-        Ok(ManagerProxy::new(connection).await?)
-    }
-
-    async fn get_logind_session(connection: &'_ Connection) -> LeaperPowerResult<SessionProxy<'_>> {
-        Ok(SessionProxy::new(connection).await?)
-    }
-
-    logind_fns![
-        get_logind_session => [
-            lock["Failed to lock the session"],
-            terminate["Failed to terminate the session"],
-        ],
-        get_logind_manager => [
-            hibernate["Failed to hibernate"](false),
-            reboot["Failed to reboot"](false),
-            power_off["Failed to power off"](false),
-        ],
-    ];
 }
 
 #[to_layer_message]
@@ -343,38 +280,29 @@ impl LeaperPower {
 pub enum LeaperPowerMsg {
     Exit,
 
-    ConnectZbus,
-    ZbusConnected(LeaperPowerResult<Connection>),
-
-    Lock,
-    LogOut,
-    Hibernate,
-    Reboot,
-    Shutdown,
+    Connected(CoreResult<PowerActions>),
 
+    Action(PowerAction),
     ActionResult(LeaperPowerResult<()>),
 
     IcedEvent(Event),
 }
 
 #[lerror]
-#[lerr(prefix = "[leaper-power]", result_name = LeaperPowerResult)]
+#[lerr(prefix = "[leaper-power]", result_name = LeaperPowerResult, common)]
 pub enum LeaperPowerError {
-    #[lerr(str = "[std::io] {0}")]
-    IO(#[lerr(from, wrap = Arc)] std::io::Error),
-
     #[lerr(str = "Layershell error: {0}")]
     LayerShell(#[lerr(from, wrap = Arc)] iced_layershell::Error),
-    #[lerr(str = "Failed to connect to session bus: {0}")]
-    ZBus(#[lerr(from)] zbus::Error),
+    #[lerr(str = "[iced] {0}")]
+    Iced(#[lerr(from, wrap = Arc)] iced::Error),
 
     #[lerr(str = "{0}")]
     Config(#[lerr(from)] LeaperAppModeConfigError),
+    #[lerr(str = "{0}")]
+    Core(#[lerr(from)] CoreError),
 
     #[lerr(str = "No ProjectDirs!")]
     NoProjectDirs,
-    #[lerr(str = "Empty cmd args list for action {0}")]
-    ActionCMDEmpty(String),
     #[lerr(str = "No dbus connection!")]
     NoDBusConnection,
 }
@@ -1,10 +1,17 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    process::ExitStatus,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
 use directories::ProjectDirs;
 use iced::{
-    Event,
-    keyboard::{self, Key, key},
-    widget::{center, text_input},
+    Event, Length,
+    keyboard::{self, Key, Modifiers, key},
+    widget::{center, column, text, text_input},
 };
 use iced_layershell::{
     build_pattern::MainSettings,
@@ -16,14 +23,83 @@ use iced_layershell::{
 use macros::lerror;
 use mode::{
     LeaperMode,
-    config::{LeaperAppModeConfigError, LeaperModeConfig},
+    catcher::KeyCatcher,
+    config::{Capability, LeaperAppModeConfigError, LeaperModeConfig},
+    keys::{Binding, KeyBindings, KeyLayout},
 };
+use parsers::template::Template;
+use style::hints::hint_bar;
+
+/// Set by `leaper runner --watch`
+static START_WATCHING: AtomicBool = AtomicBool::new(false);
 
 #[derive(Default)]
 pub struct LeaperRunner {
     config: LeaperModeConfig,
 
     input: String,
+    /// Keep the window open and run the command again on every Enter
+    watch: bool,
+    running: bool,
+    last_status: Option<RunStatus>,
+    prompt: Option<TemplatePrompt>,
+    /// Enter gets captured by the input, so Ctrl is tracked separately
+    modifiers: keyboard::Modifiers,
+}
+
+/// Command template being filled in, one placeholder per Enter
+struct TemplatePrompt {
+    name: String,
+    template: Template,
+    values: Vec<String>,
+}
+
+impl TemplatePrompt {
+    fn next_placeholder(&self) -> Option<&str> {
+        self.template.placeholders().get(self.values.len()).copied()
+    }
+
+    /// Values are shell-quoted, so each one stays a single argument
+    fn render(&self) -> String {
+        let values = self
+            .template
+            .placeholders()
+            .into_iter()
+            .zip(&self.values)
+            .map(|(name, value)| {
+                let quoted = shlex::try_quote(value)
+                    .map(|quoted| quoted.into_owned())
+                    .unwrap_or_else(|_| value.clone());
+
+                (name, quoted)
+            })
+            .collect::<HashMap<_, _>>();
+
+        self.template
+            .render(|name| values.get(name).map(String::as_str).unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RunStatus {
+    Exited(ExitStatus),
+    Failed(Arc<std::io::Error>),
+    Detached(Result<(), String>),
+}
+
+impl std::fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exited(status) => match status.code() {
+                Some(0) => write!(f, "Exited successfully"),
+                Some(code) => write!(f, "Exited with code {code}"),
+                None => write!(f, "Terminated by a signal"),
+            },
+            Self::Failed(err) => write!(f, "Failed to run: {err}"),
+            Self::Detached(Ok(())) => write!(f, "Running in the daemon, see `leaper jobs`"),
+            Self::Detached(Err(err)) => write!(f, "Daemon couldn't run it: {err}"),
+        }
+    }
 }
 
 impl LeaperMode for LeaperRunner {
@@ -34,6 +110,12 @@ impl LeaperMode for LeaperRunner {
     fn run() -> Result<(), Self::RunError> {
         let project_dirs = Self::project_dirs();
         let config = LeaperModeConfig::open(&project_dirs)?;
+        config.renderer.apply();
+        KeyLayout::init();
+
+        if !config.allows(Capability::Runner) {
+            return Err(LeaperRunnerError::Disabled);
+        }
 
         let Settings {
             fonts,
@@ -50,7 +132,10 @@ impl LeaperMode for LeaperRunner {
                 anchor: Anchor::empty(),
                 layer: Layer::Overlay,
                 exclusive_zone: 0,
-                size: Some((600, 100)),
+                size: Some(Self::layer_size(
+                    START_WATCHING.load(Ordering::Relaxed),
+                    config.show_hints,
+                )),
                 margin: (0, 0, 0, 0),
                 keyboard_interactivity: KeyboardInteractivity::Exclusive,
                 start_mode: StartMode::Active,
@@ -63,11 +148,11 @@ impl LeaperMode for LeaperRunner {
             virtual_keyboard_support,
         };
 
-        iced_layershell::build_pattern::application("leaper", Self::update, Self::view)
-            .settings(settings)
-            .theme(Self::theme)
-            .subscription(Self::subscription)
-            .run_with(move || Self::init(project_dirs, config, ()))?;
+        mode::run_mode!(Self {
+            settings: settings,
+            window_size: Self::WINDOW_SIZE,
+            init: move || Self::init(project_dirs, config, ()),
+        });
 
         Ok(())
     }
@@ -82,6 +167,7 @@ impl LeaperMode for LeaperRunner {
     {
         let runner = Self {
             config,
+            watch: START_WATCHING.load(Ordering::Relaxed),
             ..Default::default()
         };
         let task = text_input::focus(Self::INPUT_ID);
@@ -90,16 +176,51 @@ impl LeaperMode for LeaperRunner {
     }
 
     fn view(&self) -> Self::Element<'_> {
-        center(
-            text_input("Input command to run...", &self.input)
-                .id(Self::INPUT_ID)
-                .size(30)
-                .padding(10)
-                .style(style::text_input)
-                .on_input(Self::Msg::Input)
-                .on_submit(Self::Msg::TryRun),
+        let placeholder = match &self.prompt {
+            Some(prompt) => format!(
+                "{} › {}",
+                prompt.name,
+                prompt.next_placeholder().unwrap_or_default()
+            ),
+            None => "Input command to run...".into(),
+        };
+        let input = text_input(&placeholder, &self.input)
+            .id(Self::INPUT_ID)
+            .size(30)
+            .padding(10)
+            .style(style::text_input)
+            .on_input(Self::Msg::Input)
+            .on_submit(Self::Msg::TryRun);
+
+        let status = self.watch.then(|| {
+            let status = match (&self.last_status, self.running) {
+                (_, true) => "Running...".to_string(),
+                (Some(status), false) => status.to_string(),
+                (None, false) => "Watching, Enter runs the command again".to_string(),
+            };
+
+            text(status).size(14).width(Length::Fill)
+        });
+
+        let hints = self
+            .config
+            .show_hints
+            .then(|| hint_bar(Self::BINDINGS.hints(|action| self.available(action))));
+
+        KeyCatcher::new(
+            center(
+                column![input]
+                    .push_maybe(status)
+                    .push_maybe(hints)
+                    .spacing(5),
+            )
+            .padding(10),
+            |event| {
+                Self::BINDINGS
+                    .catches(event, |action| self.available(action))
+                    .then(|| Self::Msg::IcedEvent(Event::Keyboard(event.clone())))
+            },
         )
-        .padding(10)
         .into()
     }
 
@@ -108,15 +229,88 @@ impl LeaperMode for LeaperRunner {
             Self::Msg::Exit => return iced::exit(),
 
             Self::Msg::Input(new_input) => self.input = new_input,
+            Self::Msg::TryRun if self.running => {
+                tracing::debug!("Previous run is still going, ignoring");
+            }
             Self::Msg::TryRun => {
+                if let Some(prompt) = &mut self.prompt {
+                    prompt.values.push(std::mem::take(&mut self.input));
+
+                    if prompt.next_placeholder().is_some() {
+                        return Self::Task::none();
+                    }
+
+                    self.input = prompt.render();
+                    self.prompt = None;
+                } else if let Some(template) = self
+                    .config
+                    .runner
+                    .templates
+                    .iter()
+                    .find(|template| template.name == self.input.trim())
+                {
+                    match Template::parse(&template.command) {
+                        Ok(parsed) => {
+                            let prompt = TemplatePrompt {
+                                name: template.name.clone(),
+                                template: parsed,
+                                values: vec![],
+                            };
+
+                            match prompt.next_placeholder() {
+                                Some(_) => {
+                                    self.input.clear();
+                                    self.prompt = Some(prompt);
+
+                                    return Self::Task::none();
+                                }
+                                None => self.input = prompt.render(),
+                            }
+                        }
+                        Err(err) => {
+                            tracing::error!("Invalid command template {:?}: {err}", template.name);
+                            return Self::Task::none();
+                        }
+                    }
+                }
+
                 let split = shlex::split(&self.input);
 
                 match split {
                     None => {
-                        tracing::warn!("Failed to split {:?} into command arguments!", self.input)
+                        tracing::warn!(
+                            "Failed to split {} into command arguments!",
+                            leaper_tracing::redact::line(&self.input)
+                        )
                     }
                     Some(mut split) => match split.is_empty() {
                         true => tracing::warn!("Command is empty!"),
+                        false if self.config.runner.detach || self.modifiers.control() => {
+                            return Self::Task::perform(Self::run_detached(split), |res| {
+                                Self::Msg::Finished(RunStatus::Detached(res))
+                            });
+                        }
+                        false if self.watch => {
+                            let cmd = split.remove(0);
+                            self.running = true;
+
+                            // Waited for on the executor, the UI keeps running meanwhile
+                            return Self::Task::perform(
+                                async move {
+                                    tokio::process::Command::new(cmd)
+                                        .args(split)
+                                        .spawn()?
+                                        .wait()
+                                        .await
+                                },
+                                |res| {
+                                    Self::Msg::Finished(match res {
+                                        Ok(status) => RunStatus::Exited(status),
+                                        Err(err) => RunStatus::Failed(Arc::new(err)),
+                                    })
+                                },
+                            );
+                        }
                         false => {
                             let cmd = split.remove(0);
 
@@ -125,19 +319,67 @@ impl LeaperMode for LeaperRunner {
                                     tracing::debug!("Command spawned successfully!");
                                     return Self::Task::done(Self::Msg::Exit);
                                 }
-                                Err(err) => tracing::error!("Failed to run the command: {err}"),
+                                Err(err) => {
+                                    tracing::error!("Failed to run the command: {err}");
+                                    self.last_status = Some(RunStatus::Failed(Arc::new(err)));
+                                }
                             }
                         }
                     },
                 }
             }
 
+            Self::Msg::Finished(RunStatus::Detached(Ok(()))) if !self.watch => {
+                return Self::Task::done(Self::Msg::Exit);
+            }
+            Self::Msg::Finished(status) => {
+                if let RunStatus::Detached(Err(err)) = &status {
+                    tracing::error!("Failed to run the command in the daemon: {err}");
+                }
+
+                self.running = false;
+                self.last_status = Some(status);
+            }
+            Self::Msg::ToggleWatch => {
+                self.watch = !self.watch;
+                self.last_status = None;
+
+                let (width, height) = Self::layer_size(self.watch, self.config.show_hints);
+
+                return Self::Task::done(Self::Msg::SizeChange((width, height)));
+            }
+
             Self::Msg::IcedEvent(event) => {
+                if let Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) = event {
+                    self.modifiers = modifiers;
+                }
+
                 if let Event::Keyboard(event) = event
-                    && let keyboard::Event::KeyPressed { key, .. } = event
-                    && let Key::Named(key::Named::Escape) | Key::Character("q" | "Q") = key.as_ref()
+                    && let keyboard::Event::KeyPressed { key, modifiers, .. } = event
                 {
-                    return Self::Task::done(Self::Msg::Exit);
+                    let action = Self::BINDINGS
+                        .find(key.as_ref(), modifiers, |action| self.available(action));
+
+                    match action {
+                        Some(KeyAction::Run | KeyAction::RunDetached) => {
+                            return Self::Task::done(Self::Msg::TryRun);
+                        }
+                        Some(KeyAction::ToggleWatch) => {
+                            return Self::Task::done(Self::Msg::ToggleWatch);
+                        }
+                        Some(KeyAction::Cancel) => {
+                            self.prompt = None;
+                            self.input.clear();
+
+                            return text_input::focus(Self::INPUT_ID);
+                        }
+                        Some(KeyAction::Clear) => {
+                            self.input.clear();
+                            return text_input::focus(Self::INPUT_ID);
+                        }
+                        Some(KeyAction::Exit) => return Self::Task::done(Self::Msg::Exit),
+                        None => {}
+                    }
                 }
             }
 
@@ -168,6 +410,88 @@ impl LeaperMode for LeaperRunner {
 
 impl LeaperRunner {
     pub const INPUT_ID: &'static str = "command_input";
+    const WINDOW_SIZE: (f32, f32) = (600.0, 100.0);
+    /// Extra room for the status line in watch mode
+    const STATUS_HEIGHT: u32 = 25;
+    /// Extra room for the hint bar
+    const HINTS_HEIGHT: u32 = 25;
+
+    /// Enter and Ctrl+Enter are mostly handled by the input's submit, they're listed here
+    /// for when it lost focus and for the hints
+    const BINDINGS: KeyBindings<KeyAction> = KeyBindings(&[
+        Binding::new(Key::Named(key::Named::Enter), KeyAction::Run, "run"),
+        Binding::new(
+            Key::Named(key::Named::Enter),
+            KeyAction::RunDetached,
+            "run in daemon",
+        )
+        .with(Modifiers::CTRL),
+        Binding::new(Key::Character("w"), KeyAction::ToggleWatch, "watch").with(Modifiers::CTRL),
+        Binding::new(
+            Key::Named(key::Named::Escape),
+            KeyAction::Cancel,
+            "cancel template",
+        ),
+        Binding::new(Key::Named(key::Named::Escape), KeyAction::Clear, "clear"),
+        Binding::new(Key::Named(key::Named::Escape), KeyAction::Exit, "exit"),
+        Binding::new(Key::Character("q"), KeyAction::Exit, "exit"),
+    ]);
+
+    fn available(&self, action: KeyAction) -> bool {
+        let clears = self.config.escape_clears && !self.input.is_empty();
+
+        match action {
+            KeyAction::Run | KeyAction::ToggleWatch => true,
+            KeyAction::RunDetached => !self.config.runner.detach,
+            KeyAction::Cancel => self.prompt.is_some(),
+            KeyAction::Clear => self.prompt.is_none() && clears,
+            KeyAction::Exit => self.prompt.is_none() && !clears,
+        }
+    }
+
+    /// Starts the runner in watch mode, see [`LeaperRunnerMsg::ToggleWatch`]
+    pub fn start_watching() {
+        START_WATCHING.store(true, Ordering::Relaxed);
+    }
+
+    async fn run_detached(cmd: Vec<String>) -> Result<(), String> {
+        let client = daemon::client::connect()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        client
+            .run_detached(daemon::client::context::current(), cmd)
+            .await
+            .map_err(|err| err.to_string())?
+            .map(|run| tracing::debug!("Daemon is running the command as {run:?}"))
+    }
+
+    fn layer_size(watch: bool, hints: bool) -> (u32, u32) {
+        let (width, mut height) = (Self::WINDOW_SIZE.0 as u32, Self::WINDOW_SIZE.1 as u32);
+
+        if watch {
+            height += Self::STATUS_HEIGHT;
+        }
+
+        if hints {
+            height += Self::HINTS_HEIGHT;
+        }
+
+        (width, height)
+    }
+}
+
+/// What the runner's [`KeyBindings`] map to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyAction {
+    Run,
+    /// Ctrl is tracked separately, so this ends up in the same [`LeaperRunnerMsg::TryRun`]
+    RunDetached,
+    ToggleWatch,
+    /// Drops the template prompt
+    Cancel,
+    Clear,
+    Exit,
 }
 
 #[to_layer_message]
@@ -177,6 +501,9 @@ pub enum LeaperRunnerMsg {
 
     Input(String),
     TryRun,
+    Finished(RunStatus),
+    /// Ctrl+W, keeps the window open after running the command
+    ToggleWatch,
 
     IcedEvent(Event),
 }
@@ -186,7 +513,12 @@ pub enum LeaperRunnerMsg {
 pub enum LeaperRunnerError {
     #[lerr(str = "[iced_layershell] {0}")]
     LayerShell(#[lerr(from, wrap = Arc)] iced_layershell::Error),
+    #[lerr(str = "[iced] {0}")]
+    Iced(#[lerr(from, wrap = Arc)] iced::Error),
 
     #[lerr(str = "{0}")]
     Config(#[lerr(from)] LeaperAppModeConfigError),
+
+    #[lerr(str = "Runner is disabled in kiosk mode")]
+    Disabled,
 }
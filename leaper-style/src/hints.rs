@@ -0,0 +1,41 @@
+use iced::{
+    Element, Length,
+    alignment::Vertical,
+    widget::{Row, container, row, text},
+};
+
+use mode::LeaperModeTheme;
+
+/// Bottom bar listing the shortcuts available right now,
+/// fed by [`mode::keys::KeyBindings::hints`]
+pub fn hint_bar<'a, Msg: 'a>(
+    hints: Vec<(String, &'static str)>,
+) -> Element<'a, Msg, LeaperModeTheme> {
+    let hints = hints.into_iter().map(|(keys, hint)| {
+        row![
+            container(text(keys).size(12))
+                .padding([2, 6])
+                .style(key_hint),
+            text(hint).size(12),
+        ]
+        .spacing(5)
+        .align_y(Vertical::Center)
+        .into()
+    });
+
+    Row::with_children(hints)
+        .spacing(15)
+        .width(Length::Fill)
+        .wrap()
+        .into()
+}
+
+fn key_hint(theme: &LeaperModeTheme) -> container::Style {
+    let palette = theme.extended_palette();
+
+    let mut style = container::bordered_box(theme);
+    style.background = Some(palette.background.strong.color.into());
+    style.border = style.border.rounded(5.0);
+
+    style
+}
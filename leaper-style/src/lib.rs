@@ -1,3 +1,6 @@
+pub mod hints;
+pub mod osk;
+
 use iced::{Color, widget};
 
 use mode::LeaperModeTheme;
@@ -31,6 +34,7 @@ pub fn list_button(
     theme: &LeaperModeTheme,
     status: widget::button::Status,
     selected: bool,
+    accent: Option<Color>,
 ) -> widget::button::Style {
     let status = match selected {
         true => widget::button::Status::Hovered,
@@ -47,6 +51,26 @@ pub fn list_button(
         .color(palette.background.strong.color)
         .rounded(10.0);
 
+    if let Some(accent) = accent.filter(|_| selected) {
+        style.background = Some(accent.scale_alpha(0.35).into());
+        style.border = style.border.color(accent).width(2.0);
+    }
+
+    style
+}
+
+/// Filter toggle, selected ones look pressed
+pub fn chip(
+    theme: &LeaperModeTheme,
+    status: widget::button::Status,
+    selected: bool,
+) -> widget::button::Style {
+    let mut style = match selected {
+        true => widget::button::primary(theme, status),
+        false => widget::button::secondary(theme, status),
+    };
+    style.border = style.border.rounded(15.0);
+
     style
 }
 
@@ -59,3 +83,23 @@ pub fn grid_button(
 
     style
 }
+
+pub fn key_button(
+    theme: &LeaperModeTheme,
+    status: widget::button::Status,
+) -> widget::button::Style {
+    let mut style = widget::button::secondary(theme, status);
+    style.border = style.border.rounded(8.0);
+
+    style
+}
+
+pub fn tooltip(theme: &LeaperModeTheme) -> widget::container::Style {
+    let palette = theme.extended_palette();
+
+    let mut style = widget::container::bordered_box(theme);
+    style.background = Some(palette.background.weak.color.scale_alpha(0.95).into());
+    style.border = style.border.rounded(10.0);
+
+    style
+}
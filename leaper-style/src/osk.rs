@@ -0,0 +1,138 @@
+use iced::{
+    Element, Length,
+    alignment::{Horizontal, Vertical},
+    widget::{Column, Row, button, container, text},
+};
+
+use mode::LeaperModeTheme;
+
+/// Key emitted by the on-screen keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OskKey {
+    Char(char),
+    Backspace,
+    Enter,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OskLayout {
+    #[default]
+    Qwerty,
+    PinPad,
+}
+
+impl OskLayout {
+    fn rows(&self) -> &'static [&'static [OskKey]] {
+        use OskKey::*;
+
+        match self {
+            Self::Qwerty => &[
+                &[
+                    Char('1'),
+                    Char('2'),
+                    Char('3'),
+                    Char('4'),
+                    Char('5'),
+                    Char('6'),
+                    Char('7'),
+                    Char('8'),
+                    Char('9'),
+                    Char('0'),
+                ],
+                &[
+                    Char('q'),
+                    Char('w'),
+                    Char('e'),
+                    Char('r'),
+                    Char('t'),
+                    Char('y'),
+                    Char('u'),
+                    Char('i'),
+                    Char('o'),
+                    Char('p'),
+                ],
+                &[
+                    Char('a'),
+                    Char('s'),
+                    Char('d'),
+                    Char('f'),
+                    Char('g'),
+                    Char('h'),
+                    Char('j'),
+                    Char('k'),
+                    Char('l'),
+                    Backspace,
+                ],
+                &[
+                    Char('z'),
+                    Char('x'),
+                    Char('c'),
+                    Char('v'),
+                    Char('b'),
+                    Char('n'),
+                    Char('m'),
+                    Char(' '),
+                    Enter,
+                ],
+            ],
+            Self::PinPad => &[
+                &[Char('1'), Char('2'), Char('3')],
+                &[Char('4'), Char('5'), Char('6')],
+                &[Char('7'), Char('8'), Char('9')],
+                &[Backspace, Char('0'), Enter],
+            ],
+        }
+    }
+
+    fn key_size(&self) -> f32 {
+        match self {
+            Self::Qwerty => 50.0,
+            Self::PinPad => 70.0,
+        }
+    }
+}
+
+/// Rows of buttons that map presses to `on_key` messages,
+/// for feeding text into the focused input on touch devices
+pub fn on_screen_keyboard<'a, Msg: Clone + 'a>(
+    layout: OskLayout,
+    on_key: impl Fn(OskKey) -> Msg,
+) -> Element<'a, Msg, LeaperModeTheme> {
+    let size = layout.key_size();
+
+    let rows = layout.rows().iter().map(|keys| {
+        Row::with_children(keys.iter().map(|key| {
+            let (label, width) = match key {
+                OskKey::Char(' ') => ("␣".to_string(), size * 2.0),
+                OskKey::Char(ch) => (ch.to_string(), size),
+                OskKey::Backspace => ("⌫".to_string(), size),
+                OskKey::Enter => ("⏎".to_string(), size),
+            };
+
+            button(
+                text(label)
+                    .size(size * 0.45)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .align_x(Horizontal::Center)
+                    .align_y(Vertical::Center),
+            )
+            .width(width)
+            .height(size)
+            .style(super::key_button)
+            .on_press(on_key(*key))
+            .into()
+        }))
+        .spacing(5)
+        .into()
+    });
+
+    container(
+        Column::with_children(rows)
+            .spacing(5)
+            .align_x(Horizontal::Center),
+    )
+    .padding(10)
+    .center_x(Length::Fill)
+    .into()
+}
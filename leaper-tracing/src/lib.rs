@@ -1,29 +1,185 @@
-use color_eyre::Result;
-use tracing_subscriber::prelude::*;
+pub mod limit;
+pub mod redact;
 
-pub fn init_tracing(trace: bool, debug: bool, error: bool) -> Result<()> {
+pub use tracing;
+
+use std::sync::{Mutex, OnceLock};
+
+use color_eyre::{Result, eyre::eyre};
+use tracing_subscriber::{EnvFilter, Registry, filter::LevelFilter, prelude::*, reload};
+
+/// Targets of the workspace crates, each gets its own directive so they can be tuned
+/// separately (e.g. `RUST_LOG=leaper_db=trace`)
+pub const TARGETS: &[&str] = &[
+    "leaper",
+    "leaper_core",
+    "leaper_daemon",
+    "leaper_db",
+    "leaper_error",
+    "leaper_executor",
+    "leaper_launcher",
+    "leaper_lock",
+    "leaper_mode",
+    "leaper_parsers",
+    "leaper_power",
+    "leaper_runner",
+    "leaper_style",
+    "leaper_tracing",
+    "leaper_tui",
+];
+
+/// Levels [`cycle_level`] goes through
+const CYCLE: [LevelFilter; 3] = [LevelFilter::INFO, LevelFilter::DEBUG, LevelFilter::TRACE];
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+/// Level of the workspace targets the filter was last built with
+static LEVEL: Mutex<LevelFilter> = Mutex::new(LevelFilter::INFO);
+/// RUST_LOG at startup, kept on top of the level whenever the filter is rebuilt
+static ENV_DIRECTIVES: OnceLock<String> = OnceLock::new();
+
+/// Where the logs go, see `log` in the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogOutputs {
+    /// The systemd journal, with the levels as priorities and the span/event fields as
+    /// journal fields
+    pub journald: bool,
+    /// Pretty output on stderr
+    pub stderr: bool,
+}
+
+impl LogOutputs {
+    /// The journal when `journald` is set, stderr only along with it if `keep_stderr` is
+    pub fn new(journald: bool, keep_stderr: bool) -> Self {
+        Self {
+            journald,
+            stderr: !journald || keep_stderr,
+        }
+    }
+}
+
+impl Default for LogOutputs {
+    fn default() -> Self {
+        Self::new(false, false)
+    }
+}
+
+pub fn init_tracing(trace: bool, debug: bool, error: bool, outputs: LogOutputs) -> Result<()> {
     let level = error
-        .then_some("error")
-        .or_else(|| (cfg!(feature = "profile") || trace).then_some("trace"))
-        .or_else(|| (cfg!(debug_assertions) || debug).then_some("debug"))
-        .unwrap_or("info");
-    let directives = ["leaper", "leaper-daemon"]
-        .map(|target| format!("{target}={level}"))
-        .join(",");
+        .then_some(LevelFilter::ERROR)
+        .or_else(|| (cfg!(feature = "profile") || trace).then_some(LevelFilter::TRACE))
+        .or_else(|| (cfg!(debug_assertions) || debug).then_some(LevelFilter::DEBUG))
+        .unwrap_or(LevelFilter::INFO);
+
+    if let Ok(env) = std::env::var(EnvFilter::DEFAULT_ENV)
+        && !env.trim().is_empty()
+    {
+        let _ = ENV_DIRECTIVES.set(env.trim().to_string());
+    }
+
+    let directives = filter_directives(level, &[]);
+    let (filter, handle) = reload::Layer::new(EnvFilter::try_new(&directives)?);
+
+    // Without the journal socket (e.g. not running systemd) it falls back to stderr
+    let (journald, journald_err) = match outputs.journald {
+        true => match tracing_journald::layer() {
+            Ok(layer) => (Some(layer), None),
+            Err(err) => (None, Some(err)),
+        },
+        false => (None, None),
+    };
+    let stderr = outputs.stderr || journald.is_none();
 
     #[cfg(not(feature = "profile"))]
-    let layer = tracing_subscriber::fmt::layer().pretty();
+    let layer = stderr.then(|| tracing_subscriber::fmt::layer().pretty());
 
     #[cfg(feature = "profile")]
-    let layer = tracing_tracy::TracyLayer::default();
+    let layer = {
+        let _ = stderr;
+        tracing_tracy::TracyLayer::default()
+    };
 
     let registry = tracing_subscriber::registry()
+        .with(filter)
         .with(layer)
-        .with(tracing_subscriber::EnvFilter::new(directives));
+        .with(journald);
 
     registry.try_init()?;
 
-    tracing::debug!("Logging initialized!");
+    if let Some(err) = journald_err {
+        tracing::warn!("Can't log to the journal, logging to stderr instead: {err}");
+    }
+
+    let _ = FILTER.set(handle);
+    *LEVEL.lock().unwrap() = level;
+
+    tracing::debug!("Logging initialized with {directives:?}");
 
     Ok(())
 }
+
+/// `level` for every workspace target, everything else stays off
+pub fn directives(level: LevelFilter) -> String {
+    TARGETS
+        .iter()
+        .map(|target| format!("{target}={level}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Swaps the filter of the running subscriber, rebuilt from the workspace level, RUST_LOG
+/// and `filter`. A bare level in it (e.g. `debug`) replaces the workspace level, any other
+/// [`EnvFilter`] directives go on top of the rest (replacing ones set before).
+/// Returns the directives now in effect
+pub fn set_filter(filter: &str) -> Result<String> {
+    let (levels, extra): (Vec<_>, Vec<_>) = filter
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .partition(|directive| directive.parse::<LevelFilter>().is_ok());
+
+    let mut level = LEVEL.lock().unwrap();
+    let new_level = match levels.last() {
+        Some(new_level) => new_level.parse().expect("Parsed before"),
+        None => *level,
+    };
+
+    let directives = filter_directives(new_level, &extra);
+    let env_filter = EnvFilter::try_new(&directives)?;
+
+    FILTER
+        .get()
+        .ok_or_else(|| eyre!("Logging isn't initialized"))?
+        .reload(env_filter)?;
+
+    *level = new_level;
+    drop(level);
+
+    tracing::info!("Log filter changed to {directives:?}");
+
+    Ok(directives)
+}
+
+/// `level` for the workspace targets, then RUST_LOG, then `extra`, later directives win
+/// over earlier ones for the same targets
+fn filter_directives(level: LevelFilter, extra: &[&str]) -> String {
+    std::iter::once(directives(level))
+        .chain(ENV_DIRECTIVES.get().cloned())
+        .chain(extra.iter().map(ToString::to_string))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Moves the workspace targets to the next level of info -> debug -> trace -> info,
+/// for toggling verbosity with a signal
+pub fn cycle_level() -> Result<LevelFilter> {
+    let current = *LEVEL.lock().unwrap();
+    let next = CYCLE
+        .iter()
+        .position(|level| *level == current)
+        .map(|ind| CYCLE[(ind + 1) % CYCLE.len()])
+        .unwrap_or(CYCLE[0]);
+
+    set_filter(&next.to_string())?;
+
+    Ok(next)
+}
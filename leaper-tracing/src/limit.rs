@@ -0,0 +1,163 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash, RandomState},
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Shared by [`error_limited!`](crate::error_limited) and [`warn_limited!`](crate::warn_limited)
+pub static LIMITER: LazyLock<RateLimiter> = LazyLock::new(|| RateLimiter::new(RateLimiter::WINDOW));
+
+/// Lets a message through once per key and window, counting the ones held back in between.
+/// Remembers at most [`Self::MAX_KEYS`] keys, the daemon keeps running while paths come
+/// and go
+pub struct RateLimiter {
+    window: Duration,
+    keys: Mutex<HashMap<u64, Limited>>,
+    hasher: RandomState,
+}
+
+struct Limited {
+    logged: Instant,
+    suppressed: u64,
+}
+
+impl RateLimiter {
+    pub const WINDOW: Duration = Duration::from_secs(60);
+    pub const MAX_KEYS: usize = 4096;
+
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            keys: Default::default(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// `Some` if a message for `key` should be logged now, with the number of messages
+    /// held back since the last one that was
+    pub fn check(&self, key: impl Hash) -> Option<u64> {
+        let key = self.hasher.hash_one(key);
+        let now = Instant::now();
+
+        let mut keys = self.keys.lock().unwrap();
+
+        match keys.get_mut(&key) {
+            Some(limited) if now.duration_since(limited.logged) < self.window => {
+                limited.suppressed += 1;
+                None
+            }
+            Some(limited) => {
+                limited.logged = now;
+                Some(std::mem::take(&mut limited.suppressed))
+            }
+            None => {
+                if keys.len() >= Self::MAX_KEYS {
+                    self.prune(&mut keys, now);
+                }
+
+                keys.insert(
+                    key,
+                    Limited {
+                        logged: now,
+                        suppressed: 0,
+                    },
+                );
+                Some(0)
+            }
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Forgets the keys whose window is over, they'd get logged right away anyway (only
+    /// their count of held back messages goes). The oldest one goes if that's not enough
+    fn prune(&self, keys: &mut HashMap<u64, Limited>, now: Instant) {
+        keys.retain(|_, limited| now.duration_since(limited.logged) < self.window);
+
+        if keys.len() < Self::MAX_KEYS {
+            return;
+        }
+
+        if let Some(oldest) = keys
+            .iter()
+            .min_by_key(|(_, limited)| limited.logged)
+            .map(|(key, _)| *key)
+        {
+            keys.remove(&oldest);
+        }
+    }
+}
+
+/// [`tracing::error!`] that logs once per key (e.g. path + error kind) every
+/// [`RateLimiter::WINDOW`], for errors that would otherwise repeat every frame/walk
+#[macro_export]
+macro_rules! error_limited {
+    ($key:expr, $($arg:tt)+) => {
+        $crate::log_limited!(error, $key, $($arg)+)
+    };
+}
+
+/// [`tracing::warn!`] counterpart of [`error_limited!`]
+#[macro_export]
+macro_rules! warn_limited {
+    ($key:expr, $($arg:tt)+) => {
+        $crate::log_limited!(warn, $key, $($arg)+)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_limited {
+    ($level:ident, $key:expr, $($arg:tt)+) => {
+        match $crate::limit::LIMITER.check($key) {
+            Some(0) => $crate::tracing::$level!($($arg)+),
+            Some(suppressed) => $crate::tracing::$level!(
+                "{} [{suppressed} more since the last report]",
+                format_args!($($arg)+)
+            ),
+            None => {}
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn once_per_window() {
+        let limiter = RateLimiter::new(RateLimiter::WINDOW);
+
+        assert_eq!(limiter.check("a"), Some(0));
+        assert_eq!(limiter.check("a"), None);
+        assert_eq!(limiter.check("a"), None);
+        assert_eq!(limiter.check("b"), Some(0));
+    }
+
+    #[test]
+    fn counts_held_back() {
+        let limiter = RateLimiter::new(Duration::from_millis(20));
+
+        assert_eq!(limiter.check("a"), Some(0));
+        assert_eq!(limiter.check("a"), None);
+        assert_eq!(limiter.check("a"), None);
+
+        std::thread::sleep(Duration::from_millis(30));
+
+        assert_eq!(limiter.check("a"), Some(2));
+    }
+
+    #[test]
+    fn bounded() {
+        let limiter = RateLimiter::new(RateLimiter::WINDOW);
+
+        for key in 0..RateLimiter::MAX_KEYS * 2 {
+            limiter.check(key);
+        }
+
+        assert_eq!(limiter.keys.lock().unwrap().len(), RateLimiter::MAX_KEYS);
+        // The newest key is still limited
+        assert_eq!(limiter.check(RateLimiter::MAX_KEYS * 2 - 1), None);
+    }
+}
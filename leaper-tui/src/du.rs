@@ -0,0 +1,160 @@
+use std::{path::PathBuf, sync::Arc};
+
+use directories::ProjectDirs;
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, List, ListState},
+};
+
+use db::{fs::DirUsage, init_db};
+use leaper_core::files::{FileCatalog, human_size};
+use mode::config::LeaperModeConfig;
+
+use crate::{LeaperTuiError, LeaperTuiResult};
+
+/// ncdu-style view of the fs index: children of a directory by the size of
+/// everything indexed under them, with drill-down navigation
+pub struct LeaperDuTui {
+    catalog: FileCatalog,
+
+    dir: PathBuf,
+    entries: Vec<DirUsage>,
+    list_state: ListState,
+}
+
+impl LeaperDuTui {
+    const BAR_WIDTH: usize = 20;
+
+    pub fn run(dir: PathBuf) -> LeaperTuiResult<()> {
+        let project_dirs =
+            ProjectDirs::from("com", "tukanoid", "leaper").ok_or(LeaperTuiError::NoProjectDirs)?;
+        let config = LeaperModeConfig::open(&project_dirs)?;
+        let dir = std::path::absolute(dir)?;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(Arc::new)?;
+
+        let mut tui = runtime.block_on(async {
+            let db = init_db(config.db_port).await?;
+            let mut tui = Self {
+                catalog: FileCatalog::new(db),
+                dir,
+                entries: vec![],
+                list_state: ListState::default(),
+            };
+            tui.open(None).await?;
+
+            LeaperTuiResult::Ok(tui)
+        })?;
+
+        let terminal = ratatui::init();
+        let res = tui.event_loop(terminal, &runtime);
+        ratatui::restore();
+
+        res
+    }
+
+    /// Loads [`Self::dir`], selecting the entry named `select` if it's there
+    async fn open(&mut self, select: Option<String>) -> LeaperTuiResult<()> {
+        self.entries = self.catalog.clone().usage(&self.dir).await?;
+
+        let selected = select
+            .and_then(|name| self.entries.iter().position(|entry| entry.name == name))
+            .unwrap_or_default();
+        self.list_state.select(Some(selected));
+
+        Ok(())
+    }
+
+    fn event_loop(
+        &mut self,
+        mut terminal: DefaultTerminal,
+        runtime: &tokio::runtime::Runtime,
+    ) -> LeaperTuiResult<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame)).map_err(Arc::new)?;
+
+            let Event::Key(key) = event::read().map_err(Arc::new)? else {
+                continue;
+            };
+
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(());
+                }
+
+                KeyCode::Up => self.list_state.select_previous(),
+                KeyCode::Down => self.list_state.select_next(),
+
+                KeyCode::Enter | KeyCode::Right => {
+                    let Some(entry) = self
+                        .list_state
+                        .selected()
+                        .and_then(|ind| self.entries.get(ind))
+                        .filter(|entry| entry.is_dir)
+                    else {
+                        continue;
+                    };
+
+                    self.dir.push(&entry.name);
+                    runtime.block_on(self.open(None))?;
+                }
+                KeyCode::Backspace | KeyCode::Left => {
+                    let Some(name) = self.dir.file_name().map(|name| name.to_string_lossy()) else {
+                        continue;
+                    };
+                    let name = name.to_string();
+
+                    self.dir.pop();
+                    runtime.block_on(self.open(Some(name)))?;
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let total: u64 = self.entries.iter().map(|entry| entry.size).sum();
+        let largest = self.entries.first().map(|entry| entry.size).unwrap_or(1);
+
+        let list = List::new(self.entries.iter().map(|entry| {
+            let filled = (entry.size as f64 / largest.max(1) as f64 * Self::BAR_WIDTH as f64)
+                .round() as usize;
+            let bar = format!(
+                "{}{}",
+                "#".repeat(filled),
+                " ".repeat(Self::BAR_WIDTH - filled)
+            );
+            let name = match entry.is_dir {
+                true => format!("{}/", entry.name),
+                false => entry.name.clone(),
+            };
+
+            Line::from(format!(
+                "{:>10} [{bar}] {name} ({} files)",
+                human_size(entry.size),
+                entry.files
+            ))
+        }))
+        .block(Block::bordered().title(format!(
+            " {} — {} (Enter: open, Backspace: up) ",
+            self.dir.display(),
+            human_size(total)
+        )))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+
+        frame.render_stateful_widget(list, frame.area(), &mut self.list_state);
+    }
+}
@@ -0,0 +1,180 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{Local, TimeZone};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, List, ListState, Paragraph},
+};
+
+use daemon::{LeaperDaemonClient, client::context};
+use db::commands::CommandRun;
+
+use crate::{LeaperTuiError, LeaperTuiResult};
+
+/// Commands the runner handed to the daemon, with their output.
+/// Refreshes on its own while anything is still running
+pub struct LeaperJobsTui {
+    client: LeaperDaemonClient,
+
+    runs: Vec<CommandRun>,
+    list_state: ListState,
+    /// Lines scrolled down in the output of the selected run
+    scroll: u16,
+}
+
+impl LeaperJobsTui {
+    const LIMIT: usize = 100;
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+    const PAGE: u16 = 10;
+
+    pub fn run() -> LeaperTuiResult<()> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(Arc::new)?;
+
+        let mut tui = runtime.block_on(async {
+            let client = daemon::client::connect()
+                .await
+                .map_err(|err| LeaperTuiError::Daemon(err.to_string()))?;
+
+            let mut tui = Self {
+                client,
+                runs: vec![],
+                list_state: ListState::default().with_selected(Some(0)),
+                scroll: 0,
+            };
+            tui.refresh().await?;
+
+            LeaperTuiResult::Ok(tui)
+        })?;
+
+        let terminal = ratatui::init();
+        let res = tui.event_loop(terminal, &runtime);
+        ratatui::restore();
+
+        res
+    }
+
+    async fn refresh(&mut self) -> LeaperTuiResult<()> {
+        self.runs = self
+            .client
+            .command_runs(context::current(), Self::LIMIT)
+            .await
+            .map_err(|err| LeaperTuiError::Daemon(err.to_string()))?
+            .map_err(LeaperTuiError::Daemon)?;
+
+        Ok(())
+    }
+
+    fn event_loop(
+        &mut self,
+        mut terminal: DefaultTerminal,
+        runtime: &tokio::runtime::Runtime,
+    ) -> LeaperTuiResult<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame)).map_err(Arc::new)?;
+
+            let running = self
+                .runs
+                .iter()
+                .any(|run| run.finished.is_none() && !run.interrupted);
+
+            if !event::poll(Self::REFRESH_INTERVAL).map_err(Arc::new)? {
+                if running {
+                    runtime.block_on(self.refresh())?;
+                }
+
+                continue;
+            }
+
+            let Event::Key(key) = event::read().map_err(Arc::new)? else {
+                continue;
+            };
+
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(());
+                }
+
+                KeyCode::Char('r') => runtime.block_on(self.refresh())?,
+
+                KeyCode::Up => {
+                    self.list_state.select_previous();
+                    self.scroll = 0;
+                }
+                KeyCode::Down => {
+                    self.list_state.select_next();
+                    self.scroll = 0;
+                }
+
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(Self::PAGE),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(Self::PAGE),
+
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [list_area, output_area] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Fill(1)])
+                .areas(frame.area());
+
+        let list = List::new(self.runs.iter().map(|run| {
+            let started = Local
+                .timestamp_millis_opt(run.started)
+                .single()
+                .map(|started| started.format("%H:%M:%S").to_string())
+                .unwrap_or_else(|| "?".into());
+            let status = match (run.finished, run.exit_code) {
+                (None, _) if run.interrupted => "int".to_string(),
+                (None, _) => "…".to_string(),
+                (Some(_), Some(code)) => code.to_string(),
+                (Some(_), None) => "sig".to_string(),
+            };
+
+            Line::from(format!("{started} [{status:>3}] {}", run.cmd.join(" ")))
+        }))
+        .block(Block::bordered().title(" Commands (r: refresh, PgUp/PgDn: scroll) "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|ind| self.runs.get(ind));
+        let (title, output) = match selected {
+            Some(run) => {
+                let duration = run
+                    .duration()
+                    .map(|duration| format!("{duration:.2?}"))
+                    .unwrap_or_else(|| "running".into());
+
+                (
+                    format!(" {} ({duration}) ", run.cmd.join(" ")),
+                    run.output.as_str(),
+                )
+            }
+            None => (" Output ".into(), "No detached commands yet"),
+        };
+
+        frame.render_widget(
+            Paragraph::new(output)
+                .block(Block::bordered().title(title))
+                .scroll((self.scroll, 0)),
+            output_area,
+        );
+    }
+}
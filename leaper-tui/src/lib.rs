@@ -0,0 +1,212 @@
+pub mod du;
+pub mod jobs;
+
+use std::sync::Arc;
+
+use directories::ProjectDirs;
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    widgets::{Block, List, ListState, Paragraph},
+};
+
+use db::{apps::AppWithIcon, init_db};
+use leaper_core::{
+    CoreError, catalog::AppCatalog, launch::Launcher, search::AppSearch, sort::Launches,
+};
+use macros::lerror;
+use mode::config::{LeaperAppModeConfigError, LeaperModeConfig};
+
+/// Terminal launcher for headless/SSH sessions,
+/// backed by the same catalog, search and launch services as the iced modes
+pub struct LeaperTui {
+    catalog: AppCatalog,
+    launcher: Launcher,
+    app_search: AppSearch,
+
+    apps: Vec<AppWithIcon>,
+    filtered: Vec<AppWithIcon>,
+    launches: Launches,
+
+    search: String,
+    list_state: ListState,
+}
+
+impl LeaperTui {
+    pub fn run() -> LeaperTuiResult<()> {
+        let project_dirs =
+            ProjectDirs::from("com", "tukanoid", "leaper").ok_or(LeaperTuiError::NoProjectDirs)?;
+        let config = LeaperModeConfig::open(&project_dirs)?;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(Arc::new)?;
+
+        let mut tui = runtime.block_on(Self::init(config))?;
+
+        let terminal = ratatui::init();
+        let res = tui.event_loop(terminal, &runtime);
+        ratatui::restore();
+
+        res
+    }
+
+    async fn init(config: LeaperModeConfig) -> LeaperTuiResult<Self> {
+        let db = init_db(config.db_port).await?;
+        let catalog = AppCatalog::new(db, &config.launcher);
+
+        let mut apps = catalog.clone().apps().await?;
+        apps.retain(|app| !config.hides_app(&app.categories));
+
+        let launches = catalog.clone().launches().await.unwrap_or_else(|err| {
+            tracing::warn!("Failed to load launch history: {err}");
+            Default::default()
+        });
+
+        let launcher = Launcher::new(&config);
+        let mut app_search = AppSearch::new(config.launcher);
+        app_search.set_choices(catalog.clone().query_choices().await.unwrap_or_else(|err| {
+            tracing::warn!("Failed to load past search choices: {err}");
+            Default::default()
+        }));
+        app_search.sort(&mut apps, &launches);
+
+        Ok(Self {
+            catalog,
+            launcher,
+            app_search,
+
+            apps,
+            filtered: vec![],
+            launches,
+
+            search: String::new(),
+            list_state: ListState::default().with_selected(Some(0)),
+        })
+    }
+
+    fn event_loop(
+        &mut self,
+        mut terminal: DefaultTerminal,
+        runtime: &tokio::runtime::Runtime,
+    ) -> LeaperTuiResult<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame)).map_err(Arc::new)?;
+
+            let Event::Key(key) = event::read().map_err(Arc::new)? else {
+                continue;
+            };
+
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(());
+                }
+
+                KeyCode::Up => self.list_state.select_previous(),
+                KeyCode::Down => self.list_state.select_next(),
+
+                KeyCode::Enter => {
+                    let Some(app) = self
+                        .list_state
+                        .selected()
+                        .and_then(|ind| self.visible_apps().get(ind))
+                        .cloned()
+                    else {
+                        continue;
+                    };
+
+                    runtime.block_on(self.launcher.launch(&app))?;
+
+                    if let Err(err) =
+                        runtime.block_on(self.catalog.clone().record_launch(app.id.clone()))
+                    {
+                        tracing::warn!("Failed to record the launch of {}: {err}", app.name);
+                    }
+
+                    if !self.search.is_empty()
+                        && let Err(err) = runtime.block_on(
+                            self.catalog
+                                .clone()
+                                .record_choice(self.search.clone(), app.id.clone()),
+                        )
+                    {
+                        tracing::warn!("Failed to remember the choice of {}: {err}", app.name);
+                    }
+
+                    return Ok(());
+                }
+
+                KeyCode::Backspace => {
+                    self.search.pop();
+                    self.update_search();
+                }
+                KeyCode::Char(ch) => {
+                    self.search.push(ch);
+                    self.update_search();
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    fn update_search(&mut self) {
+        self.filtered = match self.search.is_empty() {
+            true => vec![],
+            false => self
+                .app_search
+                .search(&self.apps, &self.launches, &self.search),
+        };
+
+        self.list_state.select_first();
+    }
+
+    fn visible_apps(&self) -> &[AppWithIcon] {
+        match self.search.is_empty() {
+            true => &self.apps,
+            false => &self.filtered,
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search_area, list_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(frame.area());
+
+        frame.render_widget(
+            Paragraph::new(self.search.as_str()).block(Block::bordered().title(" Search ")),
+            search_area,
+        );
+
+        let list = List::new(self.visible_apps().iter().map(|app| app.name.as_str()))
+            .block(Block::bordered().title(" Apps "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+    }
+}
+
+#[lerror]
+#[lerr(prefix = "[leaper-tui]", result_name = LeaperTuiResult, common)]
+pub enum LeaperTuiError {
+    #[lerr(str = "{0}")]
+    Config(#[lerr(from)] LeaperAppModeConfigError),
+    #[lerr(str = "{0}")]
+    DB(#[lerr(from, wrap = Arc)] db::DBError),
+    #[lerr(str = "{0}")]
+    Core(#[lerr(from)] CoreError),
+
+    #[lerr(str = "[daemon] {0}")]
+    Daemon(String),
+
+    #[lerr(str = "No ProjectDirs!")]
+    NoProjectDirs,
+}
@@ -1,25 +1,161 @@
-use clap::{Parser, ValueEnum};
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
 
 /// A Launcher/Command Runner
 #[derive(Parser)]
 #[command(author, version, about, long_about = "None")]
 pub struct Cli {
-    #[arg(value_enum, default_value_t = Default::default())]
-    pub mode: AppMode,
+    #[command(subcommand)]
+    pub cmd: Option<LeaperCmd>,
+
+    /// Restricted mode: disables the runner, configured app categories and power actions except lock
+    #[arg(long, global = true)]
+    pub kiosk: bool,
+    /// Keep everything in memory and index in-process, nothing is written to disk
+    #[arg(long, global = true)]
+    pub ephemeral: bool,
+    /// Renderer to draw with (auto, wgpu, gl, software), overrides the config
+    #[arg(long, global = true)]
+    pub renderer: Option<mode::renderer::Renderer>,
+    /// Machine-readable output: JSON results for `leaper query`, errors to stderr as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
 
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub trace: bool,
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub debug: bool,
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub error: bool,
 }
 
-#[derive(Default, Clone, Copy, ValueEnum)]
-pub enum AppMode {
+#[derive(Default, Clone, Subcommand)]
+pub enum LeaperCmd {
     #[default]
     Launcher,
-    Runner,
+    Runner {
+        /// Keep the window open and re-run the command on every Enter (toggle with Ctrl+W)
+        #[arg(short, long)]
+        watch: bool,
+    },
     Power,
-    Lock,
+    Lock {
+        /// Show the lock UI in a regular window without locking the session,
+        /// only the password "demo" unlocks it
+        #[arg(long)]
+        demo: bool,
+
+        #[command(subcommand)]
+        cmd: Option<LockCmd>,
+    },
+
+    /// Fuzzy search the files indexed by the daemon and open the picked one
+    Find,
+
+    /// Terminal launcher for headless/SSH sessions
+    Tui,
+    /// Browse the commands the runner handed to the daemon and their output
+    Jobs,
+    /// Disk usage of indexed files, biggest directories first
+    Du {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+
+    /// Search the indexed catalog without any GUI, same ranking as the launcher
+    Query {
+        #[command(subcommand)]
+        cmd: QueryCmd,
+    },
+
+    /// Query the running daemon
+    Daemon {
+        #[command(subcommand)]
+        cmd: DaemonCmd,
+    },
+
+    /// DB maintenance, coordinated through the daemon
+    Db {
+        #[command(subcommand)]
+        cmd: DbCmd,
+    },
+
+    /// Run local diagnostics and print a report to attach to bug reports
+    Doctor,
+
+    /// Link `leaper-launcher`, `leaper-lock`, etc. to this binary, each runs its subcommand
+    InstallSymlinks {
+        /// Where to put the links, next to the binary by default
+        dir: Option<PathBuf>,
+        /// Replace files that are in the way
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Export user data (DB tables and config) into an archive
+    Export {
+        #[arg(short, long, default_value = "leaper-backup.tar.zst")]
+        output: PathBuf,
+    },
+    /// Import user data from an archive created with `leaper export`
+    Import {
+        input: PathBuf,
+        /// Keep the current config instead of restoring the archived one
+        #[arg(long)]
+        skip_config: bool,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum QueryCmd {
+    /// Apps matching the pattern, best first (all apps in launcher order if it's empty)
+    Apps {
+        #[arg(default_value = "")]
+        pattern: String,
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum DaemonCmd {
+    /// Show uptime and the history of indexing/search jobs
+    Status,
+    /// Index a directory right away, so its contents show up in searches
+    Index {
+        path: PathBuf,
+        /// Also index the parent directories up to `/`
+        #[arg(short, long)]
+        parents: bool,
+    },
+    /// Hash the contents of indexed files that don't have a hash yet
+    Hash,
+    /// List indexed files with identical contents (needs `leaper daemon hash` first)
+    Duplicates {
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Change what the daemon logs without restarting it
+    LogLevel {
+        /// A level (error, warn, info, debug, trace) for all leaper crates,
+        /// or `RUST_LOG`-style directives, e.g. `leaper_db=trace,leaper_daemon=info`
+        filter: String,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum DbCmd {
+    /// Export the whole index as SurrealQL
+    Backup { path: PathBuf },
+    /// Remove dangling records and old job history, then compact storage
+    Compact,
+}
+
+#[derive(Clone, Subcommand)]
+pub enum LockCmd {
+    /// Set a numeric PIN for quick unlock
+    SetPin,
+    /// Remove the quick unlock PIN, leaving only the password
+    ClearPin,
 }
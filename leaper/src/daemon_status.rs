@@ -0,0 +1,213 @@
+use std::{io::Write, path::PathBuf, time::Duration};
+
+use chrono::{Local, TimeZone};
+use color_eyre::{Result, eyre::eyre};
+
+use daemon::client::context;
+
+use crate::runtime;
+
+const JOBS_LIMIT: usize = 10;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Prints the daemon status along with its recent job history
+pub fn status() -> Result<()> {
+    runtime::with_daemon(async |client| {
+        let status = client.status(context::current()).await?;
+        let jobs = client
+            .jobs(context::current(), JOBS_LIMIT)
+            .await?
+            .map_err(|err| eyre!("Failed to fetch job history: {err}"))?;
+
+        println!("leaper-daemon v{}", status.version);
+        println!("  uptime    | {:?}", status.uptime);
+        println!(
+            "  searching | {}",
+            match status.searching_apps {
+                true => "yes",
+                false => "no",
+            }
+        );
+
+        println!(
+            "  reindex   | {}",
+            match status.next_reindex {
+                Some(next) => format!("next in {:?}", Duration::from_secs(next.as_secs())),
+                None => "disabled".into(),
+            }
+        );
+
+        let entries = status.desktop_entries;
+        println!(
+            "  entries   | {} parsed, {} skipped, {} failed, {} queued",
+            entries.parsed, entries.skipped, entries.failed, entries.queued
+        );
+        println!(
+            "  apps      | {} created, {} updated, {} name conflicts, {} masked, {} errors ({} batches)",
+            entries.created,
+            entries.updated,
+            entries.conflicts,
+            entries.masked,
+            entries.write_errors,
+            entries.batches
+        );
+
+        match status.inhibitors.is_empty() {
+            true => println!("  inhibit   | none"),
+            false => {
+                for inhibitor in &status.inhibitors {
+                    let since = Local
+                        .timestamp_millis_opt(inhibitor.since)
+                        .single()
+                        .map(|since| since.format("%H:%M:%S").to_string())
+                        .unwrap_or_else(|| "?".into());
+
+                    println!(
+                        "  inhibit   | {} since {since}: {} [{}]",
+                        inhibitor.app, inhibitor.reason, inhibitor.cookie
+                    );
+                }
+            }
+        }
+
+        println!("\n[jobs]");
+
+        if jobs.is_empty() {
+            println!("  no jobs recorded yet");
+        }
+
+        for job in jobs {
+            let started = Local
+                .timestamp_millis_opt(job.started)
+                .single()
+                .map(|started| started.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "?".into());
+            let duration = match job.duration() {
+                Some(duration) => format!("{duration:.2?}"),
+                None if job.interrupted => "interrupted".into(),
+                None => "running".into(),
+            };
+
+            println!(
+                "  {started} | {:<12} | {duration:>10} | {} items, {} errors",
+                job.kind,
+                job.items,
+                job.errors.len()
+            );
+
+            for err in &job.errors {
+                println!("      {err}");
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Asks the daemon to index `path` and reports progress inline until it's done
+pub fn index(path: PathBuf, parents: bool) -> Result<()> {
+    let path = path.canonicalize()?;
+
+    runtime::with_daemon(async |client| {
+        let mut ctx = context::current();
+        ctx.deadline = std::time::Instant::now() + Duration::from_secs(60 * 60);
+
+        let index = client.index(ctx, path.clone(), parents);
+        tokio::pin!(index);
+
+        let summary = loop {
+            tokio::select! {
+                summary = &mut index => break summary?,
+                _ = tokio::time::sleep(PROGRESS_INTERVAL) => {
+                    let progress = client.index_progress(context::current()).await?;
+
+                    if let Some(progress) = progress.iter().find(|progress| progress.root == path) {
+                        print!("\rIndexing {path:?}: {} entries", progress.items);
+                        std::io::stdout().flush()?;
+                    }
+                }
+            }
+        };
+
+        println!(
+            "\rIndexed {path:?}: {} entries, {} errors",
+            summary.items, summary.errors
+        );
+
+        Ok(())
+    })
+}
+
+/// Asks the daemon to hash indexed files and reports progress inline until it's done
+pub fn hash() -> Result<()> {
+    runtime::with_daemon(async |client| {
+        let mut ctx = context::current();
+        ctx.deadline = std::time::Instant::now() + Duration::from_secs(60 * 60);
+
+        let hash = client.hash_files(ctx);
+        tokio::pin!(hash);
+
+        let summary = loop {
+            tokio::select! {
+                summary = &mut hash => break summary?,
+                _ = tokio::time::sleep(PROGRESS_INTERVAL) => {
+                    if let Some(progress) = client.hash_progress(context::current()).await? {
+                        print!("\rHashing: {}/{} files", progress.done, progress.total);
+                        std::io::stdout().flush()?;
+                    }
+                }
+            }
+        };
+
+        println!(
+            "\rHashed {} files, {} errors",
+            summary.items, summary.errors
+        );
+
+        Ok(())
+    })
+}
+
+/// Prints groups of indexed files with identical contents
+pub fn duplicates(limit: usize) -> Result<()> {
+    runtime::with_daemon(async |client| {
+        let groups = client
+            .duplicates(context::current(), limit)
+            .await?
+            .map_err(|err| eyre!("Failed to fetch duplicates: {err}"))?;
+
+        if groups.is_empty() {
+            println!("No duplicates found");
+        }
+
+        for group in groups {
+            println!(
+                "[{}] {} copies of {} bytes, {} bytes wasted",
+                &group.hash[..12.min(group.hash.len())],
+                group.paths.len(),
+                group.size,
+                group.wasted()
+            );
+
+            for path in &group.paths {
+                println!("  {path}");
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Swaps the log filter of the running daemon
+pub fn log_level(filter: String) -> Result<()> {
+    runtime::with_daemon(async |client| {
+        let directives = client
+            .set_log_filter(context::current(), filter)
+            .await?
+            .map_err(|err| eyre!("Failed to change the log filter: {err}"))?;
+
+        println!("Daemon logs {directives}");
+
+        Ok(())
+    })
+}
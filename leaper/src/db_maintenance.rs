@@ -0,0 +1,48 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use color_eyre::{Result, eyre::eyre};
+
+use daemon::client::context;
+
+use crate::runtime;
+
+/// Maintenance can take a while on big indexes
+const TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Has the daemon export the DB, `path` is resolved here since the daemon writes it
+pub fn backup(path: PathBuf) -> Result<()> {
+    let path = std::path::absolute(&path)?;
+
+    runtime::with_daemon(async |client| {
+        client
+            .backup(long_context(), path.clone())
+            .await?
+            .map_err(|err| eyre!("Backup failed: {err}"))?;
+
+        println!("Backed up the DB to {path:?}");
+
+        Ok(())
+    })
+}
+
+pub fn compact() -> Result<()> {
+    runtime::with_daemon(async |client| {
+        client
+            .compact(long_context())
+            .await?
+            .map_err(|err| eyre!("Compaction failed: {err}"))?;
+
+        println!("Compacted the DB");
+
+        Ok(())
+    })
+}
+
+fn long_context() -> context::Context {
+    let mut ctx = context::current();
+    ctx.deadline = Instant::now() + TIMEOUT;
+    ctx
+}
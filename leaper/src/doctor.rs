@@ -0,0 +1,226 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use color_eyre::{Result, eyre::OptionExt};
+use directories::ProjectDirs;
+
+use db::{InstrumentedDBQuery, experimental_features, stats::GetDBStatsQuery, try_init_db};
+use mode::{
+    config::LeaperModeConfig,
+    wayland::{LAYER_SHELL, SESSION_LOCK, WaylandGlobals},
+};
+
+use crate::runtime;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs local diagnostics and prints a report meant to be attached to bug reports,
+/// nothing leaves the machine
+pub fn run() -> Result<()> {
+    let project_dirs = ProjectDirs::from("com", "tukanoid", "leaper")
+        .ok_or_eyre("Failed to get project directories")?;
+
+    let mut report = Report::default();
+
+    let config = config_section(&project_dirs, &mut report);
+
+    runtime::block_on(async {
+        db_section(config.db_port, &mut report).await;
+        daemon_section(&mut report).await;
+
+        Ok(())
+    })?;
+
+    wayland_section(&mut report);
+
+    println!("{report}");
+
+    Ok(())
+}
+
+fn config_section(project_dirs: &ProjectDirs, report: &mut Report) -> LeaperModeConfig {
+    let section = report.section("config");
+    let path = LeaperModeConfig::path(project_dirs);
+
+    if !path.exists() {
+        section.warn(format!("{path:?} doesn't exist, defaults will be used"));
+        return LeaperModeConfig::default();
+    }
+
+    match LeaperModeConfig::open(project_dirs) {
+        Ok(config) => {
+            section.ok(format!("{path:?} is valid"));
+            config
+        }
+        Err(err) => {
+            section.fail(format!("{path:?} is invalid: {err}"));
+            LeaperModeConfig::default()
+        }
+    }
+}
+
+async fn db_section(port: u16, report: &mut Report) {
+    let section = report.section("database");
+
+    let start = Instant::now();
+    let db = match tokio::time::timeout(TIMEOUT, try_init_db(port)).await {
+        Ok(Ok(db)) => {
+            section.ok(format!(
+                "Reachable at localhost:{port} (connected in {:?})",
+                start.elapsed()
+            ));
+
+            if !experimental_features() {
+                section.warn(
+                    "Server rejected experimental capabilities, running in compatibility mode",
+                );
+            }
+
+            db
+        }
+        Ok(Err(err)) => return section.fail(format!("Unreachable at localhost:{port}: {err}")),
+        Err(_) => return section.fail(format!("Timed out connecting to localhost:{port}")),
+    };
+
+    let start = Instant::now();
+    let stats = match GetDBStatsQuery.instrumented_execute(db).await {
+        Ok(stats) => stats,
+        Err(err) => return section.fail(format!("Failed to query index counts: {err}")),
+    };
+    section.ok(format!("Query latency: {:?}", start.elapsed()));
+
+    let index = format!(
+        "Index: {} apps, {} icons, {} fs nodes, {} files",
+        stats.apps, stats.icons, stats.fs_nodes, stats.files
+    );
+    match stats.apps {
+        0 => section.warn(format!("{index} (is the daemon running?)")),
+        _ => section.ok(index),
+    }
+
+    let icons = format!(
+        "Icon hit rate: {}/{} apps have an icon",
+        stats.apps_with_icon, stats.apps
+    );
+    match stats.apps > 0 && stats.apps_with_icon * 2 < stats.apps {
+        true => section.warn(icons),
+        false => section.ok(icons),
+    }
+}
+
+async fn daemon_section(report: &mut Report) {
+    let section = report.section("daemon");
+
+    let socket = match daemon::socket_path() {
+        Ok(socket) => socket,
+        Err(err) => return section.fail(format!("No socket to reach it at: {err}")),
+    };
+
+    let start = Instant::now();
+    let status = tokio::time::timeout(TIMEOUT, async {
+        let client = daemon::client::connect().await?;
+        let status = client.status(daemon::client::context::current()).await?;
+
+        Result::<_>::Ok(status)
+    })
+    .await;
+
+    match status {
+        Ok(Ok(status)) => {
+            section.ok(format!(
+                "Reachable at {} (v{}, responded in {:?})",
+                socket.display(),
+                status.version,
+                start.elapsed()
+            ));
+            section.ok(format!("Uptime: {:?}", status.uptime));
+
+            if status.searching_apps {
+                section.warn("Currently searching for apps and icons");
+            }
+        }
+        Ok(Err(err)) => section.fail(format!("Unreachable at {}: {err}", socket.display())),
+        Err(_) => section.fail(format!("Timed out connecting to {}", socket.display())),
+    }
+}
+
+fn wayland_section(report: &mut Report) {
+    let section = report.section("wayland");
+
+    let globals = match WaylandGlobals::query() {
+        Ok(globals) => globals,
+        Err(err) => return section.fail(format!("Failed to query compositor globals: {err}")),
+    };
+
+    for (interface, used_by) in [
+        (LAYER_SHELL, "launcher, runner and power menu"),
+        (SESSION_LOCK, "lock screen"),
+    ] {
+        match globals.has(interface) {
+            true => section.ok(format!("{interface} is available")),
+            false => section.fail(format!("{interface} is missing (needed by {used_by})")),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Report(Vec<Section>);
+
+impl Report {
+    fn section(&mut self, name: &'static str) -> &mut Section {
+        self.0.push(Section {
+            name,
+            checks: vec![],
+        });
+        self.0.last_mut().unwrap()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "leaper doctor v{}", env!("CARGO_PKG_VERSION"))?;
+
+        for Section { name, checks } in &self.0 {
+            writeln!(f, "\n[{name}]")?;
+
+            for (status, msg) in checks {
+                let status = match status {
+                    CheckStatus::Ok => "ok",
+                    CheckStatus::Warn => "warn",
+                    CheckStatus::Fail => "FAIL",
+                };
+
+                writeln!(f, "  {status:>4} | {msg}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct Section {
+    name: &'static str,
+    checks: Vec<(CheckStatus, String)>,
+}
+
+impl Section {
+    fn ok(&mut self, msg: impl Into<String>) {
+        self.checks.push((CheckStatus::Ok, msg.into()));
+    }
+
+    fn warn(&mut self, msg: impl Into<String>) {
+        self.checks.push((CheckStatus::Warn, msg.into()));
+    }
+
+    fn fail(&mut self, msg: impl Into<String>) {
+        self.checks.push((CheckStatus::Fail, msg.into()));
+    }
+}
+
+enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
@@ -0,0 +1,242 @@
+use std::{error::Error, io, process::ExitCode, sync::Arc};
+
+use color_eyre::{Report, eyre::eyre};
+use serde::Serialize;
+use tokio::signal::unix::{SignalKind, signal};
+
+use daemon::client::RpcError;
+use db::DBError;
+use mode::config::LeaperAppModeConfigError;
+
+/// Exit statuses of the `leaper` binary, stable so scripts wrapping it can branch on them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitStatus {
+    Success = 0,
+    /// Anything without a more specific status
+    Failure = 1,
+    /// The config couldn't be read, parsed or written
+    Config = 2,
+    /// No daemon listening, or it went away mid-request
+    DaemonUnreachable = 3,
+    Db = 4,
+    /// Interrupted by SIGINT (e.g. Ctrl+C at a prompt) or SIGTERM, see [`exit_on_signals`]
+    Cancelled = 5,
+    /// Invalid command line arguments
+    Usage = 6,
+}
+
+impl ExitStatus {
+    /// Status of the first error in the chain that has a specific one
+    pub fn of(report: &Report) -> Self {
+        report
+            .chain()
+            .find_map(Self::classify)
+            .unwrap_or(Self::Failure)
+    }
+
+    fn classify(err: &(dyn Error + 'static)) -> Option<Self> {
+        if downcast::<LeaperAppModeConfigError>(err).is_some() {
+            return Some(Self::Config);
+        }
+
+        if downcast::<DBError>(err).is_some() {
+            return Some(Self::Db);
+        }
+
+        if let Some(err) = downcast::<RpcError>(err) {
+            return match err {
+                RpcError::Shutdown | RpcError::Send(_) | RpcError::Receive(_) => {
+                    Some(Self::DaemonUnreachable)
+                }
+                _ => None,
+            };
+        }
+
+        // Not Interrupted, that's a syscall cut short (EINTR) rather than the user
+        match downcast::<io::Error>(err)?.kind() {
+            io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted => Some(Self::DaemonUnreachable),
+            _ => None,
+        }
+    }
+
+    /// Prints the error to stderr, as a single JSON object with `json`
+    pub fn report(self, report: &Report, json: bool) {
+        if !json {
+            eprintln!("Error: {report:?}");
+            return;
+        }
+
+        let error = JsonError {
+            status: self as u8,
+            reason: self,
+            error: report.to_string(),
+            causes: report.chain().skip(1).map(ToString::to_string).collect(),
+        };
+
+        match serde_json::to_string(&error) {
+            Ok(error) => eprintln!("{error}"),
+            Err(err) => eprintln!("Error: {report:?}\n(failed to serialize it as JSON: {err})"),
+        }
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
+
+/// What `--json` prints to stderr on failure
+#[derive(Serialize)]
+struct JsonError {
+    status: u8,
+    reason: ExitStatus,
+    error: String,
+    causes: Vec<String>,
+}
+
+/// `err` as a `T`, also when it's one of the crates' errors wrapped the way their enums
+/// hold them (e.g. in an `Arc`), which is how they show up in the chain
+fn downcast<T: Error + 'static>(err: &(dyn Error + 'static)) -> Option<&T> {
+    err.downcast_ref::<T>()
+        .or_else(|| err.downcast_ref::<Arc<T>>().map(AsRef::as_ref))
+        .or_else(|| err.downcast_ref::<Box<T>>().map(AsRef::as_ref))
+}
+
+/// Exits with [`ExitStatus::Cancelled`] on SIGINT or SIGTERM, instead of dying of the
+/// signal with no status of ours
+pub fn exit_on_signals(json: bool) {
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(err) => {
+                tracing::warn!("Failed to watch for SIGINT/SIGTERM: {err}");
+                return;
+            }
+        };
+
+        let received = runtime.block_on(async {
+            let mut interrupt = signal(SignalKind::interrupt())?;
+            let mut terminate = signal(SignalKind::terminate())?;
+
+            io::Result::Ok(tokio::select! {
+                _ = interrupt.recv() => "SIGINT",
+                _ = terminate.recv() => "SIGTERM",
+            })
+        });
+
+        match received {
+            Ok(received) => {
+                let status = ExitStatus::Cancelled;
+                status.report(&eyre!("Interrupted by {received}"), json);
+
+                std::process::exit(status as i32);
+            }
+            Err(err) => tracing::warn!("Failed to watch for SIGINT/SIGTERM: {err}"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use launcher::LeaperLauncherError;
+    use leaper_core::CoreError;
+    use leaper_error::CommonError;
+    use leaper_tui::LeaperTuiError;
+    use lock::LeaperLockError;
+    use power::LeaperPowerError;
+
+    use super::*;
+
+    fn config_error() -> LeaperAppModeConfigError {
+        toml::from_str::<toml::Table>("=")
+            .expect_err("Not TOML")
+            .into()
+    }
+
+    fn io_error(kind: io::ErrorKind) -> CommonError {
+        Arc::new(io::Error::from(kind)).into()
+    }
+
+    fn status(err: impl Error + Send + Sync + 'static) -> ExitStatus {
+        ExitStatus::of(&Report::new(err))
+    }
+
+    #[test]
+    fn failure() {
+        assert_eq!(
+            ExitStatus::of(&eyre!("Something broke")),
+            ExitStatus::Failure
+        );
+        assert_eq!(
+            status(LeaperLauncherError::NoFileName("/".into())),
+            ExitStatus::Failure
+        );
+    }
+
+    #[test]
+    fn config() {
+        assert_eq!(status(config_error()), ExitStatus::Config);
+        assert_eq!(
+            status(LeaperPowerError::Config(config_error())),
+            ExitStatus::Config
+        );
+        assert_eq!(
+            ExitStatus::of(&Report::new(config_error()).wrap_err("Failed to start")),
+            ExitStatus::Config
+        );
+    }
+
+    #[test]
+    fn daemon_unreachable() {
+        assert_eq!(status(RpcError::Shutdown), ExitStatus::DaemonUnreachable);
+        assert_eq!(
+            status(CoreError::Common(io_error(
+                io::ErrorKind::ConnectionRefused
+            ))),
+            ExitStatus::DaemonUnreachable
+        );
+    }
+
+    #[test]
+    fn db() {
+        let err = DBError::SurrealExtra("broken".into());
+
+        assert_eq!(
+            status(LeaperTuiError::Core(CoreError::DB(Arc::new(err)))),
+            ExitStatus::Db
+        );
+    }
+
+    #[test]
+    fn eintr_is_not_cancelled() {
+        assert_eq!(
+            status(LeaperLockError::Common(io_error(
+                io::ErrorKind::Interrupted
+            ))),
+            ExitStatus::Failure
+        );
+    }
+
+    #[test]
+    fn stable_codes() {
+        let codes = [
+            ExitStatus::Success,
+            ExitStatus::Failure,
+            ExitStatus::Config,
+            ExitStatus::DaemonUnreachable,
+            ExitStatus::Db,
+            ExitStatus::Cancelled,
+            ExitStatus::Usage,
+        ]
+        .map(|status| status as u8);
+
+        assert_eq!(codes, [0, 1, 2, 3, 4, 5, 6]);
+    }
+}
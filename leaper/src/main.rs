@@ -1,28 +1,174 @@
 mod cli;
+mod daemon_status;
+mod db_maintenance;
+mod doctor;
+mod exit;
+mod multi_call;
+mod query;
+mod runtime;
+mod user_data;
+
+use std::process::ExitCode;
 
 use clap::Parser;
 use color_eyre::Result;
 use mode::{LeaperMode, LeaperModeMultiWindow};
 
-fn main() -> Result<()> {
-    use crate::cli::Cli;
+use crate::{cli::Cli, exit::ExitStatus};
+
+/// Exits with an [`ExitStatus`] instead of the generic 1, see `leaper --help` for `--json`
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse_from(multi_call::args()) {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+
+            // --help and --version go through here as well
+            return match err.use_stderr() {
+                true => ExitStatus::Usage.into(),
+                false => ExitStatus::Success.into(),
+            };
+        }
+    };
+    let json = cli.json;
+
+    exit::exit_on_signals(json);
+
+    match run(cli) {
+        Ok(()) => ExitStatus::Success.into(),
+        Err(report) => {
+            let status = ExitStatus::of(&report);
+            status.report(&report, json);
+
+            status.into()
+        }
+    }
+}
+
+/// Commands that don't use the config, or only inspect it like doctor, so main shouldn't
+/// create a default one for them
+fn opens_config(cmd: &cli::LeaperCmd) -> bool {
+    use crate::cli::{LeaperCmd, LockCmd};
+
+    !matches!(
+        cmd,
+        LeaperCmd::Doctor
+            | LeaperCmd::InstallSymlinks { .. }
+            | LeaperCmd::Lock {
+                cmd: Some(LockCmd::SetPin | LockCmd::ClearPin),
+                ..
+            }
+    )
+}
+
+/// Outputs the config main opened asks for, the defaults if it's broken or wasn't opened.
+/// A broken one is reported by the command that needs the config
+fn log_outputs(config: Option<&mode::config::LeaperModeConfig>) -> leaper_tracing::LogOutputs {
+    let Some(config) = config else {
+        return Default::default();
+    };
+
+    leaper_tracing::LogOutputs::new(config.log.journald.enabled(), config.log.keep_stderr)
+}
+
+fn run(cli: Cli) -> Result<()> {
+    use crate::cli::{DaemonCmd, DbCmd, LeaperCmd, LockCmd, QueryCmd};
 
     color_eyre::install()?;
 
     let Cli {
-        mode,
+        cmd,
+        kiosk,
+        ephemeral,
+        renderer,
+        json,
+        trace,
+        debug,
+        error,
+    } = cli;
+
+    if kiosk {
+        mode::config::LeaperModeConfig::force_kiosk();
+    }
+
+    if ephemeral {
+        mode::config::LeaperModeConfig::force_ephemeral();
+    }
+
+    if let Some(renderer) = renderer {
+        mode::config::LeaperModeConfig::force_renderer(renderer);
+    }
+
+    let cmd = cmd.unwrap_or_default();
+
+    // Opened once the overrides are in, subcommands that need it get this one
+    let open_config = || {
+        mode::config::LeaperModeConfig::open(
+            &<launcher::LeaperLauncher as LeaperMode>::project_dirs(),
+        )
+    };
+    let config = opens_config(&cmd).then(open_config);
+
+    leaper_tracing::init_tracing(
         trace,
         debug,
         error,
-    } = Cli::parse();
+        log_outputs(config.as_ref().and_then(|config| config.as_ref().ok())),
+    )?;
+
+    match cmd {
+        LeaperCmd::Launcher => launcher::LeaperLauncher::run()?,
+        LeaperCmd::Runner { watch } => {
+            if watch {
+                runner::LeaperRunner::start_watching();
+            }
+
+            runner::LeaperRunner::run()?
+        }
+        LeaperCmd::Power => power::LeaperPower::run()?,
+        LeaperCmd::Lock { demo, cmd } => match cmd {
+            None => {
+                if demo {
+                    lock::LeaperLock::start_demo();
+                }
+
+                lock::LeaperLock::run()?
+            }
+            Some(LockCmd::SetPin) => lock::LeaperLock::set_pin()?,
+            Some(LockCmd::ClearPin) => lock::LeaperLock::clear_pin()?,
+        },
+
+        LeaperCmd::Find => launcher::finder::LeaperFinder::run()?,
+
+        LeaperCmd::Tui => leaper_tui::LeaperTui::run()?,
+        LeaperCmd::Jobs => leaper_tui::jobs::LeaperJobsTui::run()?,
+        LeaperCmd::Du { path } => leaper_tui::du::LeaperDuTui::run(path)?,
+
+        LeaperCmd::Query { cmd } => match cmd {
+            QueryCmd::Apps { pattern, limit } => query::apps(pattern, limit, json)?,
+        },
+
+        LeaperCmd::Daemon { cmd } => match cmd {
+            DaemonCmd::Status => daemon_status::status()?,
+            DaemonCmd::Index { path, parents } => daemon_status::index(path, parents)?,
+            DaemonCmd::Hash => daemon_status::hash()?,
+            DaemonCmd::Duplicates { limit } => daemon_status::duplicates(limit)?,
+            DaemonCmd::LogLevel { filter } => daemon_status::log_level(filter)?,
+        },
+        LeaperCmd::Db { cmd } => match cmd {
+            DbCmd::Backup { path } => db_maintenance::backup(path)?,
+            DbCmd::Compact => db_maintenance::compact()?,
+        },
+        LeaperCmd::Doctor => doctor::run()?,
 
-    leaper_tracing::init_tracing(trace, debug, error)?;
+        LeaperCmd::InstallSymlinks { dir, force } => multi_call::install_symlinks(dir, force)?,
 
-    match mode {
-        cli::AppMode::Launcher => launcher::LeaperLauncher::run()?,
-        cli::AppMode::Runner => runner::LeaperRunner::run()?,
-        cli::AppMode::Power => power::LeaperPower::run()?,
-        cli::AppMode::Lock => lock::LeaperLock::run()?,
+        LeaperCmd::Export { output } => {
+            user_data::export(&output, config.unwrap_or_else(open_config)?)?
+        }
+        LeaperCmd::Import { input, skip_config } => {
+            user_data::import(&input, skip_config, config.unwrap_or_else(open_config))?
+        }
     }
 
     Ok(())
@@ -0,0 +1,68 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use color_eyre::{
+    Result,
+    eyre::{OptionExt, eyre},
+};
+
+/// Subcommands reachable through a `leaper-<name>` symlink to the binary, so compositor
+/// configs can run e.g. `leaper-launcher`. Not the daemon, it has a binary of its own
+const APPLETS: &[&str] = &[
+    "launcher", "runner", "power", "lock", "find", "tui", "jobs", "du",
+];
+
+/// Command line with the applet the binary was invoked as (argv[0]) turned into its
+/// subcommand, `leaper-lock --demo` parses as `leaper lock --demo`
+pub fn args() -> Vec<OsString> {
+    let mut args = std::env::args_os().collect::<Vec<_>>();
+
+    let applet = args
+        .first()
+        .map(PathBuf::from)
+        .and_then(|invoked| {
+            invoked
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("leaper-"))
+                .map(ToString::to_string)
+        })
+        .filter(|applet| APPLETS.contains(&applet.as_str()));
+
+    if let Some(applet) = applet {
+        args.insert(1, applet.into());
+    }
+
+    args
+}
+
+/// Links every applet to the running binary, in `dir` or next to the binary
+pub fn install_symlinks(dir: Option<PathBuf>, force: bool) -> Result<()> {
+    let exe = std::env::current_exe()?.canonicalize()?;
+    let dir = match dir {
+        Some(dir) => dir,
+        None => exe
+            .parent()
+            .ok_or_eyre("The binary isn't in a directory")?
+            .to_path_buf(),
+    };
+
+    for applet in APPLETS {
+        let link = dir.join(format!("leaper-{applet}"));
+
+        if link.symlink_metadata().is_ok() {
+            match force || link.read_link().is_ok_and(|target| target == exe) {
+                true => std::fs::remove_file(&link)?,
+                false => {
+                    return Err(eyre!(
+                        "{link:?} already exists and isn't a link to {exe:?}, pass --force to replace it"
+                    ));
+                }
+            }
+        }
+
+        std::os::unix::fs::symlink(&exe, &link)?;
+        println!("{link:?} -> {exe:?}");
+    }
+
+    Ok(())
+}
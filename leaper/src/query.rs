@@ -0,0 +1,84 @@
+use color_eyre::{Result, eyre::OptionExt};
+use directories::ProjectDirs;
+use serde::Serialize;
+
+use db::{apps::AppWithIcon, init_db};
+use leaper_core::{catalog::AppCatalog, search::AppSearch};
+use mode::config::LeaperModeConfig;
+
+use crate::runtime;
+
+/// One line (or JSON object) of `leaper query apps`
+#[derive(Serialize)]
+struct AppMatch<'a> {
+    rank: usize,
+    name: &'a str,
+    desktop_id: String,
+    path: &'a str,
+    exec: &'a [String],
+    categories: &'a [String],
+}
+
+impl<'a> AppMatch<'a> {
+    fn new(rank: usize, app: &'a AppWithIcon) -> Self {
+        Self {
+            rank,
+            name: &app.name,
+            desktop_id: app.desktop_id(),
+            path: &app.desktop_entry_path,
+            exec: &app.exec,
+            categories: &app.categories,
+        }
+    }
+}
+
+/// Runs `pattern` through the launcher's search against the indexed apps and prints the
+/// matches best first, so other launchers and ranking tests can use it without a GUI
+pub fn apps(pattern: String, limit: usize, json: bool) -> Result<()> {
+    let project_dirs = ProjectDirs::from("com", "tukanoid", "leaper")
+        .ok_or_eyre("Failed to get project directories")?;
+    let config = LeaperModeConfig::open(&project_dirs)?;
+
+    runtime::block_on(async {
+        let db = init_db(config.db_port).await?;
+        let catalog = AppCatalog::new(db, &config.launcher);
+
+        let mut apps = catalog.clone().apps().await?;
+        apps.retain(|app| !config.hides_app(&app.categories));
+
+        let launches = catalog.clone().launches().await.unwrap_or_else(|err| {
+            tracing::warn!("Failed to load launch history: {err}");
+            Default::default()
+        });
+
+        let mut app_search = AppSearch::new(config.launcher);
+        app_search.set_choices(catalog.query_choices().await.unwrap_or_else(|err| {
+            tracing::warn!("Failed to load past search choices: {err}");
+            Default::default()
+        }));
+
+        let matches = match pattern.is_empty() {
+            true => {
+                app_search.sort(&mut apps, &launches);
+                apps
+            }
+            false => app_search.search(&apps, &launches, &pattern),
+        };
+        let matches = matches
+            .iter()
+            .take(limit)
+            .enumerate()
+            .map(|(ind, app)| AppMatch::new(ind + 1, app));
+
+        if json {
+            println!("{}", serde_json::to_string(&matches.collect::<Vec<_>>())?);
+            return Ok(());
+        }
+
+        for app in matches {
+            println!("{:>3}  {:<32}  {}", app.rank, app.name, app.desktop_id);
+        }
+
+        Ok(())
+    })
+}
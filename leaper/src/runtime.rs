@@ -0,0 +1,17 @@
+use color_eyre::Result;
+
+/// Runs `f` on a runtime of its own, for the subcommands that only need one for the call
+pub fn block_on<T>(f: impl Future<Output = Result<T>>) -> Result<T> {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(f)
+}
+
+/// [`block_on`] with a connection to the daemon
+pub fn with_daemon<T>(f: impl AsyncFnOnce(daemon::LeaperDaemonClient) -> Result<T>) -> Result<T> {
+    block_on(async {
+        let client = daemon::client::connect().await?;
+        f(client).await
+    })
+}
@@ -0,0 +1,128 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+};
+
+use color_eyre::Result;
+
+use db::{
+    init_db,
+    user_data::{USER_TABLES, UserTableHandler},
+};
+use mode::{
+    LeaperMode,
+    config::{LeaperModeConfig, LeaperModeConfigResult},
+};
+
+use crate::runtime;
+
+const CONFIG_ENTRY: &str = "config.toml";
+const TABLES_DIR: &str = "tables";
+
+pub fn export(output: &Path, config: LeaperModeConfig) -> Result<()> {
+    let project_dirs = <launcher::LeaperLauncher as LeaperMode>::project_dirs();
+    // The file as written, `config` has the command line overrides applied
+    let config_bytes = std::fs::read(LeaperModeConfig::path(&project_dirs))?;
+
+    let tables = runtime::block_on(async {
+        let db = init_db(config.db_port).await?;
+        let mut tables = Vec::with_capacity(USER_TABLES.len());
+
+        for handler in USER_TABLES {
+            tables.push((handler.table, handler.export(db.clone()).await?));
+        }
+
+        Ok(tables)
+    })?;
+
+    let encoder = zstd::Encoder::new(File::create(output)?, 0)?.auto_finish();
+    let mut archive = tar::Builder::new(encoder);
+
+    append(&mut archive, CONFIG_ENTRY, &config_bytes)?;
+
+    for (table, data) in &tables {
+        append(&mut archive, format!("{TABLES_DIR}/{table}.json"), data)?;
+    }
+
+    archive.into_inner()?.flush()?;
+
+    tracing::info!("Exported config and {} tables to {output:?}", tables.len());
+
+    Ok(())
+}
+
+/// `config` is the current one, only needed if the archive's isn't taken over, so a
+/// broken one can still be replaced by importing
+pub fn import(
+    input: &Path,
+    skip_config: bool,
+    config: LeaperModeConfigResult<LeaperModeConfig>,
+) -> Result<()> {
+    let project_dirs = <launcher::LeaperLauncher as LeaperMode>::project_dirs();
+
+    let mut archive = tar::Archive::new(zstd::Decoder::new(File::open(input)?)?);
+    let mut config_str = None;
+    let mut tables = vec![];
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+
+        let mut data = vec![];
+        entry.read_to_end(&mut data)?;
+
+        if path == Path::new(CONFIG_ENTRY) {
+            config_str = Some(String::from_utf8(data)?);
+            continue;
+        }
+
+        match path
+            .strip_prefix(TABLES_DIR)
+            .ok()
+            .and_then(|file| file.file_stem())
+            .and_then(|stem| stem.to_str())
+        {
+            Some(table) => tables.push((table.to_string(), data)),
+            None => tracing::warn!("Skipping unknown archive entry {path:?}"),
+        }
+    }
+
+    let config = match config_str {
+        Some(config_str) if !skip_config => LeaperModeConfig::replace(&project_dirs, &config_str)?,
+        _ => config?,
+    };
+
+    runtime::block_on(async {
+        let db = init_db(config.db_port).await?;
+
+        for (table, data) in tables {
+            let handler = match UserTableHandler::find(&table) {
+                Ok(handler) => handler,
+                Err(err) => {
+                    tracing::warn!("{err}, skipping...");
+                    continue;
+                }
+            };
+
+            let count = handler.import(db.clone(), data).await?;
+            tracing::info!("Imported {count} rows into {table}");
+        }
+
+        Ok(())
+    })
+}
+
+fn append(
+    archive: &mut tar::Builder<impl Write>,
+    path: impl AsRef<Path>,
+    data: &[u8],
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+
+    archive.append_data(&mut header, path, data)?;
+
+    Ok(())
+}